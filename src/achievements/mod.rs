@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+use crate::storage::{self, StorageError};
+
+//-----------------------------------------------------------------------------
+
+const ACHIEVEMENTS_FILE: &str = "achievements.toml";
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AchievementId {
+    Untouchable,
+    Sharpshooter,
+    SpeedDemon,
+}
+
+pub struct Achievement {
+    pub id: AchievementId,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub unlocked: bool,
+}
+
+impl Achievement {
+    fn new(id: AchievementId, name: &'static str, description: &'static str) -> Self {
+        Self { id, name, description, unlocked: false }
+    }
+
+    // returns true only for the first unlock
+    pub fn unlock(&mut self) -> bool {
+        let newly_unlocked = !self.unlocked;
+        self.unlocked = true;
+        newly_unlocked
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize)]
+struct AchievementsSave {
+    unlocked: Vec<AchievementId>,
+}
+
+pub struct AchievementSystem {
+    achievements: Vec<Achievement>,
+}
+
+// everything locked, nothing is read from disk
+impl Default for AchievementSystem {
+    fn default() -> Self {
+        Self {
+            achievements: vec![
+                Achievement::new(AchievementId::Untouchable, "Untouchable", "Defeat a boss without taking damage"),
+                Achievement::new(AchievementId::Sharpshooter, "Sharpshooter", "Reach a combo of 50"),
+                Achievement::new(AchievementId::SpeedDemon, "Speed Demon", "Defeat a boss in under 60 seconds"),
+            ],
+        }
+    }
+}
+
+impl AchievementSystem {
+    pub fn load() -> Self {
+        let mut system = AchievementSystem::default();
+        match storage::load::<AchievementsSave>(ACHIEVEMENTS_FILE) {
+            Ok(save) => save.unlocked.iter().for_each(|id| {
+                system.get_mut(*id).unlock();
+            }),
+            Err(StorageError::NotFound(_)) => (),
+            Err(err) => log::warn!("Failed to load achievements: {}", err),
+        }
+        system
+    }
+
+    pub fn save(&self) -> Result<(), StorageError> {
+        let unlocked = self.achievements.iter()
+            .filter(|achievement| achievement.unlocked)
+            .map(|achievement| achievement.id)
+            .collect();
+        storage::save(ACHIEVEMENTS_FILE, &AchievementsSave { unlocked })
+    }
+
+    pub fn get(&self, id: AchievementId) -> &Achievement {
+        self.achievements.iter()
+            .find(|achievement| achievement.id == id)
+            .expect("Every achievement id is registered")
+    }
+
+    pub fn unlocked(&self, id: AchievementId) -> bool {
+        self.achievements.iter().any(|achievement| achievement.id == id && achievement.unlocked)
+    }
+
+    // returns the achievement unlocked by this event, if it wasn't unlocked before
+    pub fn check(&mut self, event: &GameEvent) -> Option<AchievementId> {
+        let id = match event {
+            GameEvent::NoDamageBossKill => AchievementId::Untouchable,
+            GameEvent::ComboOf50 => AchievementId::Sharpshooter,
+            GameEvent::BossKillUnder60Seconds => AchievementId::SpeedDemon,
+            GameEvent::BossIntroStarted
+            | GameEvent::BossIntroEnded
+            | GameEvent::LowHpWarningStarted
+            | GameEvent::LowHpWarningStopped
+            | GameEvent::BossStunned
+            | GameEvent::BossStunEnded
+            | GameEvent::BossStuck
+            | GameEvent::WeaponArmDetached
+            | GameEvent::WeaponArmDestroyed
+            | GameEvent::BossEnraged
+            | GameEvent::KineticBarrierDestroyed
+            | GameEvent::ForceZoneEntered
+            | GameEvent::ForceZoneLeft
+            | GameEvent::CutsceneStarted
+            | GameEvent::CutsceneEnded
+            | GameEvent::DialogueLineChanged
+            | GameEvent::DialogueEnded
+            | GameEvent::PlayerHit
+            | GameEvent::CometDestroyed
+            | GameEvent::BombUsed
+            | GameEvent::WaveComplete
+            | GameEvent::StageChanged { .. }
+            | GameEvent::Graze { .. }
+            | GameEvent::ShotReflected { .. }
+            | GameEvent::PowerUpGranted { .. }
+            | GameEvent::ShotConverted { .. }
+            | GameEvent::PickupCollected
+            | GameEvent::EnergyAbsorbed { .. }
+            | GameEvent::KillCam { .. }
+            | GameEvent::VictoryLapStarted
+            | GameEvent::VictoryLapEnded => return None,
+        };
+
+        if self.get_mut(id).unlock() {
+            return Some(id);
+        }
+        None
+    }
+
+    fn get_mut(&mut self, id: AchievementId) -> &mut Achievement {
+        self.achievements.iter_mut()
+            .find(|achievement| achievement.id == id)
+            .expect("Every achievement id is registered")
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -1,4 +1,6 @@
+use std::path::Path;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use winit::{
     event::{Event, WindowEvent},
@@ -7,28 +9,67 @@ use winit::{
 };
 use winit::dpi::PhysicalSize;
 
+use crate::attract::AttractMode;
+use crate::audio::mixer::Mixer;
+use crate::autosave::Autosave;
+use crate::cli::Options;
 use crate::game_level::GameLevel;
 use crate::globals::set_screen_size;
+use crate::input::Bindings;
 use crate::input_mgr::InputMgr;
 use crate::math::Vec2i;
+use crate::profiling::{FrameTimingStats, DEFAULT_FRAME_TIMING_WINDOW};
+use crate::replay::{Replay, ReplayHeader};
+use crate::settings::Settings;
+use crate::spawners::spawners_impl::create_level_spawner;
+use crate::timing::ticks_to_duration;
 
-pub struct App;
+// a frame stalled for longer drops the rest of the lag instead of catching up
+const MAX_UPDATES_PER_FRAME: u32 = 5;
+
+pub struct App {
+    options: Options,
+    // the run continues from it, the options are already set up for its level
+    autosave: Option<Autosave>,
+    // played instead of the devices, the options are already set up from its header
+    replay: Option<Replay>,
+}
 
 impl App {
-    pub fn new() -> App {
-        App {}
+    pub fn new(options: Options, autosave: Option<Autosave>, replay: Option<Replay>) -> App {
+        App { options, autosave, replay }
     }
 
     pub fn run(&self) {
+        if let Some(ticks) = self.options.headless_ticks {
+            return self.run_headless(ticks);
+        }
+
         let event_loop = EventLoop::new();
         let window = WindowBuilder::new().build(&event_loop).unwrap();
 
-        let input_mgr = Rc::new(InputMgr {});
-        let mut game = GameLevel::new(Rc::clone(&input_mgr));
+        let settings = Settings::load();
+        let input_mgr = self.create_input_mgr(&settings);
+        // a replay is only watched, it doesn't count as a run of the player
+        let mut game = self.create_game(Rc::clone(&input_mgr), &settings, self.replay.is_none());
+        let mut recording = self.start_recording(&game, &settings);
+        let record_path = self.options.record.clone();
+        let replay_ticks = self.replay.as_ref().map(Replay::ticks);
+        let mut ticks = 0;
+        // a replay being watched is a demo already
+        let mut attract = self.replay.is_none().then(AttractMode::new);
 
         window.set_inner_size(PhysicalSize { width: 600, height: 600 });
         set_screen_size(Vec2i { x: 200, y: 200 });
 
+        let mut mixer = Mixer::new();
+        let mut frame_timing = FrameTimingStats::new(DEFAULT_FRAME_TIMING_WINDOW);
+        let mut last_frame: Option<Instant> = None;
+        let tick_duration = ticks_to_duration(1);
+        let mut lag = tick_duration;
+        #[cfg(feature = "hot-reload")]
+        let mut tuning_watcher = crate::tuning::TuningWatcher::new();
+
         event_loop.run(move |event, _, control_flow| {
             // ControlFlow::Poll continuously runs the event loop, even if the OS hasn't
             // dispatched any events. This is ideal for games and similar applications.
@@ -39,17 +80,173 @@ impl App {
                     ..
                 } => {
                     println!("Exit requested");
+                    save_recording(recording.as_ref(), record_path.as_deref());
                     control_flow.set_exit();
                 }
+                Event::WindowEvent { event, .. } => {
+                    input_mgr.handle_event(&event);
+                }
                 Event::MainEventsCleared => {
                     window.request_redraw();  // temporary, don't really understand why it is needed here
                 }
                 Event::RedrawRequested(_) => {
-                    game.update();
-                    game.render();
+                    let now = Instant::now();
+                    if let Some(last_frame) = last_frame {
+                        frame_timing.record(now - last_frame);
+                        if frame_timing.is_frame_spike() {
+                            log::debug!("Frame spike: {}", frame_timing);
+                        }
+                        lag += now - last_frame;
+                    }
+                    last_frame = Some(now);
+                    #[cfg(feature = "hot-reload")]
+                    if let Some(tuning) = tuning_watcher.as_mut().and_then(|watcher| watcher.poll()) {
+                        game.set_tuning(tuning);
+                    }
+
+                    // fixed timestep, the game advances a tick per 1/60 s whatever the frame rate is
+                    let mut updates = 0;
+                    while lag >= tick_duration && updates < MAX_UPDATES_PER_FRAME {
+                        input_mgr.update();
+                        lag -= tick_duration;
+                        updates += 1;
+                        // the pause menu is the only menu there is so far
+                        if attract.as_mut().is_some_and(|attract| attract.update(input_mgr.last_input(), game.paused())) {
+                            continue;
+                        }
+                        if let Some(recording) = &mut recording {
+                            recording.record(input_mgr.last_input());
+                        }
+                        game.update();
+                        mixer.play(game.sound_events());
+                        if let Some(event) = game.rumble() {
+                            input_mgr.rumble(event);
+                        }
+                        ticks += 1;
+                    }
+                    if updates == MAX_UPDATES_PER_FRAME {
+                        lag = Duration::ZERO;
+                    }
+                    attract.as_ref().and_then(AttractMode::demo).unwrap_or(&game).render();
+                    // there is no main menu yet, so quitting the run or the end of the replay closes the game
+                    if game.quit_requested() || replay_ticks.is_some_and(|replay_ticks| ticks >= replay_ticks) {
+                        save_recording(recording.as_ref(), record_path.as_deref());
+                        control_flow.set_exit();
+                    }
                 }
                 _ => ()
             }
         });
     }
+
+    fn run_headless(&self, ticks: u32) {
+        let settings = Settings::load();
+        let input_mgr = self.create_input_mgr(&settings);
+        let mut game = self.create_game(Rc::clone(&input_mgr), &settings, false);
+        let mut recording = self.start_recording(&game, &settings);
+        let ticks = self.replay.as_ref().map_or(ticks, |replay| ticks.min(replay.ticks()));
+        game.set_particles_enabled(false);
+        game.set_starfield_enabled(false);
+        game.set_damage_numbers_enabled(false);
+
+        set_screen_size(Vec2i { x: 200, y: 200 });
+
+        let mut frame_timing = FrameTimingStats::new(DEFAULT_FRAME_TIMING_WINDOW);
+        for _ in 0..ticks {
+            let start = Instant::now();
+            input_mgr.update();
+            if let Some(recording) = &mut recording {
+                recording.record(input_mgr.last_input());
+            }
+            game.update();
+            frame_timing.record(start.elapsed());
+        }
+        save_recording(recording.as_ref(), self.options.record.as_deref());
+        println!("Headless run finished after {} ticks", ticks);
+        println!("{:?}", game.hud_state());
+        for objective in &game.hud_state().objectives {
+            println!("Objective: {}", objective);
+        }
+        if cfg!(feature = "metrics") {
+            println!("Update phases: {}", game.phase_totals());
+            println!("Frame times: {}", frame_timing);
+        }
+        if let Some(summary) = game.level_summary() {
+            println!("{}", summary);
+        }
+        if let Some(summary) = game.run_summary() {
+            println!("{}", summary);
+        }
+    }
+
+    // headless runs don't persist anything, so balance runs never touch the player's files
+    fn create_game(&self, input_mgr: Rc<InputMgr>, settings: &Settings, persistence: bool) -> GameLevel {
+        let mut game = GameLevel::new(input_mgr, create_level_spawner(self.options.level));
+        // before the loadout, which checks the unlocked achievements
+        game.set_persistence(persistence);
+        game.set_difficulty(self.options.difficulty);
+        game.set_god_mode(self.options.god_mode);
+        game.set_boss_only(self.options.boss_only);
+        game.set_run_info(self.options.level, self.options.seed);
+        game.set_ship_kind(self.options.ship);
+        game.set_auto_fire(settings.auto_fire, settings.auto_fire_interval());
+        game.set_aim_mode(settings.aim_mode);
+        game.set_palette(settings.palette);
+        game.set_rumble_settings(settings.rumble.clone());
+        game.set_slow_frame_threshold(settings.slow_frame_threshold());
+        game.set_damage_numbers_enabled(self.options.damage_numbers);
+        match (&self.replay, &self.autosave) {
+            (Some(replay), _) => replay.set_up(&mut game),
+            (None, Some(autosave)) => {
+                game.set_loadout(&autosave.loadout);
+                game.resume_from_autosave(autosave);
+            }
+            (None, None) => game.set_loadout(&settings.loadout),
+        }
+        if self.options.continue_run && !game.continue_from_checkpoint() {
+            log::warn!("No checkpoint for level {}, starting over", self.options.level);
+        }
+        #[cfg(feature = "debug-skip-wave")]
+        if let Some(wave) = self.options.wave {
+            game.skip_to_wave(wave);
+        }
+        #[cfg(feature = "debug-commands")]
+        if let Some(hp) = self.options.boss_hp {
+            game.set_boss_hp(hp);
+        }
+        game
+    }
+
+    // the recorded input replaces the devices, so nothing pressed meanwhile can change the replay
+    fn create_input_mgr(&self, settings: &Settings) -> Rc<InputMgr> {
+        let bindings = Bindings::from_config(&settings.bindings);
+        match &self.replay {
+            Some(replay) => Rc::new(InputMgr::from_sources(bindings, vec![Box::new(replay.source())])),
+            None => Rc::new(InputMgr::new(bindings)),
+        }
+    }
+
+    // the seed is always set when recording, main picks one if it wasn't given
+    fn start_recording(&self, game: &GameLevel, settings: &Settings) -> Option<Replay> {
+        self.options.record.as_ref()?;
+        let mut header = ReplayHeader::new(self.options.seed?, self.options.level, self.options.difficulty);
+        header.ship = game.ship_kind();
+        header.loadout = game.loadout();
+        header.mirror = self.options.mirror;
+        header.boss_only = self.options.boss_only;
+        header.auto_fire = settings.auto_fire;
+        header.auto_fire_interval_ms = settings.auto_fire_interval().as_millis() as u64;
+        header.aim_mode = settings.aim_mode;
+        Some(Replay::new(header))
+    }
+}
+
+fn save_recording(recording: Option<&Replay>, path: Option<&Path>) {
+    let (Some(recording), Some(path)) = (recording, path) else {
+        return;
+    };
+    match recording.save(path) {
+        Ok(()) => println!("Replay of {} ticks saved to {}", recording.ticks(), path.display()),
+        Err(err) => log::error!("Failed to save the replay to {}: {}", path.display(), err),
+    }
 }
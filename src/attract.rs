@@ -0,0 +1,289 @@
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::game_level::GameLevel;
+use crate::globals::{restore_globals, snapshot_globals, GlobalsSnapshot};
+use crate::input::{Bindings, SourceInput};
+use crate::input_mgr::InputMgr;
+use crate::paths;
+use crate::replay::Replay;
+use crate::spawners::spawners_impl::create_level_spawner;
+use crate::timing::duration_to_ticks;
+
+//-----------------------------------------------------------------------------
+
+const IDLE_BEFORE_DEMO: Duration = Duration::from_secs(30);
+const DEMOS_DIR_NAME: &str = "demos";
+const DEMO_EXTENSION: &str = "replay";
+
+//-----------------------------------------------------------------------------
+
+// a bundled replay played in a sandbox, the paused run behind it gets its globals back afterwards
+struct Demo {
+    game: GameLevel,
+    input_mgr: Rc<InputMgr>,
+    ticks_left: u32,
+    globals: GlobalsSnapshot,
+}
+
+impl Demo {
+    fn start(replay: &Replay) -> Demo {
+        let globals = snapshot_globals();
+        replay.apply_globals();
+        let input_mgr = Rc::new(InputMgr::from_sources(Bindings::new(), vec![Box::new(replay.source())]));
+        let mut game = GameLevel::new(Rc::clone(&input_mgr), create_level_spawner(replay.header.level));
+        game.set_demo(true);
+        replay.set_up(&mut game);
+        Demo { game, input_mgr, ticks_left: replay.ticks(), globals }
+    }
+
+    // true once the replay ran out
+    fn step(&mut self) -> bool {
+        self.input_mgr.update();
+        self.game.update();
+        self.ticks_left -= 1;
+        self.ticks_left == 0
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// plays the bundled replays one after another while the menu sits idle, any input returns to the menu
+pub struct AttractMode {
+    demos: Vec<PathBuf>,
+    next: usize,
+    idle_ticks: i64,
+    last_input: SourceInput,
+    demo: Option<Demo>,
+    // the input which ended the demo is held back until it's released, so the menu doesn't act on it
+    swallowing: bool,
+}
+
+impl AttractMode {
+    pub fn new() -> Self {
+        AttractMode::with_demos(bundled_demos())
+    }
+
+    pub fn with_demos(demos: Vec<PathBuf>) -> Self {
+        Self { demos, next: 0, idle_ticks: 0, last_input: SourceInput::default(), demo: None, swallowing: false }
+    }
+
+    pub fn demo(&self) -> Option<&GameLevel> {
+        self.demo.as_ref().map(|demo| &demo.game)
+    }
+
+    // called once per tick with the live input, true while the demo, or the input that ended it, takes the
+    // tick and the game must not be updated
+    pub fn update(&mut self, input: SourceInput, in_menu: bool) -> bool {
+        let active = any_input(&self.last_input, &input);
+        self.last_input = input;
+
+        if let Some(demo) = &mut self.demo {
+            if active {
+                self.swallowing = true;
+                self.stop_demo();
+            } else if demo.step() {
+                self.stop_demo();
+            }
+            return true;
+        }
+        if self.swallowing {
+            self.swallowing = holding(&input);
+            return true;
+        }
+
+        self.idle_ticks = if in_menu && !active { self.idle_ticks + 1 } else { 0 };
+        if self.idle_ticks < duration_to_ticks(IDLE_BEFORE_DEMO) || self.demos.is_empty() {
+            return false;
+        }
+        self.idle_ticks = 0;
+        self.start_next_demo();
+        self.demo.is_some()
+    }
+
+    // a demo which can't be played is dropped from the rotation, it won't get any better
+    fn start_next_demo(&mut self) {
+        while !self.demos.is_empty() {
+            let idx = self.next % self.demos.len();
+            let path = &self.demos[idx];
+            match Replay::load(path) {
+                Ok(replay) if replay.ticks() > 0 => {
+                    self.demo = Some(Demo::start(&replay));
+                    self.next = idx + 1;
+                    return;
+                }
+                Ok(_) => log::warn!("Demo {} has no input, skipped", path.display()),
+                Err(err) => log::warn!("Demo {} can't be played, skipped: {}", path.display(), err),
+            }
+            self.demos.remove(idx);
+            self.next = idx;
+        }
+        log::warn!("No playable demo left, the attract mode is off");
+    }
+
+    fn stop_demo(&mut self) {
+        if let Some(demo) = self.demo.take() {
+            restore_globals(demo.globals);
+        }
+        self.idle_ticks = 0;
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+fn bundled_demos() -> Vec<PathBuf> {
+    let dir = paths::assets_dir().join(DEMOS_DIR_NAME);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::warn!("No demos in {}, the attract mode is off: {}", dir.display(), err);
+            return vec![];
+        }
+    };
+    let mut demos: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == DEMO_EXTENSION))
+        .collect();
+    demos.sort();
+    demos
+}
+
+// the mouse reports where it is all the time, only moving it counts
+fn any_input(last: &SourceInput, input: &SourceInput) -> bool {
+    input.aim != last.aim || holding(input)
+}
+
+fn holding(input: &SourceInput) -> bool {
+    *input != SourceInput { aim: input.aim, ..SourceInput::default() }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use std::path::Path;
+
+    use super::*;
+    use crate::difficulty::Difficulty;
+    use crate::globals::{seed_rng, set_screen_size, with_rng};
+    use crate::input::Axis;
+    use crate::math::Vec2i;
+    use crate::replay::ReplayHeader;
+    use crate::timing::game_tick;
+
+    fn demos_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("space-attract-{}-{}", name, std::process::id()))
+    }
+
+    fn save_demo(dir: &Path, name: &str, level: i32, ticks: u32) -> PathBuf {
+        let mut replay = Replay::new(ReplayHeader::new(176, level, Difficulty::Normal));
+        for tick in 0..ticks {
+            replay.record(SourceInput { axis: Axis::new(((tick / 30) % 3) as f32 - 1.0, 0.0), fire: true, ..SourceInput::default() });
+        }
+        let path = dir.join(name);
+        replay.save(&path).unwrap();
+        path
+    }
+
+    fn idle(attract: &mut AttractMode, ticks: i64) -> bool {
+        (0..ticks).map(|_| attract.update(SourceInput::default(), true)).last().unwrap()
+    }
+
+    fn demo_level(attract: &AttractMode) -> &'static str {
+        attract.demo().unwrap().hud_state().level_name
+    }
+
+    #[test]
+    fn demo_starts_after_idling_in_the_menu() {
+        set_screen_size(Vec2i::new(200, 200));
+        let dir = demos_dir("idle");
+        let mut attract = AttractMode::with_demos(vec![save_demo(&dir, "a.replay", 1, 100)]);
+        let idle_ticks = duration_to_ticks(IDLE_BEFORE_DEMO);
+
+        // the idle time only counts in the menu
+        assert!(!(0..idle_ticks).any(|_| attract.update(SourceInput::default(), false)));
+        assert!(!idle(&mut attract, idle_ticks - 1));
+        assert!(attract.demo().is_none());
+        assert!(idle(&mut attract, 1));
+        assert!(idle(&mut attract, 1));
+        assert!(attract.demo().unwrap().hud_state().demo);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn input_ending_the_demo_is_swallowed() {
+        set_screen_size(Vec2i::new(200, 200));
+        let dir = demos_dir("input");
+        let mut attract = AttractMode::with_demos(vec![save_demo(&dir, "a.replay", 1, 100)]);
+        idle(&mut attract, duration_to_ticks(IDLE_BEFORE_DEMO) + 10);
+
+        let fire = SourceInput { fire: true, ..SourceInput::default() };
+        assert!(attract.update(fire, true));
+        assert!(attract.demo().is_none());
+        assert!(attract.update(fire, true));
+        assert!(attract.update(SourceInput::default(), true));
+        assert!(!attract.update(SourceInput::default(), true));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn paused_run_gets_its_globals_back() {
+        set_screen_size(Vec2i::new(200, 200));
+        let dir = demos_dir("globals");
+        let mut attract = AttractMode::with_demos(vec![save_demo(&dir, "a.replay", 1, 100)]);
+        seed_rng(5);
+        let tick = game_tick();
+        idle(&mut attract, duration_to_ticks(IDLE_BEFORE_DEMO) + 100);
+
+        assert!(attract.demo().is_none());
+        assert_eq!(game_tick(), tick);
+        assert_eq!(with_rng(|rng| rng.gen::<u64>()), StdRng::seed_from_u64(5).gen::<u64>());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn broken_demos_are_skipped_and_the_rest_take_turns() {
+        set_screen_size(Vec2i::new(200, 200));
+        let dir = demos_dir("rotation");
+        let first = save_demo(&dir, "a.replay", 1, 10);
+        let empty = save_demo(&dir, "b.replay", 1, 0);
+        let second = save_demo(&dir, "c.replay", 2, 10);
+        let mut attract = AttractMode::with_demos(vec![first, dir.join("missing.replay"), empty, second]);
+        let idle_ticks = duration_to_ticks(IDLE_BEFORE_DEMO);
+
+        let mut levels = vec![];
+        for _ in 0..3 {
+            idle(&mut attract, idle_ticks + 1);
+            levels.push(demo_level(&attract));
+            idle(&mut attract, 10);
+        }
+        assert_eq!(attract.demos.len(), 2);
+        assert_ne!(levels[0], levels[1]);
+        assert_eq!(levels[0], levels[2]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn no_playable_demo_turns_the_attract_mode_off() {
+        let dir = demos_dir("off");
+        let mut attract = AttractMode::with_demos(vec![dir.join("missing.replay")]);
+        assert!(!idle(&mut attract, duration_to_ticks(IDLE_BEFORE_DEMO) * 2));
+        assert!(attract.demos.is_empty());
+    }
+
+    #[test]
+    fn bundled_demos_can_be_played() {
+        set_screen_size(Vec2i::new(200, 200));
+        let demos = bundled_demos();
+        assert!(demos.len() >= 2);
+        for demo in &demos {
+            assert!(Replay::load(demo).is_ok_and(|replay| replay.ticks() > 0), "{}", demo.display());
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,105 @@
+use crate::audio::music_fade::MusicFade;
+use crate::globals::screen_size;
+use crate::math::Vec2i;
+use crate::sound_events::{MusicTrack, SoundEvent, SoundKind};
+
+//-----------------------------------------------------------------------------
+
+// the audio side of the sound events, keeps the music state and pans the sounds by their position;
+// there is no audio backend yet, so what would be played only goes to the trace log
+pub struct Mixer {
+    track: MusicTrack,
+    music_volume: f32,
+    // the fade being played out and the ticks it has been running for
+    fade: Option<(MusicFade, u32)>,
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        Self { track: MusicTrack::Level, music_volume: 1.0, fade: None }
+    }
+
+    // the events of a single tick, called after every update
+    pub fn play(&mut self, events: &[SoundEvent]) {
+        let music = (self.track, self.music_volume);
+        if let Some((fade, elapsed_ticks)) = &mut self.fade {
+            *elapsed_ticks += 1;
+            self.music_volume = fade.volume_at(*elapsed_ticks);
+        }
+        for event in events {
+            match event.kind {
+                // a volume set on its own stops the fade
+                SoundKind::SetMusicVolume { volume } => {
+                    self.music_volume = volume;
+                    self.fade = None;
+                }
+                SoundKind::FadeMusic { fade } => {
+                    self.music_volume = fade.volume_at(0);
+                    self.fade = Some((fade, 0));
+                }
+                SoundKind::ChangeTrack { track } => self.track = track,
+                kind => log::trace!("Sound {:?} panned {:.2}", kind, pan(event.pos)),
+            }
+        }
+        if music != (self.track, self.music_volume) {
+            log::trace!("Music {:?} at volume {:.2}", self.track, self.music_volume);
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// -1 is the left edge of the screen, 1 the right one
+fn pan(pos: Vec2i) -> f32 {
+    let width = screen_size().x.max(1) as f32;
+    (pos.x as f32 / width * 2.0 - 1.0).clamp(-1.0, 1.0)
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::globals::set_screen_size;
+
+    fn event(kind: SoundKind) -> SoundEvent {
+        SoundEvent { kind, pos: Vec2i::zero() }
+    }
+
+    #[test]
+    fn music_follows_the_events() {
+        let mut mixer = Mixer::new();
+        mixer.play(&[event(SoundKind::BombUsed), event(SoundKind::SetMusicVolume { volume: 0.5 })]);
+        assert_eq!((mixer.track, mixer.music_volume), (MusicTrack::Level, 0.5));
+
+        mixer.play(&[event(SoundKind::ChangeTrack { track: MusicTrack::VictoryFanfare })]);
+        assert_eq!((mixer.track, mixer.music_volume), (MusicTrack::VictoryFanfare, 0.5));
+    }
+
+    #[test]
+    fn fade_plays_out_over_the_ticks() {
+        let mut mixer = Mixer::new();
+        mixer.play(&[event(SoundKind::FadeMusic { fade: MusicFade::new(1.0, 0.0, 4) })]);
+        assert_eq!(mixer.music_volume, 1.0);
+        mixer.play(&[]);
+        assert_eq!(mixer.music_volume, 0.75);
+        for _ in 0..10 {
+            mixer.play(&[]);
+        }
+        assert_eq!(mixer.music_volume, 0.0);
+
+        mixer.play(&[event(SoundKind::SetMusicVolume { volume: 1.0 })]);
+        mixer.play(&[]);
+        assert_eq!(mixer.music_volume, 1.0);
+    }
+
+    #[test]
+    fn sounds_are_panned_across_the_screen() {
+        set_screen_size(Vec2i::new(200, 200));
+        assert_eq!(pan(Vec2i::new(0, 50)), -1.0);
+        assert_eq!(pan(Vec2i::new(100, 50)), 0.0);
+        assert_eq!(pan(Vec2i::new(250, 50)), 1.0);
+    }
+}
+
+//-----------------------------------------------------------------------------
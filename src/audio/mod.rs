@@ -0,0 +1,2 @@
+pub mod mixer;
+pub mod music_fade;
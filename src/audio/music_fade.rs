@@ -0,0 +1,26 @@
+//-----------------------------------------------------------------------------
+
+// linear volume ramp over a number of game ticks, sent to the audio side once which plays it out
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MusicFade {
+    pub start_volume: f32,
+    pub end_volume: f32,
+    pub duration_ticks: u32,
+}
+
+impl MusicFade {
+    pub fn new(start_volume: f32, end_volume: f32, duration_ticks: u32) -> Self {
+        Self { start_volume, end_volume, duration_ticks }
+    }
+
+    pub fn volume_at(&self, elapsed_ticks: u32) -> f32 {
+        let progress = if self.duration_ticks == 0 {
+            1.0
+        } else {
+            (elapsed_ticks as f32 / self.duration_ticks as f32).min(1.0)
+        };
+        self.start_volume + (self.end_volume - self.start_volume) * progress
+    }
+}
+
+//-----------------------------------------------------------------------------
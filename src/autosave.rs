@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use crate::checkpoint::{ScoreSnapshot, ShipSnapshot};
+use crate::cli::Options;
+use crate::difficulty::Difficulty;
+use crate::loadout::LoadoutConfig;
+use crate::spawners::spawners_impl::LEVELS_COUNT;
+use crate::storage::{self, StorageError};
+
+//-----------------------------------------------------------------------------
+
+const AUTOSAVE_FILE: &str = "autosave.toml";
+// bumped on incompatible changes, saves of other versions are ignored
+pub const AUTOSAVE_VERSION: u32 = 1;
+
+//-----------------------------------------------------------------------------
+
+// written when a level is cleared, the run continues from the start of the next level
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Autosave {
+    pub version: u32,
+    pub cleared_level: i32,
+    pub ship_snapshot: ShipSnapshot,
+    pub score_snapshot: ScoreSnapshot,
+    pub loadout: LoadoutConfig,
+    pub difficulty: Difficulty,
+    pub seed: Option<u64>,
+}
+
+impl Autosave {
+    pub fn next_level(&self) -> i32 {
+        self.cleared_level + 1
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// a single slot, every save replaces the previous one
+pub struct AutosaveManager;
+
+impl AutosaveManager {
+    pub fn save(autosave: Autosave) {
+        if let Err(err) = storage::save(AUTOSAVE_FILE, &autosave) {
+            log::warn!("Failed to save autosave: {}", err);
+        }
+    }
+
+    pub fn load() -> Option<Autosave> {
+        match storage::load::<Autosave>(AUTOSAVE_FILE) {
+            Ok(autosave) => AutosaveManager::validate(autosave),
+            Err(StorageError::NotFound(_)) => None,
+            Err(err) => {
+                log::warn!("Failed to load autosave: {}", err);
+                None
+            }
+        }
+    }
+
+    // saves of another version or for a level which doesn't exist are ignored
+    fn validate(autosave: Autosave) -> Option<Autosave> {
+        if autosave.version != AUTOSAVE_VERSION {
+            log::warn!("Autosave format version {} is not supported, ignoring it", autosave.version);
+            return None;
+        }
+        if autosave.next_level() > LEVELS_COUNT {
+            log::warn!("Autosave continues with unknown level {}, ignoring it", autosave.next_level());
+            return None;
+        }
+        Some(autosave)
+    }
+
+    pub fn clear() {
+        match storage::remove(AUTOSAVE_FILE) {
+            Ok(()) | Err(StorageError::NotFound(_)) => (),
+            Err(err) => log::warn!("Failed to clear autosave: {}", err),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// a resumed run takes the level, difficulty and seed of the autosave, a new one is warned that it replaces it
+pub fn resolve_autosave(options: &mut Options, autosave: Option<Autosave>) -> Option<Autosave> {
+    if !options.resume {
+        if let Some(autosave) = autosave {
+            log::warn!(
+                "Clearing a level replaces the autosave of level {}, run with --resume to continue it",
+                autosave.cleared_level
+            );
+        }
+        return None;
+    }
+
+    match autosave {
+        Some(autosave) => {
+            options.level = autosave.next_level();
+            options.difficulty = autosave.difficulty;
+            options.seed = autosave.seed;
+            Some(autosave)
+        }
+        None => {
+            log::warn!("No autosave to resume, starting a new run");
+            None
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn autosave(cleared_level: i32) -> Autosave {
+        Autosave {
+            version: AUTOSAVE_VERSION,
+            cleared_level,
+            ship_snapshot: ShipSnapshot { hp: 50, energy: 20 },
+            score_snapshot: ScoreSnapshot::default(),
+            loadout: LoadoutConfig::default(),
+            difficulty: Difficulty::Nightmare,
+            seed: Some(7),
+        }
+    }
+
+    #[test]
+    fn unknown_version_or_level_is_ignored() {
+        assert!(AutosaveManager::validate(autosave(1)).is_some());
+        assert!(AutosaveManager::validate(Autosave { version: AUTOSAVE_VERSION + 1, ..autosave(1) }).is_none());
+        assert!(AutosaveManager::validate(autosave(LEVELS_COUNT)).is_none());
+    }
+
+    #[test]
+    fn resume_continues_at_next_level() {
+        let mut options = Options { resume: true, ..Options::default() };
+        let resumed = resolve_autosave(&mut options, Some(autosave(1)));
+        assert!(resumed.is_some());
+        assert_eq!(options.level, 2);
+        assert_eq!(options.difficulty, Difficulty::Nightmare);
+        assert_eq!(options.seed, Some(7));
+    }
+
+    #[test]
+    fn new_run_leaves_autosave_to_be_replaced() {
+        let mut options = Options { level: 3, ..Options::default() };
+        assert!(resolve_autosave(&mut options, Some(autosave(1))).is_none());
+        assert_eq!(options.level, 3);
+        assert_eq!(options.difficulty, Difficulty::Normal);
+        assert_eq!(options.seed, None);
+    }
+
+    #[test]
+    fn resume_without_autosave_starts_new_run() {
+        let mut options = Options { resume: true, ..Options::default() };
+        assert!(resolve_autosave(&mut options, None).is_none());
+        assert_eq!(options.level, 1);
+    }
+}
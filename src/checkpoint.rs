@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use crate::run_summary::RunStats;
+use crate::storage::{self, StorageError};
+
+//-----------------------------------------------------------------------------
+
+const CHECKPOINT_FILE: &str = "checkpoint.toml";
+
+//-----------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShipSnapshot {
+    pub hp: i32,
+    pub energy: i32,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScoreSnapshot {
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub damage_taken: i32,
+    pub comets_destroyed: u32,
+    #[serde(default)]
+    pub clean_waves: u32,
+    #[serde(default)]
+    pub bosses_defeated: u32,
+}
+
+impl ScoreSnapshot {
+    pub fn from_stats(stats: &RunStats) -> Self {
+        Self {
+            shots_fired: stats.shots_fired,
+            shots_hit: stats.shots_hit,
+            damage_taken: stats.damage_taken,
+            comets_destroyed: stats.comets_destroyed,
+            clean_waves: stats.clean_waves,
+            bosses_defeated: stats.bosses_defeated,
+        }
+    }
+
+    pub fn to_stats(&self) -> RunStats {
+        RunStats {
+            shots_fired: self.shots_fired,
+            shots_hit: self.shots_hit,
+            damage_taken: self.damage_taken,
+            comets_destroyed: self.comets_destroyed,
+            clean_waves: self.clean_waves,
+            bosses_defeated: self.bosses_defeated,
+            ..RunStats::default()
+        }
+    }
+}
+
+// the boss isn't saved, it respawns at full hp
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub level: i32,
+    // waves cleared so far, the boss comes after the first one
+    pub wave: u32,
+    pub ship_snapshot: ShipSnapshot,
+    pub score_snapshot: ScoreSnapshot,
+}
+
+//-----------------------------------------------------------------------------
+
+pub struct CheckpointManager;
+
+impl CheckpointManager {
+    pub fn save(checkpoint: Checkpoint) {
+        if let Err(err) = storage::save(CHECKPOINT_FILE, &checkpoint) {
+            log::warn!("Failed to save checkpoint: {}", err);
+        }
+    }
+
+    pub fn load() -> Option<Checkpoint> {
+        match storage::load::<Checkpoint>(CHECKPOINT_FILE) {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(StorageError::NotFound(_)) => None,
+            Err(err) => {
+                log::warn!("Failed to load checkpoint: {}", err);
+                None
+            }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
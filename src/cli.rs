@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::difficulty::Difficulty;
+use crate::entities::ship::ShipKind;
+use crate::replay::ReplayHeader;
+use crate::spawners::spawners_impl::{create_level_spawner, LEVELS_COUNT};
+
+//-----------------------------------------------------------------------------
+
+pub const USAGE: &str = "\
+Usage: space [OPTIONS]
+
+Options:
+  --level N             start from level N (1-3)
+  --seed X              seed the random generator
+  --difficulty D        normal, nightmare (every shot kills), dynamic (adapts to the player)
+                        or progressive (each boss killed makes the next one harder)
+  --ship S              standard, tank (more hp, slower) or interceptor (less hp, faster)
+  --godmode             ship ignores damage, the run is marked as assisted
+  --boss-only           skip comets and spawn the boss right away
+  --headless-ticks N    run N updates without a window and exit
+  --portable            keep config and saves next to the executable
+  --no-damage-numbers   hide floating damage numbers
+  --continue            continue from the last checkpoint
+  --resume              start the level after the last autosaved one
+  --mirror              mirror boss patterns and spawns horizontally
+  --deterministic       fixed-point movement, always on for seeded runs
+  --record FILE         save the input of the run to FILE, seeds the run if --seed isn't given
+  --replay FILE         play a recorded run, the level, seed, difficulty and ship come from it
+  --validate-assets     check the levels and bosses, report every problem and exit
+  --wave N              play the waves before N without input and start there, debug-skip-wave builds only
+  --boss-hp N           the boss spawns with N hp, debug-commands builds only
+  --help                show this message";
+
+//-----------------------------------------------------------------------------
+
+pub struct Options {
+    pub level: i32,
+    pub seed: Option<u64>,
+    pub difficulty: Difficulty,
+    pub god_mode: bool,
+    pub boss_only: bool,
+    pub headless_ticks: Option<u32>,
+    pub portable: bool,
+    pub damage_numbers: bool,
+    pub mirror: bool,
+    pub continue_run: bool,
+    pub resume: bool,
+    pub deterministic: bool,
+    pub validate_assets: bool,
+    pub ship: ShipKind,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    #[cfg(feature = "debug-skip-wave")]
+    pub wave: Option<u32>,
+    #[cfg(feature = "debug-commands")]
+    pub boss_hp: Option<i32>,
+}
+
+pub enum CliError {
+    HelpRequested,
+    Invalid(String),
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            seed: None,
+            difficulty: Difficulty::Normal,
+            god_mode: false,
+            boss_only: false,
+            headless_ticks: None,
+            portable: false,
+            damage_numbers: true,
+            mirror: false,
+            continue_run: false,
+            resume: false,
+            deterministic: false,
+            validate_assets: false,
+            ship: ShipKind::Standard,
+            record: None,
+            replay: None,
+            #[cfg(feature = "debug-skip-wave")]
+            wave: None,
+            #[cfg(feature = "debug-commands")]
+            boss_hp: None,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, CliError> {
+    let mut options = Options::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--level" => options.level = parse_value(&arg, args.next())?,
+            "--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+            "--difficulty" => options.difficulty = parse_value(&arg, args.next())?,
+            "--ship" => options.ship = parse_value(&arg, args.next())?,
+            "--record" => options.record = Some(parse_value(&arg, args.next())?),
+            "--replay" => options.replay = Some(parse_value(&arg, args.next())?),
+            #[cfg(feature = "debug-skip-wave")]
+            "--wave" => options.wave = Some(parse_value(&arg, args.next())?),
+            #[cfg(feature = "debug-commands")]
+            "--boss-hp" => options.boss_hp = Some(parse_value(&arg, args.next())?),
+            "--headless-ticks" => options.headless_ticks = Some(parse_value(&arg, args.next())?),
+            "--godmode" => options.god_mode = true,
+            "--boss-only" => options.boss_only = true,
+            "--portable" => options.portable = true,
+            "--no-damage-numbers" => options.damage_numbers = false,
+            "--mirror" => options.mirror = true,
+            "--continue" => options.continue_run = true,
+            "--resume" => options.resume = true,
+            "--deterministic" => options.deterministic = true,
+            "--validate-assets" => options.validate_assets = true,
+            "--help" | "-h" => return Err(CliError::HelpRequested),
+            _ => return Err(CliError::Invalid(format!("unknown argument '{}'", arg))),
+        }
+    }
+
+    validate(&options)?;
+    Ok(options)
+}
+
+// checked again once a resumed run has picked its level
+pub fn validate(options: &Options) -> Result<(), CliError> {
+    if !(1..=LEVELS_COUNT).contains(&options.level) {
+        return Err(CliError::Invalid(format!("level must be in range 1-{}", LEVELS_COUNT)));
+    }
+    if options.replay.is_some() && (options.record.is_some() || options.continue_run || options.resume) {
+        return Err(CliError::Invalid("--replay can't be combined with --record, --continue or --resume".to_string()));
+    }
+    if options.boss_only && !create_level_spawner(options.level).has_boss() {
+        return Err(CliError::Invalid(format!("level {} has no boss, --boss-only can't be used with it", options.level)));
+    }
+    Ok(())
+}
+
+impl Options {
+    // the recorded run is played as it was, whatever the other flags say
+    pub fn apply_replay(&mut self, header: &ReplayHeader) {
+        self.seed = Some(header.seed);
+        self.level = header.level;
+        self.difficulty = header.difficulty;
+        self.ship = header.ship;
+        self.mirror = header.mirror;
+        self.boss_only = header.boss_only;
+    }
+}
+
+fn parse_value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, CliError> {
+    let value = value.ok_or_else(|| CliError::Invalid(format!("missing value for {}", flag)))?;
+    value.parse().map_err(|_| CliError::Invalid(format!("invalid value '{}' for {}", value, flag)))
+}
+
+//-----------------------------------------------------------------------------
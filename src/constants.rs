@@ -14,12 +14,31 @@ pub const COMET_SHARD_MAX_ANGLE_DELTA: i32 = 30;
 pub const SHIP_MAX_HP: i32 = 100;
 pub const SHIP_MOVE_STEP: i32 = 5;
 
+pub const DASH_DISTANCE: i32 = 60;
+pub const DASH_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(1);
+pub const DASH_INVULNERABILITY: std::time::Duration = std::time::Duration::from_millis(100);
+
+pub const SHIP_MAX_ENERGY: i32 = 100;
+pub const ENERGY_REGEN_RATE: i32 = 1;
+pub const ENERGY_KILL_BONUS: i32 = 10;
+pub const BOMB_ENERGY_COST: i32 = 60;
+
+// boss shots passing this close to the ship without hitting it give energy
+pub const GRAZE_DISTANCE: i32 = 20;
+pub const GRAZE_ENERGY: i32 = 2;
+
 pub const MAX_SHOTS: i32 = 15;
 pub const SHOT_SPEED: i32 = 25;
+pub const AUTO_FIRE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+// shot damage falloff, starts after the given distance and never drops below the min percent
+pub const SHOT_FALLOFF_START_DISTANCE: i32 = 300;
+pub const SHOT_FALLOFF_DISTANCE: i32 = 500;
+pub const SHOT_MIN_DAMAGE_PERCENT: f32 = 0.5;
+
+// targets with more max hp get a health bar
+pub const HEALTH_BAR_THRESHOLD: i32 = 3;
 
 // difficulty
 pub const MIN_COMETS: i32 = 10;
 pub const POINTS_TO_ADD_COMET: i32 = 20;
-
-pub const EASY_BOSS_MAX_HP: i32 = 300;
-pub const EASY_BOSS_WIDTH: i32 = 200;
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use crate::timing::duration_to_ticks;
+
+//-----------------------------------------------------------------------------
+
+const MIN_LINE_DURATION: Duration = Duration::from_millis(500);
+const MAX_LINE_DURATION: Duration = Duration::from_secs(15);
+// ends the dialogue even if the lines say otherwise, so it can never lock the level
+const MAX_DIALOGUE_DURATION: Duration = Duration::from_secs(60);
+
+//-----------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+pub struct DialogueLine {
+    pub speaker: &'static str,
+    pub text: &'static str,
+    pub duration: Duration,
+}
+
+#[derive(Debug)]
+pub enum DialogueError {
+    NoLines,
+    EmptyText(usize),
+    BadDuration(usize),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DialogueChange {
+    NextLine,
+    Finished,
+}
+
+// ticks are counted only while the level updates, so the dialogue stands still during the pause
+pub struct Dialogue {
+    lines: Vec<DialogueLine>,
+    current: usize,
+    line_ticks: u64,
+    total_ticks: u64,
+}
+
+impl Dialogue {
+    pub fn new(lines: Vec<DialogueLine>) -> Result<Self, DialogueError> {
+        if lines.is_empty() {
+            return Err(DialogueError::NoLines);
+        }
+        for (idx, line) in lines.iter().enumerate() {
+            if line.text.trim().is_empty() {
+                return Err(DialogueError::EmptyText(idx));
+            }
+            if line.duration < MIN_LINE_DURATION || line.duration > MAX_LINE_DURATION {
+                return Err(DialogueError::BadDuration(idx));
+            }
+        }
+
+        Ok(Self {
+            lines,
+            current: 0,
+            line_ticks: 0,
+            total_ticks: 0,
+        })
+    }
+
+    pub fn line(&self) -> Option<&DialogueLine> {
+        self.lines.get(self.current)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.current >= self.lines.len()
+    }
+
+    pub fn skip(&mut self) {
+        self.current = self.lines.len();
+    }
+
+    pub fn update(&mut self) -> Option<DialogueChange> {
+        if self.finished() {
+            return None;
+        }
+
+        self.line_ticks += 1;
+        self.total_ticks += 1;
+        if self.total_ticks >= Dialogue::to_ticks(MAX_DIALOGUE_DURATION) {
+            self.skip();
+            return Some(DialogueChange::Finished);
+        }
+
+        let line_duration = self.lines[self.current].duration;
+        if self.line_ticks < Dialogue::to_ticks(line_duration) {
+            return None;
+        }
+
+        self.current += 1;
+        self.line_ticks = 0;
+        if self.finished() {
+            Some(DialogueChange::Finished)
+        } else {
+            Some(DialogueChange::NextLine)
+        }
+    }
+
+    fn to_ticks(duration: Duration) -> u64 {
+        duration_to_ticks(duration) as u64
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &'static str, duration: Duration) -> DialogueLine {
+        DialogueLine { speaker: "boss", text, duration }
+    }
+
+    fn run_ticks(dialogue: &mut Dialogue, ticks: i64) -> Vec<DialogueChange> {
+        (0..ticks).filter_map(|_| dialogue.update()).collect()
+    }
+
+    #[test]
+    fn invalid_scripts_are_rejected() {
+        assert!(matches!(Dialogue::new(vec![]), Err(DialogueError::NoLines)));
+        let lines = vec![line("hi", Duration::from_secs(2)), line("  ", Duration::from_secs(2))];
+        assert!(matches!(Dialogue::new(lines), Err(DialogueError::EmptyText(1))));
+        let lines = vec![line("hi", Duration::from_millis(100))];
+        assert!(matches!(Dialogue::new(lines), Err(DialogueError::BadDuration(0))));
+        let lines = vec![line("hi", Duration::from_secs(2)), line("bye", Duration::from_secs(16))];
+        assert!(matches!(Dialogue::new(lines), Err(DialogueError::BadDuration(1))));
+    }
+
+    #[test]
+    fn lines_follow_their_durations() {
+        let lines = vec![line("first", Duration::from_secs(2)), line("second", Duration::from_secs(1))];
+        let mut dialogue = Dialogue::new(lines).unwrap();
+        assert_eq!(dialogue.line().unwrap().text, "first");
+
+        let line_ticks = duration_to_ticks(Duration::from_secs(2));
+        assert!(run_ticks(&mut dialogue, line_ticks - 1).is_empty());
+        assert_eq!(dialogue.update(), Some(DialogueChange::NextLine));
+        assert_eq!(dialogue.line().unwrap().text, "second");
+
+        let line_ticks = duration_to_ticks(Duration::from_secs(1));
+        assert!(run_ticks(&mut dialogue, line_ticks - 1).is_empty());
+        assert_eq!(dialogue.update(), Some(DialogueChange::Finished));
+        assert!(dialogue.finished());
+        assert!(dialogue.line().is_none());
+        assert_eq!(dialogue.update(), None);
+    }
+
+    #[test]
+    fn long_script_is_cut_at_max_duration() {
+        let lines = vec![line("long", MAX_LINE_DURATION); 5];
+        let mut dialogue = Dialogue::new(lines).unwrap();
+
+        let max_ticks = duration_to_ticks(MAX_DIALOGUE_DURATION);
+        let changes = run_ticks(&mut dialogue, max_ticks);
+        assert_eq!(changes.last(), Some(&DialogueChange::Finished));
+        assert!(dialogue.finished());
+    }
+
+    #[test]
+    fn skip_finishes_at_once() {
+        let mut dialogue = Dialogue::new(vec![line("hi", Duration::from_secs(2))]).unwrap();
+        dialogue.skip();
+        assert!(dialogue.finished());
+        assert_eq!(dialogue.update(), None);
+    }
+}
@@ -0,0 +1,68 @@
+pub mod dialogue;
+
+use std::time::Duration;
+
+use crate::timing::GameInstant;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BackgroundConfig {
+    // the level stays visible behind the text box
+    Level,
+    Starfield,
+    Black,
+}
+
+#[derive(Clone, Debug)]
+pub struct CutsceneFrame {
+    pub text: &'static str,
+    pub duration: Duration,
+    pub background: BackgroundConfig,
+}
+
+//-----------------------------------------------------------------------------
+
+pub struct CutscenePlayer {
+    frames: Vec<CutsceneFrame>,
+    current: usize,
+    frame_started: GameInstant,
+}
+
+impl CutscenePlayer {
+    pub fn new(frames: Vec<CutsceneFrame>) -> Self {
+        Self {
+            frames,
+            current: 0,
+            frame_started: GameInstant::now(),
+        }
+    }
+
+    pub fn frame(&self) -> Option<&CutsceneFrame> {
+        self.frames.get(self.current)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.current >= self.frames.len()
+    }
+
+    // moves to the next frame once the current one was shown long enough, true when all frames are done
+    pub fn advance(&mut self) -> bool {
+        let frame_done = self.frame().is_none_or(|frame| self.frame_started.elapsed() >= frame.duration);
+        if frame_done && !self.finished() {
+            self.next_frame();
+        }
+        self.finished()
+    }
+
+    pub fn next_frame(&mut self) {
+        self.current += 1;
+        self.frame_started = GameInstant::now();
+    }
+
+    pub fn skip(&mut self) {
+        self.current = self.frames.len();
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,291 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Normal,
+    Nightmare,
+    Dynamic,
+    Progressive,
+}
+
+impl FromStr for Difficulty {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Difficulty::Normal),
+            "nightmare" => Ok(Difficulty::Nightmare),
+            "dynamic" => Ok(Difficulty::Dynamic),
+            "progressive" => Ok(Difficulty::Progressive),
+            _ => Err(()),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// every shot is a one-hit kill, both for the ship and for the boss
+#[derive(Copy, Clone)]
+pub struct NightmareMode {
+    pub active: bool,
+}
+
+impl NightmareMode {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self { active: difficulty == Difficulty::Nightmare }
+    }
+
+    pub fn boss_shot_damage(&self, damage: i32) -> i32 {
+        if self.active { i32::MAX } else { damage }
+    }
+
+    pub fn player_shot_damage(&self, damage: i32, boss_max_hp: i32) -> i32 {
+        if self.active { boss_max_hp } else { damage }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+const ADAPTIVE_MIN_MULTIPLIER: f32 = 0.85;
+const ADAPTIVE_MAX_MULTIPLIER: f32 = 1.15;
+const ADAPTIVE_STEP: f32 = 0.05;
+// one step per 20 seconds at most
+const ADAPTIVE_STEP_TICKS: u32 = 20 * 60;
+const ADAPTIVE_DEATH_FREEZE_TICKS: u32 = 10 * 60;
+// older events matter less, halves in about 10 seconds
+const ADAPTIVE_PERFORMANCE_DECAY: f32 = 0.9989;
+const ADAPTIVE_PERFORMANCE_THRESHOLD: f32 = 20.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PerformanceEvent {
+    DamageTaken(i32),
+    Death,
+    BossDamageDealt(i32),
+    Graze,
+}
+
+// nudges the boss speed, shot speed and shooting intervals by how well the player is doing, ticks are counted instead of the
+// wall clock so the same event stream always gives the same multiplier
+pub struct AdaptiveDifficulty {
+    active: bool,
+    multiplier: f32,
+    performance: f32,
+    ticks_since_step: u32,
+    frozen_ticks: u32,
+}
+
+impl AdaptiveDifficulty {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            active: difficulty == Difficulty::Dynamic,
+            multiplier: 1.0,
+            performance: 0.0,
+            ticks_since_step: 0,
+            frozen_ticks: 0,
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    pub fn multiplier(&self) -> f32 {
+        self.multiplier
+    }
+
+    pub fn reset(&mut self) {
+        self.multiplier = 1.0;
+        self.performance = 0.0;
+        self.ticks_since_step = 0;
+        self.frozen_ticks = 0;
+    }
+
+    pub fn record(&mut self, event: PerformanceEvent) {
+        if !self.active {
+            return;
+        }
+        self.performance += match event {
+            PerformanceEvent::DamageTaken(damage) => -(damage as f32),
+            PerformanceEvent::Death => {
+                self.frozen_ticks = ADAPTIVE_DEATH_FREEZE_TICKS;
+                -100.0
+            }
+            PerformanceEvent::BossDamageDealt(damage) => damage as f32 * 0.5,
+            PerformanceEvent::Graze => 2.0,
+        };
+    }
+
+    // the multiplier stays as is while frozen, e.g. during the boss intro
+    pub fn update(&mut self, frozen: bool) {
+        if !self.active {
+            return;
+        }
+
+        self.performance *= ADAPTIVE_PERFORMANCE_DECAY;
+        if frozen || self.frozen_ticks > 0 {
+            self.frozen_ticks = self.frozen_ticks.saturating_sub(1);
+            return;
+        }
+
+        self.ticks_since_step += 1;
+        if self.ticks_since_step < ADAPTIVE_STEP_TICKS {
+            return;
+        }
+        self.ticks_since_step = 0;
+
+        let step = if self.performance > ADAPTIVE_PERFORMANCE_THRESHOLD {
+            ADAPTIVE_STEP
+        } else if self.performance < -ADAPTIVE_PERFORMANCE_THRESHOLD {
+            -ADAPTIVE_STEP
+        } else {
+            0.0
+        };
+        self.multiplier = (self.multiplier + step).clamp(ADAPTIVE_MIN_MULTIPLIER, ADAPTIVE_MAX_MULTIPLIER);
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+const PROGRESSIVE_DIFFICULTY_FACTOR: f32 = 1.15;
+
+// every boss killed in the run makes the following ones harder, for good
+#[derive(Copy, Clone, Debug)]
+pub struct ProgressiveDifficulty {
+    active: bool,
+    run_difficulty: f32,
+}
+
+impl ProgressiveDifficulty {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self { active: difficulty == Difficulty::Progressive, run_difficulty: 1.0 }
+    }
+
+    pub fn run_difficulty(&self) -> f32 {
+        self.run_difficulty
+    }
+
+    pub fn record_boss_kill(&mut self) {
+        if self.active {
+            self.run_difficulty *= PROGRESSIVE_DIFFICULTY_FACTOR;
+        }
+    }
+
+    // a resumed run picks up where it stopped
+    pub fn restore(&mut self, bosses_defeated: u32) {
+        self.run_difficulty = 1.0;
+        for _ in 0..bosses_defeated {
+            self.record_boss_kill();
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::boss::easy_stages::SimpleShootingDown;
+    use crate::entities::boss::test_support::test_boss::TestBoss;
+    use crate::entities::destroyable::Destroyable;
+    use crate::entities::shape::Shape;
+    use crate::entities::ship::Ship;
+    use crate::math::Vec2i;
+
+    fn run_ticks(adaptive: &mut AdaptiveDifficulty, ticks: u32, event: Option<PerformanceEvent>) {
+        for _ in 0..ticks {
+            if let Some(event) = event {
+                adaptive.record(event);
+            }
+            adaptive.update(false);
+        }
+    }
+
+    #[test]
+    fn nightmare_boss_shot_kills_ship() {
+        let nightmare = NightmareMode::new(Difficulty::Nightmare);
+        let mut ship = Ship::new(Shape::new(Vec2i::zero(), 10), 100, 100);
+        ship.hit(nightmare.boss_shot_damage(1));
+        assert!(!ship.alive());
+    }
+
+    #[test]
+    fn nightmare_player_shot_kills_boss() {
+        let nightmare = NightmareMode::new(Difficulty::Nightmare);
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 1000);
+        boss.take_hit(nightmare.player_shot_damage(1, boss.hp_max()));
+        assert!(!boss.alive());
+    }
+
+    #[test]
+    fn normal_shots_keep_their_damage() {
+        let normal = NightmareMode::new(Difficulty::Normal);
+        assert_eq!(normal.boss_shot_damage(10), 10);
+        assert_eq!(normal.player_shot_damage(10, 1000), 10);
+    }
+
+    #[test]
+    fn multiplier_stays_within_bounds() {
+        let mut adaptive = AdaptiveDifficulty::new(Difficulty::Dynamic);
+        run_ticks(&mut adaptive, ADAPTIVE_STEP_TICKS * 20, Some(PerformanceEvent::Graze));
+        assert_eq!(adaptive.multiplier(), ADAPTIVE_MAX_MULTIPLIER);
+
+        run_ticks(&mut adaptive, ADAPTIVE_STEP_TICKS * 20, Some(PerformanceEvent::DamageTaken(10)));
+        assert_eq!(adaptive.multiplier(), ADAPTIVE_MIN_MULTIPLIER);
+    }
+
+    #[test]
+    fn multiplier_changes_one_step_per_interval() {
+        let mut adaptive = AdaptiveDifficulty::new(Difficulty::Dynamic);
+        run_ticks(&mut adaptive, ADAPTIVE_STEP_TICKS - 1, Some(PerformanceEvent::Graze));
+        assert_eq!(adaptive.multiplier(), 1.0);
+
+        run_ticks(&mut adaptive, 1, Some(PerformanceEvent::Graze));
+        assert_eq!(adaptive.multiplier(), 1.0 + ADAPTIVE_STEP);
+
+        run_ticks(&mut adaptive, ADAPTIVE_STEP_TICKS - 1, Some(PerformanceEvent::Graze));
+        assert_eq!(adaptive.multiplier(), 1.0 + ADAPTIVE_STEP);
+    }
+
+    #[test]
+    fn multiplier_frozen_after_death() {
+        let mut adaptive = AdaptiveDifficulty::new(Difficulty::Dynamic);
+        adaptive.record(PerformanceEvent::Death);
+        run_ticks(&mut adaptive, ADAPTIVE_DEATH_FREEZE_TICKS + ADAPTIVE_STEP_TICKS - 1, None);
+        assert_eq!(adaptive.multiplier(), 1.0);
+    }
+
+    #[test]
+    fn multiplier_frozen_during_intro() {
+        let mut adaptive = AdaptiveDifficulty::new(Difficulty::Dynamic);
+        for _ in 0..ADAPTIVE_STEP_TICKS * 2 {
+            adaptive.record(PerformanceEvent::Graze);
+            adaptive.update(true);
+        }
+        assert_eq!(adaptive.multiplier(), 1.0);
+    }
+
+    #[test]
+    fn same_events_give_same_multiplier() {
+        let events = [PerformanceEvent::Graze, PerformanceEvent::BossDamageDealt(7), PerformanceEvent::DamageTaken(3)];
+        let run = || {
+            let mut adaptive = AdaptiveDifficulty::new(Difficulty::Dynamic);
+            for tick in 0..ADAPTIVE_STEP_TICKS as usize * 5 {
+                adaptive.record(events[tick % events.len()]);
+                adaptive.update(false);
+            }
+            adaptive.multiplier()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn inactive_unless_dynamic() {
+        let mut adaptive = AdaptiveDifficulty::new(Difficulty::Normal);
+        run_ticks(&mut adaptive, ADAPTIVE_STEP_TICKS * 2, Some(PerformanceEvent::Graze));
+        assert_eq!(adaptive.multiplier(), 1.0);
+    }
+}
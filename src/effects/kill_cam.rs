@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use crate::timing::GameInstant;
+
+//-----------------------------------------------------------------------------
+
+pub const KILL_CAM_DURATION: Duration = Duration::from_millis(500);
+const KILL_CAM_EASE_IN: Duration = Duration::from_millis(100);
+const KILL_CAM_EASE_OUT: Duration = Duration::from_millis(200);
+pub const KILL_CAM_TIME_SCALE: f32 = 0.2;
+
+//-----------------------------------------------------------------------------
+
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// time scale at the given point of the beat: eases down, holds and eases back once the beat is over
+pub fn kill_cam_time_scale(elapsed: Duration) -> f32 {
+    let slowdown = 1.0 - KILL_CAM_TIME_SCALE;
+    if elapsed < KILL_CAM_EASE_IN {
+        return 1.0 - slowdown * smoothstep(elapsed.as_secs_f32() / KILL_CAM_EASE_IN.as_secs_f32());
+    }
+    if elapsed < KILL_CAM_DURATION {
+        return KILL_CAM_TIME_SCALE;
+    }
+    let t = (elapsed - KILL_CAM_DURATION).as_secs_f32() / KILL_CAM_EASE_OUT.as_secs_f32();
+    KILL_CAM_TIME_SCALE + slowdown * smoothstep(t)
+}
+
+//-----------------------------------------------------------------------------
+
+// dramatic beat on the boss killing blow, the game clock stands still while paused so the pause doesn't count
+pub struct KillCam {
+    started: GameInstant,
+}
+
+impl KillCam {
+    pub fn new() -> Self {
+        Self { started: GameInstant::now() }
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        kill_cam_time_scale(self.started.elapsed())
+    }
+
+    pub fn finished(&self) -> bool {
+        self.started.elapsed() >= KILL_CAM_DURATION + KILL_CAM_EASE_OUT
+    }
+
+    // the whole beat, easing back included
+    pub fn total_duration() -> Duration {
+        KILL_CAM_DURATION + KILL_CAM_EASE_OUT
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::{advance_game_clock, duration_to_ticks};
+
+    fn scale_at(millis: u64) -> f32 {
+        kill_cam_time_scale(Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn time_scale_curve() {
+        assert_eq!(scale_at(0), 1.0);
+        assert!((scale_at(50) - 0.6).abs() < 1e-6);
+        assert_eq!(scale_at(100), KILL_CAM_TIME_SCALE);
+        assert_eq!(scale_at(300), KILL_CAM_TIME_SCALE);
+        assert_eq!(scale_at(500), KILL_CAM_TIME_SCALE);
+        assert!((scale_at(600) - 0.6).abs() < 1e-6);
+        assert_eq!(scale_at(700), 1.0);
+        assert_eq!(scale_at(2000), 1.0);
+    }
+
+    #[test]
+    fn finishes_after_easing_back() {
+        let kill_cam = KillCam::new();
+        for _ in 1..duration_to_ticks(KillCam::total_duration()) {
+            advance_game_clock();
+        }
+        assert!(!kill_cam.finished());
+        advance_game_clock();
+        assert!(kill_cam.finished());
+        assert_eq!(kill_cam.time_scale(), 1.0);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod kill_cam;
+pub mod target_lock;
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use crate::timing::GameInstant;
+
+//-----------------------------------------------------------------------------
+
+pub const TARGET_LOCK_FADE_DURATION: Duration = Duration::from_millis(400);
+
+// degrees per tick
+const ROTATION_SPEED: f32 = 3.0;
+// around the ship, relative to its width
+const RADIUS_FACTOR: f32 = 0.9;
+
+//-----------------------------------------------------------------------------
+
+// reticle around the ship while the boss is tracking it
+pub struct TargetLock {
+    radius: f32,
+    rotation: f32,
+    active: bool,
+    fading_since: Option<GameInstant>,
+}
+
+impl TargetLock {
+    pub fn new() -> Self {
+        Self { radius: 0.0, rotation: 0.0, active: false, fading_since: None }
+    }
+
+    pub fn activate(&mut self, ship_width: i32) {
+        self.radius = ship_width as f32 * RADIUS_FACTOR;
+        self.active = true;
+        self.fading_since = None;
+    }
+
+    pub fn release(&mut self) {
+        if self.active {
+            self.active = false;
+            self.fading_since = Some(GameInstant::now());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = TargetLock::new();
+    }
+
+    pub fn update(&mut self) {
+        if self.visible() {
+            self.rotation = (self.rotation + ROTATION_SPEED).rem_euclid(360.0);
+        }
+    }
+
+    pub fn visible(&self) -> bool {
+        self.opacity() > 0.0
+    }
+
+    // 1.0 while locked, then fades out
+    pub fn opacity(&self) -> f32 {
+        if self.active {
+            return 1.0;
+        }
+        match self.fading_since {
+            Some(since) => 1.0 - (since.elapsed().as_secs_f32() / TARGET_LOCK_FADE_DURATION.as_secs_f32()).min(1.0),
+            None => 0.0,
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,120 @@
+//-----------------------------------------------------------------------------
+
+pub const CLIP_IDLE: &str = "idle";
+pub const CLIP_BANK_LEFT: &str = "bank_left";
+pub const CLIP_BANK_RIGHT: &str = "bank_right";
+pub const CLIP_CHARGING: &str = "charging";
+pub const CLIP_DEATH: &str = "death";
+
+//-----------------------------------------------------------------------------
+
+pub struct AnimationClip {
+    pub name: &'static str,
+    pub frames: &'static [u32],
+    pub frame_ticks: i32,
+    pub looping: bool,
+}
+
+impl AnimationClip {
+    // one pass through all the frames
+    pub fn duration_ticks(&self) -> i32 {
+        self.frame_ticks.max(1) * self.frames.len() as i32
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// advanced with the simulation ticks, the renderer only asks for the current frame
+pub struct Animator {
+    clips: &'static [AnimationClip],
+    current: usize,
+    elapsed_ticks: i32,
+}
+
+//-----------------------------------------------------------------------------
+
+impl Animator {
+    pub fn new(clips: &'static [AnimationClip]) -> Self {
+        assert!(!clips.is_empty(), "Animator needs at least one clip");
+        Self {
+            clips,
+            current: 0,
+            elapsed_ticks: 0,
+        }
+    }
+
+    // requesting the clip which is already playing doesn't restart it, unknown clips are ignored
+    pub fn play(&mut self, name: &str) {
+        if self.current_clip().name == name {
+            return;
+        }
+
+        let Some(idx) = self.clips.iter().position(|clip| clip.name == name) else {
+            log::warn!("Unknown animation clip {}", name);
+            return;
+        };
+        self.current = idx;
+        self.elapsed_ticks = 0;
+    }
+
+    pub fn update(&mut self) {
+        if !self.finished() {
+            self.elapsed_ticks += 1;
+        }
+    }
+
+    pub fn current_clip(&self) -> &AnimationClip {
+        &self.clips[self.current]
+    }
+
+    pub fn current_frame(&self) -> u32 {
+        let clip = self.current_clip();
+        let frame_idx = (self.elapsed_ticks / clip.frame_ticks.max(1)) as usize;
+        let frame_idx = if clip.looping {
+            frame_idx % clip.frames.len()
+        } else {
+            frame_idx.min(clip.frames.len() - 1)
+        };
+        clip.frames[frame_idx]
+    }
+
+    // looping clips never finish
+    pub fn finished(&self) -> bool {
+        let clip = self.current_clip();
+        !clip.looping && self.elapsed_ticks >= clip.duration_ticks()
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIPS: &[AnimationClip] = &[
+        AnimationClip { name: CLIP_IDLE, frames: &[0, 1], frame_ticks: 2, looping: true },
+        AnimationClip { name: CLIP_DEATH, frames: &[5, 6], frame_ticks: 2, looping: false },
+    ];
+
+    #[test]
+    fn unknown_clip_is_ignored() {
+        let mut animator = Animator::new(CLIPS);
+        animator.update();
+        animator.play(CLIP_CHARGING);
+        assert_eq!(animator.current_clip().name, CLIP_IDLE);
+        animator.update();
+        assert_eq!(animator.current_frame(), 1);
+    }
+
+    #[test]
+    fn one_shot_clip_finishes_on_last_frame() {
+        let mut animator = Animator::new(CLIPS);
+        animator.play(CLIP_DEATH);
+        for _ in 0..CLIPS[1].duration_ticks() {
+            assert!(!animator.finished());
+            animator.update();
+        }
+        assert!(animator.finished());
+        assert_eq!(animator.current_frame(), 6);
+    }
+}
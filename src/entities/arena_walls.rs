@@ -0,0 +1,82 @@
+use crate::math::{Rect, Vec2i};
+
+//-----------------------------------------------------------------------------
+
+// about 1s at 60 ticks per second
+const SLIDE_TICKS: i32 = 60;
+const WALL_THICKNESS: i32 = 4;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum WallsState {
+    Closing,
+    Closed,
+    Opening,
+}
+
+// two barriers sliding in from the screen sides for a boss fight
+pub struct ArenaWalls {
+    // part of the screen width covered by each wall when fully closed
+    fraction: f32,
+    state: WallsState,
+    tick: i32,
+}
+
+impl ArenaWalls {
+    pub fn new(fraction: f32) -> Self {
+        Self { fraction: fraction.clamp(0.0, 0.45), state: WallsState::Closing, tick: 0 }
+    }
+
+    pub fn open(&mut self) {
+        if self.state != WallsState::Opening {
+            // continues from the current position if the walls are still closing
+            self.tick = SLIDE_TICKS - self.tick.min(SLIDE_TICKS);
+            self.state = WallsState::Opening;
+        }
+    }
+
+    pub fn update(&mut self) {
+        if self.state == WallsState::Closed {
+            return;
+        }
+        self.tick += 1;
+        if self.tick >= SLIDE_TICKS && self.state == WallsState::Closing {
+            self.state = WallsState::Closed;
+        }
+    }
+
+    // true once the walls slid away completely
+    pub fn gone(&self) -> bool {
+        self.state == WallsState::Opening && self.tick >= SLIDE_TICKS
+    }
+
+    // 0.0 fully open, 1.0 fully closed
+    fn progress(&self) -> f32 {
+        let slid = self.tick.min(SLIDE_TICKS) as f32 / SLIDE_TICKS as f32;
+        match self.state {
+            WallsState::Closing => slid,
+            WallsState::Closed => 1.0,
+            WallsState::Opening => 1.0 - slid,
+        }
+    }
+
+    // how much of the screen each wall currently covers, in pixels
+    pub fn inset(&self, screen_width: i32) -> i32 {
+        (screen_width as f32 * self.fraction * self.progress()).round() as i32
+    }
+
+    // the visible barriers, at the inner edges of the covered area
+    pub fn wall_rects(&self, screen_rect: &Rect) -> [Rect; 2] {
+        let inset = self.inset(screen_rect.bottom_right.x - screen_rect.top_left.x);
+        let left_x = screen_rect.top_left.x + inset;
+        let right_x = screen_rect.bottom_right.x - inset;
+        let (top, bottom) = (screen_rect.top_left.y, screen_rect.bottom_right.y);
+        [
+            Rect { top_left: Vec2i { x: left_x - WALL_THICKNESS, y: top }, bottom_right: Vec2i { x: left_x, y: bottom } },
+            Rect { top_left: Vec2i { x: right_x, y: top }, bottom_right: Vec2i { x: right_x + WALL_THICKNESS, y: bottom } },
+        ]
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -1,13 +1,78 @@
 pub mod boss_stages;
 pub mod easy_stages;
+pub mod hard_stages;
+pub mod health_view;
+pub mod kinetic_barrier;
+pub mod laser_beam;
 pub mod stage_factories;
+pub mod stunned_stage;
+#[cfg(test)]
+pub mod test_support;
+pub mod wall_bounce;
+pub mod weapon_arm;
 
-use boss_stages::{BossStage, BossStagesFactory};
+use boss_stages::{BossStage, BossStagesFactory, BossTuning, ShotProfile, StageName};
+use health_view::BossHealthView;
+use kinetic_barrier::{KineticBarrier, KINETIC_BARRIER_ANGULAR_SPEED, KINETIC_BARRIER_MAX_HP};
+use laser_beam::LaserBeam;
+use stunned_stage::StunnedStage;
+use weapon_arm::{WeaponArm, WEAPON_ARM_MAX_HP, WEAPON_ARM_WIDTH};
 
 use super::shape::{Shape, Shaped};
 use super::ship::Ship;
 use super::shot::Shot;
+use super::animator::{AnimationClip, Animator, CLIP_CHARGING, CLIP_DEATH, CLIP_IDLE};
 use super::destroyable::Destroyable;
+use super::flash::{FlashState, Tint};
+use super::patterns::Pattern;
+
+use crate::globals::{mirror_mode, screen_rect};
+use crate::power_ups::PowerUpKind;
+use crate::timing::{Cooldown, GameInstant};
+#[cfg(feature = "hot-reload")]
+use crate::tuning::Tuning;
+use crate::math::{Rect, Vec2i};
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// damage taken within the window which stuns the boss, five regular shots in a row
+const STUN_DAMAGE_THRESHOLD: i32 = 50;
+const STUN_DAMAGE_WINDOW: Duration = Duration::from_secs(1);
+const STUN_COOLDOWN: Duration = Duration::from_secs(8);
+const MAX_DAMAGE_MARKS: usize = 24;
+// the last stage moves and shoots this much faster
+const ENRAGE_MULTIPLIER: f32 = 1.25;
+// the red tint fades in over half a second
+const ENRAGE_TINT_RAMP_TICKS: f32 = 30.0;
+
+#[derive(Copy, Clone, Debug)]
+pub enum BossMuzzle {
+    Center,
+    LeftWing,
+    RightWing,
+}
+
+impl BossMuzzle {
+    // relative to the boss shape so it follows the boss and scales with its width
+    pub fn position(&self, boss_shape: &Shape) -> Vec2i {
+        let width = boss_shape.width();
+        let offset = match self {
+            BossMuzzle::Center => Vec2i { x: width / 2, y: width },
+            BossMuzzle::LeftWing => Vec2i { x: width / 8, y: width * 3 / 4 },
+            BossMuzzle::RightWing => Vec2i { x: width * 7 / 8, y: width * 3 / 4 },
+        };
+        // the wings swap places in mirror mode
+        let offset = if mirror_mode() { Vec2i { x: width - offset.x, y: offset.y } } else { offset };
+        (boss_shape.pos() + offset).clamp_to_rect(&screen_rect(), 0)
+    }
+}
+
+const BOSS_CLIPS: &[AnimationClip] = &[
+    AnimationClip { name: CLIP_IDLE, frames: &[0, 1, 2, 1], frame_ticks: 8, looping: true },
+    AnimationClip { name: CLIP_CHARGING, frames: &[3, 4], frame_ticks: 3, looping: true },
+    AnimationClip { name: CLIP_DEATH, frames: &[5, 6, 7, 8, 9], frame_ticks: 6, looping: false },
+];
 
 pub struct Boss {
     shape: Shape,
@@ -15,9 +80,30 @@ pub struct Boss {
     stage_factory: Box<dyn BossStagesFactory>,
     stage: Box<dyn BossStage>,
     stage_idx: i32,
+    stage_history: Vec<(StageName, GameInstant)>,
 
     hp: i32,
     max_hp: i32,
+    difficulty_multiplier: f32,
+    enrage_multiplier: f32,
+    enrage_tint: f32,
+    intro: bool,
+    interrupted_stage: Option<Box<dyn BossStage>>,
+    stun_cooldown: Cooldown,
+    // hits within the last STUN_DAMAGE_WINDOW, oldest first
+    recent_hits: VecDeque<(GameInstant, i32)>,
+    predictability_sum: f32,
+    predictability_ticks: u32,
+    // impact points relative to the boss position, oldest first
+    damage_marks: VecDeque<Vec2i>,
+
+    health_view: BossHealthView,
+    arm_detached: bool,
+    // kinetic barriers are still up
+    shielded: bool,
+    tuning: BossTuning,
+    flash: FlashState,
+    animator: Animator,
 }
 
 impl Shaped for Boss {
@@ -39,27 +125,787 @@ impl Destroyable for Boss {
     fn hp_max(&self) -> i32 {
         self.max_hp
     }
+
+    fn flash(&self) -> &FlashState {
+        &self.flash
+    }
+    fn flash_mut(&mut self) -> &mut FlashState {
+        &mut self.flash
+    }
 }
 
 impl Boss {
     pub fn new(shape: Shape, max_hp: i32, stage_factory: Box<dyn BossStagesFactory>) -> Boss {
         let stage_idx = 0;
         let initial_stage = stage_factory.create(stage_idx);
+        let health_view = BossHealthView::new(stage_factory.hp_thresholds());
+        let now = GameInstant::now();
+        let stage_history = vec![(initial_stage.name(), now)];
         Boss{
             shape,
             stage_factory,
             stage: initial_stage,
             stage_idx,
+            stage_history,
             hp: max_hp,
-            max_hp
+            max_hp,
+            difficulty_multiplier: 1.0,
+            enrage_multiplier: 1.0,
+            enrage_tint: 0.0,
+            intro: false,
+            interrupted_stage: None,
+            stun_cooldown: Cooldown::started_at(STUN_COOLDOWN, now - STUN_COOLDOWN),
+            recent_hits: VecDeque::new(),
+            predictability_sum: 0.0,
+            predictability_ticks: 0,
+            damage_marks: VecDeque::with_capacity(MAX_DAMAGE_MARKS),
+            health_view,
+            arm_detached: false,
+            shielded: false,
+            tuning: BossTuning::default(),
+            flash: FlashState::new(),
+            animator: Animator::new(BOSS_CLIPS),
         }
     }
 
+    // horizontally centered, right above the visible area; the width is needed before the boss exists
+    pub fn spawn_position(screen_rect: &Rect, width: i32) -> Vec2i {
+        let top_center = screen_rect.top_center();
+        Vec2i { x: top_center.x - width / 2, y: top_center.y - width }
+    }
+
     pub fn fly(&mut self, ship: &Ship) {
-        self.stage.update_pos(&mut self.shape, &ship)
+        let old_pos = self.shape.pos();
+        self.stage.update_pos(&mut self.shape, ship);
+
+        self.predictability_sum += self.stage.movement_predictability().clamp(0.0, 1.0);
+        self.predictability_ticks += 1;
+
+        let factor = self.stage.speed_factor() * self.speed_multiplier();
+        if factor != 1.0 {
+            let delta = self.shape.pos() - old_pos;
+            let scaled_delta = Vec2i {
+                x: (delta.x as f32 * factor).round() as i32,
+                y: (delta.y as f32 * factor).round() as i32,
+            };
+            self.shape.set_pos(old_pos + scaled_delta);
+        }
+    }
+
+    // averaged over the ticks the boss has been moving
+    pub fn average_predictability(&self) -> f32 {
+        if self.predictability_ticks == 0 {
+            return 1.0;
+        }
+        self.predictability_sum / self.predictability_ticks as f32
+    }
+
+    pub fn difficulty_multiplier(&self) -> f32 {
+        self.difficulty_multiplier
+    }
+    // a harder boss also shoots more often
+    pub fn set_difficulty_multiplier(&mut self, multiplier: f32) {
+        self.difficulty_multiplier = multiplier;
+        let scale = 1.0 / multiplier.max(f32::EPSILON);
+        if self.tuning.shot_interval_scale != scale {
+            self.tuning.shot_interval_scale = scale;
+            self.stage.set_tuning(self.tuning);
+        }
+    }
+
+    // the boss moves and shoots faster for the rest of the fight
+    pub fn enrage(&mut self) {
+        self.enrage_multiplier = ENRAGE_MULTIPLIER;
+    }
+
+    pub fn is_enraged(&self) -> bool {
+        self.enrage_multiplier > 1.0
+    }
+
+    // 0..1, how far the red tint of the enrage has faded in
+    pub fn enrage_tint(&self) -> f32 {
+        self.enrage_tint
+    }
+
+    fn speed_multiplier(&self) -> f32 {
+        self.difficulty_multiplier * self.enrage_multiplier
     }
 
     pub fn shoot(&mut self, ship: &Ship) -> Option<Vec<Shot>> {
-        self.stage.shoot(&self.shape, &ship)
+        if self.intro {
+            return None;
+        }
+        let mut shots = self.stage.shoot(&self.shape, self.hp_percent(), ship)?;
+        let multiplier = self.speed_multiplier();
+        if multiplier != 1.0 {
+            shots.iter_mut().for_each(|shot| shot.scale_speed(multiplier));
+        }
+        Some(shots)
+    }
+
+    // shot speed and width the current stage fires with, the difficulty included
+    pub fn effective_shot_profile(&self) -> ShotProfile {
+        let profile = self.stage.shot_profile();
+        let speed = ((profile.speed() as f32 * self.difficulty_multiplier).round() as i32).max(1);
+        ShotProfile::new(speed, profile.width())
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.stage_factory.boss_name()
+    }
+
+    pub fn stage_factory(&self) -> &dyn BossStagesFactory {
+        self.stage_factory.as_ref()
+    }
+
+    // the same boss made anew with its stages replaced, only for a boss which has just spawned
+    #[cfg(feature = "hot-reload")]
+    pub fn with_stage_factory(self, wrap: impl FnOnce(Box<dyn BossStagesFactory>) -> Box<dyn BossStagesFactory>) -> Boss {
+        Boss::new(self.shape, self.max_hp, wrap(self.stage_factory))
+    }
+
+    #[cfg(feature = "hot-reload")]
+    pub fn apply_tuning(&mut self, tuning: &Tuning) {
+        if let Some(stage_tuning) = tuning.stage(self.stage.name()) {
+            self.stage.apply_stage_tuning(stage_tuning);
+            self.stage.set_tuning(self.tuning);
+        }
+    }
+
+    pub fn arena_walls(&self) -> Option<f32> {
+        self.stage_factory.arena_walls()
+    }
+
+    // the arm comes off the left wing once, when the hp drops below the threshold
+    pub fn detach_arm(&mut self) -> Option<WeaponArm> {
+        let threshold = self.stage_factory.weapon_arm_threshold()?;
+        if self.arm_detached || self.intro || !self.alive() || self.hp_percent() >= threshold {
+            return None;
+        }
+
+        self.arm_detached = true;
+        let wing = BossMuzzle::LeftWing.position(&self.shape);
+        Some(WeaponArm::new(Shape::new(wing - WEAPON_ARM_WIDTH / 2, WEAPON_ARM_WIDTH), WEAPON_ARM_MAX_HP))
+    }
+
+    // evenly spaced around the boss, raised once when the fight starts
+    pub fn raise_barriers(&self) -> Vec<KineticBarrier> {
+        let count = self.stage_factory.kinetic_barriers();
+        if count == 0 {
+            return vec![];
+        }
+        let center = self.shape.center();
+        let radius = self.shape.width();
+        Pattern::Ring { count }.angles(center, center).into_iter()
+            .map(|angle| KineticBarrier::new(center, radius, angle, KINETIC_BARRIER_ANGULAR_SPEED, KINETIC_BARRIER_MAX_HP))
+            .collect()
+    }
+
+    pub fn set_shielded(&mut self, shielded: bool) {
+        self.shielded = shielded;
+    }
+
+    pub fn on_arm_destroyed(&mut self) {
+        self.tuning.arm_lost = true;
+        self.stage.set_tuning(self.tuning);
+    }
+
+    // only a boss killed in its last stage drops anything
+    pub fn drop_powerup(&self) -> Option<PowerUpKind> {
+        if !self.in_last_stage() {
+            return None;
+        }
+        self.stage_factory.final_stage_drop()
+    }
+
+    pub fn kill_cam(&self) -> bool {
+        self.stage_factory.kill_cam()
+    }
+
+    // during the intro the boss only moves, it can't be hurt and stays in the first stage
+    pub fn start_intro(&mut self) {
+        self.intro = true;
+    }
+
+    pub fn end_intro(&mut self) {
+        self.intro = false;
+    }
+
+    pub fn skip_intro(&mut self) {
+        self.stage.skip(&mut self.shape);
+        self.end_intro();
+    }
+
+    pub fn in_intro(&self) -> bool {
+        self.intro
+    }
+
+    // not while appearing or dying, a dying boss has no hp left
+    pub fn can_be_targeted(&self) -> bool {
+        !self.intro && self.stage_idx > 0 && self.alive()
+    }
+
+    pub fn beam(&self) -> Option<LaserBeam> {
+        if self.intro {
+            return None;
+        }
+        self.stage.beam(&self.shape)
+    }
+
+    pub fn take_safe_column(&mut self) -> Option<i32> {
+        self.stage.take_safe_column()
+    }
+
+    pub fn stage_idx(&self) -> i32 {
+        self.stage_idx
+    }
+
+    pub fn stage_name(&self) -> StageName {
+        self.stage.name()
+    }
+
+    pub fn intensity_hint(&self) -> f32 {
+        self.stage.intensity_hint()
+    }
+
+    // balance tooling only, see BossStage::estimated_dps
+    pub fn estimated_dps(&self) -> f32 {
+        self.stage.estimated_dps(self)
+    }
+
+    // returns the damage actually dealt, enough damage within a short window stuns the boss
+    pub fn take_hit(&mut self, damage: i32) -> i32 {
+        if self.invulnerable() {
+            return 0;
+        }
+        let damage = (damage as f32 * self.stage.damage_taken_multiplier()).round() as i32;
+        self.hit(damage);
+
+        if self.record_burst_damage(damage) >= STUN_DAMAGE_THRESHOLD && self.alive() && !self.stunned() && self.stun_cooldown.ready() {
+            self.stun_cooldown.reset();
+            self.recent_hits.clear();
+            let interrupted = std::mem::replace(&mut self.stage, Box::new(StunnedStage::new()));
+            self.interrupted_stage = Some(interrupted);
+            self.record_stage();
+        }
+        damage
+    }
+
+    // total damage of the hits within the window, this one included
+    fn record_burst_damage(&mut self, damage: i32) -> i32 {
+        let now = GameInstant::now();
+        while self.recent_hits.front().is_some_and(|(time, _)| now.saturating_duration_since(*time) >= STUN_DAMAGE_WINDOW) {
+            self.recent_hits.pop_front();
+        }
+        self.recent_hits.push_back((now, damage));
+        self.recent_hits.iter().map(|(_, damage)| damage).sum()
+    }
+
+    pub fn add_damage_mark(&mut self, world_pos: Vec2i) {
+        if self.damage_marks.len() >= MAX_DAMAGE_MARKS {
+            self.damage_marks.pop_front();
+        }
+        self.damage_marks.push_back(world_pos - self.shape.pos());
+    }
+
+    // relative to the boss position, so the marks move along with the boss
+    pub fn damage_marks(&self) -> &VecDeque<Vec2i> {
+        &self.damage_marks
+    }
+
+    pub fn stunned(&self) -> bool {
+        self.interrupted_stage.is_some()
+    }
+
+    // the interrupted stage continues where it was, its timers skip the stun; hp thresholds crossed
+    // meanwhile are handled by the regular stage switch afterwards
+    fn end_stun(&mut self) -> bool {
+        if !self.stage.completed(self) {
+            return false;
+        }
+        let stunned_for = self.time_in_current_stage();
+        match self.interrupted_stage.take() {
+            Some(mut interrupted) => {
+                interrupted.delay_timers(stunned_for);
+                self.stage = interrupted;
+                self.stage.set_tuning(self.tuning);
+                self.record_stage();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // switches to the next stage once the current one is completed and not busy
+    pub fn next_stage(&mut self) -> bool {
+        if self.stunned() {
+            return self.end_stun();
+        }
+
+        if self.intro || self.in_last_stage() || !self.stage.completed(self) || !self.stage.can_be_interrupted() {
+            return false;
+        }
+
+        self.stage_idx += 1;
+        self.stage = self.stage_factory.create(self.stage_idx);
+        self.stage.set_tuning(self.tuning);
+        self.record_stage();
+        if self.in_last_stage() && self.stage_factory.enrage_in_last_stage() {
+            self.enrage();
+        }
+        // the boss patches up half of the marks between stages
+        let patched = self.damage_marks.len() / 2;
+        self.damage_marks.drain(..patched);
+        true
+    }
+
+    fn record_stage(&mut self) {
+        self.stage_history.push((self.stage.name(), GameInstant::now()));
+    }
+
+    // every stage the boss went through, a stun and the return from it are recorded as well
+    pub fn stage_history(&self) -> &[(StageName, GameInstant)] {
+        &self.stage_history
+    }
+
+    pub fn time_in_current_stage(&self) -> Duration {
+        match self.stage_history.last() {
+            Some((_, started)) => started.elapsed(),
+            None => Duration::ZERO,
+        }
+    }
+
+    // the last stage lasts until the boss dies
+    pub fn in_last_stage(&self) -> bool {
+        self.stage_idx + 1 >= self.stage_factory.stages_count()
+    }
+
+    // 1-based, every crossed stage hp threshold starts the next phase
+    pub fn phase(&self) -> u8 {
+        let hp_percent = self.hp_percent();
+        let crossed = self.stage_factory.hp_thresholds().iter()
+            .filter(|threshold| hp_percent < **threshold)
+            .count();
+        crossed as u8 + 1
+    }
+
+    pub fn invulnerable(&self) -> bool {
+        let in_window = self.stage.vulnerability_window()
+            .is_some_and(|(start, duration)| start.elapsed() < duration);
+        self.intro || self.shielded || self.stage.invulnerable() || in_window
+    }
+
+    pub fn remaining_hp(&self) -> i32 {
+        self.hp.max(0)
+    }
+
+    pub fn max_hp(&self) -> i32 {
+        self.max_hp
+    }
+
+    #[cfg(any(test, feature = "debug-commands"))]
+    pub fn set_hp(&mut self, hp: i32) {
+        self.hp = hp.clamp(0, self.max_hp);
+    }
+
+    pub fn health_view(&self) -> &BossHealthView {
+        &self.health_view
+    }
+    pub fn update_effects(&mut self) {
+        let invulnerable = self.invulnerable();
+        self.health_view.update(self.hp_percent(), invulnerable);
+
+        let tint = match (invulnerable, self.is_enraged()) {
+            (true, _) => Tint::Invulnerable,
+            (false, true) => Tint::Enraged,
+            (false, false) => Tint::None,
+        };
+        if tint != self.flash.tint() {
+            self.flash.set_tint(tint, None);
+        }
+        if self.is_enraged() {
+            self.enrage_tint = (self.enrage_tint + 1.0 / ENRAGE_TINT_RAMP_TICKS).min(1.0);
+        }
+        self.flash.update();
+        if self.animator.current_clip().name != CLIP_DEATH {
+            self.animator.play(if self.stage.telegraphing() { CLIP_CHARGING } else { CLIP_IDLE });
+        }
+        self.animator.update();
+    }
+
+    pub fn animator(&self) -> &Animator {
+        &self.animator
+    }
+
+    // length of the death clip
+    pub fn death_ticks() -> u32 {
+        BOSS_CLIPS.iter()
+            .find(|clip| clip.name == CLIP_DEATH)
+            .map_or(0, |clip| clip.duration_ticks() as u32)
+    }
+
+    pub fn start_dying(&mut self) {
+        self.animator.play(CLIP_DEATH);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::easy_stages::{AppearStage, SimpleShootingDown, Targeted, APPEAR_IMMUNITY_DURATION};
+    use super::hard_stages::BeamSweepStage;
+    use super::stage_factories::{EasyBossStageFactory, MediumBossStageFactory};
+    use super::stunned_stage::STUN_DURATION;
+    use super::test_support::test_boss::TestBoss;
+    use crate::constants::{SHOT_SPEED, SHOT_WIDTH};
+    use crate::timing::{advance_game_clock, duration_to_ticks};
+
+    fn ship() -> Ship {
+        Ship::new(Shape::new(Vec2i::zero(), 10), 100, 100)
+    }
+
+    fn advance(duration: Duration) {
+        for _ in 0..duration_to_ticks(duration) {
+            advance_game_clock();
+        }
+    }
+
+    #[test]
+    fn barriers_are_spaced_evenly_around_the_boss() {
+        let shape = Shape::new(Vec2i::new(100, 100), 40);
+        let boss = Boss::new(shape.clone(), 100, Box::new(MediumBossStageFactory));
+        let barriers = boss.raise_barriers();
+        assert_eq!(barriers.len(), 2);
+        let center = boss.shape().center();
+        let offset = barriers.iter().fold(Vec2i::zero(), |sum, barrier| sum + (barrier.shape().center() - center));
+        assert!(offset.x.abs() <= 1 && offset.y.abs() <= 1, "{:?}", offset);
+
+        assert!(Boss::new(shape, 100, Box::new(EasyBossStageFactory)).raise_barriers().is_empty());
+    }
+
+    #[test]
+    fn burst_of_hits_stuns_boss() {
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 1000);
+        for _ in 0..STUN_DAMAGE_THRESHOLD / 10 - 1 {
+            boss.take_hit(10);
+        }
+        assert!(!boss.stunned());
+
+        boss.take_hit(10);
+        assert!(boss.stunned());
+    }
+
+    #[test]
+    fn spread_out_hits_dont_stun() {
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 1000);
+        for _ in 0..STUN_DAMAGE_THRESHOLD / 10 {
+            boss.take_hit(10);
+            advance(STUN_DAMAGE_WINDOW / 2);
+        }
+        assert!(!boss.stunned());
+    }
+
+    #[test]
+    fn interrupted_stage_doesnt_catch_up_after_stun() {
+        let ship = ship();
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 1000);
+        while boss.shoot(&ship).is_none() {
+            advance_game_clock();
+        }
+
+        for _ in 0..STUN_DAMAGE_THRESHOLD / 10 {
+            boss.take_hit(10);
+        }
+        assert!(boss.stunned());
+        advance(STUN_DURATION);
+        assert!(boss.next_stage());
+
+        assert!(!boss.stunned());
+        assert!(boss.shoot(&ship).is_none());
+    }
+
+    // hp is left at 1, below every stage threshold
+    fn fly_to_last_stage(boss: &mut Boss) {
+        let ship = ship();
+        boss.set_hp(1);
+        for _ in 0..100_000 {
+            if boss.in_last_stage() {
+                return;
+            }
+            advance_game_clock();
+            boss.fly(&ship);
+            boss.next_stage();
+        }
+        panic!("boss never reached its last stage");
+    }
+
+    fn easy_boss_past_appear() -> Boss {
+        let ship = ship();
+        let mut boss = Boss::new(Shape::new(Vec2i::zero(), 100), 100, Box::new(EasyBossStageFactory));
+        while !boss.next_stage() {
+            advance_game_clock();
+            boss.fly(&ship);
+        }
+        boss
+    }
+
+    #[test]
+    fn easy_boss_runs_through_its_stages() {
+        let ship = ship();
+        let width = 100;
+        let spawn = Boss::spawn_position(&screen_rect(), width);
+        let mut boss = Boss::new(Shape::new(spawn, width), 100, Box::new(EasyBossStageFactory));
+        assert_eq!(boss.stage_name(), "Appear");
+
+        // descends until it reaches the target height
+        let mut last_y = boss.shape().pos().y;
+        while !boss.next_stage() {
+            assert_eq!(boss.stage_name(), "Appear");
+            advance_game_clock();
+            boss.fly(&ship);
+            assert!(boss.shape().pos().y >= last_y);
+            last_y = boss.shape().pos().y;
+        }
+        assert!(last_y > spawn.y);
+        assert_eq!(boss.stage_name(), "SimpleShootingDown");
+
+        // the first shot comes within two shooting intervals
+        let interval_ticks = duration_to_ticks(boss.stage.shot_interval().unwrap());
+        let shot_tick = (0..interval_ticks * 2).find(|_| {
+            advance_game_clock();
+            boss.fly(&ship);
+            boss.shoot(&ship).is_some()
+        });
+        assert!(shot_tick.is_some());
+
+        // and the stage holds until the hp drops below the threshold
+        assert!(!boss.next_stage());
+        boss.take_hit(31);
+        assert!(boss.next_stage());
+        assert_eq!(boss.stage_name(), "SpreadShooting");
+    }
+
+    #[test]
+    fn stages_change_exactly_at_hp_thresholds() {
+        let mut boss = easy_boss_past_appear();
+        assert_eq!(boss.stage_name(), "SimpleShootingDown");
+
+        boss.set_hp(70);
+        assert!(!boss.next_stage());
+        boss.set_hp(69);
+        assert!(boss.next_stage());
+        assert_eq!(boss.stage_name(), "SpreadShooting");
+
+        boss.set_hp(40);
+        assert!(!boss.next_stage());
+        boss.set_hp(39);
+        assert!(boss.next_stage());
+        assert_eq!(boss.stage_name(), "Targeted");
+    }
+
+    #[test]
+    fn only_last_stage_drops_a_power_up() {
+        let mut boss = Boss::new(Shape::new(Vec2i::zero(), 100), 100, Box::new(EasyBossStageFactory));
+        assert!(!boss.in_last_stage());
+        assert_eq!(boss.drop_powerup(), None);
+
+        boss.stage_idx = EasyBossStageFactory.stages_count() - 1;
+        assert!(boss.in_last_stage());
+        assert_eq!(boss.drop_powerup(), Some(PowerUpKind::PiercingShots));
+    }
+
+    #[test]
+    fn factory_can_turn_the_drop_off() {
+        let boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 100);
+        assert!(boss.in_last_stage());
+        assert_eq!(boss.drop_powerup(), None);
+    }
+
+    #[test]
+    fn fresh_boss_is_in_first_phase() {
+        let mut boss = Boss::new(Shape::new(Vec2i::zero(), 100), 100, Box::new(EasyBossStageFactory));
+        assert_eq!(boss.phase(), 1);
+        boss.set_hp(50);
+        assert_eq!(boss.phase(), 2);
+        boss.set_hp(30);
+        assert_eq!(boss.phase(), 3);
+    }
+
+    #[test]
+    fn phase_follows_hp_thresholds() {
+        let mut boss = easy_boss_past_appear();
+        boss.set_hp(70);
+        assert_eq!(boss.phase(), 1);
+        boss.set_hp(69);
+        assert_eq!(boss.phase(), 2);
+        boss.set_hp(39);
+        assert_eq!(boss.phase(), 3);
+    }
+
+    #[test]
+    fn set_hp_clamps_to_max_hp() {
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 100);
+        boss.set_hp(500);
+        assert_eq!(boss.remaining_hp(), 100);
+        boss.set_hp(-5);
+        assert_eq!(boss.remaining_hp(), 0);
+        assert!(!boss.alive());
+    }
+
+    #[test]
+    fn single_stage_boss_never_changes_stage() {
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 100);
+        assert!(boss.in_last_stage());
+        boss.set_hp(1);
+        assert!(!boss.next_stage());
+        assert_eq!(boss.stage_name(), "SimpleShootingDown");
+    }
+
+    #[test]
+    fn damage_marks_move_with_boss() {
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 1000);
+        boss.shape_mut().set_pos(Vec2i::new(100, 50));
+        boss.add_damage_mark(Vec2i::new(110, 60));
+
+        boss.shape_mut().set_pos(Vec2i::new(40, 70));
+        let mark = boss.damage_marks()[0];
+        assert_eq!(boss.shape().pos() + mark, Vec2i::new(50, 80));
+    }
+
+    #[test]
+    fn oldest_damage_marks_are_evicted() {
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 1000);
+        for x in 0..MAX_DAMAGE_MARKS as i32 + 2 {
+            boss.add_damage_mark(Vec2i::new(x, 0));
+        }
+        assert_eq!(boss.damage_marks().len(), MAX_DAMAGE_MARKS);
+        assert_eq!(boss.damage_marks()[0], Vec2i::new(2, 0));
+    }
+
+    #[test]
+    fn stage_change_patches_older_half_of_marks() {
+        let mut boss = easy_boss_past_appear();
+        let pos = boss.shape().pos();
+        for x in 0..10 {
+            boss.add_damage_mark(pos + Vec2i::new(x, 0));
+        }
+        boss.set_hp(1);
+        assert!(boss.next_stage());
+        assert_eq!(boss.damage_marks().len(), 5);
+        assert_eq!(boss.damage_marks()[0], Vec2i::new(5, 0));
+    }
+
+    #[test]
+    fn shot_profile_falls_back_to_constants() {
+        let profile = ShotProfile::default();
+        assert_eq!(profile.speed(), SHOT_SPEED);
+        assert_eq!(profile.width(), SHOT_WIDTH);
+        assert_eq!(ShotProfile::new(3, 0).width(), 1);
+    }
+
+    #[test]
+    fn difficulty_stacks_on_shot_profile() {
+        let ship = ship();
+        let stage = SimpleShootingDown::with_shot_profile(ShotProfile::new(4, 30));
+        let mut boss = TestBoss::with_stage(Box::new(stage), 1000);
+        boss.set_difficulty_multiplier(1.5);
+
+        let profile = boss.effective_shot_profile();
+        assert_eq!((profile.speed(), profile.width()), (6, 30));
+
+        let shots = loop {
+            advance_game_clock();
+            if let Some(shots) = boss.shoot(&ship) {
+                break shots;
+            }
+        };
+        assert!(shots.iter().all(|shot| shot.shape().width() == 30 && shot.velocity().y.round() == 6.0));
+    }
+
+    #[test]
+    fn last_stage_doesnt_enrage_by_default() {
+        let mut boss = Boss::new(Shape::new(Vec2i::zero(), 100), 1000, Box::new(EasyBossStageFactory));
+        fly_to_last_stage(&mut boss);
+        assert!(!boss.is_enraged());
+
+        boss.enrage();
+        assert!(boss.is_enraged());
+    }
+
+    #[test]
+    fn factory_can_enrage_in_last_stage() {
+        let mut boss = Boss::new(Shape::new(Vec2i::zero(), 100), 1000, Box::new(MediumBossStageFactory));
+        fly_to_last_stage(&mut boss);
+        assert!(boss.is_enraged());
+    }
+
+    #[test]
+    fn predictability_averaged_over_ticks() {
+        let ship = ship();
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 1000);
+        assert_eq!(boss.average_predictability(), 1.0);
+        boss.fly(&ship);
+        assert_eq!(boss.average_predictability(), 1.0);
+
+        let mut boss = TestBoss::with_stage(Box::new(Targeted::new()), 1000);
+        boss.fly(&ship);
+        assert_eq!(boss.average_predictability(), 0.5);
+    }
+
+    #[test]
+    fn harder_boss_shoots_more_often() {
+        let mut boss = TestBoss::with_stage(Box::new(SimpleShootingDown::new()), 1000);
+        let base = boss.stage.shot_interval().unwrap();
+
+        boss.set_difficulty_multiplier(2.0);
+        assert_eq!(boss.stage.shot_interval().unwrap(), base / 2);
+        boss.set_difficulty_multiplier(1.0);
+        assert_eq!(boss.stage.shot_interval().unwrap(), base);
+    }
+
+    #[test]
+    fn charging_clip_plays_while_telegraphing() {
+        let ship = ship();
+        let mut boss = TestBoss::with_stage(Box::new(BeamSweepStage::new()), 1000);
+        boss.update_effects();
+        assert_eq!(boss.animator().current_clip().name, CLIP_IDLE);
+
+        let mut ticks = 0;
+        while boss.animator().current_clip().name != CLIP_CHARGING {
+            assert!(ticks < 10_000, "boss never telegraphed");
+            advance_game_clock();
+            boss.fly(&ship);
+            boss.update_effects();
+            ticks += 1;
+        }
+        assert!(boss.beam().is_none());
+
+        while boss.beam().is_none() {
+            advance_game_clock();
+            boss.fly(&ship);
+        }
+        boss.update_effects();
+        assert_eq!(boss.animator().current_clip().name, CLIP_IDLE);
+    }
+
+    #[test]
+    fn no_damage_during_vulnerability_window() {
+        let mut boss = TestBoss::with_stage(Box::new(AppearStage::new()), 100);
+        boss.fly(&ship());
+
+        assert!(boss.invulnerable());
+        assert_eq!(boss.take_hit(10), 0);
+        assert_eq!(boss.remaining_hp(), 100);
+    }
+
+    #[test]
+    fn full_damage_after_vulnerability_window() {
+        let mut boss = TestBoss::with_stage(Box::new(AppearStage::new()), 100);
+        boss.fly(&ship());
+        advance(APPEAR_IMMUNITY_DURATION);
+
+        assert!(!boss.invulnerable());
+        assert_eq!(boss.take_hit(10), 10);
+        assert_eq!(boss.remaining_hp(), 90);
     }
 }
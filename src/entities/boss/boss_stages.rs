@@ -1,15 +1,234 @@
-use crate::entities::shape::Shape;
-use crate::entities::boss::Boss;
+use crate::entities::shape::{Shape, Shaped};
+use crate::entities::boss::{Boss, BossMuzzle};
+use crate::entities::boss::easy_stages::{make_boss_shot, BOSS_DAMAGE};
+use crate::entities::patterns::Pattern;
 use crate::entities::ship::Ship;
 use crate::entities::shot::Shot;
+use crate::entities::boss::laser_beam::LaserBeam;
+use crate::constants::{SHOT_SPEED, SHOT_WIDTH};
+use crate::globals::playfield_rect;
+use crate::math::Rect;
+use crate::power_ups::PowerUpKind;
+use crate::timing::{Cooldown, GameInstant};
+#[cfg(feature = "hot-reload")]
+use crate::tuning::StageTuning;
+
+use std::time::Duration;
+
+//-----------------------------------------------------------------------------
+
+// per-stage overrides of the boss shot speed and size, the global constants are used otherwise
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ShotProfile {
+    pub speed: Option<i32>,
+    pub width: Option<i32>,
+}
+
+impl ShotProfile {
+    pub fn new(speed: i32, width: i32) -> Self {
+        Self { speed: Some(speed), width: Some(width) }
+    }
+
+    pub fn speed(&self) -> i32 {
+        self.speed.unwrap_or(SHOT_SPEED)
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width.unwrap_or(SHOT_WIDTH).max(1)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+pub type StageName = &'static str;
+
+// changes of the boss affecting how its stages behave
+#[derive(Copy, Clone, Debug)]
+pub struct BossTuning {
+    // the detached weapon arm was destroyed
+    pub arm_lost: bool,
+    // applied to the shooting intervals, below 1.0 the boss shoots more often
+    pub shot_interval_scale: f32,
+}
+
+impl Default for BossTuning {
+    fn default() -> Self {
+        Self { arm_lost: false, shot_interval_scale: 1.0 }
+    }
+}
 
 pub trait BossStage {
+    fn name(&self) -> StageName;
+
     fn update_pos(&mut self, boss_shape: &mut Shape, ship: &Ship);
-    fn shoot(&mut self, boss_shape: &Shape, ship: &Ship) -> Option<Vec<Shot>>;
+    fn shoot(&mut self, boss_shape: &Shape, boss_hp_percent: f32, ship: &Ship) -> Option<Vec<Shot>>;
 
     fn completed(&self, boss: &Boss) -> bool;
+
+    fn invulnerable(&self) -> bool {
+        false
+    }
+
+    // start and length of a stretch of the stage during which the boss takes no damage, always vulnerable by default
+    fn vulnerability_window(&self) -> Option<(GameInstant, Duration)> {
+        None
+    }
+
+    // area the boss moves within during the stage, the whole playfield by default
+    fn movement_rect(&self, playfield: &Rect) -> Rect {
+        playfield.clone()
+    }
+
+    // keeps the whole boss inside movement_rect, for stages to call from update_pos
+    fn clamp_to_movement_rect(&self, boss_shape: &mut Shape) {
+        let rect = self.movement_rect(&playfield_rect());
+        boss_shape.set_pos(boss_shape.pos().clamp_to_rect(&rect, boss_shape.width()));
+    }
+
+    // stage in the middle of an action which must not be cut (e.g. a dash) returns false
+    fn can_be_interrupted(&self) -> bool {
+        true
+    }
+
+    // scales the movement done by update_pos, combined with the boss difficulty multiplier
+    fn speed_factor(&self) -> f32 {
+        1.0
+    }
+
+    // applied to every hit the boss takes during the stage
+    fn damage_taken_multiplier(&self) -> f32 {
+        1.0
+    }
+
+    // 0.0 for random movement, 1.0 for a fully predictable one
+    fn movement_predictability(&self) -> f32 {
+        1.0
+    }
+
+    // shots fired by a single shoot call, for difficulty metrics
+    fn shot_count_this_interval(&self) -> u32 {
+        0
+    }
+
+    fn shot_profile(&self) -> ShotProfile {
+        ShotProfile::default()
+    }
+
+    // time between volleys, stages which don't shoot have none
+    fn shot_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    fn shot_damage(&self) -> i32 {
+        BOSS_DAMAGE
+    }
+
+    // damage per second if every shot hits, a rough number for balance tooling only
+    fn estimated_dps(&self, _boss: &Boss) -> f32 {
+        match self.shot_interval().filter(|interval| !interval.is_zero()) {
+            Some(interval) => self.shot_count_this_interval() as f32 * self.shot_damage() as f32 / interval.as_secs_f32(),
+            None => 0.0,
+        }
+    }
+
+    // how hectic the stage is in 0..1, drives the music intensity
+    fn intensity_hint(&self) -> f32 {
+        0.5
+    }
+
+    // winding up an attack, the boss plays its charging clip meanwhile
+    fn telegraphing(&self) -> bool {
+        false
+    }
+
+    // beam hurting the ship while it touches it, checked every tick
+    fn beam(&self, _boss_shape: &Shape) -> Option<LaserBeam> {
+        None
+    }
+
+    // timer of shoot_toward_ship, stages which don't use it have none
+    fn aim_cooldown(&mut self) -> Option<&mut Cooldown> {
+        None
+    }
+
+    // one shot toward the ship every interval, for stages to call from shoot, it leaves from the wing nearer the ship
+    fn shoot_toward_ship(&mut self, boss_shape: &Shape, ship: &Ship, interval: Duration) -> Option<Vec<Shot>> {
+        let profile = self.shot_profile();
+        let cooldown = self.aim_cooldown()?;
+        cooldown.set_interval(interval);
+        if !cooldown.ready() {
+            return None;
+        }
+
+        cooldown.reset();
+        let target = ship.shape().center();
+        let origin = [BossMuzzle::LeftWing, BossMuzzle::RightWing]
+            .map(|muzzle| muzzle.position(boss_shape))
+            .into_iter()
+            .min_by_key(|pos| (pos.x - target.x).abs())?;
+        let shots: Vec<Shot> = Pattern::Aimed.volley(origin, target, &profile).iter()
+            .filter_map(|spec| make_boss_shot(spec.origin, spec.angle, &spec.profile, None))
+            .collect();
+        if shots.is_empty() { None } else { Some(shots) }
+    }
+
+    // handed to every stage the boss switches to and again when the tuning changes
+    fn set_tuning(&mut self, _tuning: BossTuning) {}
+
+    // values from tuning.toml, set_tuning is called right after to apply them
+    #[cfg(feature = "hot-reload")]
+    fn apply_stage_tuning(&mut self, _tuning: &StageTuning) {}
+
+    // moves every timer of the stage forward, so the time it was interrupted for (e.g. by a stun) is skipped
+    // instead of caught up on
+    fn delay_timers(&mut self, _by: Duration) {}
+
+    // jumps to the end of a scripted movement, e.g. when the intro is skipped
+    fn skip(&mut self, _boss_shape: &mut Shape) {}
+
+    // x of a column left uncovered by the last volley, handed out once
+    fn take_safe_column(&mut self) -> Option<i32> {
+        None
+    }
 }
 
 pub trait BossStagesFactory {
     fn create(&self, idx: i32) -> Box<dyn BossStage>;
+    fn stages_count(&self) -> i32;
+
+    // hp percents at which stages are switched, used to split the health bar
+    fn hp_thresholds(&self) -> Vec<f32>;
+
+    // shown on the intro banner
+    fn boss_name(&self) -> &'static str;
+
+    // part of the screen width each arena wall covers during the fight, no walls by default
+    fn arena_walls(&self) -> Option<f32> {
+        None
+    }
+
+    // hp percent below which the weapon arm detaches, no arm by default
+    fn weapon_arm_threshold(&self) -> Option<f32> {
+        None
+    }
+
+    // barriers orbiting the boss which have to be destroyed before it can be hurt, none by default
+    fn kinetic_barriers(&self) -> u32 {
+        0
+    }
+
+    // granted when the boss dies in its last stage, the strongest power-up by default
+    fn final_stage_drop(&self) -> Option<PowerUpKind> {
+        Some(PowerUpKind::Reflector)
+    }
+
+    // slow motion beat on the killing blow, a mid-boss can turn it off
+    fn kill_cam(&self) -> bool {
+        true
+    }
+
+    // the boss enrages on reaching its last stage, otherwise only when enrage is called
+    fn enrage_in_last_stage(&self) -> bool {
+        false
+    }
 }
@@ -1,36 +1,50 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use crate::entities::shape::{Shape, Shaped};
 use crate::entities::destroyable::Destroyable;
 use crate::entities::ship::Ship;
 use crate::entities::shot::Shot;
+use crate::entities::patterns::{Pattern, ShotSpec};
 
-use crate::globals::screen_rect;
-use crate::constants::{SHOT_SPEED, SHOT_WIDTH};
+use crate::globals::{mirror_angle, mirror_mode, playfield_rect, screen_rect};
+use crate::timing::{Cooldown, GameInstant};
+use crate::constants::SHOT_WIDTH;
+use crate::math::{Rect, Vec2i};
+use crate::math::angles::ShotAngleIterator;
+#[cfg(feature = "hot-reload")]
+use crate::tuning::StageTuning;
 
-use super::boss_stages::BossStage;
-use super::Boss;
+use super::boss_stages::{BossStage, BossTuning, ShotProfile, StageName};
+use super::wall_bounce::WallBounce;
+use super::{Boss, BossMuzzle};
 
 //-----------------------------------------------------------------------------
 
 const APPEAR_MOVE_SPEED: i32 = 8;
-const APPEAR_TARGET_HEIGHT: i32 = 50;
+// distance from the edge the boss enters from
+const APPEAR_TARGET_MARGIN_PX: i32 = 50;
+// the boss can't be hit right as it enters
+pub const APPEAR_IMMUNITY_DURATION: Duration = Duration::from_millis(500);
 
 const SIMPLE_SHOOTING_STAGE_MOVE_SPEED: i32 = 12;
 const SIMPLE_SHOOTING_STAGE_SHOOTING_INTERVAL: Duration = Duration::from_millis(300);
 
 const SPREAD_SHOOTING_STAGE_MOVE_SPEED: i32 = 8;
 const SPREAD_SHOOTING_STAGE_SHOOTING_INTERVAL: Duration = Duration::from_millis(500);
-const SPREAD_SHOOTING_ANGLE_RANGE: i32 = 120;
+const SPREAD_SHOOTING_ANGLE_RANGE: i32 = 180;
+const SPREAD_SHOOTING_MIN_RANGE: i32 = 60;
 const SPREAD_SHOOTING_ANGLE_STEP: usize = 20;
+const SPREAD_SHOOTING_DENSE_VOLLEY_SHOTS: usize = 6;
 
 const TARGETED_STAGE_MOVE_SPEED: i32 = 15;
 const TARGETED_STAGE_SHOOTING_INTERVAL: Duration = Duration::from_millis(500);
 
-const STAGE_1_FINISH_HP_THRESHOLD: f32 = 0.7f32;
-const STAGE_2_FINISH_HP_THRESHOLD: f32 = 0.4f32;
+pub const STAGE_1_FINISH_HP_THRESHOLD: f32 = 0.7f32;
+pub const STAGE_2_FINISH_HP_THRESHOLD: f32 = 0.4f32;
 
-const BOSS_DAMAGE: i32 = 10;
+pub(super) const BOSS_DAMAGE: i32 = 10;
+// wider spread trades off power
+const SPREAD_SHOT_DAMAGE: i32 = 6;
 
 const ANGLE_DOWN: i32 = 180;
 
@@ -42,69 +56,201 @@ enum Direction {
     Right,
 }
 
-fn move_horizontally(direction: &mut Direction, boss_shape: &mut Shape, move_speed: i32) {
-    let x_offset = match direction {
-        Direction::Left => -move_speed,
-        Direction::Right => move_speed,
-    };
-
-    let mut new_pos = boss_shape.pos();
-    let screen_rect = screen_rect();
+impl Direction {
+    fn is_horizontal(&self) -> bool {
+        matches!(self, Direction::Left | Direction::Right)
+    }
 
-    new_pos.x += x_offset;
-    boss_shape.set_pos(new_pos);
-    if !boss_shape.in_rect(&screen_rect) {
-        new_pos.x -= x_offset * 2;
-        boss_shape.set_pos(new_pos);
-        *direction = match direction {
+    fn opposite(self) -> Direction {
+        match self {
             Direction::Left => Direction::Right,
             Direction::Right => Direction::Left,
-        };
+        }
+    }
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Left => write!(f, "Left"),
+            Direction::Right => write!(f, "Right"),
+        }
+    }
+}
+
+// the direction is in the pattern space, so the boss starts the other way in mirror mode
+fn move_horizontally(direction: &mut Direction, boss_shape: &mut Shape, move_speed: i32, rect: &Rect) {
+    debug_assert!(direction.is_horizontal(), "{} is not a horizontal direction", direction);
+    let offset = match direction {
+        Direction::Left => Vec2i { x: -move_speed, y: 0 },
+        Direction::Right => Vec2i { x: move_speed, y: 0 },
+    };
+    let offset = if mirror_mode() { -offset } else { offset };
+
+    let mut bounce = WallBounce::new(offset);
+    let new_pos = bounce.apply(boss_shape.pos(), boss_shape, rect);
+    if bounce.velocity() != offset {
+        *direction = direction.opposite();
+    }
+    boss_shape.set_pos(new_pos);
+    // the arena walls may have closed in on the boss
+    if !boss_shape.in_rect(rect) {
+        boss_shape.set_pos(new_pos.clamp_to_rect(rect, boss_shape.width()));
     }
 }
 
 //-----------------------------------------------------------------------------
 
-fn shoot_down(shoot_time: &mut Instant, boss_shape: &Shape, shooting_interval: Duration) -> Option<Vec<Shot>> {
-    let now = Instant::now();
-    if *shoot_time + shooting_interval <= now {
-        *shoot_time = now;
+fn shoot_down(cooldown: &mut Cooldown, boss_shape: &Shape, profile: &ShotProfile) -> Option<Vec<Shot>> {
+    if cooldown.ready() {
+        cooldown.reset();
 
-        let shot = make_boss_shot(&boss_shape, ANGLE_DOWN);
-        return Some(vec![shot]);
+        let origin = BossMuzzle::Center.position(boss_shape);
+        let specs = Pattern::Single { angle: ANGLE_DOWN }.volley(origin, origin, profile);
+        return Some(make_boss_shots(&specs, None));
     }
 
     None
 }
 
-fn make_boss_shot(boss_shape: &Shape, angle: i32) -> Shot {
-    let shot_shape = Shape::new(boss_shape.center(), SHOT_WIDTH);
-    Shot::new(shot_shape, SHOT_SPEED, angle, BOSS_DAMAGE)
+fn make_boss_shots(specs: &[ShotSpec], damage: Option<i32>) -> Vec<Shot> {
+    specs.iter()
+        .filter_map(|spec| make_boss_shot(spec.origin, spec.angle, &spec.profile, damage))
+        .collect()
+}
+
+// aimed angles are undefined when the boss and the target overlap, such shots are skipped
+// the angle is in the pattern space, the origin is already a world position
+// the damage override is preferred over BOSS_DAMAGE
+pub(super) fn make_boss_shot(origin: Vec2i, angle: f32, profile: &ShotProfile, damage: Option<i32>) -> Option<Shot> {
+    if !angle.is_finite() {
+        log::warn!("Skipping boss shot from {} with invalid angle {}", origin, angle);
+        return None;
+    }
+
+    let width = profile.width();
+    let shot_shape = Shape::new(origin - width / 2, width);
+    let shot = Shot::new(shot_shape, profile.speed(), mirror_angle(angle.round() as i32), BOSS_DAMAGE);
+    Some(shot.with_damage(damage.unwrap_or(BOSS_DAMAGE)))
+}
+
+// the widest gap between the points where shots cross the target row, edges of the screen included
+// angles are in the pattern space, like in make_boss_shot
+fn safe_column(origin: Vec2i, angles: &[i32], target_y: i32) -> Option<i32> {
+    let depth = (target_y - origin.y) as f32;
+    if depth <= 0.0 {
+        return None;
+    }
+
+    let playfield_rect = playfield_rect();
+    let mut crossings: Vec<i32> = angles.iter()
+        .map(|angle| (mirror_angle(*angle) as f32).to_radians())
+        // sideways shots never reach the row, and the tiny cos of 90 degrees would overflow the crossing
+        .filter(|angle| angle.cos() < -f32::EPSILON)
+        .map(|angle| origin.x + (angle.sin() * depth / -angle.cos()).round() as i32)
+        .collect();
+    crossings.push(playfield_rect.top_left.x - SHOT_WIDTH / 2);
+    crossings.push(playfield_rect.bottom_right.x + SHOT_WIDTH / 2);
+    crossings.sort();
+
+    crossings.windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .filter(|(left, right)| right - left > SHOT_WIDTH * 2)
+        .max_by_key(|(left, right)| right - left)
+        .map(|(left, right)| (left + right) / 2)
 }
 
 //-----------------------------------------------------------------------------
 
-pub struct AppearStage;
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScreenSide {
+    Top,
+    Left,
+    Right,
+}
+
+pub struct AppearStage {
+    side: ScreenSide,
+    placed: bool,
+    started: Option<GameInstant>,
+}
 
 impl AppearStage {
     pub fn new() -> Self {
-        Self{}
+        AppearStage::appear_from_side(ScreenSide::Top)
+    }
+
+    // the side is in the pattern space, so left and right swap in mirror mode
+    pub fn appear_from_side(side: ScreenSide) -> Self {
+        let side = match (side, mirror_mode()) {
+            (ScreenSide::Left, true) => ScreenSide::Right,
+            (ScreenSide::Right, true) => ScreenSide::Left,
+            (side, _) => side,
+        };
+        Self { side, placed: false, started: None }
+    }
+
+    // right outside the chosen edge, the top one keeps the spawn position
+    fn start_position(&self, boss_shape: &Shape) -> Vec2i {
+        let screen = screen_rect();
+        let width = boss_shape.width();
+        match self.side {
+            ScreenSide::Top => boss_shape.pos(),
+            ScreenSide::Left => Vec2i { x: screen.top_left.x - width, y: screen.top_left.y + APPEAR_TARGET_MARGIN_PX },
+            ScreenSide::Right => Vec2i { x: screen.bottom_right.x, y: screen.top_left.y + APPEAR_TARGET_MARGIN_PX },
+        }
+    }
+
+    fn target_position(&self, boss_shape: &Shape) -> Vec2i {
+        let screen = screen_rect();
+        let pos = boss_shape.pos();
+        match self.side {
+            ScreenSide::Top => Vec2i { x: pos.x, y: screen.top_left.y + APPEAR_TARGET_MARGIN_PX },
+            ScreenSide::Left => Vec2i { x: screen.top_left.x + APPEAR_TARGET_MARGIN_PX, y: pos.y },
+            ScreenSide::Right => Vec2i { x: screen.bottom_right.x - boss_shape.width() - APPEAR_TARGET_MARGIN_PX, y: pos.y },
+        }
     }
 }
 
 impl BossStage for AppearStage {
-    fn update_pos(&mut self, boss_shape: &mut Shape, ship: &Ship) {
-        let mut new_pos = boss_shape.pos();
-        new_pos.y = (new_pos.y + APPEAR_MOVE_SPEED).clamp(0, APPEAR_TARGET_HEIGHT);
-        boss_shape.set_pos(new_pos)
+    fn name(&self) -> StageName {
+        "Appear"
+    }
+
+    fn intensity_hint(&self) -> f32 {
+        0.2
     }
 
-    fn shoot(&mut self, boss_shape: &Shape, ship: &Ship) -> Option<Vec<Shot>> {
+    fn update_pos(&mut self, boss_shape: &mut Shape, _ship: &Ship) {
+        if !self.placed {
+            let start = self.start_position(boss_shape);
+            boss_shape.set_pos(start);
+            self.placed = true;
+            self.started = Some(GameInstant::now());
+        }
+
+        let pos = boss_shape.pos();
+        let target = self.target_position(boss_shape);
+        let step = |from: i32, to: i32| from + (to - from).clamp(-APPEAR_MOVE_SPEED, APPEAR_MOVE_SPEED);
+        boss_shape.set_pos(Vec2i { x: step(pos.x, target.x), y: step(pos.y, target.y) })
+    }
+
+    fn shoot(&mut self, _boss_shape: &Shape, _boss_hp_percent: f32, _ship: &Ship) -> Option<Vec<Shot>> {
         None
     }
 
     fn completed(&self, boss: &Boss) -> bool {
-        boss.shape().pos().y > APPEAR_TARGET_HEIGHT
+        self.placed && boss.shape().pos() == self.target_position(boss.shape())
+    }
+
+    fn vulnerability_window(&self) -> Option<(GameInstant, Duration)> {
+        self.started.map(|started| (started, APPEAR_IMMUNITY_DURATION))
+    }
+
+    fn skip(&mut self, boss_shape: &mut Shape) {
+        let target = self.target_position(boss_shape);
+        boss_shape.set_pos(target);
+        self.placed = true;
     }
 }
 
@@ -112,25 +258,71 @@ impl BossStage for AppearStage {
 
 pub struct SimpleShootingDown {
     direction: Direction,
-    shoot_time: Instant,
+    move_speed: i32,
+    // before the boss tuning scales it
+    base_interval: Duration,
+    shoot_cooldown: Cooldown,
+    shot_profile: ShotProfile,
 }
 
 impl SimpleShootingDown {
     pub fn new() -> Self {
+        SimpleShootingDown::with_shot_profile(ShotProfile::default())
+    }
+
+    pub fn with_shot_profile(shot_profile: ShotProfile) -> Self {
         Self {
             direction: Direction::Right,
-            shoot_time: Instant::now(),
+            move_speed: SIMPLE_SHOOTING_STAGE_MOVE_SPEED,
+            base_interval: SIMPLE_SHOOTING_STAGE_SHOOTING_INTERVAL,
+            shoot_cooldown: Cooldown::new(SIMPLE_SHOOTING_STAGE_SHOOTING_INTERVAL),
+            shot_profile,
         }
     }
 }
 
 impl BossStage for SimpleShootingDown {
-    fn update_pos(&mut self, boss_shape: &mut Shape, ship: &Ship) {
-        move_horizontally(&mut self.direction, boss_shape, SIMPLE_SHOOTING_STAGE_MOVE_SPEED)
+    fn name(&self) -> StageName {
+        "SimpleShootingDown"
+    }
+
+    fn intensity_hint(&self) -> f32 {
+        0.4
+    }
+
+    fn update_pos(&mut self, boss_shape: &mut Shape, _ship: &Ship) {
+        let rect = self.movement_rect(&playfield_rect());
+        move_horizontally(&mut self.direction, boss_shape, self.move_speed, &rect)
+    }
+
+    fn shoot(&mut self, boss_shape: &Shape, _boss_hp_percent: f32, _ship: &Ship) -> Option<Vec<Shot>> {
+        shoot_down(&mut self.shoot_cooldown, boss_shape, &self.shot_profile)
+    }
+
+    fn shot_profile(&self) -> ShotProfile {
+        self.shot_profile
+    }
+
+    fn shot_count_this_interval(&self) -> u32 {
+        1
     }
 
-    fn shoot(&mut self, boss_shape: &Shape, ship: &Ship) -> Option<Vec<Shot>> {
-        shoot_down(&mut self.shoot_time, &boss_shape, SIMPLE_SHOOTING_STAGE_SHOOTING_INTERVAL)
+    fn shot_interval(&self) -> Option<Duration> {
+        Some(self.shoot_cooldown.interval())
+    }
+
+    fn set_tuning(&mut self, tuning: BossTuning) {
+        self.shoot_cooldown.set_interval(self.base_interval.mul_f64(tuning.shot_interval_scale as f64));
+    }
+
+    #[cfg(feature = "hot-reload")]
+    fn apply_stage_tuning(&mut self, tuning: &StageTuning) {
+        self.base_interval = tuning.interval().unwrap_or(self.base_interval);
+        self.move_speed = tuning.move_speed.unwrap_or(self.move_speed);
+    }
+
+    fn delay_timers(&mut self, by: Duration) {
+        self.shoot_cooldown.delay(by);
     }
 
     fn completed(&self, boss: &Boss) -> bool {
@@ -142,92 +334,448 @@ impl BossStage for SimpleShootingDown {
 
 pub struct SpreadShooting {
     direction: Direction,
-    shoot_time: Instant,
+    move_speed: i32,
+    // before the boss tuning scales it
+    base_interval: Duration,
+    shoot_cooldown: Cooldown,
+    safe_column: Option<i32>,
+    angle_range: i32,
+    shot_profile: ShotProfile,
+    // the outer third of the spread, fired by the weapon arm
+    crossfire: bool,
 }
 
 impl SpreadShooting {
     pub fn new() -> Self {
+        SpreadShooting::with_shot_profile(ShotProfile::default())
+    }
+
+    pub fn with_shot_profile(shot_profile: ShotProfile) -> Self {
         Self {
             direction: Direction::Right,
-            shoot_time: Instant::now(),
+            move_speed: SPREAD_SHOOTING_STAGE_MOVE_SPEED,
+            base_interval: SPREAD_SHOOTING_STAGE_SHOOTING_INTERVAL,
+            shoot_cooldown: Cooldown::new(SPREAD_SHOOTING_STAGE_SHOOTING_INTERVAL),
+            safe_column: None,
+            angle_range: SpreadShooting::angle_range_for_hp_percent(1.0),
+            shot_profile,
+            crossfire: true,
         }
     }
+
+    // the spread widens from the min range at full hp up to the max one when the boss is almost dead
+    pub fn angle_range_for_hp_percent(hp: f32) -> i32 {
+        let hp = hp.clamp(0.0, 1.0);
+        let range = SPREAD_SHOOTING_ANGLE_RANGE - SPREAD_SHOOTING_MIN_RANGE;
+        SPREAD_SHOOTING_MIN_RANGE + (range as f32 * (1.0 - hp)).round() as i32
+    }
+
+    // a zero step would never get past the first angle
+    pub fn fire_count_for_angle_range(range: i32, step: usize) -> usize {
+        ShotAngleIterator::spread(ANGLE_DOWN, range.max(0), step.max(1)).count()
+    }
 }
 
 impl BossStage for SpreadShooting {
-    fn update_pos(&mut self, boss_shape: &mut Shape, ship: &Ship) {
-        move_horizontally(&mut self.direction, boss_shape, SPREAD_SHOOTING_STAGE_MOVE_SPEED)
+    fn name(&self) -> StageName {
+        "SpreadShooting"
+    }
+
+    fn intensity_hint(&self) -> f32 {
+        0.6
+    }
+
+    fn update_pos(&mut self, boss_shape: &mut Shape, _ship: &Ship) {
+        let rect = self.movement_rect(&playfield_rect());
+        move_horizontally(&mut self.direction, boss_shape, self.move_speed, &rect)
     }
 
-    fn shoot(&mut self, boss_shape: &Shape, ship: &Ship) -> Option<Vec<Shot>> {
-        let now = Instant::now();
-        if self.shoot_time + SPREAD_SHOOTING_STAGE_SHOOTING_INTERVAL > now {
+    fn shoot(&mut self, boss_shape: &Shape, boss_hp_percent: f32, ship: &Ship) -> Option<Vec<Shot>> {
+        if !self.shoot_cooldown.ready() {
             return None;
         }
 
-        self.shoot_time = now;
-
-        let angle_start = ANGLE_DOWN - SPREAD_SHOOTING_ANGLE_RANGE / 2;
-        let angle_end = ANGLE_DOWN + SPREAD_SHOOTING_ANGLE_RANGE / 2;
+        self.shoot_cooldown.reset();
 
-        let mut shots = vec![];
-        for shot_angle in (angle_start..=angle_end).step_by(SPREAD_SHOOTING_ANGLE_STEP) {
-            let shot = make_boss_shot(&boss_shape, shot_angle);
-            shots.push(shot);
+        let origin = BossMuzzle::Center.position(boss_shape);
+        let mut angle_range = SpreadShooting::angle_range_for_hp_percent(boss_hp_percent);
+        if !self.crossfire {
+            angle_range = angle_range * 2 / 3;
+        }
+        self.angle_range = angle_range;
+        let pattern = Pattern::Spread { center: ANGLE_DOWN, arc: angle_range, step: SPREAD_SHOOTING_ANGLE_STEP };
+        let specs = pattern.volley(origin, ship.shape().center(), &self.shot_profile);
+        let shots = make_boss_shots(&specs, Some(SPREAD_SHOT_DAMAGE));
+
+        if specs.len() >= SPREAD_SHOOTING_DENSE_VOLLEY_SHOTS {
+            let angles: Vec<i32> = specs.iter().map(|spec| spec.angle.round() as i32).collect();
+            self.safe_column = safe_column(origin, &angles, ship.shape().center().y);
         }
 
         Some(shots)
     }
 
+    fn delay_timers(&mut self, by: Duration) {
+        self.shoot_cooldown.delay(by);
+    }
+
     fn completed(&self, boss: &Boss) -> bool {
         boss.hp_percent() < STAGE_2_FINISH_HP_THRESHOLD
     }
+
+    // the range of the last volley, it widens as the boss loses hp
+    fn shot_count_this_interval(&self) -> u32 {
+        SpreadShooting::fire_count_for_angle_range(self.angle_range, SPREAD_SHOOTING_ANGLE_STEP) as u32
+    }
+
+    fn shot_interval(&self) -> Option<Duration> {
+        Some(self.shoot_cooldown.interval())
+    }
+
+    fn shot_damage(&self) -> i32 {
+        SPREAD_SHOT_DAMAGE
+    }
+
+    // the volley widens with the lost hp, so the current hp is used instead of the last volley
+    fn estimated_dps(&self, boss: &Boss) -> f32 {
+        let mut range = SpreadShooting::angle_range_for_hp_percent(boss.hp_percent());
+        if !self.crossfire {
+            range = range * 2 / 3;
+        }
+        let shots = SpreadShooting::fire_count_for_angle_range(range, SPREAD_SHOOTING_ANGLE_STEP);
+        shots as f32 * SPREAD_SHOT_DAMAGE as f32 / self.shoot_cooldown.interval().as_secs_f32()
+    }
+
+    fn take_safe_column(&mut self) -> Option<i32> {
+        self.safe_column.take()
+    }
+
+    fn set_tuning(&mut self, tuning: BossTuning) {
+        self.crossfire = !tuning.arm_lost;
+        self.shoot_cooldown.set_interval(self.base_interval.mul_f64(tuning.shot_interval_scale as f64));
+    }
+
+    #[cfg(feature = "hot-reload")]
+    fn apply_stage_tuning(&mut self, tuning: &StageTuning) {
+        self.base_interval = tuning.interval().unwrap_or(self.base_interval);
+        self.move_speed = tuning.move_speed.unwrap_or(self.move_speed);
+    }
+
+    fn shot_profile(&self) -> ShotProfile {
+        self.shot_profile
+    }
 }
 
 //-----------------------------------------------------------------------------
 
 pub struct Targeted {
-    shoot_time: Instant,
+    // before the boss tuning scales it
+    base_interval: Duration,
+    shoot_cooldown: Cooldown,
+    shot_profile: ShotProfile,
+    max_tracking_speed: i32,
 }
 
 impl Targeted {
     pub fn new() -> Self {
+        Targeted::with_shot_profile(ShotProfile::default())
+    }
+
+    pub fn with_shot_profile(shot_profile: ShotProfile) -> Self {
         Self {
-            shoot_time: Instant::now(),
+            base_interval: TARGETED_STAGE_SHOOTING_INTERVAL,
+            shoot_cooldown: Cooldown::new(TARGETED_STAGE_SHOOTING_INTERVAL),
+            shot_profile,
+            max_tracking_speed: TARGETED_STAGE_MOVE_SPEED,
         }
     }
+
+    // a slower boss gives the player time to get out from under it
+    pub fn with_max_tracking_speed(speed: i32) -> Self {
+        Self { max_tracking_speed: speed.max(1), ..Targeted::new() }
+    }
+
+    pub fn max_tracking_speed_per_tick(&self) -> i32 {
+        self.max_tracking_speed
+    }
 }
 
 impl BossStage for Targeted {
+    fn name(&self) -> StageName {
+        "Targeted"
+    }
+
+    fn intensity_hint(&self) -> f32 {
+        0.8
+    }
+
     fn update_pos(&mut self, boss_shape: &mut Shape, ship: &Ship) {
         let boss_center = boss_shape.center();
         let ship_center = ship.shape().center();
         let diff_x = boss_center.x - ship_center.x;
+        let max_speed = self.max_tracking_speed_per_tick();
 
         let mut result = boss_shape.pos();
-        if diff_x.abs() < TARGETED_STAGE_MOVE_SPEED {
+        if diff_x.abs() < max_speed {
             result.x = ship_center.x - boss_shape.width() / 2;
         } else {
+            // towards the ship, the boss is right of it when the difference is positive
             result.x += if diff_x > 0 {
-                TARGETED_STAGE_MOVE_SPEED
+                -max_speed
             } else {
-                -TARGETED_STAGE_MOVE_SPEED
+                max_speed
             };
         }
 
-        let screen_rect = screen_rect();
-        result.x = result.x.clamp(screen_rect.top_left.x, screen_rect.bottom_right.x);
+        boss_shape.set_pos(result);
+        self.clamp_to_movement_rect(boss_shape)
+    }
 
-        boss_shape.set_pos(result)
+    // follows the ship, so it depends on how the player moves
+    fn movement_predictability(&self) -> f32 {
+        0.5
     }
 
-    fn shoot(&mut self, boss_shape: &Shape, ship: &Ship) -> Option<Vec<Shot>> {
-        shoot_down(&mut self.shoot_time, &boss_shape, TARGETED_STAGE_SHOOTING_INTERVAL)
+    fn shoot(&mut self, boss_shape: &Shape, _boss_hp_percent: f32, ship: &Ship) -> Option<Vec<Shot>> {
+        let interval = self.shoot_cooldown.interval();
+        self.shoot_toward_ship(boss_shape, ship, interval)
     }
 
-    fn completed(&self, boss: &Boss) -> bool {
+    fn aim_cooldown(&mut self) -> Option<&mut Cooldown> {
+        Some(&mut self.shoot_cooldown)
+    }
+
+    fn shot_profile(&self) -> ShotProfile {
+        self.shot_profile
+    }
+
+    fn shot_count_this_interval(&self) -> u32 {
+        1
+    }
+
+    fn shot_interval(&self) -> Option<Duration> {
+        Some(self.shoot_cooldown.interval())
+    }
+
+    fn set_tuning(&mut self, tuning: BossTuning) {
+        self.shoot_cooldown.set_interval(self.base_interval.mul_f64(tuning.shot_interval_scale as f64));
+    }
+
+    #[cfg(feature = "hot-reload")]
+    fn apply_stage_tuning(&mut self, tuning: &StageTuning) {
+        self.base_interval = tuning.interval().unwrap_or(self.base_interval);
+        self.max_tracking_speed = tuning.move_speed.unwrap_or(self.max_tracking_speed);
+    }
+
+    fn delay_timers(&mut self, by: Duration) {
+        self.shoot_cooldown.delay(by);
+    }
+
+    fn completed(&self, _boss: &Boss) -> bool {
         false
     }
 }
 
 //-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::globals::{mirror_x, set_mirror_mode, set_screen_size};
+    use crate::timing::advance_game_clock;
+
+    // centers and angles of a spread volley a few ticks after it was fired
+    fn spread_volley(mirror: bool) -> Vec<(Vec2i, i32)> {
+        set_screen_size(Vec2i::new(200, 200));
+        set_mirror_mode(mirror);
+        let boss_shape = Shape::new(Vec2i::new(mirror_x(30, 40), 20), 40);
+        let ship = Ship::new(Shape::new(Vec2i::new(mirror_x(150, 10), 180), 10), 100, 100);
+
+        let mut stage = SpreadShooting::new();
+        let mut shots = loop {
+            match stage.shoot(&boss_shape, 0.3, &ship) {
+                Some(shots) => break shots,
+                None => advance_game_clock(),
+            }
+        };
+        shots.iter_mut().for_each(|shot| (0..5).for_each(|_| shot.fly()));
+        set_mirror_mode(false);
+
+        let mut volley: Vec<_> = shots.iter().map(|shot| (shot.shape().center(), shot.angle())).collect();
+        volley.sort_by_key(|(center, angle)| (center.x, center.y, *angle));
+        volley
+    }
+
+    #[test]
+    fn fire_count_matches_range_over_step() {
+        assert_eq!(SpreadShooting::fire_count_for_angle_range(180, 20), 180 / 20 + 1);
+        assert_eq!(SpreadShooting::fire_count_for_angle_range(0, 20), 1);
+    }
+
+    #[test]
+    fn horizontal_movement_turns_at_wall() {
+        let rect = Rect { top_left: Vec2i::new(0, 0), bottom_right: Vec2i::new(100, 100) };
+        let mut shape = Shape::new(Vec2i::new(85, 10), 10);
+        let mut direction = Direction::Right;
+
+        move_horizontally(&mut direction, &mut shape, 10, &rect);
+        assert_eq!(shape.pos(), Vec2i::new(75, 10));
+        assert!(matches!(direction, Direction::Left));
+    }
+
+    #[test]
+    fn mirrored_movement_goes_the_other_way() {
+        set_screen_size(Vec2i::new(200, 200));
+        set_mirror_mode(true);
+        let rect = Rect { top_left: Vec2i::new(0, 0), bottom_right: Vec2i::new(100, 100) };
+        let mut shape = Shape::new(Vec2i::new(50, 10), 10);
+        let mut direction = Direction::Right;
+
+        move_horizontally(&mut direction, &mut shape, 10, &rect);
+        set_mirror_mode(false);
+        assert_eq!(shape.pos(), Vec2i::new(40, 10));
+        assert!(matches!(direction, Direction::Right));
+        assert!(matches!(direction.opposite(), Direction::Left));
+    }
+
+    #[test]
+    fn mirrored_spread_is_reflection_of_normal_one() {
+        let normal = spread_volley(false);
+        let mirrored = spread_volley(true);
+        assert!(normal.len() > 1);
+
+        let mut reflected: Vec<_> = normal.iter()
+            .map(|(center, angle)| (Vec2i::new(200 - center.x, center.y), (360 - angle).rem_euclid(360)))
+            .collect();
+        reflected.sort_by_key(|(center, angle)| (center.x, center.y, *angle));
+        assert_eq!(mirrored, reflected);
+    }
+
+    // ticks until the boss stands at the target, the first update puts it right outside the edge
+    fn appear_ticks(side: ScreenSide, target: Vec2i) -> usize {
+        set_screen_size(Vec2i::new(200, 200));
+        let mut boss_shape = Shape::new(Vec2i::new(80, -40), 40);
+        let ship = Ship::new(Shape::new(Vec2i::new(95, 180), 10), 100, 100);
+
+        let mut stage = AppearStage::appear_from_side(side);
+        (1..=100)
+            .find(|_| {
+                stage.update_pos(&mut boss_shape, &ship);
+                boss_shape.pos() == target
+            })
+            .expect("the boss never reached its target")
+    }
+
+    #[test]
+    fn appears_from_each_side_in_fixed_ticks() {
+        // 90 px at 8 px per tick from every edge
+        assert_eq!(appear_ticks(ScreenSide::Top, Vec2i::new(80, 50)), 12);
+        assert_eq!(appear_ticks(ScreenSide::Left, Vec2i::new(50, 50)), 12);
+        assert_eq!(appear_ticks(ScreenSide::Right, Vec2i::new(110, 50)), 12);
+    }
+
+    #[test]
+    fn appear_side_swaps_in_mirror_mode() {
+        set_mirror_mode(true);
+        let left = AppearStage::appear_from_side(ScreenSide::Left).side;
+        let top = AppearStage::appear_from_side(ScreenSide::Top).side;
+        set_mirror_mode(false);
+        assert_eq!(left, ScreenSide::Right);
+        assert_eq!(top, ScreenSide::Top);
+    }
+
+    #[test]
+    fn boss_shot_damage_override_wins() {
+        let profile = ShotProfile::default();
+        let shot = make_boss_shot(Vec2i::new(50, 50), 180.0, &profile, None).unwrap();
+        assert_eq!(shot.damage(), BOSS_DAMAGE);
+        let shot = make_boss_shot(Vec2i::new(50, 50), 180.0, &profile, Some(4)).unwrap();
+        assert_eq!(shot.damage(), 4);
+        assert!(make_boss_shot(Vec2i::new(50, 50), f32::NAN, &profile, Some(4)).is_none());
+    }
+
+    #[test]
+    fn spread_stage_fires_the_spread_pattern() {
+        set_screen_size(Vec2i::new(200, 200));
+        let boss_shape = Shape::new(Vec2i::new(80, 20), 40);
+        let ship = Ship::new(Shape::new(Vec2i::new(150, 180), 10), 100, 100);
+
+        let mut stage = SpreadShooting::new();
+        let shots = loop {
+            match stage.shoot(&boss_shape, 0.5, &ship) {
+                Some(shots) => break shots,
+                None => advance_game_clock(),
+            }
+        };
+        let fired: Vec<i32> = shots.iter().map(|shot| shot.angle()).collect();
+
+        let pattern = Pattern::Spread { center: ANGLE_DOWN, arc: stage.angle_range, step: SPREAD_SHOOTING_ANGLE_STEP };
+        let expected: Vec<i32> = pattern.angles(Vec2i::zero(), Vec2i::zero()).iter().map(|angle| *angle as i32).collect();
+        assert_eq!(fired, expected);
+    }
+
+    #[test]
+    fn lost_arm_drops_the_crossfire_third() {
+        set_screen_size(Vec2i::new(200, 200));
+        let boss_shape = Shape::new(Vec2i::new(80, 20), 40);
+        let ship = Ship::new(Shape::new(Vec2i::new(95, 180), 10), 100, 100);
+        let volley = |stage: &mut SpreadShooting| loop {
+            match stage.shoot(&boss_shape, 0.0, &ship) {
+                Some(_) => break stage.angle_range,
+                None => advance_game_clock(),
+            }
+        };
+
+        let mut stage = SpreadShooting::new();
+        let full = volley(&mut stage);
+        stage.set_tuning(BossTuning { arm_lost: true, ..BossTuning::default() });
+        assert_eq!(volley(&mut stage), full * 2 / 3);
+    }
+
+    #[test]
+    fn targeted_shot_leaves_from_the_wing_nearer_the_ship() {
+        set_screen_size(Vec2i::new(200, 200));
+        let boss_shape = Shape::new(Vec2i::new(80, 20), 40);
+        let shot_x = |ship_x: i32| {
+            let ship = Ship::new(Shape::new(Vec2i::new(ship_x, 180), 10), 100, 100);
+            let mut stage = Targeted::new();
+            loop {
+                match stage.shoot(&boss_shape, 1.0, &ship) {
+                    Some(shots) => break shots[0].shape().center().x,
+                    None => advance_game_clock(),
+                }
+            }
+        };
+        assert_eq!(shot_x(10), BossMuzzle::LeftWing.position(&boss_shape).x);
+        assert_eq!(shot_x(180), BossMuzzle::RightWing.position(&boss_shape).x);
+    }
+
+    #[test]
+    fn sentinel_tracks_the_ship_slower() {
+        use crate::entities::boss::boss_stages::BossStagesFactory;
+        use crate::entities::boss::stage_factories::EasyBossStageFactory;
+
+        set_screen_size(Vec2i::new(200, 200));
+        let ship = Ship::new(Shape::new(Vec2i::new(180, 180), 10), 100, 100);
+        let step = |mut stage: Box<dyn BossStage>| {
+            let mut boss_shape = Shape::new(Vec2i::new(60, 40), 40);
+            stage.update_pos(&mut boss_shape, &ship);
+            boss_shape.pos().x - 60
+        };
+        let sentinel = step(EasyBossStageFactory.create(3));
+        assert!(sentinel > 0);
+        assert!(sentinel < step(Box::new(Targeted::new())));
+    }
+
+    #[test]
+    fn safe_column_ignores_sideways_shots() {
+        set_screen_size(Vec2i::new(200, 200));
+        let column = safe_column(Vec2i::new(100, 20), &[90, 180, 270], 180);
+        assert!(column.is_some());
+    }
+
+    #[test]
+    fn fire_count_with_zero_step() {
+        assert_eq!(SpreadShooting::fire_count_for_angle_range(10, 0), 11);
+    }
+}
@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use crate::entities::shape::Shape;
+use crate::entities::destroyable::Destroyable;
+use crate::entities::ship::Ship;
+use crate::entities::shot::Shot;
+
+use crate::globals::{mirror_angle, screen_size};
+use crate::timing::{Cooldown, TICKS_PER_SECOND};
+
+use super::boss_stages::{BossStage, StageName};
+use super::laser_beam::LaserBeam;
+use super::{Boss, BossMuzzle};
+
+//-----------------------------------------------------------------------------
+
+// from right through down to left, 180 degrees over 3s at 60 ticks per second
+const BEAM_SWEEP_START_ANGLE: f32 = 90.0;
+const BEAM_SWEEP_END_ANGLE: f32 = 270.0;
+const BEAM_SWEEP_SPEED: f32 = 1.0;
+const BEAM_SWEEP_COOLDOWN: Duration = Duration::from_millis(1500);
+const BEAM_TELEGRAPH_DURATION: Duration = Duration::from_millis(500);
+const BEAM_WIDTH: i32 = 6;
+const BEAM_DAMAGE: i32 = 1;
+
+pub const HARD_STAGE_FINISH_HP_THRESHOLD: f32 = 0.15f32;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SweepPhase {
+    Sweeping,
+    Cooldown,
+}
+
+// the hardest of the built-in stages, the boss stays put and sweeps a laser across the screen
+pub struct BeamSweepStage {
+    current_angle: f32,
+    sweep_speed: f32,
+    phase: SweepPhase,
+    cooldown: Cooldown,
+}
+
+impl BeamSweepStage {
+    pub fn new() -> Self {
+        Self {
+            current_angle: BEAM_SWEEP_START_ANGLE,
+            sweep_speed: BEAM_SWEEP_SPEED,
+            phase: SweepPhase::Sweeping,
+            cooldown: Cooldown::new(BEAM_SWEEP_COOLDOWN),
+        }
+    }
+}
+
+impl BossStage for BeamSweepStage {
+    fn name(&self) -> StageName {
+        "BeamSweep"
+    }
+
+    fn intensity_hint(&self) -> f32 {
+        0.9
+    }
+
+    fn update_pos(&mut self, _boss_shape: &mut Shape, _ship: &Ship) {
+        match self.phase {
+            SweepPhase::Sweeping => {
+                self.current_angle += self.sweep_speed;
+                if self.current_angle >= BEAM_SWEEP_END_ANGLE {
+                    self.phase = SweepPhase::Cooldown;
+                    self.cooldown.reset();
+                }
+            }
+            SweepPhase::Cooldown => {
+                if self.cooldown.ready() {
+                    self.current_angle = BEAM_SWEEP_START_ANGLE;
+                    self.phase = SweepPhase::Sweeping;
+                }
+            }
+        }
+    }
+
+    fn shoot(&mut self, _boss_shape: &Shape, _boss_hp_percent: f32, _ship: &Ship) -> Option<Vec<Shot>> {
+        None
+    }
+
+    // the end of the cooldown warns about the next sweep
+    fn telegraphing(&self) -> bool {
+        self.phase == SweepPhase::Cooldown && self.cooldown.time_remaining() <= BEAM_TELEGRAPH_DURATION
+    }
+
+    fn beam(&self, boss_shape: &Shape) -> Option<LaserBeam> {
+        if self.phase != SweepPhase::Sweeping {
+            return None;
+        }
+
+        let screen = screen_size();
+        Some(LaserBeam {
+            origin: BossMuzzle::Center.position(boss_shape),
+            angle: mirror_angle(self.current_angle.round() as i32) as f32,
+            // long enough to reach any corner
+            length: screen.x + screen.y,
+            width: BEAM_WIDTH,
+            damage: BEAM_DAMAGE,
+        })
+    }
+
+    // the beam hurts every tick it touches the ship, assumed to touch it for the whole sweep
+    fn estimated_dps(&self, _boss: &Boss) -> f32 {
+        let sweep_secs = (BEAM_SWEEP_END_ANGLE - BEAM_SWEEP_START_ANGLE) / BEAM_SWEEP_SPEED / TICKS_PER_SECOND as f32;
+        let cycle_secs = sweep_secs + BEAM_SWEEP_COOLDOWN.as_secs_f32();
+        BEAM_DAMAGE as f32 * TICKS_PER_SECOND as f32 * sweep_secs / cycle_secs
+    }
+
+    // a sweep must not be cut in the middle
+    fn can_be_interrupted(&self) -> bool {
+        self.phase == SweepPhase::Cooldown
+    }
+
+    fn delay_timers(&mut self, by: Duration) {
+        self.cooldown.delay(by);
+    }
+
+    fn completed(&self, boss: &Boss) -> bool {
+        boss.hp_percent() < HARD_STAGE_FINISH_HP_THRESHOLD
+    }
+}
+
+//-----------------------------------------------------------------------------
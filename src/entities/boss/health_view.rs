@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use crate::timing::GameInstant;
+
+//-----------------------------------------------------------------------------
+
+const GHOST_DELAY: Duration = Duration::from_millis(500);
+
+//-----------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+pub struct BossHealthView {
+    percent: f32,
+    ghost_percent: f32,
+    segment_edges: Vec<f32>,
+    invulnerable: bool,
+    // hp percent of a detached part, shown as its own segment
+    secondary_percent: Option<f32>,
+
+    last_damage_time: GameInstant,
+}
+
+//-----------------------------------------------------------------------------
+
+impl BossHealthView {
+    pub fn new(segment_edges: Vec<f32>) -> Self {
+        let mut view = Self {
+            percent: 1.0,
+            ghost_percent: 1.0,
+            segment_edges: vec![],
+            invulnerable: false,
+            secondary_percent: None,
+            last_damage_time: GameInstant::now(),
+        };
+        view.set_segment_edges(segment_edges);
+        view
+    }
+
+    pub fn set_segment_edges(&mut self, mut segment_edges: Vec<f32>) {
+        segment_edges.retain(|edge| *edge > 0.0 && *edge < 1.0);
+        segment_edges.sort_by(|lhs, rhs| rhs.total_cmp(lhs));
+        segment_edges.dedup();
+        self.segment_edges = segment_edges;
+    }
+
+    pub fn update(&mut self, hp_percent: f32, invulnerable: bool) {
+        self.update_at(hp_percent, invulnerable, GameInstant::now())
+    }
+
+    // the ghost keeps the pre-damage value until no damage was taken for GHOST_DELAY
+    pub fn update_at(&mut self, hp_percent: f32, invulnerable: bool, now: GameInstant) {
+        let hp_percent = hp_percent.clamp(0.0, 1.0);
+        if hp_percent < self.percent {
+            self.last_damage_time = now;
+        }
+
+        self.percent = hp_percent;
+        if hp_percent > self.ghost_percent || self.last_damage_time + GHOST_DELAY <= now {
+            self.ghost_percent = hp_percent;
+        }
+        self.invulnerable = invulnerable;
+    }
+
+    pub fn percent(&self) -> f32 {
+        self.percent
+    }
+    pub fn ghost_percent(&self) -> f32 {
+        self.ghost_percent
+    }
+    pub fn segment_edges(&self) -> &[f32] {
+        &self.segment_edges
+    }
+    pub fn invulnerable(&self) -> bool {
+        self.invulnerable
+    }
+
+    pub fn secondary_percent(&self) -> Option<f32> {
+        self.secondary_percent
+    }
+    pub fn set_secondary_percent(&mut self, percent: Option<f32>) {
+        self.secondary_percent = percent.map(|percent| percent.clamp(0.0, 1.0));
+    }
+
+    pub fn segments_remaining(&self) -> usize {
+        self.segment_edges.iter().filter(|edge| self.percent > **edge).count() + 1
+    }
+}
+
+//-----------------------------------------------------------------------------
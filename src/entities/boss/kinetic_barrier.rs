@@ -0,0 +1,87 @@
+use crate::entities::destroyable::Destroyable;
+use crate::entities::flash::FlashState;
+use crate::entities::shape::{Shape, Shaped};
+use crate::math::Vec2i;
+use crate::math::polar::PolarVec;
+
+//-----------------------------------------------------------------------------
+
+pub const KINETIC_BARRIER_WIDTH: i32 = 16;
+pub const KINETIC_BARRIER_MAX_HP: i32 = 30;
+// degrees per tick, a full orbit takes 3 seconds
+pub const KINETIC_BARRIER_ANGULAR_SPEED: f32 = 2.0;
+
+//-----------------------------------------------------------------------------
+
+// orbits the boss and eats player shots, the boss can't be hurt while any barrier is up
+pub struct KineticBarrier {
+    shape: Shape,
+    orbit_center: Vec2i,
+    radius: i32,
+    angle: f32,
+    angular_speed: f32,
+    hp: i32,
+    hp_max: i32,
+    flash: FlashState,
+}
+
+impl Shaped for KineticBarrier {
+    fn shape(&self) -> &Shape {
+        &self.shape
+    }
+    fn shape_mut(&mut self) -> &mut Shape {
+        &mut self.shape
+    }
+}
+
+impl Destroyable for KineticBarrier {
+    fn hp(&self) -> i32 {
+        self.hp
+    }
+    fn hp_mut(&mut self) -> &mut i32 {
+        &mut self.hp
+    }
+    fn hp_max(&self) -> i32 {
+        self.hp_max
+    }
+
+    fn flash(&self) -> &FlashState {
+        &self.flash
+    }
+    fn flash_mut(&mut self) -> &mut FlashState {
+        &mut self.flash
+    }
+}
+
+impl KineticBarrier {
+    pub fn new(orbit_center: Vec2i, radius: i32, angle: f32, angular_speed: f32, hp: i32) -> Self {
+        let mut barrier = Self {
+            shape: Shape::new(orbit_center, KINETIC_BARRIER_WIDTH),
+            orbit_center,
+            radius,
+            angle,
+            angular_speed,
+            hp,
+            hp_max: hp,
+            flash: FlashState::new(),
+        };
+        barrier.place();
+        barrier
+    }
+
+    // the orbit follows the boss
+    pub fn update(&mut self, orbit_center: Vec2i) {
+        self.flash.update();
+        self.orbit_center = orbit_center;
+        self.angle = (self.angle + self.angular_speed).rem_euclid(360.0);
+        self.place();
+    }
+
+    fn place(&mut self) {
+        let offset = PolarVec::new(self.radius as f32, self.angle).to_cartesian();
+        let center = self.orbit_center + Vec2i { x: offset.x.round() as i32, y: offset.y.round() as i32 };
+        self.shape.set_pos(center - self.shape.width() / 2);
+    }
+}
+
+//-----------------------------------------------------------------------------
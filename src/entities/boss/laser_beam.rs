@@ -0,0 +1,49 @@
+use crate::entities::shape::Shape;
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+// straight beam from the origin, the angle is clockwise from "up" like the shot angles
+#[derive(Copy, Clone, Debug)]
+pub struct LaserBeam {
+    pub origin: Vec2i,
+    pub angle: f32,
+    pub length: i32,
+    pub width: i32,
+    // dealt every tick the ship touches the beam
+    pub damage: i32,
+}
+
+impl LaserBeam {
+    pub fn end(&self) -> Vec2i {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        Vec2i {
+            x: self.origin.x + (sin * self.length as f32).round() as i32,
+            y: self.origin.y - (cos * self.length as f32).round() as i32,
+        }
+    }
+
+    // the shape is treated as a circle inscribed into it
+    pub fn intersects(&self, shape: &Shape) -> bool {
+        let center = shape.center();
+        let end = self.end();
+
+        let segment = end - self.origin;
+        let to_center = center - self.origin;
+        let segment_len_sq = (segment.x * segment.x + segment.y * segment.y) as f32;
+        let t = if segment_len_sq > 0.0 {
+            ((to_center.x * segment.x + to_center.y * segment.y) as f32 / segment_len_sq).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let closest_x = self.origin.x as f32 + segment.x as f32 * t;
+        let closest_y = self.origin.y as f32 + segment.y as f32 * t;
+        let dx = center.x as f32 - closest_x;
+        let dy = center.y as f32 - closest_y;
+        let reach = (self.width + shape.width()) as f32 / 2.0;
+        dx * dx + dy * dy <= reach * reach
+    }
+}
+
+//-----------------------------------------------------------------------------
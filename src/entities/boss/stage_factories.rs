@@ -1,7 +1,21 @@
+use crate::power_ups::PowerUpKind;
+
 use super::BossStage;
 use super::BossStagesFactory;
+use super::boss_stages::ShotProfile;
 
 use super::easy_stages::*;
+use super::hard_stages::*;
+
+// heavy slow orbs and fast thin needles
+const HEAVY_ORB_SHOT_PROFILE: ShotProfile = ShotProfile { speed: Some(8), width: Some(40) };
+// narrows the playfield by 15% per side
+const ARENA_WALLS_FRACTION: f32 = 0.15;
+const NEEDLE_SHOT_PROFILE: ShotProfile = ShotProfile { speed: Some(45), width: Some(4) };
+const WEAPON_ARM_HP_THRESHOLD: f32 = 0.5;
+const WARDEN_KINETIC_BARRIERS: u32 = 2;
+// the first boss follows the ship at less than half the usual speed, so standing still isn't punished at once
+const SENTINEL_TRACKING_SPEED: i32 = 6;
 
 pub struct EasyBossStageFactory;
 impl BossStagesFactory for EasyBossStageFactory {
@@ -10,8 +24,66 @@ impl BossStagesFactory for EasyBossStageFactory {
             0 => Box::new(AppearStage::new()),
             1 => Box::new(SimpleShootingDown::new()),
             2 => Box::new(SpreadShooting::new()),
-            3 => Box::new(Targeted::new()),
+            3 => Box::new(Targeted::with_max_tracking_speed(SENTINEL_TRACKING_SPEED)),
             _ => panic!("Index out of range")
         }
     }
+
+    fn stages_count(&self) -> i32 {
+        4
+    }
+
+    fn hp_thresholds(&self) -> Vec<f32> {
+        vec![STAGE_1_FINISH_HP_THRESHOLD, STAGE_2_FINISH_HP_THRESHOLD]
+    }
+
+    fn boss_name(&self) -> &'static str {
+        "Sentinel"
+    }
+
+    fn final_stage_drop(&self) -> Option<PowerUpKind> {
+        Some(PowerUpKind::PiercingShots)
+    }
+}
+
+pub struct MediumBossStageFactory;
+impl BossStagesFactory for MediumBossStageFactory {
+    fn create(&self, idx: i32) -> Box<dyn BossStage> {
+        match idx {
+            0 => Box::new(AppearStage::appear_from_side(ScreenSide::Left)),
+            1 => Box::new(SimpleShootingDown::with_shot_profile(HEAVY_ORB_SHOT_PROFILE)),
+            2 => Box::new(SpreadShooting::with_shot_profile(NEEDLE_SHOT_PROFILE)),
+            3 => Box::new(BeamSweepStage::new()),
+            4 => Box::new(Targeted::with_shot_profile(NEEDLE_SHOT_PROFILE)),
+            _ => panic!("Index out of range")
+        }
+    }
+
+    fn stages_count(&self) -> i32 {
+        5
+    }
+
+    fn hp_thresholds(&self) -> Vec<f32> {
+        vec![STAGE_1_FINISH_HP_THRESHOLD, STAGE_2_FINISH_HP_THRESHOLD, HARD_STAGE_FINISH_HP_THRESHOLD]
+    }
+
+    fn boss_name(&self) -> &'static str {
+        "Warden"
+    }
+
+    fn arena_walls(&self) -> Option<f32> {
+        Some(ARENA_WALLS_FRACTION)
+    }
+
+    fn weapon_arm_threshold(&self) -> Option<f32> {
+        Some(WEAPON_ARM_HP_THRESHOLD)
+    }
+
+    fn kinetic_barriers(&self) -> u32 {
+        WARDEN_KINETIC_BARRIERS
+    }
+
+    fn enrage_in_last_stage(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use crate::entities::shape::Shape;
+use crate::entities::ship::Ship;
+use crate::entities::shot::Shot;
+use crate::timing::GameInstant;
+
+use super::boss_stages::{BossStage, StageName};
+use super::Boss;
+
+//-----------------------------------------------------------------------------
+
+pub const STUN_DURATION: Duration = Duration::from_millis(1200);
+const STUNNED_DAMAGE_MULTIPLIER: f32 = 1.5;
+
+//-----------------------------------------------------------------------------
+
+// pushed on top of the interrupted stage, the boss neither moves nor shoots
+pub struct StunnedStage {
+    started: GameInstant,
+}
+
+impl StunnedStage {
+    pub fn new() -> Self {
+        Self { started: GameInstant::now() }
+    }
+}
+
+impl BossStage for StunnedStage {
+    fn name(&self) -> StageName {
+        "Stunned"
+    }
+
+    fn intensity_hint(&self) -> f32 {
+        0.3
+    }
+
+    fn update_pos(&mut self, _boss_shape: &mut Shape, _ship: &Ship) {}
+
+    fn shoot(&mut self, _boss_shape: &Shape, _boss_hp_percent: f32, _ship: &Ship) -> Option<Vec<Shot>> {
+        None
+    }
+
+    fn completed(&self, _boss: &Boss) -> bool {
+        self.started.elapsed() >= STUN_DURATION
+    }
+
+    fn damage_taken_multiplier(&self) -> f32 {
+        STUNNED_DAMAGE_MULTIPLIER
+    }
+}
+
+//-----------------------------------------------------------------------------
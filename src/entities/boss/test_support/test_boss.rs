@@ -0,0 +1,58 @@
+use crate::entities::boss::Boss;
+use crate::entities::boss::boss_stages::{BossStage, BossStagesFactory};
+use crate::entities::shape::Shape;
+use crate::math::Vec2i;
+use crate::power_ups::PowerUpKind;
+
+use std::cell::RefCell;
+
+//-----------------------------------------------------------------------------
+
+const TEST_BOSS_WIDTH: i32 = 200;
+
+//-----------------------------------------------------------------------------
+
+// real boss running a single known stage, set_hp moves it across hp thresholds
+pub struct TestBoss;
+
+impl TestBoss {
+    pub fn with_stage(stage: Box<dyn BossStage>, hp: i32) -> Boss {
+        let shape = Shape::new(Vec2i::zero(), TEST_BOSS_WIDTH);
+        Boss::new(shape, hp, Box::new(SingleStageFactory { stage: RefCell::new(Some(stage)) }))
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// a boss with one stage is always in its last stage, so the stage is created only once
+struct SingleStageFactory {
+    stage: RefCell<Option<Box<dyn BossStage>>>,
+}
+
+impl BossStagesFactory for SingleStageFactory {
+    fn create(&self, _idx: i32) -> Box<dyn BossStage> {
+        self.stage.borrow_mut().take().expect("Single stage created twice")
+    }
+
+    fn stages_count(&self) -> i32 {
+        1
+    }
+
+    fn hp_thresholds(&self) -> Vec<f32> {
+        vec![]
+    }
+
+    fn boss_name(&self) -> &'static str {
+        "Test Boss"
+    }
+
+    fn final_stage_drop(&self) -> Option<PowerUpKind> {
+        None
+    }
+
+    fn kill_cam(&self) -> bool {
+        false
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,65 @@
+use crate::entities::shape::Shape;
+use crate::math::{Rect, Vec2i};
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone)]
+pub struct WallBounce {
+    velocity: Vec2i,
+}
+
+//-----------------------------------------------------------------------------
+
+impl WallBounce {
+    pub fn new(velocity: Vec2i) -> Self {
+        Self { velocity }
+    }
+
+    pub fn velocity(&self) -> Vec2i {
+        self.velocity
+    }
+
+    // moves by the velocity, reversing the component which would take the shape out of the rect
+    pub fn apply(&mut self, pos: Vec2i, shape: &Shape, rect: &Rect) -> Vec2i {
+        let max_pos = rect.bottom_right - shape.width();
+        let mut new_pos = pos + self.velocity;
+
+        if new_pos.x < rect.top_left.x || new_pos.x > max_pos.x {
+            self.velocity.x = -self.velocity.x;
+            new_pos.x = pos.x + self.velocity.x;
+        }
+        if new_pos.y < rect.top_left.y || new_pos.y > max_pos.y {
+            self.velocity.y = -self.velocity.y;
+            new_pos.y = pos.y + self.velocity.y;
+        }
+
+        new_pos
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect() -> Rect {
+        Rect { top_left: Vec2i::new(0, 0), bottom_right: Vec2i::new(100, 100) }
+    }
+
+    #[test]
+    fn moves_by_velocity_inside_rect() {
+        let shape = Shape::new(Vec2i::new(50, 50), 10);
+        let mut bounce = WallBounce::new(Vec2i::new(3, -2));
+        assert_eq!(bounce.apply(shape.pos(), &shape, &rect()), Vec2i::new(53, 48));
+        assert_eq!(bounce.velocity(), Vec2i::new(3, -2));
+    }
+
+    #[test]
+    fn bounces_off_each_edge() {
+        let shape = Shape::new(Vec2i::new(88, 1), 10);
+        let mut bounce = WallBounce::new(Vec2i::new(5, -5));
+        assert_eq!(bounce.apply(shape.pos(), &shape, &rect()), Vec2i::new(83, 6));
+        assert_eq!(bounce.velocity(), Vec2i::new(-5, 5));
+    }
+}
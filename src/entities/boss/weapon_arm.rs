@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use crate::entities::destroyable::Destroyable;
+use crate::entities::flash::FlashState;
+use crate::entities::patterns::{aimed_angle, Pattern, PatternTimer};
+use crate::entities::shape::{Shape, Shaped};
+use crate::entities::ship::Ship;
+use crate::entities::shot::Shot;
+use crate::globals::boss_arena_rect;
+use crate::timing::Cooldown;
+
+use super::boss_stages::ShotProfile;
+use super::easy_stages::make_boss_shot;
+
+//-----------------------------------------------------------------------------
+
+pub const WEAPON_ARM_WIDTH: i32 = 40;
+pub const WEAPON_ARM_MAX_HP: i32 = 40;
+
+const ARM_MOVE_SPEED: i32 = 6;
+const ARM_VOLLEY_INTERVAL: Duration = Duration::from_millis(1200);
+const ARM_BURST_SHOTS: u32 = 3;
+const ARM_BURST_INTERVAL_TICKS: u32 = 5;
+
+//-----------------------------------------------------------------------------
+
+// detached from the boss, chases the ship and fires aimed bursts until destroyed
+pub struct WeaponArm {
+    shape: Shape,
+    hp: i32,
+    hp_max: i32,
+    flash: FlashState,
+
+    volley_cooldown: Cooldown,
+    burst: Pattern,
+    burst_timer: PatternTimer,
+}
+
+impl Shaped for WeaponArm {
+    fn shape(&self) -> &Shape {
+        &self.shape
+    }
+    fn shape_mut(&mut self) -> &mut Shape {
+        &mut self.shape
+    }
+}
+
+impl Destroyable for WeaponArm {
+    fn hp(&self) -> i32 {
+        self.hp
+    }
+    fn hp_mut(&mut self) -> &mut i32 {
+        &mut self.hp
+    }
+    fn hp_max(&self) -> i32 {
+        self.hp_max
+    }
+
+    fn flash(&self) -> &FlashState {
+        &self.flash
+    }
+    fn flash_mut(&mut self) -> &mut FlashState {
+        &mut self.flash
+    }
+}
+
+impl WeaponArm {
+    pub fn new(shape: Shape, hp: i32) -> Self {
+        Self {
+            shape,
+            hp,
+            hp_max: hp,
+            flash: FlashState::new(),
+            volley_cooldown: Cooldown::new(ARM_VOLLEY_INTERVAL),
+            burst: Pattern::Single { angle: 180 },
+            burst_timer: PatternTimer::default(),
+        }
+    }
+
+    pub fn update(&mut self, ship: &Ship) -> Vec<Shot> {
+        self.flash.update();
+        self.chase(ship);
+        self.shoot(ship)
+    }
+
+    fn chase(&mut self, ship: &Ship) {
+        let diff_x = ship.shape().center().x - self.shape.center().x;
+        let mut pos = self.shape.pos();
+        pos.x += diff_x.clamp(-ARM_MOVE_SPEED, ARM_MOVE_SPEED);
+
+        self.shape.set_pos(pos.clamp_to_rect(&boss_arena_rect(), self.shape.width()));
+    }
+
+    // the burst is aimed once, when the volley starts
+    fn shoot(&mut self, ship: &Ship) -> Vec<Shot> {
+        let origin = self.shape.center();
+        let target = ship.shape().center();
+        if self.volley_cooldown.ready() {
+            self.volley_cooldown.reset();
+            let angle = aimed_angle(origin, target);
+            if angle.is_finite() {
+                self.burst = Pattern::Burst { angle: angle.round() as i32, shots: ARM_BURST_SHOTS, intra_interval: ARM_BURST_INTERVAL_TICKS };
+                self.burst.start(&mut self.burst_timer);
+            }
+        }
+
+        self.burst.update(origin, target, &ShotProfile::default(), &mut self.burst_timer)
+            .iter()
+            .filter_map(|spec| make_boss_shot(spec.origin, spec.angle, &spec.profile, None))
+            .collect()
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -1,17 +1,57 @@
 use rand::Rng;
 use rand::distributions::{Distribution, Standard};
 
+use super::animator::{AnimationClip, Animator, CLIP_DEATH, CLIP_IDLE};
 use super::shape::{Shape, Shaped};
 
 use crate::constants::*;
+use crate::globals::{deterministic, with_rng};
 use crate::math::Vec2i;
+use crate::math::fixed::{movement_step, truncate_step, Fixed};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum CometKind {
     Simple,
     Double,
 }
 
+impl CometKind {
+    pub const ALL: [CometKind; 2] = [CometKind::Simple, CometKind::Double];
+
+    // name used in level definitions and boss scripts
+    pub fn name(&self) -> &'static str {
+        match self {
+            CometKind::Simple => "simple",
+            CometKind::Double => "double",
+        }
+    }
+}
+
+impl std::fmt::Display for CometKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownCometKind(pub String);
+
+impl std::fmt::Display for UnknownCometKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown comet kind '{}'", self.0)
+    }
+}
+
+impl std::str::FromStr for CometKind {
+    type Err = UnknownCometKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CometKind::ALL.into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| UnknownCometKind(s.to_string()))
+    }
+}
+
 impl Distribution<CometKind> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> CometKind {
         match rng.gen_range(0..1) {
@@ -22,12 +62,22 @@ impl Distribution<CometKind> for Standard {
     }
 }
 
+const COMET_CLIPS: &[AnimationClip] = &[
+    AnimationClip { name: CLIP_IDLE, frames: &[0, 1, 2, 3], frame_ticks: 6, looping: true },
+    AnimationClip { name: CLIP_DEATH, frames: &[4, 5, 6], frame_ticks: 4, looping: false },
+];
+
 pub struct Comet {
     kind: CometKind,
     shape: Shape,
 
     angle: i32,
     speed: i32,
+
+    // index of the background spawner which created the comet
+    owner: Option<usize>,
+
+    animator: Animator,
 }
 
 impl Shaped for Comet {
@@ -48,9 +98,29 @@ impl Comet {
             shape,
             angle,
             speed,
+            owner: None,
+            animator: Animator::new(COMET_CLIPS),
         }
     }
 
+    pub fn owner(&self) -> Option<usize> {
+        self.owner
+    }
+    pub fn set_owner(&mut self, owner: usize) {
+        self.owner = Some(owner);
+    }
+
+    pub fn animator(&self) -> &Animator {
+        &self.animator
+    }
+    pub fn update_animation(&mut self) {
+        self.animator.update();
+    }
+
+    pub fn start_dying(&mut self) {
+        self.animator.play(CLIP_DEATH);
+    }
+
     pub fn get_width(kind: CometKind) -> i32 {
         match kind {
             CometKind::Simple => SIMPLE_COMET_WIDTH,
@@ -58,25 +128,34 @@ impl Comet {
         }
     }
 
-    pub fn damage(&self) -> i32 {
+    // dealt to the ship on contact, the comet is destroyed by it
+    pub fn collision_damage(&self) -> i32 {
         match self.kind {
             CometKind::Simple => SIMPLE_COMET_DAMAGE,
             CometKind::Double => DOUBLE_COMET_DAMAGE,
         }
     }
 
-    pub fn kind(&self) -> CometKind {
-        self.kind
+    pub fn angle(&self) -> i32 {
+        self.angle
+    }
+    pub fn set_angle(&mut self, angle: i32) {
+        self.angle = angle;
     }
 
     pub fn fly(&mut self) {
+        if deterministic() {
+            self.shape.set_pos(self.shape.pos() + movement_step(self.angle, Fixed::from_int(self.speed)));
+            return;
+        }
+
         let angle_rad = (self.angle as f32).to_radians();
         let x_diff = (self.speed as f32) * angle_rad.sin();
         let y_diff =  (self.speed as f32) * angle_rad.cos();
 
         let mut pos = self.shape.pos();
-        pos.x += x_diff as i32;
-        pos.y -= y_diff as i32;
+        pos.x += truncate_step(x_diff);
+        pos.y -= truncate_step(y_diff);
         self.shape.set_pos(pos);
     }
 
@@ -84,9 +163,9 @@ impl Comet {
         match self.kind {
             CometKind::Simple => None,
             CometKind::Double => {
-                let mut rng = rand::thread_rng();
-                let shard1_angle_delta = rng.gen_range(0..COMET_SHARD_MAX_ANGLE_DELTA);
-                let shard2_angle_delta = rng.gen_range(0..-COMET_SHARD_MAX_ANGLE_DELTA);
+                let (shard1_angle_delta, shard2_angle_delta) = with_rng(|rng| {
+                    (rng.gen_range(0..COMET_SHARD_MAX_ANGLE_DELTA), rng.gen_range(0..COMET_SHARD_MAX_ANGLE_DELTA))
+                });
 
                 let mut result = vec![];
                 for angle_delta in [shard1_angle_delta, -shard2_angle_delta] {
@@ -100,3 +179,24 @@ impl Comet {
         }
     }
 }
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_names_round_trip() {
+        for kind in CometKind::ALL {
+            assert_eq!(kind.to_string().parse::<CometKind>(), Ok(kind));
+        }
+        assert_eq!("simple".parse::<CometKind>(), Ok(CometKind::Simple));
+    }
+
+    #[test]
+    fn unknown_kind_is_an_error() {
+        assert_eq!("straight_down".parse::<CometKind>(), Err(UnknownCometKind("straight_down".to_string())));
+        assert_eq!("Simple".parse::<CometKind>(), Err(UnknownCometKind("Simple".to_string())));
+    }
+}
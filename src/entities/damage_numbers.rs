@@ -0,0 +1,145 @@
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+pub const MAX_DAMAGE_NUMBERS: usize = 32;
+
+// ~0.6s and ~150ms at 60 updates per second
+const LIFETIME_TICKS: i32 = 36;
+const BATCH_WINDOW_TICKS: i32 = 9;
+const RISE_SPEED: f32 = 0.75;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DamageTarget {
+    Boss,
+    Comet,
+}
+
+impl DamageTarget {
+    // comets are destroyed by a single hit, so each of them gets its own number
+    fn batched(&self) -> bool {
+        match self {
+            DamageTarget::Boss => true,
+            DamageTarget::Comet => false,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone)]
+pub struct DamageNumber {
+    target: DamageTarget,
+    amount: i32,
+    critical: bool,
+
+    x: f32,
+    y: f32,
+
+    age: i32,
+    ticks_since_hit: i32,
+}
+
+impl DamageNumber {
+    pub fn amount(&self) -> i32 {
+        self.amount
+    }
+
+    pub fn critical(&self) -> bool {
+        self.critical
+    }
+
+    pub fn pos(&self) -> Vec2i {
+        Vec2i { x: self.x as i32, y: self.y as i32 }
+    }
+
+    // 1.0 when just shown, 0.0 when fully faded
+    pub fn opacity(&self) -> f32 {
+        1.0 - self.age as f32 / LIFETIME_TICKS as f32
+    }
+
+    fn update(&mut self) {
+        self.y -= RISE_SPEED;
+        self.age += 1;
+        self.ticks_since_hit += 1;
+    }
+
+    fn alive(&self) -> bool {
+        self.age < LIFETIME_TICKS
+    }
+
+    fn accepts_hit_on(&self, target: DamageTarget) -> bool {
+        self.target == target && target.batched() && self.ticks_since_hit <= BATCH_WINDOW_TICKS
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+pub struct DamageNumbers {
+    numbers: Vec<DamageNumber>,
+    enabled: bool,
+}
+
+impl DamageNumbers {
+    pub fn new() -> Self {
+        Self {
+            numbers: Vec::with_capacity(MAX_DAMAGE_NUMBERS),
+            enabled: true,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.numbers.clear();
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn numbers(&self) -> &[DamageNumber] {
+        &self.numbers
+    }
+
+    pub fn clear(&mut self) {
+        self.numbers.clear();
+    }
+
+    // consecutive hits on the same target are accumulated into its active number
+    pub fn add(&mut self, target: DamageTarget, pos: Vec2i, amount: i32, critical: bool) {
+        if !self.enabled {
+            return;
+        }
+
+        if let Some(active) = self.numbers.iter_mut().rev().find(|number| number.accepts_hit_on(target)) {
+            active.amount = active.amount.saturating_add(amount);
+            active.critical |= critical;
+            active.age = 0;
+            active.ticks_since_hit = 0;
+            return;
+        }
+
+        if self.numbers.len() < MAX_DAMAGE_NUMBERS {
+            self.numbers.push(DamageNumber {
+                target,
+                amount,
+                critical,
+                x: pos.x as f32,
+                y: pos.y as f32,
+                age: 0,
+                ticks_since_hit: 0,
+            });
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.numbers.iter_mut().for_each(|number| number.update());
+        self.numbers.retain(|number| number.alive());
+    }
+}
+
+//-----------------------------------------------------------------------------
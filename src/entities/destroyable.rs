@@ -1,17 +1,94 @@
+use super::flash::FlashState;
+use super::shape::Shape;
+
+use crate::constants::HEALTH_BAR_THRESHOLD;
+use crate::math::Vec2i;
+
+// gap between the bar and the top edge of its owner
+const HEALTH_BAR_OFFSET: i32 = 2;
+const HEALTH_BAR_HEIGHT: i32 = 2;
+
+#[derive(Copy, Clone, Debug)]
+pub struct HealthBar {
+    pub current: i32,
+    pub max: i32,
+    pub width: i32,
+}
+
+impl HealthBar {
+    // top left corner of the bar drawn above the given shape
+    pub fn pos_above(shape: &Shape) -> Vec2i {
+        Vec2i { x: shape.pos().x, y: shape.pos().y - HEALTH_BAR_OFFSET - HEALTH_BAR_HEIGHT }
+    }
+
+    pub fn size(&self) -> Vec2i {
+        Vec2i { x: self.width, y: HEALTH_BAR_HEIGHT }
+    }
+
+    // the part of the bar still filled
+    pub fn filled_size(&self) -> Vec2i {
+        Vec2i { x: self.width * self.current / self.max, y: HEALTH_BAR_HEIGHT }
+    }
+}
+
 pub trait Destroyable {
     fn hp(&self) -> i32;
     fn hp_mut(&mut self) -> &mut i32;
     fn hp_max(&self) -> i32;
 
+    fn flash(&self) -> &FlashState;
+    fn flash_mut(&mut self) -> &mut FlashState;
+
     fn hp_percent(&self) -> f32 {
         let result = self.hp() as f32 / self.hp_max() as f32;
         result.max(0.0f32)
     }
 
     fn hit(&mut self, damage: i32) {
-        *self.hp_mut() -= damage;
+        *self.hp_mut() = self.hp().saturating_sub(damage);
+        self.flash_mut().on_hit();
     }
     fn alive(&self) -> bool {
         self.hp() > 0
     }
+
+    // only tough targets get a bar, weak ones die too fast for it to matter
+    fn health_bar(&self, width: i32) -> Option<HealthBar> {
+        if self.hp_max() <= HEALTH_BAR_THRESHOLD {
+            return None;
+        }
+        Some(HealthBar { current: self.hp().max(0), max: self.hp_max(), width })
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::boss::kinetic_barrier::KineticBarrier;
+
+    fn barrier(hp: i32) -> KineticBarrier {
+        KineticBarrier::new(Vec2i::new(100, 100), 20, 0.0, 0.0, hp)
+    }
+
+    #[test]
+    fn only_targets_above_the_threshold_get_a_bar() {
+        assert!(barrier(HEALTH_BAR_THRESHOLD).health_bar(10).is_none());
+        let mut tough = barrier(HEALTH_BAR_THRESHOLD + 1);
+        tough.hit(2);
+        let bar = tough.health_bar(20).unwrap();
+        assert_eq!((bar.current, bar.max), (HEALTH_BAR_THRESHOLD - 1, HEALTH_BAR_THRESHOLD + 1));
+        assert_eq!(bar.filled_size().x, 20 * (HEALTH_BAR_THRESHOLD - 1) / (HEALTH_BAR_THRESHOLD + 1));
+    }
+
+    #[test]
+    fn bar_sits_above_its_owner() {
+        let shape = Shape::new(Vec2i::new(40, 60), 10);
+        let pos = HealthBar::pos_above(&shape);
+        assert_eq!(pos.x, shape.pos().x);
+        assert_eq!(pos.y + HEALTH_BAR_HEIGHT + HEALTH_BAR_OFFSET, shape.pos().y);
+    }
 }
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,86 @@
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+// about 0.2s, the energy is credited only when the particle reaches the ship
+const ARRIVAL_TICKS: i32 = 12;
+const MAX_IN_FLIGHT: usize = 16;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug)]
+pub struct AbsorbParticle {
+    x: f32,
+    y: f32,
+    amount: i32,
+    age: i32,
+}
+
+impl AbsorbParticle {
+    pub fn pos(&self) -> Vec2i {
+        Vec2i { x: self.x.round() as i32, y: self.y.round() as i32 }
+    }
+
+    pub fn amount(&self) -> i32 {
+        self.amount
+    }
+
+    // covers the remaining distance in equal parts, so it always arrives in time even if the ship moves
+    fn update(&mut self, target: Vec2i) {
+        let ticks_left = (ARRIVAL_TICKS - self.age).max(1) as f32;
+        self.x += (target.x as f32 - self.x) / ticks_left;
+        self.y += (target.y as f32 - self.y) / ticks_left;
+        self.age += 1;
+    }
+
+    fn arrived(&self) -> bool {
+        self.age >= ARRIVAL_TICKS
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// streams energy from grazed bullets to the ship, it works even with the visual particles disabled
+pub struct EnergyAbsorb {
+    particles: Vec<AbsorbParticle>,
+}
+
+impl EnergyAbsorb {
+    pub fn new() -> Self {
+        Self { particles: Vec::with_capacity(MAX_IN_FLIGHT) }
+    }
+
+    pub fn particles(&self) -> &[AbsorbParticle] {
+        &self.particles
+    }
+
+    // energy still in flight is dropped, e.g. when the ship dies
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    // over the cap the amount joins the newest particle instead
+    pub fn spawn(&mut self, pos: Vec2i, amount: i32) {
+        if self.particles.len() >= MAX_IN_FLIGHT {
+            if let Some(newest) = self.particles.last_mut() {
+                newest.amount += amount;
+            }
+            return;
+        }
+        self.particles.push(AbsorbParticle { x: pos.x as f32, y: pos.y as f32, amount, age: 0 });
+    }
+
+    // returns the energy of the particles which reached the ship this tick
+    pub fn update(&mut self, target: Vec2i) -> i32 {
+        self.particles.iter_mut().for_each(|particle| particle.update(target));
+
+        let absorbed = self.particles.iter()
+            .filter(|particle| particle.arrived())
+            .map(|particle| particle.amount)
+            .sum();
+        self.particles.retain(|particle| !particle.arrived());
+        absorbed
+    }
+}
+
+//-----------------------------------------------------------------------------
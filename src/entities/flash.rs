@@ -0,0 +1,132 @@
+//-----------------------------------------------------------------------------
+
+const HIT_FLASH_TICKS: i32 = 5;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Tint {
+    None,
+    Invulnerable,
+    Enraged,
+}
+
+// visual feedback only, decays with the simulation ticks so it freezes together with the game
+#[derive(Copy, Clone, Debug)]
+pub struct FlashState {
+    flash_ticks_left: i32,
+    tint: Tint,
+    tint_ticks_left: Option<i32>,
+}
+
+//-----------------------------------------------------------------------------
+
+impl FlashState {
+    pub fn new() -> Self {
+        Self {
+            flash_ticks_left: 0,
+            tint: Tint::None,
+            tint_ticks_left: None,
+        }
+    }
+
+    // hits while invulnerable are not acknowledged, the tint already tells the story
+    pub fn on_hit(&mut self) {
+        if self.tint != Tint::Invulnerable {
+            self.flash_ticks_left = HIT_FLASH_TICKS;
+        }
+    }
+
+    // None duration keeps the tint until it is replaced
+    pub fn set_tint(&mut self, tint: Tint, duration_ticks: Option<i32>) {
+        self.tint = tint;
+        self.tint_ticks_left = duration_ticks;
+        if tint == Tint::Invulnerable {
+            self.flash_ticks_left = 0;
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.flash_ticks_left = (self.flash_ticks_left - 1).max(0);
+
+        if let Some(ticks_left) = &mut self.tint_ticks_left {
+            *ticks_left -= 1;
+            if *ticks_left <= 0 {
+                self.tint = Tint::None;
+                self.tint_ticks_left = None;
+            }
+        }
+    }
+
+    pub fn flash_intensity(&self) -> f32 {
+        self.flash_ticks_left as f32 / HIT_FLASH_TICKS as f32
+    }
+
+    pub fn tint(&self) -> Tint {
+        self.tint
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_flash_decays_with_updates() {
+        let mut flash = FlashState::new();
+        flash.on_hit();
+        assert_eq!(flash.flash_intensity(), 1.0);
+
+        for _ in 0..HIT_FLASH_TICKS {
+            flash.update();
+        }
+        assert_eq!(flash.flash_intensity(), 0.0);
+    }
+
+    #[test]
+    fn hit_while_invulnerable_doesnt_flash() {
+        let mut flash = FlashState::new();
+        flash.set_tint(Tint::Invulnerable, None);
+        flash.on_hit();
+        assert_eq!(flash.flash_intensity(), 0.0);
+    }
+
+    #[test]
+    fn invulnerable_tint_cancels_running_flash() {
+        let mut flash = FlashState::new();
+        flash.on_hit();
+        flash.set_tint(Tint::Invulnerable, Some(3));
+        assert_eq!(flash.flash_intensity(), 0.0);
+    }
+
+    #[test]
+    fn hit_during_other_tints_flashes() {
+        let mut flash = FlashState::new();
+        flash.set_tint(Tint::Enraged, None);
+        flash.on_hit();
+        assert_eq!(flash.flash_intensity(), 1.0);
+        assert_eq!(flash.tint(), Tint::Enraged);
+    }
+
+    #[test]
+    fn timed_tint_expires() {
+        let mut flash = FlashState::new();
+        flash.set_tint(Tint::Enraged, Some(2));
+        flash.update();
+        assert_eq!(flash.tint(), Tint::Enraged);
+        flash.update();
+        assert_eq!(flash.tint(), Tint::None);
+    }
+
+    #[test]
+    fn untimed_tint_stays() {
+        let mut flash = FlashState::new();
+        flash.set_tint(Tint::Enraged, None);
+        for _ in 0..100 {
+            flash.update();
+        }
+        assert_eq!(flash.tint(), Tint::Enraged);
+    }
+}
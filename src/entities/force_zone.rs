@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use crate::constants::SHIP_MOVE_STEP;
+use crate::timing::GameInstant;
+use crate::math::{Rect, Vec2i};
+
+//-----------------------------------------------------------------------------
+
+// the ship can always outrun the current, even when several zones overlap
+const MAX_SHIP_DRIFT: i32 = SHIP_MOVE_STEP - 1;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ZoneChange {
+    Entered,
+    Left,
+}
+
+// pushes shots and the ship by a constant drift each tick while they are inside
+#[derive(Clone)]
+pub struct ForceZone {
+    rect: Rect,
+    drift: Vec2i,
+    expires_at: Option<GameInstant>,
+}
+
+impl ForceZone {
+    pub fn new(rect: Rect, drift: Vec2i, duration: Option<Duration>) -> Self {
+        Self {
+            rect,
+            drift,
+            expires_at: duration.map(|duration| GameInstant::now() + duration),
+        }
+    }
+
+    pub fn rect(&self) -> &Rect {
+        &self.rect
+    }
+
+    pub fn drift(&self) -> Vec2i {
+        self.drift
+    }
+
+    pub fn expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| GameInstant::now() >= expires_at)
+    }
+
+    pub fn contains(&self, point: Vec2i) -> bool {
+        point.x >= self.rect.top_left.x && point.x < self.rect.bottom_right.x
+            && point.y >= self.rect.top_left.y && point.y < self.rect.bottom_right.y
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+pub struct ForceZones {
+    zones: Vec<ForceZone>,
+    ship_inside: bool,
+}
+
+impl ForceZones {
+    pub fn new(zones: Vec<ForceZone>) -> Self {
+        Self { zones, ship_inside: false }
+    }
+
+    pub fn zones(&self) -> &[ForceZone] {
+        &self.zones
+    }
+
+    pub fn ship_inside(&self) -> bool {
+        self.ship_inside
+    }
+
+    pub fn update(&mut self) {
+        self.zones.retain(|zone| !zone.expired());
+    }
+
+    // overlapping zones sum up
+    pub fn drift_at(&self, point: Vec2i) -> Vec2i {
+        self.zones.iter()
+            .filter(|zone| zone.contains(point))
+            .fold(Vec2i { x: 0, y: 0 }, |sum, zone| sum + zone.drift())
+    }
+
+    pub fn ship_drift(&self, point: Vec2i) -> Vec2i {
+        self.drift_at(point).clamp(Vec2i { x: -MAX_SHIP_DRIFT, y: -MAX_SHIP_DRIFT }, Vec2i { x: MAX_SHIP_DRIFT, y: MAX_SHIP_DRIFT })
+    }
+
+    pub fn update_ship(&mut self, point: Vec2i) -> Option<ZoneChange> {
+        let inside = self.zones.iter().any(|zone| zone.contains(point));
+        if inside == self.ship_inside {
+            return None;
+        }
+        self.ship_inside = inside;
+        Some(if inside { ZoneChange::Entered } else { ZoneChange::Left })
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::shape::{Shape, Shaped};
+    use crate::entities::shot::Shot;
+    use crate::timing::{advance_game_clock, duration_to_ticks};
+
+    const ANGLE_DOWN: i32 = 180;
+
+    fn crosswind(drift: Vec2i) -> ForceZone {
+        let rect = Rect { top_left: Vec2i::new(0, 50), bottom_right: Vec2i::new(200, 100) };
+        ForceZone::new(rect, drift, None)
+    }
+
+    #[test]
+    fn straight_shot_leaves_crosswind_at_predicted_offset() {
+        let zones = ForceZones::new(vec![crosswind(Vec2i::new(3, 0))]);
+        let mut shot = Shot::new(Shape::new(Vec2i::new(99, -1), 2), 5, ANGLE_DOWN, 10);
+
+        // moved first, drifted after, like in the game: the center is inside at y = 50, 55, ..., 95
+        while shot.shape().center().y < 150 {
+            shot.fly();
+            shot.drift(zones.drift_at(shot.shape().center()));
+        }
+        assert_eq!(shot.shape().center().x, 100 + 10 * 3);
+    }
+
+    #[test]
+    fn overlapping_zones_sum_up() {
+        let zones = ForceZones::new(vec![crosswind(Vec2i::new(3, 0)), crosswind(Vec2i::new(-1, 2))]);
+        assert_eq!(zones.drift_at(Vec2i::new(10, 60)), Vec2i::new(2, 2));
+        assert_eq!(zones.drift_at(Vec2i::new(10, 10)), Vec2i::new(0, 0));
+    }
+
+    #[test]
+    fn ship_can_outrun_the_current() {
+        let zones = ForceZones::new(vec![crosswind(Vec2i::new(SHIP_MOVE_STEP * 2, 0))]);
+        assert_eq!(zones.ship_drift(Vec2i::new(10, 60)), Vec2i::new(MAX_SHIP_DRIFT, 0));
+    }
+
+    #[test]
+    fn ship_enters_and_leaves() {
+        let mut zones = ForceZones::new(vec![crosswind(Vec2i::new(3, 0))]);
+        assert_eq!(zones.update_ship(Vec2i::new(10, 10)), None);
+        assert_eq!(zones.update_ship(Vec2i::new(10, 60)), Some(ZoneChange::Entered));
+        assert_eq!(zones.update_ship(Vec2i::new(20, 60)), None);
+        assert_eq!(zones.update_ship(Vec2i::new(20, 100)), Some(ZoneChange::Left));
+    }
+
+    #[test]
+    fn timed_zone_expires() {
+        let duration = Duration::from_secs(1);
+        let rect = Rect { top_left: Vec2i::new(0, 0), bottom_right: Vec2i::new(10, 10) };
+        let mut zones = ForceZones::new(vec![ForceZone::new(rect, Vec2i::new(1, 0), Some(duration))]);
+        for _ in 0..duration_to_ticks(duration) {
+            advance_game_clock();
+        }
+        zones.update();
+        assert!(zones.zones().is_empty());
+    }
+}
@@ -1,6 +1,20 @@
+pub mod animator;
+pub mod arena_walls;
 pub mod comet;
 pub mod shape;
 pub mod ship;
+pub mod shockwave;
 pub mod shot;
+pub mod spawner;
+pub mod spawn_safety;
 pub mod boss;
 pub mod destroyable;
+pub mod energy_absorb;
+pub mod flash;
+pub mod force_zone;
+pub mod particles;
+pub mod patterns;
+pub mod pickup;
+pub mod damage_numbers;
+pub mod projectile_manager;
+pub mod visual_tag;
@@ -0,0 +1,196 @@
+use std::f32::consts::TAU;
+
+use rand::Rng;
+
+use crate::globals::with_cosmetic_rng;
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+pub const MAX_PARTICLES: usize = 1024;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParticleKind {
+    Spark,
+    Debris,
+    Smoke,
+}
+
+#[derive(Copy, Clone)]
+pub struct Particle {
+    kind: ParticleKind,
+
+    x: f32,
+    y: f32,
+    velocity_x: f32,
+    velocity_y: f32,
+    gravity: f32,
+    drag: f32,
+
+    age: i32,
+    lifetime: i32,
+}
+
+impl Particle {
+    pub fn kind(&self) -> ParticleKind {
+        self.kind
+    }
+
+    pub fn pos(&self) -> Vec2i {
+        Vec2i { x: self.x as i32, y: self.y as i32 }
+    }
+
+    // 1.0 when just emitted, 0.0 at the end of lifetime
+    pub fn life_left(&self) -> f32 {
+        1.0 - self.age as f32 / self.lifetime as f32
+    }
+
+    fn update(&mut self) {
+        self.velocity_x *= 1.0 - self.drag;
+        self.velocity_y = self.velocity_y * (1.0 - self.drag) + self.gravity;
+        self.x += self.velocity_x;
+        self.y += self.velocity_y;
+        self.age += 1;
+    }
+
+    fn alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone)]
+pub struct Emitter {
+    pub kind: ParticleKind,
+    pub pos: Vec2i,
+    pub count: usize,
+    pub max_speed: f32,
+    pub lifetime: i32,
+    pub gravity: f32,
+    pub drag: f32,
+}
+
+impl Emitter {
+    pub fn hit_spark(pos: Vec2i) -> Self {
+        Self { kind: ParticleKind::Spark, pos, count: 6, max_speed: 4.0, lifetime: 8, gravity: 0.0, drag: 0.1 }
+    }
+
+    pub fn explosion(pos: Vec2i) -> Self {
+        Self { kind: ParticleKind::Debris, pos, count: 24, max_speed: 6.0, lifetime: 20, gravity: 0.2, drag: 0.05 }
+    }
+
+    pub fn smoke(pos: Vec2i) -> Self {
+        Self { kind: ParticleKind::Smoke, pos, count: 12, max_speed: 1.5, lifetime: 40, gravity: -0.05, drag: 0.02 }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+pub struct ParticlePool {
+    particles: Vec<Particle>,
+    capacity: usize,
+    enabled: bool,
+}
+
+impl ParticlePool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            particles: Vec::with_capacity(capacity),
+            capacity,
+            enabled: true,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.particles.clear();
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    // particles over the capacity are dropped, the pool never grows
+    pub fn emit(&mut self, emitter: &Emitter) {
+        if !self.enabled {
+            return;
+        }
+
+        let count = emitter.count.min(self.capacity - self.particles.len());
+        with_cosmetic_rng(|rng| {
+            for _ in 0..count {
+                let angle = rng.gen_range(0.0..TAU);
+                let speed = rng.gen_range(0.0..=emitter.max_speed);
+                self.particles.push(Particle {
+                    kind: emitter.kind,
+                    x: emitter.pos.x as f32,
+                    y: emitter.pos.y as f32,
+                    velocity_x: speed * angle.cos(),
+                    velocity_y: speed * angle.sin(),
+                    gravity: emitter.gravity,
+                    drag: emitter.drag,
+                    age: 0,
+                    lifetime: emitter.lifetime,
+                });
+            }
+        });
+    }
+
+    pub fn update(&mut self) {
+        self.particles.iter_mut().for_each(|particle| particle.update());
+        self.particles.retain(|particle| particle.alive());
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::globals::seed_cosmetic_rng;
+
+    fn positions(pool: &ParticlePool) -> Vec<Vec2i> {
+        pool.particles().iter().map(|particle| particle.pos()).collect()
+    }
+
+    #[test]
+    fn pool_never_grows_past_its_capacity() {
+        let mut pool = ParticlePool::new(MAX_PARTICLES);
+        let buffer = pool.particles.as_ptr();
+        let mut emitter = Emitter::explosion(Vec2i::new(100, 100));
+        emitter.count = 10_000;
+        pool.emit(&emitter);
+        pool.emit(&Emitter::hit_spark(Vec2i::new(50, 50)));
+        assert_eq!(pool.particles().len(), MAX_PARTICLES);
+        assert_eq!(pool.particles.capacity(), MAX_PARTICLES);
+        assert_eq!(pool.particles.as_ptr(), buffer);
+    }
+
+    #[test]
+    fn seeded_effects_repeat_exactly() {
+        let run = || {
+            seed_cosmetic_rng(158);
+            let mut pool = ParticlePool::new(MAX_PARTICLES);
+            pool.emit(&Emitter::explosion(Vec2i::new(100, 100)));
+            pool.emit(&Emitter::smoke(Vec2i::new(40, 60)));
+            (0..10).map(|_| {
+                pool.update();
+                positions(&pool)
+            }).collect::<Vec<_>>()
+        };
+        let first = run();
+        assert!(first[0].iter().any(|pos| *pos != Vec2i::new(100, 100)));
+        assert_eq!(run(), first);
+    }
+}
+
+//-----------------------------------------------------------------------------
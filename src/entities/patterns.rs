@@ -0,0 +1,145 @@
+use super::boss::boss_stages::ShotProfile;
+
+use crate::globals::mirror_mode;
+use crate::math::Vec2i;
+use crate::math::angles::{angle_to_target, ShotAngleIterator};
+
+//-----------------------------------------------------------------------------
+
+// a shot to be fired, the angle is in the pattern space and NaN when it's undefined (aimed at the origin)
+#[derive(Copy, Clone, Debug)]
+pub struct ShotSpec {
+    pub origin: Vec2i,
+    pub angle: f32,
+    pub profile: ShotProfile,
+}
+
+// bullet patterns shared by everything which shoots, angles are degrees clockwise from "up"
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Pattern {
+    Single { angle: i32 },
+    // stepped across the arc around the center, see ShotAngleIterator
+    Spread { center: i32, arc: i32, step: usize },
+    // the shots of a volley go one by one, intra_interval ticks apart
+    Burst { angle: i32, shots: u32, intra_interval: u32 },
+    Aimed,
+    Ring { count: u32 },
+}
+
+// progress of the current volley, kept by the shooter
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PatternTimer {
+    pending: u32,
+    wait: u32,
+}
+
+// the target is a world position, so the angle is mirrored back into the pattern space
+pub fn aimed_angle(origin: Vec2i, target: Vec2i) -> f32 {
+    let angle = angle_to_target(origin, target);
+    if mirror_mode() { (360.0 - angle).rem_euclid(360.0) } else { angle }
+}
+
+impl Pattern {
+    // angles of the shots fired at once
+    pub fn angles(&self, origin: Vec2i, target: Vec2i) -> Vec<f32> {
+        match *self {
+            Pattern::Single { angle } | Pattern::Burst { angle, .. } => vec![angle as f32],
+            Pattern::Spread { center, arc, step } => {
+                ShotAngleIterator::spread(center, arc.max(0), step).map(|angle| angle as f32).collect()
+            }
+            Pattern::Aimed => vec![aimed_angle(origin, target)],
+            Pattern::Ring { count } => {
+                let count = count.max(1);
+                (0..count).map(|idx| idx as f32 * 360.0 / count as f32).collect()
+            }
+        }
+    }
+
+    // all shots of a volley at once, a burst has no spacing here
+    pub fn volley(&self, origin: Vec2i, target: Vec2i, profile: &ShotProfile) -> Vec<ShotSpec> {
+        self.angles(origin, target).into_iter()
+            .map(|angle| ShotSpec { origin, angle, profile: *profile })
+            .collect()
+    }
+
+    // starts a new volley, fired by the following updates
+    pub fn start(&self, timer: &mut PatternTimer) {
+        timer.pending = match *self {
+            Pattern::Burst { shots, .. } => shots,
+            _ => 1,
+        };
+        timer.wait = 0;
+    }
+
+    // shots due this tick
+    pub fn update(&self, origin: Vec2i, target: Vec2i, profile: &ShotProfile, timer: &mut PatternTimer) -> Vec<ShotSpec> {
+        if timer.pending == 0 {
+            return vec![];
+        }
+        if timer.wait > 0 {
+            timer.wait -= 1;
+            return vec![];
+        }
+
+        timer.pending -= 1;
+        if let Pattern::Burst { intra_interval, .. } = *self {
+            timer.wait = intra_interval;
+        }
+        self.volley(origin, target, profile)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::globals::set_mirror_mode;
+
+    const ORIGIN: Vec2i = Vec2i { x: 100, y: 100 };
+
+    fn angles(pattern: Pattern, target: Vec2i) -> Vec<f32> {
+        pattern.angles(ORIGIN, target)
+    }
+
+    #[test]
+    fn spread_and_ring_angles() {
+        let spread = Pattern::Spread { center: 180, arc: 60, step: 30 };
+        assert_eq!(angles(spread, ORIGIN), vec![150.0, 180.0, 210.0]);
+        assert_eq!(angles(Pattern::Ring { count: 4 }, ORIGIN), vec![0.0, 90.0, 180.0, 270.0]);
+        assert_eq!(angles(Pattern::Ring { count: 0 }, ORIGIN), vec![0.0]);
+    }
+
+    #[test]
+    fn aimed_is_mirrored_into_pattern_space() {
+        let target = Vec2i::new(200, 100);
+        assert_eq!(angles(Pattern::Aimed, target), vec![90.0]);
+        set_mirror_mode(true);
+        let mirrored = angles(Pattern::Aimed, target);
+        set_mirror_mode(false);
+        assert_eq!(mirrored, vec![270.0]);
+        assert!(angles(Pattern::Aimed, ORIGIN)[0].is_nan());
+    }
+
+    #[test]
+    fn burst_spaces_its_shots() {
+        let burst = Pattern::Burst { angle: 180, shots: 3, intra_interval: 2 };
+        let mut timer = PatternTimer::default();
+        let profile = ShotProfile::default();
+        assert!(burst.update(ORIGIN, ORIGIN, &profile, &mut timer).is_empty());
+
+        burst.start(&mut timer);
+        let fired: Vec<usize> = (0..10).map(|_| burst.update(ORIGIN, ORIGIN, &profile, &mut timer).len()).collect();
+        assert_eq!(fired, vec![1, 0, 0, 1, 0, 0, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn volley_fires_once_per_start() {
+        let single = Pattern::Single { angle: 90 };
+        let mut timer = PatternTimer::default();
+        let profile = ShotProfile::default();
+        single.start(&mut timer);
+        assert_eq!(single.update(ORIGIN, ORIGIN, &profile, &mut timer).len(), 1);
+        assert!(single.update(ORIGIN, ORIGIN, &profile, &mut timer).is_empty());
+    }
+}
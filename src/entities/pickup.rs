@@ -0,0 +1,49 @@
+use super::shape::{Shape, Shaped};
+
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+const PICKUP_WIDTH: i32 = 4;
+const FALL_SPEED: i32 = 1;
+const MAGNET_SPEED: f32 = 4.0;
+
+//-----------------------------------------------------------------------------
+
+// small point item, slowly falls and flies to the ship once it's close enough
+pub struct ScorePickup {
+    shape: Shape,
+}
+
+impl Shaped for ScorePickup {
+    fn shape(&self) -> &Shape {
+        &self.shape
+    }
+    fn shape_mut(&mut self) -> &mut Shape {
+        &mut self.shape
+    }
+}
+
+impl ScorePickup {
+    pub fn new(center: Vec2i) -> Self {
+        Self { shape: Shape::new(center - PICKUP_WIDTH / 2, PICKUP_WIDTH) }
+    }
+
+    pub fn update(&mut self, ship_center: Vec2i, magnet_radius: i32) {
+        let diff = ship_center - self.shape.center();
+        let distance = self.shape.center().distance_to(ship_center);
+
+        let step = if distance > 0.0 && distance <= magnet_radius as f32 {
+            let speed = MAGNET_SPEED.min(distance);
+            Vec2i {
+                x: (diff.x as f32 / distance * speed).round() as i32,
+                y: (diff.y as f32 / distance * speed).round() as i32,
+            }
+        } else {
+            Vec2i { x: 0, y: FALL_SPEED }
+        };
+        self.shape.set_pos(self.shape.pos() + step);
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,143 @@
+use super::shape::Shaped;
+use super::shot::Shot;
+
+use crate::globals::playfield_rect;
+
+//-----------------------------------------------------------------------------
+
+pub struct ProjectileManager {
+    player_shots: Vec<Shot>,
+    boss_shots: Vec<Shot>,
+    // the shots which left the screen on the last update, kept around so updating doesn't allocate
+    missed_player_shots: Vec<Shot>,
+    missed_boss_shots: Vec<Shot>,
+}
+
+//-----------------------------------------------------------------------------
+
+impl ProjectileManager {
+    // reserves room for capacity shots of each side, so busy volleys don't reallocate mid-fight
+    pub fn prealloc(capacity: usize) -> Self {
+        Self {
+            player_shots: Vec::with_capacity(capacity),
+            boss_shots: Vec::with_capacity(capacity),
+            missed_player_shots: Vec::with_capacity(capacity),
+            missed_boss_shots: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn player_shots(&self) -> &Vec<Shot> {
+        &self.player_shots
+    }
+    pub fn player_shots_mut(&mut self) -> &mut Vec<Shot> {
+        &mut self.player_shots
+    }
+
+    pub fn boss_shots(&self) -> &Vec<Shot> {
+        &self.boss_shots
+    }
+    pub fn boss_shots_mut(&mut self) -> &mut Vec<Shot> {
+        &mut self.boss_shots
+    }
+
+    pub fn add_player_shot(&mut self, shot: Shot) {
+        self.player_shots.push(shot);
+    }
+    pub fn add_boss_shots(&mut self, mut shots: Vec<Shot>) {
+        self.boss_shots.append(&mut shots);
+    }
+
+    pub fn clear(&mut self) {
+        self.player_shots.clear();
+        self.boss_shots.clear();
+    }
+
+    // moves every shot and drops the ones which left the screen, they're returned until the next update
+    pub fn update_all(&mut self) -> (&[Shot], &[Shot]) {
+        ProjectileManager::update_shots(&mut self.player_shots, &mut self.missed_player_shots);
+        ProjectileManager::update_shots(&mut self.boss_shots, &mut self.missed_boss_shots);
+        (&self.missed_player_shots, &self.missed_boss_shots)
+    }
+
+    pub fn boss_shots_colliding(&self, target: &dyn Shaped) -> Vec<usize> {
+        self.boss_shots.iter()
+            .enumerate()
+            .filter(|(_, shot)| shot.intersects(target))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    // indices are expected in ascending order, as returned by the collision queries, the shots come out
+    // in the order they were fired
+    pub fn take_boss_shots(&mut self, indices: &[usize]) -> Vec<Shot> {
+        if indices.is_empty() {
+            return vec![];
+        }
+        let mut idx = 0;
+        let mut next = indices.iter().peekable();
+        self.boss_shots
+            .extract_if(.., |_| {
+                let taken = next.next_if_eq(&&idx).is_some();
+                idx += 1;
+                taken
+            })
+            .collect()
+    }
+
+    // a single pass over the shots, the order of the remaining ones is kept
+    fn update_shots(shots: &mut Vec<Shot>, missed: &mut Vec<Shot>) {
+        let playfield_rect = playfield_rect();
+        shots.iter_mut().for_each(|shot| shot.fly());
+
+        missed.clear();
+        missed.extend(shots.extract_if(.., |shot| !shot.shape().appear_in_rect(&playfield_rect)));
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::shape::Shape;
+    use crate::globals::set_screen_size;
+    use crate::math::Vec2i;
+
+    const CAPACITY: usize = 64;
+
+    // every other shot flies off the top, the rest stays on the screen
+    fn volley(y: i32) -> Vec<Shot> {
+        (0..CAPACITY as i32 / 2)
+            .map(|x| Shot::new(Shape::new(Vec2i::new(x * 4, y), 2), 5, if x % 2 == 0 { 0 } else { 180 }, x))
+            .collect()
+    }
+
+    #[test]
+    fn updating_doesnt_allocate() {
+        set_screen_size(Vec2i::new(200, 200));
+        let mut projectiles = ProjectileManager::prealloc(CAPACITY);
+        let buffers = [&projectiles.boss_shots, &projectiles.missed_boss_shots].map(|shots| shots.as_ptr());
+
+        for _ in 0..100 {
+            projectiles.add_boss_shots(volley(1));
+            let (_, missed) = projectiles.update_all();
+            assert!(!missed.is_empty());
+            let hit: Vec<usize> = (0..projectiles.boss_shots.len()).step_by(3).collect();
+            projectiles.take_boss_shots(&hit);
+        }
+        assert_eq!([&projectiles.boss_shots, &projectiles.missed_boss_shots].map(|shots| shots.as_ptr()), buffers);
+        assert_eq!(projectiles.missed_boss_shots.capacity(), CAPACITY);
+    }
+
+    #[test]
+    fn taken_shots_keep_their_spawn_order() {
+        let mut projectiles = ProjectileManager::prealloc(CAPACITY);
+        projectiles.add_boss_shots(volley(100));
+        let taken: Vec<i32> = projectiles.take_boss_shots(&[1, 4, 5, 9]).iter().map(|shot| shot.damage()).collect();
+        assert_eq!(taken, [1, 4, 5, 9]);
+        let left: Vec<i32> = projectiles.boss_shots.iter().take(5).map(|shot| shot.damage()).collect();
+        assert_eq!(left, [0, 2, 3, 6, 7]);
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -9,6 +9,12 @@ pub struct Shape {
     width: i32,
 }
 
+impl std::fmt::Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Shape@({},{}) size={}", self.pos.x, self.pos.y, self.width)
+    }
+}
+
 //-----------------------------------------------------------------------------
 
 impl Shape {
@@ -54,7 +60,7 @@ pub trait Shaped {
     fn shape(&self) -> &Shape;
     fn shape_mut(&mut self) -> &mut Shape;
 
-    fn intersects<T: Shaped>(&self, rhs: &T) -> bool {
+    fn intersects<T: Shaped + ?Sized>(&self, rhs: &T) -> bool where Self: Sized {
         let lhs_shape = self.shape();
         let rhs_shape = rhs.shape();
 
@@ -62,6 +68,6 @@ pub trait Shaped {
         let distance_square = pos_diff.x.pow(2) + pos_diff.y.pow(2);
         let intersection_distance_square = (lhs_shape.width() / 2 + rhs_shape.width() / 2).pow(2);
 
-        return distance_square > intersection_distance_square;
+        distance_square <= intersection_distance_square
     }
 }
@@ -1,15 +1,99 @@
+use super::animator::{AnimationClip, Animator, CLIP_BANK_LEFT, CLIP_BANK_RIGHT, CLIP_IDLE};
+use super::comet::Comet;
 use super::destroyable::Destroyable;
+use super::flash::FlashState;
 use super::shape::Shape;
 use super::shape::Shaped;
+use super::shot::Shot;
 
-use std::time::{Duration, Instant};
+use crate::constants::{AUTO_FIRE_INTERVAL, BOMB_ENERGY_COST, DASH_COOLDOWN, DASH_DISTANCE, DASH_INVULNERABILITY, ENERGY_REGEN_RATE, SHIP_MAX_ENERGY, SHIP_MAX_HP, SHIP_MOVE_STEP, SHOT_SPEED, SHOT_WIDTH};
+use crate::globals::{playfield_rect, screen_rect};
+use crate::loadout::{Loadout, Passive, SecondaryWeapon};
+use crate::timing::{Cooldown, GameInstant};
+use crate::math::Vec2i;
+
+use serde::{Deserialize, Serialize};
+
+use std::str::FromStr;
+use std::time::Duration;
+
+const SHIP_SHOT_DAMAGE: i32 = 10;
+const ANGLE_UP: i32 = 0;
+const SPEED_PASSIVE_MULTIPLIER: f32 = 1.1;
+
+const SHIP_CLIPS: &[AnimationClip] = &[
+    AnimationClip { name: CLIP_IDLE, frames: &[0, 1], frame_ticks: 10, looping: true },
+    AnimationClip { name: CLIP_BANK_LEFT, frames: &[2, 3], frame_ticks: 4, looping: true },
+    AnimationClip { name: CLIP_BANK_RIGHT, frames: &[4, 5], frame_ticks: 4, looping: true },
+];
+
+// hull picked for the run, trades hp for speed
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ShipKind {
+    Standard,
+    Tank,
+    Interceptor,
+}
+
+impl ShipKind {
+    pub fn max_hp(&self) -> i32 {
+        match self {
+            ShipKind::Standard => SHIP_MAX_HP,
+            ShipKind::Tank => SHIP_MAX_HP * 3 / 2,
+            ShipKind::Interceptor => SHIP_MAX_HP * 7 / 10,
+        }
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        match self {
+            ShipKind::Standard => 1.0,
+            ShipKind::Tank => 0.8,
+            ShipKind::Interceptor => 1.25,
+        }
+    }
+}
+
+impl FromStr for ShipKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(ShipKind::Standard),
+            "tank" => Ok(ShipKind::Tank),
+            "interceptor" => Ok(ShipKind::Interceptor),
+            _ => Err(()),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
 
 pub struct Ship {
     shape: Shape,
 
     hp: i32,
     hp_max: i32,
-    last_time_hit: Instant,
+
+    energy: i32,
+    max_energy: i32,
+    energy_regen: i32,
+
+    speed_multiplier: f32,
+    time_scale: f32,
+    move_direction: Vec2i,
+    move_carry: f32,
+
+    god_mode: bool,
+
+    auto_fire: bool,
+    auto_fire_timer: Cooldown,
+
+    dash_cooldown: Cooldown,
+    dash_ghost: Vec2i,
+    invulnerable_until: GameInstant,
+
+    flash: FlashState,
+    animator: Animator,
 }
 
 impl Shaped for Ship {
@@ -31,15 +115,383 @@ impl Destroyable for Ship {
     fn hp_max(&self) -> i32 {
         self.hp_max
     }
+
+    fn flash(&self) -> &FlashState {
+        &self.flash
+    }
+    fn flash_mut(&mut self) -> &mut FlashState {
+        &mut self.flash
+    }
+
+    fn hit(&mut self, damage: i32) {
+        if !self.god_mode && !self.invulnerable() {
+            self.hp = self.hp.saturating_sub(damage);
+            self.flash.on_hit();
+        }
+    }
 }
 
 impl Ship {
-    pub fn new(shape: Shape, hp: i32) -> Ship {
+    pub fn new(shape: Shape, hp: i32, energy: i32) -> Ship {
+        let pos = shape.pos();
+        let now = GameInstant::now();
+        // the first dash is available right away
+        let dash_ready_since = now - DASH_COOLDOWN;
         Ship {
             shape,
             hp,
             hp_max: hp,
-            last_time_hit: Instant::now(),
+            energy,
+            max_energy: energy,
+            energy_regen: ENERGY_REGEN_RATE,
+            speed_multiplier: 1.0,
+            time_scale: 1.0,
+            move_direction: Vec2i { x: 0, y: 0 },
+            move_carry: 0.0,
+            god_mode: false,
+            auto_fire: false,
+            auto_fire_timer: Cooldown::new(AUTO_FIRE_INTERVAL),
+            dash_cooldown: Cooldown::started_at(DASH_COOLDOWN, dash_ready_since),
+            dash_ghost: pos,
+            invulnerable_until: now,
+            flash: FlashState::new(),
+            animator: Animator::new(SHIP_CLIPS),
+        }
+    }
+
+    pub fn of_kind(shape: Shape, kind: ShipKind) -> Ship {
+        let mut ship = Ship::new(shape, kind.max_hp(), SHIP_MAX_ENERGY);
+        ship.speed_multiplier = kind.speed_multiplier();
+        ship
+    }
+
+    // the ship starts the run with full energy
+    pub fn apply_loadout(&mut self, loadout: &Loadout) {
+        if loadout.secondary == SecondaryWeapon::ExtraBomb {
+            self.max_energy += BOMB_ENERGY_COST;
+            self.energy = self.max_energy;
+        }
+        match loadout.passive {
+            Passive::EnergyRegen => self.energy_regen = ENERGY_REGEN_RATE * 2,
+            Passive::Speed => self.speed_multiplier *= SPEED_PASSIVE_MULTIPLIER,
+            Passive::MagnetRadius => (),
+        }
+    }
+
+    // 8-way direction the ship moves in, from the input
+    pub fn steer(&mut self, direction: Vec2i) {
+        self.move_direction = direction;
+    }
+
+    // slows the ship with the rest of the world, e.g. during the kill cam
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale;
+    }
+
+    // pixels per tick along each axis with every modifier applied
+    pub fn speed_per_tick(&self) -> f32 {
+        SHIP_MOVE_STEP as f32 * self.speed_multiplier * self.time_scale
+    }
+
+    // effective velocity per tick, rounded
+    pub fn current_speed(&self) -> Vec2i {
+        let speed = self.speed_per_tick();
+        Vec2i {
+            x: (self.move_direction.x as f32 * speed).round() as i32,
+            y: (self.move_direction.y as f32 * speed).round() as i32,
+        }
+    }
+
+    // offset to move by this tick, the fraction of the speed is carried over
+    pub fn move_offset(&mut self) -> Vec2i {
+        self.move_carry += self.speed_per_tick();
+        let step = self.move_carry.floor();
+        self.move_carry -= step;
+        Vec2i { x: self.move_direction.x * step as i32, y: self.move_direction.y * step as i32 }
+    }
+
+    pub fn god_mode(&self) -> bool {
+        self.god_mode
+    }
+    pub fn set_god_mode(&mut self, god_mode: bool) {
+        self.god_mode = god_mode;
+    }
+
+    // fires on its own, independently from the manual shots
+    pub fn auto_fire(&mut self, enabled: bool) {
+        self.auto_fire = enabled;
+    }
+
+    pub fn set_auto_fire_interval(&mut self, interval: Duration) {
+        self.auto_fire_timer = Cooldown::new(interval);
+    }
+
+    // ramming is kept apart from shot hits so it can sound and shake differently
+    pub fn take_contact_damage(&mut self, comet: &Comet) {
+        self.hit(comet.collision_damage());
+    }
+
+    pub fn invulnerable(&self) -> bool {
+        GameInstant::now() < self.invulnerable_until
+    }
+
+    // extends the invulnerability, never shortens it
+    pub fn make_invulnerable(&mut self, duration: Duration) {
+        self.invulnerable_until = self.invulnerable_until.max(GameInstant::now() + duration);
+    }
+
+    pub fn can_dash(&self) -> bool {
+        self.dash_cooldown.ready()
+    }
+
+    // jumps by a fixed distance, staying on the screen, and ignores damage for a moment
+    pub fn dash(&mut self, direction: Vec2i) -> bool {
+        if !self.can_dash() || (direction.x == 0 && direction.y == 0) {
+            return false;
+        }
+
+        let pos = self.shape.pos();
+        let length = ((direction.x * direction.x + direction.y * direction.y) as f32).sqrt();
+        let offset = Vec2i {
+            x: (direction.x as f32 / length * DASH_DISTANCE as f32).round() as i32,
+            y: (direction.y as f32 / length * DASH_DISTANCE as f32).round() as i32,
+        };
+
+        self.shape.set_pos((pos + offset).clamp_to_rect(&playfield_rect(), self.shape.width()));
+
+        self.dash_ghost = pos;
+        self.dash_cooldown.reset();
+        self.invulnerable_until = GameInstant::now() + DASH_INVULNERABILITY;
+        true
+    }
+
+    // pushed by the environment, stays in the playfield
+    pub fn drift(&mut self, offset: Vec2i) {
+        self.shape.set_pos((self.shape.pos() + offset).clamp_to_rect(&playfield_rect(), self.shape.width()));
+    }
+
+    // where the ship dashed from, shown as a trail until the dash is ready again
+    pub fn dash_ghost(&self) -> Option<Vec2i> {
+        if self.can_dash() { None } else { Some(self.dash_ghost) }
+    }
+
+    pub fn update(&mut self) -> Option<Shot> {
+        self.add_energy(self.energy_regen);
+        self.flash.update();
+        self.update_animation();
+
+        if self.auto_fire && self.auto_fire_timer.ready() {
+            self.auto_fire_timer.reset();
+            return Some(self.shoot());
         }
+        None
+    }
+
+    pub fn animator(&self) -> &Animator {
+        &self.animator
+    }
+
+    // top center of the ship, where the nose is
+    pub fn muzzle(&self) -> Vec2i {
+        self.barrel_muzzles(1)[0]
+    }
+
+    // evenly spread along the top edge, for multishot weapons
+    pub fn barrel_muzzles(&self, barrels: i32) -> Vec<Vec2i> {
+        let pos = self.shape.pos();
+        let width = self.shape.width();
+        let screen_rect = screen_rect();
+        (1..=barrels)
+            .map(|barrel| Vec2i { x: pos.x + width * barrel / (barrels + 1), y: pos.y })
+            .map(|muzzle| muzzle.clamp_to_rect(&screen_rect, 0))
+            .collect()
+    }
+
+    pub fn shoot(&self) -> Shot {
+        self.shoot_at(ANGLE_UP)
+    }
+
+    pub fn shoot_at(&self, angle: i32) -> Shot {
+        let shot_shape = Shape::new(self.muzzle() - SHOT_WIDTH / 2, SHOT_WIDTH);
+        Shot::new(shot_shape, SHOT_SPEED, angle, SHIP_SHOT_DAMAGE).with_falloff()
+    }
+
+    // banks by where the ship is steered, a dash or a force zone pushing it doesn't count
+    fn update_animation(&mut self) {
+        let clip = match self.current_speed().x {
+            speed if speed < 0 => CLIP_BANK_LEFT,
+            speed if speed > 0 => CLIP_BANK_RIGHT,
+            _ => CLIP_IDLE,
+        };
+
+        self.animator.play(clip);
+        self.animator.update();
+    }
+
+    pub fn energy(&self) -> i32 {
+        self.energy
+    }
+    pub fn set_energy(&mut self, energy: i32) {
+        self.energy = energy.clamp(0, self.max_energy);
+    }
+    pub fn energy_percent(&self) -> f32 {
+        self.energy as f32 / self.max_energy as f32
+    }
+
+    pub fn add_energy(&mut self, amount: i32) {
+        self.energy = (self.energy + amount).min(self.max_energy);
+    }
+
+    pub fn use_bomb(&mut self, cost: i32) -> bool {
+        if self.energy < cost {
+            return false;
+        }
+
+        self.energy -= cost;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::globals::set_screen_size;
+    use crate::timing::{advance_game_clock, duration_to_ticks};
+
+    fn advance(duration: Duration) {
+        for _ in 0..duration_to_ticks(duration) {
+            advance_game_clock();
+        }
+    }
+
+    fn ship_at(pos: Vec2i) -> Ship {
+        set_screen_size(Vec2i::new(200, 200));
+        Ship::new(Shape::new(pos, 10), 100, 100)
+    }
+
+    #[test]
+    fn doubled_speed_quadruples_magnitude() {
+        let mut ship = ship_at(Vec2i::new(100, 100));
+        ship.steer(Vec2i::new(1, 0));
+        let base = ship.current_speed().magnitude_sq();
+        assert_eq!(base, (SHIP_MOVE_STEP as i64).pow(2));
+
+        ship.speed_multiplier = 2.0;
+        assert_eq!(ship.current_speed().magnitude_sq(), base * 4);
+    }
+
+    #[test]
+    fn time_scale_slows_the_ship() {
+        let mut ship = ship_at(Vec2i::new(100, 100));
+        ship.steer(Vec2i::new(0, -1));
+        ship.set_time_scale(0.0);
+        assert_eq!(ship.current_speed(), Vec2i::zero());
+        assert_eq!(ship.move_offset(), Vec2i::zero());
+    }
+
+    #[test]
+    fn speed_fraction_is_carried_over() {
+        let mut ship = ship_at(Vec2i::new(100, 100));
+        ship.steer(Vec2i::new(1, 1));
+        ship.set_time_scale(0.5);
+        let moved = (0..4).fold(Vec2i::zero(), |sum, _| sum + ship.move_offset());
+        assert_eq!(moved, Vec2i::new(SHIP_MOVE_STEP * 2, SHIP_MOVE_STEP * 2));
+    }
+
+    #[test]
+    fn ship_banks_the_way_it_is_steered() {
+        let mut ship = ship_at(Vec2i::new(100, 100));
+        ship.update();
+        assert_eq!(ship.animator().current_clip().name, CLIP_IDLE);
+        ship.steer(Vec2i::new(-1, 0));
+        ship.update();
+        assert_eq!(ship.animator().current_clip().name, CLIP_BANK_LEFT);
+        ship.steer(Vec2i::new(1, 1));
+        ship.update();
+        assert_eq!(ship.animator().current_clip().name, CLIP_BANK_RIGHT);
+    }
+
+    #[test]
+    fn dash_moves_by_dash_distance() {
+        let mut ship = ship_at(Vec2i::new(100, 100));
+        assert!(ship.dash(Vec2i::new(-1, 0)));
+        assert_eq!(ship.shape().pos(), Vec2i::new(100 - DASH_DISTANCE, 100));
+        assert_eq!(ship.dash_ghost(), Some(Vec2i::new(100, 100)));
+    }
+
+    #[test]
+    fn dash_doesnt_leave_the_screen() {
+        let mut ship = ship_at(Vec2i::new(20, 185));
+        assert!(ship.dash(Vec2i::new(-1, 1)));
+        assert_eq!(ship.shape().pos(), Vec2i::new(0, 190));
+    }
+
+    #[test]
+    fn second_dash_waits_for_cooldown() {
+        let mut ship = ship_at(Vec2i::new(100, 100));
+        assert!(ship.dash(Vec2i::new(0, -1)));
+        assert!(!ship.dash(Vec2i::new(0, -1)));
+
+        advance(DASH_COOLDOWN);
+        assert!(ship.dash(Vec2i::new(0, 1)));
+    }
+
+    #[test]
+    fn dash_is_briefly_invulnerable() {
+        let mut ship = ship_at(Vec2i::new(100, 100));
+        ship.dash(Vec2i::new(1, 0));
+        assert!(ship.invulnerable());
+
+        advance(DASH_INVULNERABILITY);
+        assert!(!ship.invulnerable());
+    }
+
+    #[test]
+    fn tank_trades_speed_for_hp() {
+        let shape = Shape::new(Vec2i::new(100, 100), 10);
+        let standard = Ship::of_kind(shape.clone(), ShipKind::Standard);
+        let tank = Ship::of_kind(shape, ShipKind::Tank);
+        assert!(tank.hp_max() > standard.hp_max());
+        assert!(tank.speed_per_tick() < standard.speed_per_tick());
+        assert_eq!("tank".parse(), Ok(ShipKind::Tank));
+    }
+
+    #[test]
+    fn speed_passive_stacks_with_the_hull() {
+        let mut ship = Ship::of_kind(Shape::new(Vec2i::new(100, 100), 10), ShipKind::Interceptor);
+        ship.apply_loadout(&Loadout { passive: Passive::Speed, ..Loadout::default() });
+        let expected = SHIP_MOVE_STEP as f32 * ShipKind::Interceptor.speed_multiplier() * SPEED_PASSIVE_MULTIPLIER;
+        assert!((ship.speed_per_tick() - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn regen_stops_at_max_energy() {
+        let mut ship = Ship::of_kind(Shape::new(Vec2i::new(100, 100), 10), ShipKind::Standard);
+        ship.set_energy(SHIP_MAX_ENERGY - 1);
+        for _ in 0..10 {
+            ship.update();
+        }
+        assert_eq!(ship.energy(), SHIP_MAX_ENERGY);
+    }
+
+    #[test]
+    fn bomb_needs_enough_energy() {
+        let mut ship = Ship::of_kind(Shape::new(Vec2i::new(100, 100), 10), ShipKind::Standard);
+        ship.set_energy(BOMB_ENERGY_COST - 1);
+        assert!(!ship.use_bomb(BOMB_ENERGY_COST));
+        assert_eq!(ship.energy(), BOMB_ENERGY_COST - 1);
+
+        ship.set_energy(BOMB_ENERGY_COST);
+        assert!(ship.use_bomb(BOMB_ENERGY_COST));
+        assert_eq!(ship.energy(), 0);
+    }
+
+    #[test]
+    fn extra_bomb_raises_the_energy_cap() {
+        let mut ship = Ship::of_kind(Shape::new(Vec2i::new(100, 100), 10), ShipKind::Standard);
+        ship.apply_loadout(&Loadout { secondary: SecondaryWeapon::ExtraBomb, ..Loadout::default() });
+        assert_eq!(ship.energy(), SHIP_MAX_ENERGY + BOMB_ENERGY_COST);
+        ship.set_energy(i32::MAX);
+        assert_eq!(ship.energy(), SHIP_MAX_ENERGY + BOMB_ENERGY_COST);
     }
 }
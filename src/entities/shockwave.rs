@@ -0,0 +1,45 @@
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+// about 0.6s at 60 ticks per second
+const EXPAND_TICKS: i32 = 36;
+
+//-----------------------------------------------------------------------------
+
+// expanding ring from the boss, clears the leftovers of the previous stage pattern
+pub struct Shockwave {
+    center: Vec2i,
+    max_radius: i32,
+    tick: i32,
+}
+
+impl Shockwave {
+    pub fn new(center: Vec2i, max_radius: i32) -> Self {
+        Self { center, max_radius, tick: 0 }
+    }
+
+    pub fn center(&self) -> Vec2i {
+        self.center
+    }
+
+    pub fn radius(&self) -> i32 {
+        self.max_radius * self.tick.min(EXPAND_TICKS) / EXPAND_TICKS
+    }
+
+    pub fn update(&mut self) {
+        self.tick += 1;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= EXPAND_TICKS
+    }
+
+    pub fn touches(&self, point: Vec2i) -> bool {
+        let diff = point - self.center;
+        let radius = self.radius();
+        diff.magnitude_sq() <= (radius as i64).pow(2)
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -1,12 +1,24 @@
 use super::shape::{Shape, Shaped};
+use crate::globals::deterministic;
+use crate::math::{Vec2f, Vec2i};
+use crate::math::fixed::{movement_step, truncate_step, Fixed};
+use crate::math::polar::PolarVec;
+use crate::constants::{SHOT_FALLOFF_DISTANCE, SHOT_FALLOFF_START_DISTANCE, SHOT_MIN_DAMAGE_PERCENT};
 
 pub struct Shot {
     shape: Shape,
 
     speed: i32,
+    speed_multiplier: f32,
     angle: i32,
 
     damage: i32,
+    // the damage drops with the distance flown, see damage_at_distance
+    falloff: bool,
+    traveled: f32,
+    // targets left to pass through, None stops on the first hit
+    piercing: Option<u8>,
+    grazed: bool,
 }
 
 impl Shaped for Shot {
@@ -23,23 +35,226 @@ impl Shot {
         Shot {
             shape,
             speed,
+            speed_multiplier: 1.0,
             angle,
             damage,
+            falloff: false,
+            traveled: 0.0,
+            piercing: None,
+            grazed: false,
         }
     }
 
+    pub fn with_damage(mut self, damage: i32) -> Shot {
+        self.damage = damage;
+        self
+    }
+
+    pub fn with_falloff(mut self) -> Shot {
+        self.falloff = true;
+        self
+    }
+
+    pub fn with_piercing(mut self, max_hits: u8) -> Shot {
+        self.piercing = Some(max_hits);
+        self
+    }
+
+    pub fn piercing(&self) -> Option<u8> {
+        self.piercing
+    }
+
+    // a shot can be grazed only once
+    pub fn graze(&mut self) -> bool {
+        !std::mem::replace(&mut self.grazed, true)
+    }
+
+    // turned by homing, unlike a reflected shot it keeps its strength
+    pub fn steer(&mut self, angle: i32) {
+        self.angle = angle;
+    }
+
+    // sent back by the reflector, it's weaker and can be grazed again
+    pub fn reflect(&mut self, angle: i32) {
+        self.angle = angle;
+        self.damage = (self.damage / 2).max(1);
+        self.speed_multiplier = 1.0;
+        self.grazed = false;
+    }
+
+    // true when the shot is spent and has to be removed
+    pub fn register_hit(&mut self) -> bool {
+        match self.piercing {
+            Some(hits) if hits > 1 => {
+                self.piercing = Some(hits - 1);
+                false
+            }
+            _ => {
+                self.piercing = Some(0);
+                true
+            }
+        }
+    }
+
+    // e.g. slow motion, 1.0 is the normal speed
+    pub fn speed_multiplier(&mut self, multiplier: f32) {
+        self.speed_multiplier = multiplier;
+    }
+
+    // e.g. the boss difficulty, unlike speed_multiplier it stays with the shot
+    pub fn scale_speed(&mut self, factor: f32) {
+        self.speed = ((self.speed as f32 * factor).round() as i32).max(1);
+    }
+
     pub fn fly(&mut self) {
-        let angle_rad = (self.angle as f32).to_radians();
-        let x_diff = (self.speed as f32) * angle_rad.sin();
-        let y_diff =  (self.speed as f32) * angle_rad.cos();
+        let old_pos = self.shape.pos();
+        if deterministic() {
+            let speed = Fixed::from_int(self.speed) * Fixed::from_f32(self.speed_multiplier);
+            self.shape.set_pos(old_pos + movement_step(self.angle, speed));
+        } else {
+            let step = self.velocity();
+            self.shape.set_pos(Vec2i { x: old_pos.x + truncate_step(step.x), y: old_pos.y + truncate_step(step.y) });
+        }
+        self.traveled += old_pos.distance_to(self.shape.pos());
+    }
 
-        let mut pos = self.shape.pos();
-        pos.x += x_diff as i32;
-        pos.y -= y_diff as i32;
-        self.shape.set_pos(pos);
+    pub fn drift(&mut self, offset: Vec2i) {
+        self.shape.set_pos(self.shape.pos() + offset);
+    }
+
+    pub fn angle(&self) -> i32 {
+        self.angle
+    }
+
+    // per tick, computed on every call since reflect changes the angle
+    pub fn velocity(&self) -> Vec2f {
+        let speed = self.speed as f32 * self.speed_multiplier;
+        PolarVec::new(speed, self.angle as f32).to_cartesian()
     }
 
     pub fn damage(&self) -> i32 {
-        self.damage
+        if self.falloff {
+            self.damage_at_distance(self.traveled.round() as i32)
+        } else {
+            self.damage
+        }
+    }
+
+    // damage the shot would deal after traveling the given distance, the shot itself isn't moved
+    pub fn damage_at_distance(&self, traveled: i32) -> i32 {
+        let falloff_progress = (traveled - SHOT_FALLOFF_START_DISTANCE).max(0) as f32 / SHOT_FALLOFF_DISTANCE as f32;
+        let percent = 1.0 - (1.0 - SHOT_MIN_DAMAGE_PERCENT) * falloff_progress.min(1.0);
+        (self.damage as f32 * percent).round() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::globals::set_deterministic;
+    use crate::math::angles::angle_from_velocity;
+    use crate::power_ups::PIERCING_SHOT_MAX_HITS;
+    use crate::slow_mo::SLOW_MO_SHOT_SPEED_MULTIPLIER;
+
+    const ANGLE_DOWN: i32 = 180;
+
+    #[test]
+    fn angle_recovered_from_velocity() {
+        for angle in 0..360 {
+            let velocity = Shot::new(Shape::new(Vec2i::zero(), 4), 10, angle, 1).velocity();
+            assert_eq!(angle_from_velocity(velocity.x, velocity.y), angle);
+        }
+    }
+
+    #[test]
+    fn slowed_shots_move_at_half_speed() {
+        let mut shots: Vec<Shot> = (0..6)
+            .map(|x| Shot::new(Shape::new(Vec2i::new(x * 10, 0), 2), 4, ANGLE_DOWN, 10))
+            .collect();
+        for shot in shots.iter_mut() {
+            shot.speed_multiplier(SLOW_MO_SHOT_SPEED_MULTIPLIER);
+            shot.fly();
+        }
+        assert!(shots.iter().all(|shot| shot.shape().pos().y == 2));
+
+        for shot in shots.iter_mut() {
+            shot.speed_multiplier(1.0);
+            shot.fly();
+        }
+        assert!(shots.iter().all(|shot| shot.shape().pos().y == 6));
+    }
+
+    #[test]
+    fn piercing_shot_passes_two_targets_and_stops_at_third() {
+        let mut shot = Shot::new(Shape::new(Vec2i::zero(), 2), 4, ANGLE_DOWN, 10).with_piercing(PIERCING_SHOT_MAX_HITS);
+        assert!(!shot.register_hit());
+        assert!(!shot.register_hit());
+        assert!(shot.register_hit());
+        assert_eq!(shot.piercing(), Some(0));
+    }
+
+    #[test]
+    fn regular_shot_stops_at_first_target() {
+        let mut shot = Shot::new(Shape::new(Vec2i::zero(), 2), 4, ANGLE_DOWN, 10);
+        assert_eq!(shot.piercing(), None);
+        assert!(shot.register_hit());
+    }
+
+    #[test]
+    fn falloff_shot_weakens_with_distance() {
+        let mut shot = Shot::new(Shape::new(Vec2i::zero(), 2), 100, ANGLE_DOWN, 10).with_falloff();
+        let start_ticks = SHOT_FALLOFF_START_DISTANCE / 100;
+        for _ in 0..start_ticks {
+            shot.fly();
+        }
+        assert_eq!(shot.damage(), 10);
+
+        for _ in 0..SHOT_FALLOFF_DISTANCE / 100 {
+            shot.fly();
+        }
+        assert_eq!(shot.damage(), shot.damage_at_distance(SHOT_FALLOFF_START_DISTANCE + SHOT_FALLOFF_DISTANCE));
+        assert_eq!(shot.damage(), (10.0 * SHOT_MIN_DAMAGE_PERCENT).round() as i32);
+    }
+
+    #[test]
+    fn shot_without_falloff_keeps_damage() {
+        let mut shot = Shot::new(Shape::new(Vec2i::zero(), 2), 100, ANGLE_DOWN, 10);
+        for _ in 0..20 {
+            shot.fly();
+        }
+        assert_eq!(shot.damage(), 10);
+    }
+
+    // where the shot ends up after 10 seconds of flight
+    fn flown_for_10_seconds(speed: i32, angle: i32, multiplier: f32, fixed_point: bool) -> Vec2i {
+        set_deterministic(fixed_point);
+        let mut shot = Shot::new(Shape::new(Vec2i::zero(), 2), speed, angle, 10);
+        shot.speed_multiplier(multiplier);
+        for _ in 0..600 {
+            shot.fly();
+        }
+        set_deterministic(false);
+        shot.shape().pos()
+    }
+
+    #[test]
+    fn fixed_and_float_paths_agree() {
+        for speed in [4, 10, 15] {
+            for multiplier in [1.0, 0.5] {
+                for angle in 0..360 {
+                    let float = flown_for_10_seconds(speed, angle, multiplier, false);
+                    let fixed = flown_for_10_seconds(speed, angle, multiplier, true);
+                    let diff = float - fixed;
+                    assert!(diff.x.abs() <= 1 && diff.y.abs() <= 1,
+                        "speed {} x{} at {}: float {} fixed {}", speed, multiplier, angle, float, fixed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn with_damage_round_trips() {
+        let shot = Shot::new(Shape::new(Vec2i::zero(), 2), 4, ANGLE_DOWN, 10).with_damage(25);
+        assert_eq!(shot.damage(), 25);
     }
 }
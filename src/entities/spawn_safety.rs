@@ -0,0 +1,101 @@
+use super::shape::{Shape, Shaped};
+
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+// anything appearing closer to the ship would hit it before the player can react
+pub const SPAWN_SAFETY_RADIUS: i32 = 30;
+pub const SPAWN_RETRIES: u32 = 5;
+// a delayed spawn waiting longer is reported
+pub const MAX_SPAWN_DELAY_TICKS: u32 = 60;
+
+//-----------------------------------------------------------------------------
+
+pub fn safe_from_ship(candidate: &Shape, ship: &Shape) -> bool {
+    let diff = candidate.center() - ship.center();
+    let min_distance = SPAWN_SAFETY_RADIUS + (candidate.width() + ship.width()) / 2;
+    diff.magnitude_sq() >= (min_distance as i64).pow(2)
+}
+
+// rolls new candidates a few times, None when every one of them was unsafe
+pub fn find_safe<T: Shaped>(ship: &Shape, mut roll: impl FnMut() -> T) -> Option<T> {
+    (0..SPAWN_RETRIES)
+        .map(|_| roll())
+        .find(|candidate| safe_from_ship(candidate.shape(), ship))
+}
+
+// the angle with its horizontal part turned away from the ship, kept as is if it already moves away
+pub fn steer_away(pos: Vec2i, angle: i32, ship_center: Vec2i) -> i32 {
+    let heading_x = (angle as f32).to_radians().sin();
+    let to_ship_x = (ship_center.x - pos.x) as f32;
+    if heading_x * to_ship_x > 0.0 {
+        (360 - angle).rem_euclid(360)
+    } else {
+        angle
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Candidate(Shape);
+
+    impl Shaped for Candidate {
+        fn shape(&self) -> &Shape {
+            &self.0
+        }
+        fn shape_mut(&mut self) -> &mut Shape {
+            &mut self.0
+        }
+    }
+
+    fn ship() -> Shape {
+        Shape::new(Vec2i::new(100, 100), 20)
+    }
+
+    #[test]
+    fn safety_radius_is_counted_from_the_edges() {
+        // the ship center is at (110, 110), both are 20 wide
+        let min_distance = SPAWN_SAFETY_RADIUS + 20;
+        assert!(safe_from_ship(&Shape::new(Vec2i::new(100 + min_distance, 100), 20), &ship()));
+        assert!(!safe_from_ship(&Shape::new(Vec2i::new(100 + min_distance - 1, 100), 20), &ship()));
+        assert!(!safe_from_ship(&ship(), &ship()));
+    }
+
+    #[test]
+    fn unsafe_rolls_give_up_after_retries() {
+        let mut rolls = 0;
+        let found = find_safe(&ship(), || {
+            rolls += 1;
+            Candidate(ship())
+        });
+        assert!(found.is_none());
+        assert_eq!(rolls, SPAWN_RETRIES);
+    }
+
+    #[test]
+    fn first_safe_roll_is_taken() {
+        let mut rolls = 0;
+        let found = find_safe(&ship(), || {
+            rolls += 1;
+            let x = if rolls < 3 { 100 } else { 300 };
+            Candidate(Shape::new(Vec2i::new(x, 100), 20))
+        });
+        assert_eq!(found.unwrap().shape().pos(), Vec2i::new(300, 100));
+        assert_eq!(rolls, 3);
+    }
+
+    #[test]
+    fn steers_horizontal_heading_away() {
+        let ship_center = Vec2i::new(50, 100);
+        // right of the ship heading down-left turns down-right
+        assert_eq!(steer_away(Vec2i::new(100, 50), 200, ship_center), 160);
+        // already heading away
+        assert_eq!(steer_away(Vec2i::new(100, 50), 160, ship_center), 160);
+        assert_eq!(steer_away(Vec2i::new(100, 50), 180, ship_center), 180);
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use super::comet::{Comet, CometKind};
+
+use crate::constants::MIN_COMET_SPEED;
+use crate::globals::{mirror_angle, mirror_x};
+use crate::math::Vec2i;
+use crate::timing::Cooldown;
+
+//-----------------------------------------------------------------------------
+
+const ANGLE_DOWN: i32 = 180;
+
+//-----------------------------------------------------------------------------
+
+// background source of comets, runs independently of the regular level spawning
+pub struct Spawner {
+    position: Vec2i,
+    comet_kind: CometKind,
+    interval: Cooldown,
+    max_alive: u8,
+    current_alive: u8,
+}
+
+impl Spawner {
+    pub fn new(position: Vec2i, comet_kind: CometKind, interval: Duration, max_alive: u8) -> Self {
+        Self {
+            position,
+            comet_kind,
+            interval: Cooldown::new(interval),
+            max_alive,
+            current_alive: 0,
+        }
+    }
+
+    pub fn try_spawn(&mut self) -> Option<Comet> {
+        if self.current_alive >= self.max_alive || !self.interval.ready() {
+            return None;
+        }
+
+        self.interval.reset();
+        self.current_alive += 1;
+
+        let width = Comet::get_width(self.comet_kind);
+        let pos = Vec2i { x: mirror_x(self.position.x, width), y: self.position.y };
+        Some(Comet::new(self.comet_kind, pos, mirror_angle(ANGLE_DOWN), MIN_COMET_SPEED))
+    }
+
+    // called for every spawned comet once it's destroyed or leaves the screen
+    pub fn on_child_death(&mut self) {
+        self.current_alive = self.current_alive.saturating_sub(1);
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,7 @@
+// what kind of object the player is looking at, the palette maps it to a color
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VisualTag {
+    PlayerShot,
+    BossShot,
+    Hazard,
+}
@@ -0,0 +1,41 @@
+use crate::entities::boss::boss_stages::StageName;
+use crate::math::Vec2i;
+use crate::power_ups::PowerUpKind;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    NoDamageBossKill,
+    ComboOf50,
+    BossKillUnder60Seconds,
+    BossIntroStarted,
+    BossIntroEnded,
+    LowHpWarningStarted,
+    LowHpWarningStopped,
+    BossStunned,
+    BossStunEnded,
+    BossStuck,
+    WeaponArmDetached,
+    WeaponArmDestroyed,
+    BossEnraged,
+    KineticBarrierDestroyed,
+    ForceZoneEntered,
+    ForceZoneLeft,
+    CutsceneStarted,
+    CutsceneEnded,
+    DialogueLineChanged,
+    DialogueEnded,
+    PlayerHit,
+    CometDestroyed,
+    BombUsed,
+    WaveComplete,
+    StageChanged { stage: StageName },
+    Graze { pos: Vec2i },
+    ShotReflected { pos: Vec2i },
+    PowerUpGranted { kind: PowerUpKind },
+    ShotConverted { pos: Vec2i },
+    PickupCollected,
+    EnergyAbsorbed { amount: i32 },
+    KillCam { boss_center: Vec2i },
+    VictoryLapStarted,
+    VictoryLapEnded,
+}
@@ -1,168 +1,3114 @@
 use std::rc::Rc;
+use std::time::Duration;
+
+use crate::achievements::AchievementSystem;
+use crate::audio::music_fade::MusicFade;
+use crate::autosave::{Autosave, AutosaveManager, AUTOSAVE_VERSION};
+use crate::checkpoint::{Checkpoint, CheckpointManager, ScoreSnapshot, ShipSnapshot};
+use crate::cutscene::{BackgroundConfig, CutsceneFrame, CutscenePlayer};
+use crate::cutscene::dialogue::{Dialogue, DialogueChange};
+use crate::effects::kill_cam::KillCam;
+use crate::effects::target_lock::TargetLock;
+use crate::difficulty::{AdaptiveDifficulty, Difficulty, NightmareMode, PerformanceEvent, ProgressiveDifficulty};
+use crate::events::GameEvent;
 
 use crate::entities::shape::{Shape, Shaped};
-use crate::entities::destroyable::Destroyable;
+use crate::entities::flash::Tint;
+use crate::entities::destroyable::{Destroyable, HealthBar};
 use crate::entities::comet::Comet;
-use crate::entities::ship::Ship;
+use crate::entities::ship::{Ship, ShipKind};
 use crate::entities::shot::Shot;
 use crate::entities::boss::Boss;
+use crate::entities::boss::health_view::BossHealthView;
+use crate::entities::projectile_manager::ProjectileManager;
+use crate::entities::visual_tag::VisualTag;
+use crate::entities::particles::{Emitter, ParticlePool, MAX_PARTICLES};
+use crate::entities::damage_numbers::{DamageNumbers, DamageTarget};
+use crate::entities::arena_walls::ArenaWalls;
+use crate::entities::pickup::ScorePickup;
+use crate::entities::boss::weapon_arm::WeaponArm;
+use crate::entities::boss::kinetic_barrier::KineticBarrier;
+use crate::entities::shockwave::Shockwave;
+use crate::entities::spawn_safety::{find_safe, safe_from_ship, steer_away, MAX_SPAWN_DELAY_TICKS};
+use crate::entities::energy_absorb::EnergyAbsorb;
+use crate::entities::force_zone::{ForceZones, ZoneChange};
+use crate::entities::spawner::Spawner as BackgroundSpawner;
 
 use crate::player_ship_controller::PlayerShipController;
 use crate::spawners::Spawner;
-use crate::spawners::spawners_impl::*;
+use crate::spawners::spawners_impl::{FINAL_BOSS_LEVEL, LEVELS_COUNT};
 
 use crate::input_mgr::InputMgr;
-use crate::globals::screen_rect;
+use crate::globals::{deterministic, mirror_mode, screen_rect, screen_size, set_playfield_inset};
+use crate::high_scores::{HighScoreEntry, HighScores};
+use crate::hud_state::HudState;
+use crate::lock_on::{LockOn, LockTarget, LockTargetKind};
+use crate::low_hp_warning::{LowHpChange, LowHpWarning};
+use crate::rumble::{RumbleEvent, RumbleQueue, RumbleSettings, RumbleSource};
+use crate::loadout::{Loadout, LoadoutConfig, Passive, PrimaryWeapon, SecondaryWeapon};
+use crate::run_summary::{LevelSummary, RunStats, RunSummary, ScoreBreakdown};
+use crate::profile_phase;
+use crate::profiling::{EntityCounts, FrameProfiler, Phase, PhaseTimings};
+use crate::power_ups::{PiercingShotPowerUp, PowerUpKind, ReflectorPowerUp, ShieldBubble, PIERCING_SHOT_DURATION, PIERCING_SHOT_MAX_HITS, REFLECTOR_DURATION, REFLECTOR_MAX_REFLECTIONS};
+use crate::slow_mo::{SlowMoEffect, SLOW_MO_DURATION, SLOW_MO_SHOT_SPEED_MULTIPLIER};
+use crate::score::challenge::ChallengeModeScore;
+use crate::screen_shake::{ScreenShake, CONTACT_DAMAGE_TRAUMA, LARGE_TRAUMA, SMALL_TRAUMA};
+use crate::starfield::Starfield;
+use crate::music_intensity::{IntensityInputs, MusicIntensity};
+use crate::objectives::{ObjectiveProgress, Objectives};
+use crate::victory_lap::{VictoryLap, VICTORY_LAP_DURATION};
+use crate::timing::{advance_game_clock, GameInstant};
+use crate::ui::palette::PalettePreset;
+use crate::ui::loadout_menu::LoadoutMenu;
+use crate::ui::pause_menu::{MenuTransition, PauseMenu, SettingsEntry};
+use crate::ui::draw_list::{DrawItem, DrawKind, DrawLayer, DrawList};
+use crate::ui::safe_zone_indicator::SafeZoneIndicator;
+use crate::sound_events::{ExplosionSize, MusicTrack, ShotOwner, SoundEvent, SoundEventQueue, SoundKind, TargetKind};
 
 use crate::math::Vec2i;
+use crate::math::angles::{angle_from_velocity, angle_lerp, angle_to_target};
+#[cfg(feature = "hot-reload")]
+use crate::tuning::Tuning;
 use crate::constants::*;
 
 pub struct GameLevel {
     comets: Vec<Comet>,
+    // spawned next to the ship, with the ticks waited so far
+    delayed_comets: Vec<(Comet, u32)>,
+    // reported once per run, the ship can block the spawns often
+    comet_spawn_blocked_reported: bool,
+    dying_comets: Vec<Comet>,
+    background_spawners: Vec<BackgroundSpawner>,
 
     ship: Ship,
-    ship_shots: Vec<Shot>,
     ship_controller: PlayerShipController,
 
     boss: Option<Boss>,
-    boss_shots: Vec<Shot>,
+    dying_bosses: Vec<Boss>,
     boss_defeated: bool,
+    boss_spawn_time: GameInstant,
+    boss_intro_started: Option<GameInstant>,
+    boss_stuck_reported: bool,
+    arena_walls: Option<ArenaWalls>,
+    kill_cam: Option<KillCam>,
+    // player shots hitting in a row, a shot leaving the screen breaks it
+    combo: u32,
+    weapon_arm: Option<WeaponArm>,
+    barriers: Vec<KineticBarrier>,
+    ship_hp_at_boss_spawn: i32,
+
+    projectiles: ProjectileManager,
+    particles: ParticlePool,
+    damage_numbers: DamageNumbers,
+    screen_shake: ScreenShake,
+    slow_mo_effects: Vec<SlowMoEffect>,
+    piercing_shots: Option<PiercingShotPowerUp>,
+    reflector: Option<ReflectorPowerUp>,
+    shield_bubble: Option<ShieldBubble>,
+    starfield: Starfield,
 
     spawner: Box<dyn Spawner>,
+
+    nightmare: NightmareMode,
+    adaptive_difficulty: AdaptiveDifficulty,
+    progressive_difficulty: ProgressiveDifficulty,
+    difficulty: Difficulty,
+    god_mode: bool,
+    ship_kind: ShipKind,
+    // saves, high scores and unlocks are written to disk
+    persistence: bool,
+    // played by the attract mode, see set_demo
+    demo: bool,
+    #[cfg(feature = "hot-reload")]
+    tuning: Tuning,
+    // hp every spawned boss starts with, see set_boss_hp
+    #[cfg(feature = "debug-commands")]
+    boss_hp: Option<i32>,
+    boss_only: bool,
+    waves_cleared: u32,
+    auto_fire: bool,
+    auto_fire_interval: Duration,
+    paused: bool,
+    profiler: FrameProfiler,
+    cutscene: Option<CutscenePlayer>,
+    dialogue: Option<Dialogue>,
+    boss_cutscene_played: bool,
+    pause_menu: PauseMenu,
+    // opened from the pause menu, the picked loadout is used from the next restart
+    loadout_menu: Option<LoadoutMenu>,
+    quit_requested: bool,
+
+    hud: HudState,
+    low_hp_warning: LowHpWarning,
+    force_zones: ForceZones,
+    lock_on: LockOn,
+    energy_absorb: EnergyAbsorb,
+    shockwave: Option<Shockwave>,
+    victory_lap: Option<VictoryLap>,
+    victory_lap_done: bool,
+    pickups: Vec<ScorePickup>,
+    target_lock: TargetLock,
+    challenge_score: ChallengeModeScore,
+    objectives: Objectives,
+    music_intensity: MusicIntensity,
+    safe_zone: Option<SafeZoneIndicator>,
+    palette: PalettePreset,
+    loadout: Loadout,
+    draw_list: DrawList,
+
+    level_number: i32,
+    seed: Option<u64>,
+    stats: RunStats,
+    level_started_at: GameInstant,
+    run_summary: Option<RunSummary>,
+    level_summary: Option<LevelSummary>,
+
+    events: Vec<GameEvent>,
+    // events left by resolve_collisions, drain_events expects to see all of them
+    collision_events: usize,
+    // saved once the events of the tick are processed, so the clean wave bonus makes it in
+    checkpoint_pending: bool,
+    step_phase: StepPhase,
+    sound_events: SoundEventQueue,
+    rumble: RumbleQueue,
+    music_fade: Option<MusicFade>,
+    achievements: AchievementSystem,
+}
+
+//-----------------------------------------------------------------------------
+
+// phases of a single update in the order they have to run, see GameLevel::update
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+enum StepPhase {
+    Idle,
+    Input,
+    Collisions,
+    Entities,
+    Spawners,
+    Events,
+    Draw,
 }
 
+//-----------------------------------------------------------------------------
+
+const SPEED_KILL_DURATION: Duration = Duration::from_secs(60);
+const BOSS_INTRO_STARFIELD_BOOST_TICKS: i32 = 60;
+const BOSS_INTRO_DURATION: Duration = Duration::from_secs(2);
+// no regular stage is expected to take that long, the last one lasts until the boss dies
+const MAX_STAGE_DURATION: Duration = Duration::from_secs(90);
+// side shots of the spread primary weapon
+const SPREAD_WEAPON_ANGLE: i32 = 15;
+// twice the most shots expected on the screen at once, during the spread stage
+const SHOT_CAPACITY: usize = 2 * 64;
+const TARGETED_STAGE_NAME: &str = "Targeted";
+// converted shots past this are cleared without a pickup
+const MAX_PICKUPS: usize = 30;
+// the boss bar spans the top of the screen
+const BOSS_BAR_MARGIN: i32 = 10;
+const BOSS_BAR_HEIGHT: i32 = 6;
+const PICKUP_MAGNET_RADIUS: i32 = 40;
+// part of the way to the homing target the missiles turn every tick
+const MISSILE_TURN_RATE: f32 = 0.1;
+const COMBO_ACHIEVEMENT_HITS: u32 = 50;
+// the level is given up on when the skipped waves take longer
+#[cfg(feature = "debug-skip-wave")]
+const MAX_SKIP_DURATION: Duration = Duration::from_secs(600);
+
+//-----------------------------------------------------------------------------
+
+
 impl GameLevel {
-    pub fn new(input_mgr: Rc<InputMgr>) -> GameLevel {
+    pub fn new(input_mgr: Rc<InputMgr>, spawner: Box<dyn Spawner>) -> GameLevel {
         let ship_shape = Shape::new(Vec2i { x: 0, y: 0 }, SHIP_WIDTH);
-        let ship = Ship::new(ship_shape, SHIP_MAX_HP);
+        let mut ship = Ship::of_kind(ship_shape, ShipKind::Standard);
+        ship.apply_loadout(&Loadout::default());
+        let force_zones = ForceZones::new(spawner.force_zones());
+        let objectives = Objectives::new(spawner.objectives());
+        let background_spawners = spawner.background_spawners();
+        let dialogue = GameLevel::create_dialogue(spawner.as_ref());
 
         let ship_controller = PlayerShipController::new(Rc::clone(&input_mgr));
-        let spawner = Box::new(EasyLevelSpawner{});
         GameLevel {
             comets: vec![],
-            ship_shots: vec![],
+            delayed_comets: vec![],
+            comet_spawn_blocked_reported: false,
+            dying_comets: vec![],
+            background_spawners,
             ship,
             ship_controller,
 
             boss: None,
-            boss_shots: vec![],
+            dying_bosses: vec![],
             boss_defeated: false,
+            boss_spawn_time: GameInstant::now(),
+            boss_intro_started: None,
+            boss_stuck_reported: false,
+            arena_walls: None,
+            kill_cam: None,
+            combo: 0,
+            weapon_arm: None,
+            barriers: vec![],
+            ship_hp_at_boss_spawn: SHIP_MAX_HP,
+
+            projectiles: ProjectileManager::prealloc(SHOT_CAPACITY),
+            particles: ParticlePool::new(MAX_PARTICLES),
+            damage_numbers: DamageNumbers::new(),
+            screen_shake: ScreenShake::new(),
+            slow_mo_effects: vec![],
+            piercing_shots: None,
+            reflector: None,
+            shield_bubble: None,
+            starfield: Starfield::new(screen_size()),
 
             spawner,
+
+            nightmare: NightmareMode::new(Difficulty::Normal),
+            adaptive_difficulty: AdaptiveDifficulty::new(Difficulty::Normal),
+            progressive_difficulty: ProgressiveDifficulty::new(Difficulty::Normal),
+            difficulty: Difficulty::Normal,
+            god_mode: false,
+            ship_kind: ShipKind::Standard,
+            persistence: false,
+            demo: false,
+            #[cfg(feature = "hot-reload")]
+            tuning: Tuning::default(),
+            #[cfg(feature = "debug-commands")]
+            boss_hp: None,
+            boss_only: false,
+            waves_cleared: 0,
+            auto_fire: false,
+            auto_fire_interval: AUTO_FIRE_INTERVAL,
+            paused: false,
+            profiler: FrameProfiler::new(),
+            cutscene: None,
+            dialogue,
+            boss_cutscene_played: false,
+            pause_menu: PauseMenu::new(),
+            loadout_menu: None,
+            quit_requested: false,
+
+            hud: HudState::default(),
+            low_hp_warning: LowHpWarning::new(),
+            force_zones,
+            lock_on: LockOn::new(),
+            energy_absorb: EnergyAbsorb::new(),
+            shockwave: None,
+            victory_lap: None,
+            victory_lap_done: false,
+            pickups: vec![],
+            target_lock: TargetLock::new(),
+            challenge_score: ChallengeModeScore::new(),
+            objectives,
+            music_intensity: MusicIntensity::new(),
+            safe_zone: None,
+            palette: PalettePreset::Default,
+            loadout: Loadout::default(),
+            draw_list: DrawList::new(),
+
+            level_number: 1,
+            seed: None,
+            stats: RunStats::default(),
+            level_started_at: GameInstant::now(),
+            run_summary: None,
+            level_summary: None,
+
+            events: vec![],
+            collision_events: 0,
+            checkpoint_pending: false,
+            step_phase: StepPhase::Idle,
+            sound_events: SoundEventQueue::new(),
+            rumble: RumbleQueue::new(),
+            music_fade: None,
+            achievements: AchievementSystem::default(),
+        }
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.nightmare = NightmareMode::new(difficulty);
+        self.adaptive_difficulty = AdaptiveDifficulty::new(difficulty);
+        self.progressive_difficulty = ProgressiveDifficulty::new(difficulty);
+        self.difficulty = difficulty;
+    }
+
+    pub fn set_god_mode(&mut self, god_mode: bool) {
+        self.god_mode = god_mode;
+        self.ship.set_god_mode(god_mode);
+    }
+
+    // the ship is built anew, so it has to be set before the run starts
+    pub fn set_ship_kind(&mut self, kind: ShipKind) {
+        self.ship_kind = kind;
+        self.start();
+    }
+
+    pub fn ship_kind(&self) -> ShipKind {
+        self.ship_kind
+    }
+
+    // runs with god mode don't count as regular ones
+    pub fn assisted(&self) -> bool {
+        self.god_mode
+    }
+
+    // off by default, so tests and headless runs never touch the player's files;
+    // the unlocked achievements are read from disk only once it's turned on
+    // for trying out the later stages of a fight without playing through the earlier ones
+    #[cfg(feature = "debug-commands")]
+    pub fn set_boss_hp(&mut self, hp: i32) {
+        self.boss_hp = Some(hp);
+    }
+
+    // stage values change right away, a new stage list waits for the next boss
+    #[cfg(feature = "hot-reload")]
+    pub fn set_tuning(&mut self, tuning: Tuning) {
+        if let Some(boss) = &mut self.boss {
+            boss.apply_tuning(&tuning);
+            if tuning.structure(boss.name()) != self.tuning.structure(boss.name()) {
+                log::info!("New stage list of {} is used from its next spawn", boss.name());
+            }
+        }
+        self.tuning = tuning;
+    }
+
+    pub fn set_persistence(&mut self, enabled: bool) {
+        if enabled && !self.persistence {
+            self.achievements = AchievementSystem::load();
+        }
+        self.persistence = enabled;
+    }
+
+    // a demo can't touch the high scores, saves or unlocks of the player, the hud flags it
+    pub fn set_demo(&mut self, demo: bool) {
+        self.demo = demo;
+        if demo {
+            self.set_persistence(false);
+        }
+    }
+
+    fn persists(&self) -> bool {
+        self.persistence && !self.assisted()
+    }
+
+    pub fn set_auto_fire(&mut self, enabled: bool, interval: Duration) {
+        self.auto_fire = enabled;
+        self.auto_fire_interval = interval;
+        self.ship.auto_fire(enabled);
+        self.ship.set_auto_fire_interval(interval);
+    }
+
+    pub fn set_aim_mode(&mut self, aim_mode: bool) {
+        self.ship_controller.set_aim_mode(aim_mode);
+    }
+
+    // only reported in the run summary
+    pub fn set_run_info(&mut self, level_number: i32, seed: Option<u64>) {
+        self.level_number = level_number;
+        self.seed = seed;
+    }
+
+    // locked or unknown options fall back to the defaults
+    pub fn set_loadout(&mut self, config: &LoadoutConfig) {
+        self.loadout = Loadout::from_config(config, &self.achievements);
+        self.start();
+    }
+
+    // a replay uses the loadout of the recorded run, whatever is unlocked now
+    pub fn set_replay_loadout(&mut self, loadout: Loadout) {
+        self.loadout = loadout;
+        self.start();
+    }
+
+    pub fn loadout(&self) -> Loadout {
+        self.loadout
+    }
+
+    pub fn run_summary(&self) -> Option<&RunSummary> {
+        self.run_summary.as_ref()
+    }
+
+    pub fn level_summary(&self) -> Option<&LevelSummary> {
+        self.level_summary.as_ref()
+    }
+
+    pub fn set_boss_only(&mut self, boss_only: bool) {
+        self.boss_only = boss_only;
+        self.objectives = self.create_objectives();
+    }
+
+    // boss rush has no level to complete the objectives in
+    fn create_objectives(&self) -> Objectives {
+        if self.boss_only {
+            return Objectives::none();
         }
+        Objectives::new(self.spawner.objectives())
+    }
+
+    // settles the objectives once, the completed ones are remembered per level
+    fn finalize_objectives(&mut self) {
+        let completed = self.objectives.finalize(&self.stats);
+        if completed.is_empty() || self.assisted() {
+            return;
+        }
+
+        self.stats.objective_bonus += completed.iter().map(|objective| objective.bonus).sum::<u32>();
+        if !self.persistence {
+            return;
+        }
+        let mut progress = ObjectiveProgress::load();
+        for objective in completed {
+            progress.mark_completed(self.level_number, objective.id);
+        }
+        if let Err(err) = progress.save() {
+            log::warn!("Failed to save objectives progress: {}", err);
+        }
+    }
+
+    // particles are purely cosmetic, so there is no point in simulating them without a window
+    pub fn set_particles_enabled(&mut self, enabled: bool) {
+        self.particles.set_enabled(enabled);
+    }
+
+    pub fn set_damage_numbers_enabled(&mut self, enabled: bool) {
+        self.damage_numbers.set_enabled(enabled);
+    }
+
+    pub fn set_starfield_enabled(&mut self, enabled: bool) {
+        self.starfield.set_enabled(enabled);
     }
 
     pub fn start(&mut self) {
         let ship_shape = Shape::new(Vec2i { x: 0, y: 0 }, SHIP_WIDTH);
-        self.ship = Ship::new(ship_shape, SHIP_MAX_HP);
+        self.ship = Ship::of_kind(ship_shape, self.ship_kind);
+        self.ship.set_god_mode(self.god_mode);
+        self.ship.auto_fire(self.auto_fire);
+        self.ship.set_auto_fire_interval(self.auto_fire_interval);
+        self.ship.apply_loadout(&self.loadout);
     }
 
     pub fn stop(&mut self) {
-        self.comets.clear();
-        self.ship_shots.clear();
+        for comet in std::mem::take(&mut self.comets) {
+            self.release_comet(&comet);
+        }
+        for (comet, _) in std::mem::take(&mut self.delayed_comets) {
+            self.release_comet(&comet);
+        }
+        self.dying_comets.clear();
+        self.dying_bosses.clear();
+        self.projectiles.clear();
+        self.particles.clear();
+        self.damage_numbers.clear();
+        self.ship_controller.clear_buffer();
+        self.screen_shake.reset();
+        self.slow_mo_effects.clear();
+        self.energy_absorb.clear();
+        self.shockwave = None;
+        self.victory_lap = None;
+        self.pickups.clear();
+        self.target_lock.clear();
+        self.arena_walls = None;
+        self.kill_cam = None;
+        self.combo = 0;
+        self.weapon_arm = None;
+        self.barriers.clear();
+        set_playfield_inset(0);
+        self.piercing_shots = None;
+        self.reflector = None;
+        self.shield_bubble = None;
+        self.rumble.clear();
+    }
+
+    // drops everything of the current run, the level starts over
+    pub fn restart(&mut self) {
+        self.stop();
+        self.start();
+
+        self.boss = None;
+        self.boss_defeated = false;
+        self.victory_lap_done = false;
+        self.boss_intro_started = None;
+        self.boss_stuck_reported = false;
+        self.comet_spawn_blocked_reported = false;
+        self.waves_cleared = 0;
+        self.cutscene = None;
+        self.dialogue = GameLevel::create_dialogue(self.spawner.as_ref());
+        self.boss_cutscene_played = false;
+        self.safe_zone = None;
+        self.music_fade = None;
+        // back to the level music, e.g. after the victory fanfare
+        let pos = self.ship.shape().center();
+        self.sound_events.push(SoundKind::ChangeTrack { track: MusicTrack::Level }, pos);
+        self.sound_events.push(SoundKind::SetMusicVolume { volume: 1.0 }, pos);
+        self.events.clear();
+        self.collision_events = 0;
+        self.checkpoint_pending = false;
+
+        self.low_hp_warning.reset();
+        self.adaptive_difficulty.reset();
+        self.progressive_difficulty.restore(0);
+        self.lock_on.clear();
+        self.challenge_score = ChallengeModeScore::new();
+        self.objectives = self.create_objectives();
+        self.music_intensity = MusicIntensity::new();
+        self.force_zones = ForceZones::new(self.spawner.force_zones());
+        self.background_spawners = self.spawner.background_spawners();
+        self.stats = RunStats::default();
+        self.level_started_at = GameInstant::now();
+        self.run_summary = None;
+        self.level_summary = None;
+        self.paused = false;
+    }
+
+    // the abandoned run still counts for the stats and achievements
+    pub fn quit(&mut self) {
+        self.process_events();
+        if self.run_summary.is_none() {
+            self.finish_run();
+        }
+        self.stop();
+        self.quit_requested = true;
+    }
+
+    pub fn quit_requested(&self) -> bool {
+        self.quit_requested
     }
 
+    pub fn pause_menu(&self) -> Option<&PauseMenu> {
+        if self.paused { Some(&self.pause_menu) } else { None }
+    }
+
+    // there is no renderer yet, so what would be drawn only goes to the trace log
     pub fn render(&self) {
-        ()
+        if !log::log_enabled!(log::Level::Trace) {
+            return;
+        }
+        for item in self.draw_list().items() {
+            log::trace!("Draw {:?} {:?} frame {} at {:?} size {:?} rotation {} tag {:?} {:?} alpha {:.2} tint {:?} flash {:.2}",
+                item.layer, item.kind, item.frame, item.pos, item.size, item.rotation, item.tag, item.style, item.alpha,
+                item.tint, item.flash);
+        }
     }
 
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    // a single tick, the phases below can also be called one by one by an embedder to run its own systems in
+    // between; the order is fixed:
+    //   apply_input -> resolve_collisions -> step_entities -> step_spawners -> drain_events -> build_draw_list
+    // the rest of the tick is skipped when apply_input returns false
     pub fn update(&mut self) {
-        self.handle_intersections();
+        if !self.apply_input() {
+            return;
+        }
+        self.resolve_collisions();
+        self.step_entities();
+        self.step_spawners();
+        self.drain_events();
+        self.build_draw_list();
+    }
+
+    // starts a tick, handles the pause and the cutscenes, false when the world doesn't step this tick
+    pub fn apply_input(&mut self) -> bool {
+        self.step_phase = StepPhase::Input;
+        self.sound_events.clear();
+
+        if self.paused {
+            self.rumble.suspend();
+            self.update_music_intensity();
+            if self.loadout_menu.is_some() {
+                self.update_loadout_menu();
+            } else {
+                self.update_pause_menu();
+            }
+            self.update_hud();
+            self.step_phase = StepPhase::Draw;
+            return false;
+        }
+        self.rumble.update();
+        // pause skips a cutscene instead, the level is frozen during it anyway
+        if self.cutscene.is_none() && self.ship_controller.pause_toggled() {
+            self.paused = true;
+            self.pause_menu.reset();
+            self.ship_controller.clear_buffer();
+            self.update_hud();
+            self.step_phase = StepPhase::Draw;
+            return false;
+        }
+        // everything timed by the game clock stands still while paused
+        advance_game_clock();
+
+        if self.cutscene.is_some() {
+            self.update_cutscene();
+            self.update_hud();
+            self.fill_draw_list();
+            self.step_phase = StepPhase::Draw;
+            return false;
+        }
+        true
+    }
+
+    // damage, deaths and removal of everything off the screen or done dying, uses the positions of the last tick;
+    // entities must not be removed from here until drain_events, the events refer to them
+    pub fn resolve_collisions(&mut self) {
+        self.enter_phase(StepPhase::Collisions);
+
+        // nothing deals damage during the victory lap
+        if self.victory_lap.is_none() {
+            let hits_before = self.stats.shots_hit;
+            profile_phase!(self.profiler, Phase::Collision, self.handle_intersections());
+            self.extend_combo(self.stats.shots_hit - hits_before);
+        }
         if !self.ship.alive() {
             self.stop();
+            if self.run_summary.is_none() {
+                self.adaptive_difficulty.record(PerformanceEvent::Death);
+                self.finish_run();
+            }
         }
         self.handle_objects_off_screen();
+        self.remove_finished_dying();
+        self.update_victory_lap();
+        self.collision_events = self.events.len();
+    }
 
+    // moves everything and updates the ship from the controller, the boss picks its stage here
+    pub fn step_entities(&mut self) {
+        self.enter_phase(StepPhase::Entities);
         self.move_entities();
-        self.spawn_entities();
     }
 
-    fn move_entities(&mut self) {
-        self.comets.iter_mut().for_each(|comet| comet.fly());
-        self.ship_shots.iter_mut().for_each(|shot| shot.fly());
+    // new comets and the boss, held back by a dialogue or the victory lap
+    pub fn step_spawners(&mut self) {
+        self.enter_phase(StepPhase::Spawners);
+        if self.dialogue.is_some() {
+            self.update_dialogue();
+        } else if self.victory_lap.is_none() {
+            profile_phase!(self.profiler, Phase::Spawner, self.spawn_entities());
+        }
+    }
 
-        self.ship_controller.update(&mut self.ship);
+    // hands the events of the tick to achievements, objectives and the music, then settles the level summary
+    pub fn drain_events(&mut self) {
+        self.enter_phase(StepPhase::Events);
+        debug_assert!(self.events.len() >= self.collision_events, "events were dropped before drain_events");
+        self.collision_events = 0;
 
-        if let Some(boss) = &mut self.boss {
-            boss.fly(&self.ship);
+        self.update_low_hp_warning();
+        profile_phase!(self.profiler, Phase::Events, self.process_events());
+        if std::mem::take(&mut self.checkpoint_pending) {
+            self.save_checkpoint();
+        }
+        self.update_music_intensity();
+        if self.boss_defeated && self.level_summary.is_none() && !self.victory_lap_pending() {
+            self.finalize_objectives();
+            let time = self.level_started_at.elapsed();
+            self.level_summary = Some(LevelSummary::new(&self.stats, self.ship.hp_max(), time));
+            self.autosave();
         }
     }
 
-    fn handle_intersections(&mut self) {
-        self.comets.retain(|comet| {
-            let intersects = self.ship.intersects(comet);
-            if intersects {
-                let comet_damage = comet.damage();
-                self.ship.hit(comet_damage);
+    // the hud and the draw list of the finished tick, ends the tick
+    pub fn build_draw_list(&mut self) {
+        self.enter_phase(StepPhase::Draw);
+        self.update_hud();
+        self.fill_draw_list();
+        self.profiler.end_frame(self.entity_counts());
+    }
+
+    fn enter_phase(&mut self, phase: StepPhase) {
+        debug_assert!(self.step_phase < phase, "{:?} called after {:?} in the same tick", phase, self.step_phase);
+        self.step_phase = phase;
+    }
+
+    // the final boss is followed by a free flight collecting its converted shots
+    fn update_victory_lap(&mut self) {
+        if let Some(lap) = &self.victory_lap {
+            if lap.finished() {
+                self.victory_lap = None;
+                self.victory_lap_done = true;
+                self.events.push(GameEvent::VictoryLapEnded);
             }
-            !intersects
-        });
-        self.boss_shots.retain(|boss_shot| {
-            let intersects = self.ship.intersects(boss_shot);
-            if intersects {
-                self.ship.hit(boss_shot.damage());
+            return;
+        }
+
+        if self.victory_lap_pending() && self.dying_bosses.is_empty() {
+            let screen = screen_size();
+            self.shockwave = Some(Shockwave::new(self.ship.shape().center(), screen.x.max(screen.y) * 3 / 2));
+            self.victory_lap = Some(VictoryLap::new(VICTORY_LAP_DURATION));
+            self.events.push(GameEvent::VictoryLapStarted);
+        }
+    }
+
+    // a dead ship skips the lap, the summary follows the boss death right away
+    fn victory_lap_pending(&self) -> bool {
+        let final_boss = self.boss_defeated && self.level_number >= FINAL_BOSS_LEVEL;
+        self.victory_lap.is_some() || (final_boss && !self.victory_lap_done && self.ship.alive())
+    }
+
+    // the last level finishes the campaign, so there is nothing to continue
+    fn autosave(&self) {
+        if !self.persists() {
+            return;
+        }
+        if self.level_number >= LEVELS_COUNT {
+            AutosaveManager::clear();
+            return;
+        }
+        AutosaveManager::save(self.make_autosave());
+    }
+
+    fn make_autosave(&self) -> Autosave {
+        Autosave {
+            version: AUTOSAVE_VERSION,
+            cleared_level: self.level_number,
+            ship_snapshot: ShipSnapshot { hp: self.ship.hp(), energy: self.ship.energy() },
+            score_snapshot: ScoreSnapshot::from_stats(&self.stats),
+            loadout: self.loadout.to_config(),
+            difficulty: self.difficulty,
+            seed: self.seed,
+        }
+    }
+
+    // continues the run with the ship and the score of the autosave
+    pub fn resume_from_autosave(&mut self, autosave: &Autosave) {
+        self.restart();
+        *self.ship.hp_mut() = autosave.ship_snapshot.hp.clamp(1, self.ship.hp_max());
+        self.ship.set_energy(autosave.ship_snapshot.energy);
+        self.stats = autosave.score_snapshot.to_stats();
+        self.progressive_difficulty.restore(self.stats.bosses_defeated);
+    }
+
+    fn finish_run(&mut self) {
+        // dying or quitting mid-level abandons the campaign
+        if !self.boss_defeated && self.persists() {
+            AutosaveManager::clear();
+        }
+
+        let score = ScoreBreakdown::from_stats(&self.stats);
+
+        let mut high_score_place = None;
+        if self.persists() {
+            let mut high_scores = HighScores::load();
+            let entry = HighScoreEntry {
+                score: score.total(),
+                level: self.level_number,
+                seed: self.seed,
+                mirror: mirror_mode(),
+                adaptive: self.adaptive_difficulty.active(),
+            };
+            high_score_place = high_scores.insert(entry);
+            if high_score_place.is_some() {
+                if let Err(err) = high_scores.save() {
+                    log::warn!("Failed to save high scores: {}", err);
+                }
             }
-            !intersects
-        });
+        }
 
-        let comets = &mut self.comets;
-        self.ship_shots.retain(|shot| {
-            let remove = GameLevel::destroy_comets_by_shot(comets, shot);
-            !remove
+        self.run_summary = Some(RunSummary {
+            score,
+            stats: self.stats.clone(),
+            high_score_place,
+            level: self.level_number,
+            seed: self.seed,
+            adaptive: self.adaptive_difficulty.active(),
+            loadout: self.loadout,
+            deterministic: deterministic(),
         });
+    }
 
-        if let Some(boss) = &mut self.boss {  // TODO: play with borriwng here
-            self.ship_shots.retain(|shot| {
-                let intersects = boss.intersects(shot);
-                if intersects {
-                    boss.hit(shot.damage())
-                }
-                !intersects
-            });
+    pub fn set_rumble_settings(&mut self, settings: RumbleSettings) {
+        self.rumble.set_settings(settings);
+    }
 
-            if !boss.alive() {
-                self.boss = None;
-                self.boss_defeated = true;
-                // TODO destroy animation
+    // rumble requested by the last update, for the gamepad backend
+    pub fn rumble(&self) -> Option<RumbleEvent> {
+        self.rumble.event()
+    }
+
+    // can be swapped at any moment, e.g. from the settings menu
+    pub fn set_palette(&mut self, palette: PalettePreset) {
+        self.palette = palette;
+    }
+
+    // rebuilt at the end of every update
+    pub fn draw_list(&self) -> &DrawList {
+        &self.draw_list
+    }
+
+    fn fill_draw_list(&mut self) {
+        let palette = self.palette;
+        // a cutscene frame can hide the level behind it, or the stars too
+        let background = self.cutscene().map_or(BackgroundConfig::Level, |frame| frame.background);
+        let list = &mut self.draw_list;
+        list.clear();
+
+        if background == BackgroundConfig::Black {
+            return;
+        }
+        for star in self.starfield.stars() {
+            // the star sheet has a frame per parallax layer
+            list.push(DrawItem::square(DrawLayer::Background, DrawKind::Star, star.pos, 1)
+                .with_frame(star.layer as u32)
+                .with_alpha(star.brightness));
+        }
+        if background == BackgroundConfig::Starfield {
+            list.sort();
+            return;
+        }
+        for zone in self.force_zones.zones() {
+            let rect = zone.rect();
+            let size = rect.bottom_right - rect.top_left;
+            list.push(DrawItem::new(DrawLayer::Zones, DrawKind::ForceZone, rect.top_left, size));
+        }
+        if let Some(walls) = &self.arena_walls {
+            for rect in walls.wall_rects(&screen_rect()) {
+                let size = rect.bottom_right - rect.top_left;
+                list.push(DrawItem::new(DrawLayer::Zones, DrawKind::ArenaWall, rect.top_left, size));
+            }
+        }
+        for comet in &self.comets {
+            let shape = comet.shape();
+            let item = DrawItem::square(DrawLayer::Hazards, DrawKind::Comet, shape.pos(), shape.width())
+                .with_frame(comet.animator().current_frame())
+                .with_rotation(comet.angle())
+                .with_tag(VisualTag::Hazard, palette);
+            list.push(item);
+        }
+        for boss in self.boss.iter().chain(self.dying_bosses.iter()) {
+            let shape = boss.shape();
+            let tint = match boss.flash().tint() {
+                Tint::Enraged => boss.enrage_tint(),
+                _ => 1.0,
+            };
+            list.push(DrawItem::square(DrawLayer::Boss, DrawKind::Boss, shape.pos(), shape.width())
+                .with_frame(boss.animator().current_frame())
+                .with_tint(boss.flash().tint(), tint)
+                .with_flash(boss.flash().flash_intensity()));
+            if let Some(beam) = boss.beam() {
+                let size = Vec2i { x: beam.width, y: beam.length };
+                list.push(DrawItem::new(DrawLayer::Shots, DrawKind::LaserBeam, beam.origin, size)
+                    .with_rotation(beam.angle.round() as i32));
+            }
+            for mark in boss.damage_marks() {
+                list.push(DrawItem::square(DrawLayer::Boss, DrawKind::DamageMark, shape.pos() + *mark, 2));
+            }
+        }
+        if let Some(arm) = &self.weapon_arm {
+            let shape = arm.shape();
+            list.push(DrawItem::square(DrawLayer::Boss, DrawKind::WeaponArm, shape.pos(), shape.width())
+                .with_flash(arm.flash().flash_intensity()));
+            push_health_bar(list, arm, shape);
+        }
+        for barrier in &self.barriers {
+            let shape = barrier.shape();
+            list.push(DrawItem::square(DrawLayer::Boss, DrawKind::KineticBarrier, shape.pos(), shape.width())
+                .with_flash(barrier.flash().flash_intensity()));
+            push_health_bar(list, barrier, shape);
+        }
+        if self.ship.alive() {
+            let shape = self.ship.shape();
+            if let Some(ghost) = self.ship.dash_ghost() {
+                list.push(DrawItem::square(DrawLayer::Ship, DrawKind::DashGhost, ghost, shape.width()).with_alpha(0.3));
+            }
+            let alpha = if self.ship.invulnerable() { 0.5 } else { 1.0 };
+            list.push(DrawItem::square(DrawLayer::Ship, DrawKind::Ship, shape.pos(), shape.width())
+                .with_frame(self.ship.animator().current_frame())
+                .with_alpha(alpha)
+                .with_flash(self.ship.flash().flash_intensity()));
+        }
+        if self.target_lock.visible() {
+            let radius = self.target_lock.radius().round() as i32;
+            let pos = self.ship.shape().center() - radius;
+            list.push(DrawItem::square(DrawLayer::Ship, DrawKind::TargetLock, pos, radius * 2)
+                .with_rotation(self.target_lock.rotation().round() as i32)
+                .with_alpha(self.target_lock.opacity()));
+        }
+        for shot in self.projectiles.player_shots() {
+            let shape = shot.shape();
+            // the second frame is the piercing shot
+            list.push(DrawItem::square(DrawLayer::Shots, DrawKind::PlayerShot, shape.pos(), shape.width())
+                .with_frame(shot.piercing().is_some() as u32)
+                .with_rotation(shot.angle())
+                .with_tag(VisualTag::PlayerShot, palette));
+        }
+        for shot in self.projectiles.boss_shots() {
+            let shape = shot.shape();
+            list.push(DrawItem::square(DrawLayer::Shots, DrawKind::BossShot, shape.pos(), shape.width())
+                .with_rotation(shot.angle())
+                .with_tag(VisualTag::BossShot, palette));
+        }
+        for pickup in &self.pickups {
+            let shape = pickup.shape();
+            list.push(DrawItem::square(DrawLayer::Shots, DrawKind::Pickup, shape.pos(), shape.width()));
+        }
+        if let Some(shockwave) = &self.shockwave {
+            let radius = shockwave.radius();
+            list.push(DrawItem::square(DrawLayer::Effects, DrawKind::Shockwave, shockwave.center() - radius, radius * 2));
+        }
+        for particle in self.particles.particles() {
+            // the particle sheet has a frame per kind
+            list.push(DrawItem::square(DrawLayer::Effects, DrawKind::Particle, particle.pos(), 1)
+                .with_frame(particle.kind() as u32)
+                .with_alpha(particle.life_left()));
+        }
+        for particle in self.energy_absorb.particles() {
+            list.push(DrawItem::square(DrawLayer::Effects, DrawKind::AbsorbParticle, particle.pos(), 2)
+                .with_frame(particle.amount().max(0) as u32));
+        }
+        for number in self.damage_numbers.numbers() {
+            let kind = if number.critical() { DrawKind::CriticalDamageNumber } else { DrawKind::DamageNumber };
+            list.push(DrawItem::square(DrawLayer::Overlay, kind, number.pos(), 0)
+                .with_frame(number.amount().max(0) as u32)
+                .with_alpha(number.opacity()));
+        }
+        if let Some(x) = self.hud.safe_zone_x {
+            let pos = Vec2i { x, y: screen_rect().bottom_right.y };
+            list.push(DrawItem::square(DrawLayer::Overlay, DrawKind::SafeZoneMarker, pos, 0));
+        }
+        if let Some(view) = &self.hud.boss_health {
+            push_boss_health_bar(list, view);
+        }
+        list.shift_playfield(self.screen_shake.offset());
+
+        list.sort();
+    }
+
+    pub fn hud_state(&self) -> &HudState {
+        &self.hud
+    }
+
+    // sounds produced by the last update
+    pub fn sound_events(&self) -> &[SoundEvent] {
+        self.sound_events.events()
+    }
+
+    fn process_events(&mut self) {
+        self.events.iter().for_each(|event| self.music_intensity.on_event(event));
+        if self.assisted() {
+            self.events.clear();
+            return;
+        }
+
+        let mut unlocked_any = false;
+        for event in self.events.drain(..) {
+            self.objectives.on_event(&event);
+            if self.challenge_score.on_event(&event) {
+                self.stats.clean_waves += 1;
+            }
+            if let Some(id) = self.achievements.check(&event) {
+                let achievement = self.achievements.get(id);
+                log::info!("Achievement unlocked: {} ({})", achievement.name, achievement.description);
+                self.stats.unlocks.push(id);
+                self.sound_events.push(SoundKind::AchievementUnlocked, self.ship.shape().center());
+                unlocked_any = true;
+            }
+        }
+
+        if unlocked_any && self.persistence {
+            if let Err(err) = self.achievements.save() {
+                log::warn!("Failed to save achievements: {}", err);
             }
         }
     }
-    fn handle_objects_off_screen(&mut self) {
-        let screen_rect = screen_rect();
 
-        self.ship_shots.retain(|shot| {
-            shot.shape().appear_in_rect(&screen_rect)
+    fn update_hud(&mut self) {
+        let lock_targets = self.lock_targets();
+        let pause_menu = self.pause_menu().map(|menu| (menu.page(), menu.selected()));
+        let loadout_menu = self.loadout_menu.as_ref().map(|menu| (menu.selected_row(), menu.loadout()));
+        let adaptive_difficulty = self.adaptive_difficulty_multiplier();
+        let hud = &mut self.hud;
+        hud.ship_hp_percent = self.ship.hp_percent();
+        hud.ship_energy_percent = self.ship.energy_percent();
+        hud.god_mode = self.ship.god_mode();
+        hud.adaptive_difficulty = adaptive_difficulty;
+        hud.demo = self.demo;
+        hud.low_hp_warning = self.low_hp_warning.active();
+        hud.in_force_zone = self.force_zones.ship_inside();
+        let arm_percent = self.weapon_arm.as_ref().map(|arm| arm.hp_percent());
+        hud.boss_health = self.boss.as_ref().map(|boss| {
+            let mut view = boss.health_view().clone();
+            view.set_secondary_percent(arm_percent);
+            view
         });
-        self.comets.retain(|comet| {
-            comet.shape().appear_in_rect(&screen_rect)
+        hud.boss_defeated = self.boss_defeated;
+        hud.shield_bubble = self.shield_bubble.is_some();
+        hud.reflector_remaining = self.reflector.filter(|reflector| reflector.active()).map(|reflector| reflector.time_remaining().as_secs_f32());
+        hud.victory_lap_score = self.victory_lap.map(|_| ScoreBreakdown::from_stats(&self.stats).total());
+        hud.boss_banner = self.boss.as_ref().filter(|boss| boss.in_intro()).map(|boss| boss.name());
+        let dialogue_line = self.dialogue.as_ref().and_then(|dialogue| dialogue.line());
+        hud.dialogue_speaker = dialogue_line.map(|line| line.speaker);
+        hud.dialogue_text = dialogue_line.map(|line| line.text);
+        hud.cutscene_text = self.cutscene.as_ref().and_then(|cutscene| cutscene.frame()).map(|frame| frame.text);
+        hud.lock_on = self.lock_on.current(&lock_targets).map(|target| (target.kind, target.hp_percent));
+        hud.safe_zone_x = self.safe_zone.filter(|indicator| indicator.visible()).map(|indicator| indicator.x());
+        hud.level_name = self.spawner.level_name();
+        hud.objectives = self.objectives.views();
+        hud.music_intensity = self.music_intensity.value();
+        hud.pause_menu = pause_menu;
+        hud.loadout_menu = loadout_menu;
+    }
+
+    fn move_entities(&mut self) {
+        self.comets.iter_mut().for_each(|comet| {
+            comet.fly();
+            comet.update_animation();
         });
+        self.update_slow_mo();
+        self.steer_missiles();
+        let (missed_shots, _) = profile_phase!(self.profiler, Phase::Shots, self.projectiles.update_all());
+        if !missed_shots.is_empty() {
+            self.combo = 0;
+        }
+        profile_phase!(self.profiler, Phase::Particles, self.particles.update());
+        self.update_energy_absorb();
+        self.update_shockwave();
+        self.update_pickups();
+        self.damage_numbers.update();
+        self.screen_shake.update();
+        self.starfield.resize(screen_size());
+        self.starfield.update();
+
+        self.update_arena_walls();
+        profile_phase!(self.profiler, Phase::Ship, self.update_ship());
+        profile_phase!(self.profiler, Phase::Boss, self.update_boss());
+        self.update_target_lock();
+        self.update_lock_on();
     }
 
-    fn destroy_comets_by_shot(comets: &mut Vec<Comet>, shot: &Shot) -> bool {
-        let idx = comets.iter().position(|x| x.intersects(shot));
-        if let Some(idx) = idx {
-            let destroyed = comets.remove(idx);
-            if let Some(mut shards_from_destroyed) = destroyed.spawn_shards() {
-                comets.append(&mut shards_from_destroyed);
+    fn lock_targets(&self) -> Vec<LockTarget> {
+        self.boss.iter()
+            .filter(|boss| boss.can_be_targeted())
+            .map(|boss| LockTarget {
+                kind: LockTargetKind::Boss,
+                id: 0,
+                pos: boss.shape().center(),
+                hp_percent: boss.hp_percent(),
+            })
+            .collect()
+    }
+
+    fn update_lock_on(&mut self) {
+        let targets = self.lock_targets();
+        if self.ship_controller.cycle_lock_pressed() {
+            self.lock_on.cycle(&targets);
+        }
+        self.lock_on.update(&targets);
+    }
+
+    // the locked target, or the nearest one without a lock
+    pub fn homing_target(&self, from: Vec2i) -> Option<LockTarget> {
+        self.lock_on.target_or_nearest(&self.lock_targets(), from).copied()
+    }
+
+    // with the missiles equipped the player shots turn toward the homing target
+    fn steer_missiles(&mut self) {
+        if self.loadout.secondary != SecondaryWeapon::Missiles {
+            return;
+        }
+        let headings: Vec<Option<i32>> = self.projectiles.player_shots().iter()
+            .map(|shot| {
+                let from = shot.shape().center();
+                let diff = self.homing_target(from)?.pos - from;
+                Some(angle_from_velocity(diff.x as f32, diff.y as f32))
+            })
+            .collect();
+        for (shot, heading) in self.projectiles.player_shots_mut().iter_mut().zip(headings) {
+            if let Some(heading) = heading {
+                shot.steer(angle_lerp(shot.angle(), heading, MISSILE_TURN_RATE));
+            }
+        }
+    }
+
+    // the ship can't fire during a dialogue, fire skips it instead
+    fn update_ship(&mut self) {
+        let can_fire = self.dialogue.is_none();
+        if let Some(shot) = self.ship_controller.update(&mut self.ship).filter(|_| can_fire) {
+            self.add_player_shot(shot);
+        }
+        if let Some(shot) = self.ship.update().filter(|_| can_fire) {
+            self.add_player_shot(shot);
+        }
+        self.ship_controller.take_dash(&mut self.ship);
+        self.apply_force_zones();
+        if self.ship_controller.take_bomb(&mut self.ship) {
+            self.projectiles.boss_shots_mut().clear();
+            self.slow_mo_effects.push(SlowMoEffect::new(SLOW_MO_DURATION));
+            self.screen_shake.add_trauma(LARGE_TRAUMA);
+            self.sound_events.push(SoundKind::BombUsed, self.ship.shape().center());
+            self.events.push(GameEvent::BombUsed);
+            self.rumble.push(RumbleSource::Bomb);
+        }
+    }
+
+    fn update_boss(&mut self) {
+        self.update_boss_intro();
+        self.update_adaptive_difficulty();
+        if let Some(boss) = &mut self.boss {
+            boss.fly(&self.ship);
+            let was_stunned = boss.stunned();
+            let was_enraged = boss.is_enraged();
+            if boss.next_stage() {
+                self.boss_stuck_reported = false;
+                #[cfg(feature = "hot-reload")]
+                boss.apply_tuning(&self.tuning);
+                if was_stunned {
+                    self.events.push(GameEvent::BossStunEnded);
+                } else {
+                    self.sound_events.push(SoundKind::BossStageChanged, boss.shape().center());
+                    // the dying boss doesn't clear the screen
+                    if boss.alive() {
+                        let screen = screen_size();
+                        self.shockwave = Some(Shockwave::new(boss.shape().center(), screen.x.max(screen.y) * 3 / 2));
+                    }
+                }
+                self.events.push(GameEvent::StageChanged { stage: boss.stage_name() });
+                log::debug!(
+                    "{} stage {} ({}), phase {}, hp {}/{}, difficulty {:.2}, shots {:?}, estimated dps {:.1}",
+                    boss.name(), boss.stage_idx(), boss.stage_name(), boss.phase(), boss.remaining_hp(), boss.max_hp(),
+                    boss.difficulty_multiplier(), boss.effective_shot_profile(), boss.estimated_dps(),
+                );
+                if !was_enraged && boss.is_enraged() {
+                    self.sound_events.push(SoundKind::BossEnraged, boss.shape().center());
+                    self.events.push(GameEvent::BossEnraged);
+                }
+            }
+            if !self.boss_stuck_reported && !boss.in_last_stage() && boss.time_in_current_stage() > MAX_STAGE_DURATION {
+                if let Some((stage, _)) = boss.stage_history().last() {
+                    log::warn!("Boss is stuck in stage {} for {:?}", stage, boss.time_in_current_stage());
+                }
+                self.boss_stuck_reported = true;
+                self.events.push(GameEvent::BossStuck);
+            }
+            boss.update_effects();
+            if let Some(shots) = boss.shoot(&self.ship) {
+                let fired = SoundKind::ShotFired { owner: ShotOwner::Boss, count: shots.len() as u32 };
+                self.sound_events.push(fired, boss.shape().center());
+                self.projectiles.add_boss_shots(shots);
+            }
+            if let Some(x) = boss.take_safe_column() {
+                self.safe_zone = Some(SafeZoneIndicator::new(x));
+            }
+            if let Some(arm) = boss.detach_arm() {
+                self.weapon_arm = Some(arm);
+                self.events.push(GameEvent::WeaponArmDetached);
             }
-            return true;
         }
+        self.update_weapon_arm();
+        self.update_barriers();
+    }
 
-        false
+    fn update_barriers(&mut self) {
+        let boss = match &mut self.boss {
+            Some(boss) => boss,
+            None => return,
+        };
+        let center = boss.shape().center();
+        self.barriers.iter_mut().for_each(|barrier| barrier.update(center));
+        boss.set_shielded(!self.barriers.is_empty());
     }
 
-    fn spawn_entities(&mut self) {
-        let player_points = 10;  // TODO implement player_points
+    // shots hitting a barrier are stopped there, the boss is exposed once all of them are gone
+    fn handle_barrier_hits(&mut self, emitters: &mut Vec<Emitter>) {
+        if self.barriers.is_empty() {
+            return;
+        }
 
-        if player_points >= self.spawner.boss_spawn_points() {
-            self.boss = Some(self.spawner.spawn_boss());
+        let barriers = &mut self.barriers;
+        let nightmare = self.nightmare;
+        self.projectiles.player_shots_mut().retain(|shot| {
+            let barrier = match barriers.iter_mut().find(|barrier| barrier.alive() && barrier.intersects(shot)) {
+                Some(barrier) => barrier,
+                None => return true,
+            };
+            let damage = nightmare.player_shot_damage(shot.damage(), barrier.hp_max());
+            barrier.hit(damage);
+            self.stats.shots_hit += 1;
+            self.damage_numbers.add(DamageTarget::Boss, shot.shape().center(), damage, false);
+            self.sound_events.push(SoundKind::Hit { target_kind: TargetKind::Boss }, shot.shape().center());
+            emitters.push(Emitter::hit_spark(shot.shape().center()));
+            false
+        });
+
+        let (alive, destroyed): (Vec<KineticBarrier>, Vec<KineticBarrier>) = self.barriers.drain(..).partition(|barrier| barrier.alive());
+        self.barriers = alive;
+        for barrier in destroyed {
+            let center = barrier.shape().center();
+            emitters.push(Emitter::explosion(center));
+            self.sound_events.push(SoundKind::Explosion { size: ExplosionSize::Small }, center);
+            self.events.push(GameEvent::KineticBarrierDestroyed);
+        }
+        if let Some(boss) = &mut self.boss {
+            boss.set_shielded(!self.barriers.is_empty());
         }
+    }
 
-        if self.boss.is_none() {
-            let comets_limit = self.spawner.calc_comets_limit(player_points);
-            if self.comets.len() < comets_limit as usize {
-                self.comets.push(self.spawner.spawn_comet(3))
+    fn update_weapon_arm(&mut self) {
+        if let Some(arm) = &mut self.weapon_arm {
+            let shots = arm.update(&self.ship);
+            if !shots.is_empty() {
+                let fired = SoundKind::ShotFired { owner: ShotOwner::Boss, count: shots.len() as u32 };
+                self.sound_events.push(fired, arm.shape().center());
+                self.projectiles.add_boss_shots(shots);
             }
         }
     }
+
+    // the boss loses the crossfire once its arm is destroyed
+    fn handle_weapon_arm_hits(&mut self, emitters: &mut Vec<Emitter>) {
+        let arm = match &mut self.weapon_arm {
+            Some(arm) => arm,
+            None => return,
+        };
+
+        let nightmare = self.nightmare;
+        self.projectiles.player_shots_mut().retain(|shot| {
+            let intersects = arm.intersects(shot);
+            if intersects {
+                let damage = nightmare.player_shot_damage(shot.damage(), arm.hp_max());
+                arm.hit(damage);
+                self.stats.shots_hit += 1;
+                self.damage_numbers.add(DamageTarget::Boss, shot.shape().center(), damage, false);
+                self.sound_events.push(SoundKind::Hit { target_kind: TargetKind::Boss }, shot.shape().center());
+                emitters.push(Emitter::hit_spark(shot.shape().center()));
+            }
+            !intersects
+        });
+
+        if !arm.alive() {
+            let center = arm.shape().center();
+            emitters.push(Emitter::explosion(center));
+            self.sound_events.push(SoundKind::Explosion { size: ExplosionSize::Small }, center);
+            self.weapon_arm = None;
+            if let Some(boss) = &mut self.boss {
+                boss.on_arm_destroyed();
+            }
+            // the reward for taking the arm out first
+            self.activate_power_up(PowerUpKind::ShieldBubble);
+            self.events.push(GameEvent::WeaponArmDestroyed);
+        }
+    }
+
+    fn save_checkpoint(&self) {
+        if !self.persists() {
+            return;
+        }
+        CheckpointManager::save(Checkpoint {
+            level: self.level_number,
+            wave: self.waves_cleared,
+            ship_snapshot: ShipSnapshot { hp: self.ship.hp(), energy: self.ship.energy() },
+            score_snapshot: ScoreSnapshot::from_stats(&self.stats),
+        });
+    }
+
+    // restarts the level from the last cleared wave, false when there is no checkpoint for this level
+    pub fn continue_from_checkpoint(&mut self) -> bool {
+        let checkpoint = match CheckpointManager::load() {
+            Some(checkpoint) if checkpoint.level == self.level_number => checkpoint,
+            _ => return false,
+        };
+
+        self.restart();
+        self.waves_cleared = checkpoint.wave;
+        *self.ship.hp_mut() = checkpoint.ship_snapshot.hp.clamp(1, self.ship.hp_max());
+        self.ship.set_energy(checkpoint.ship_snapshot.energy);
+        self.stats = checkpoint.score_snapshot.to_stats();
+        true
+    }
+
+    // restarts the level and plays it without any input until the given number of waves is cleared, so the
+    // spawns and the rng rolls match a played run with the same seed at that wave; an unseeded run has nothing
+    // to match and just gets there. false when the ship dies or the level runs out of waves first
+    #[cfg(feature = "debug-skip-wave")]
+    pub fn skip_to_wave(&mut self, wave: u32) -> bool {
+        match self.seed {
+            Some(seed) => crate::globals::seed_rng(seed),
+            None => log::warn!("Skipping to wave {} of an unseeded run, the waves before it are rolled anew", wave),
+        }
+        self.restart();
+
+        let idle_input = Rc::new(InputMgr::from_sources(crate::input::Bindings::new(), vec![]));
+        let controller = std::mem::replace(&mut self.ship_controller, PlayerShipController::new(idle_input));
+        let max_ticks = crate::timing::duration_to_ticks(MAX_SKIP_DURATION);
+        let mut ticks = 0;
+        while self.waves_cleared < wave && self.ship.alive() && ticks < max_ticks {
+            self.update();
+            ticks += 1;
+        }
+        self.ship_controller = controller;
+
+        let reached = self.waves_cleared >= wave;
+        if !reached {
+            log::warn!("Wave {} wasn't reached, stopped at {} after {} ticks", wave, self.waves_cleared, ticks);
+        }
+        reached
+    }
+
+    fn entity_counts(&self) -> EntityCounts {
+        EntityCounts {
+            comets: self.comets.len(),
+            player_shots: self.projectiles.player_shots().len(),
+            boss_shots: self.projectiles.boss_shots().len(),
+            particles: self.particles.particles().len(),
+        }
+    }
+
+    pub fn set_slow_frame_threshold(&mut self, threshold: Duration) {
+        self.profiler.set_slow_frame_threshold(threshold);
+    }
+
+    // only filled with the metrics feature on
+    pub fn phase_totals(&self) -> &PhaseTimings {
+        self.profiler.totals()
+    }
+
+    fn create_dialogue(spawner: &dyn Spawner) -> Option<Dialogue> {
+        let lines = spawner.dialogue();
+        if lines.is_empty() {
+            return None;
+        }
+        match Dialogue::new(lines) {
+            Ok(dialogue) => Some(dialogue),
+            Err(err) => {
+                log::warn!("Invalid dialogue in level {}: {:?}", spawner.level_name(), err);
+                None
+            }
+        }
+    }
+
+    fn update_dialogue(&mut self) {
+        let fire_pressed = self.ship_controller.fire_pressed();
+        let change = match &mut self.dialogue {
+            Some(dialogue) if fire_pressed => {
+                dialogue.skip();
+                Some(DialogueChange::Finished)
+            }
+            Some(dialogue) => dialogue.update(),
+            None => return,
+        };
+
+        match change {
+            Some(DialogueChange::NextLine) => {
+                self.events.push(GameEvent::DialogueLineChanged);
+                self.sound_events.push(SoundKind::DialogueLine, self.ship.shape().center());
+            }
+            Some(DialogueChange::Finished) => {
+                self.dialogue = None;
+                self.events.push(GameEvent::DialogueEnded);
+            }
+            None => (),
+        }
+    }
+
+    // the level is frozen while a cutscene plays, fire skips the current frame and pause the whole cutscene
+    fn start_cutscene(&mut self, frames: Vec<CutsceneFrame>) -> bool {
+        if frames.is_empty() {
+            return false;
+        }
+        self.cutscene = Some(CutscenePlayer::new(frames));
+        self.events.push(GameEvent::CutsceneStarted);
+        true
+    }
+
+    fn update_cutscene(&mut self) {
+        let fire_pressed = self.ship_controller.fire_pressed();
+        let skip_pressed = self.ship_controller.pause_toggled();
+        let finished = match &mut self.cutscene {
+            Some(cutscene) => {
+                if skip_pressed {
+                    cutscene.skip();
+                } else if fire_pressed {
+                    cutscene.next_frame();
+                }
+                cutscene.advance()
+            }
+            None => return,
+        };
+        if finished {
+            self.cutscene = None;
+            self.events.push(GameEvent::CutsceneEnded);
+        }
+        self.process_events();
+    }
+
+    pub fn cutscene(&self) -> Option<&CutsceneFrame> {
+        self.cutscene.as_ref().and_then(|cutscene| cutscene.frame())
+    }
+
+    fn update_adaptive_difficulty(&mut self) {
+        if !self.adaptive_difficulty.active() {
+            return;
+        }
+        let in_intro = self.boss.as_ref().is_some_and(|boss| boss.in_intro());
+        self.adaptive_difficulty.update(in_intro);
+        let multiplier = self.difficulty_multiplier();
+        if let Some(boss) = &mut self.boss {
+            boss.set_difficulty_multiplier(multiplier);
+        }
+    }
+
+    // the adaptive and the progressive difficulties are never active together, each is 1.0 otherwise
+    fn difficulty_multiplier(&self) -> f32 {
+        let adaptive = if self.adaptive_difficulty.active() { self.adaptive_difficulty.multiplier() } else { 1.0 };
+        adaptive * self.progressive_difficulty.run_difficulty()
+    }
+
+    // for the debug overlay
+    pub fn adaptive_difficulty_multiplier(&self) -> Option<f32> {
+        Some(self.adaptive_difficulty.multiplier()).filter(|_| self.adaptive_difficulty.active())
+    }
+
+    // after the regular movement, so shots curve inside the zones
+    fn apply_force_zones(&mut self) {
+        self.force_zones.update();
+
+        let force_zones = &self.force_zones;
+        let drift_shot = |shot: &mut Shot| shot.drift(force_zones.drift_at(shot.shape().center()));
+        self.projectiles.player_shots_mut().iter_mut().for_each(drift_shot);
+        self.projectiles.boss_shots_mut().iter_mut().for_each(drift_shot);
+
+        let ship_center = self.ship.shape().center();
+        self.ship.drift(self.force_zones.ship_drift(ship_center));
+
+        match self.force_zones.update_ship(self.ship.shape().center()) {
+            Some(ZoneChange::Entered) => self.events.push(GameEvent::ForceZoneEntered),
+            Some(ZoneChange::Left) => self.events.push(GameEvent::ForceZoneLeft),
+            None => (),
+        }
+    }
+
+    // not updated while paused, and switched off for good once the ship is dead
+    fn update_music_intensity(&mut self) {
+        let boss = self.boss.as_ref().filter(|boss| boss.alive());
+        let in_intro = boss.is_some_and(|boss| boss.in_intro());
+        let inputs = IntensityInputs {
+            stage_hint: boss.map(|boss| boss.intensity_hint()),
+            low_hp: self.low_hp_warning.active(),
+            calm: self.paused || in_intro || self.dialogue.is_some() || self.cutscene.is_some(),
+        };
+        self.music_intensity.update(&inputs);
+    }
+
+    fn update_low_hp_warning(&mut self) {
+        let change = if self.ship.alive() {
+            self.low_hp_warning.update(self.ship.hp_percent())
+        } else if self.low_hp_warning.active() {
+            self.low_hp_warning.reset();
+            Some(LowHpChange::Stopped)
+        } else {
+            None
+        };
+
+        let pos = self.ship.shape().center();
+        match change {
+            Some(LowHpChange::Started) => {
+                self.events.push(GameEvent::LowHpWarningStarted);
+                self.sound_events.push(SoundKind::LowHpWarningStarted, pos);
+            }
+            Some(LowHpChange::Stopped) => {
+                self.events.push(GameEvent::LowHpWarningStopped);
+                self.sound_events.push(SoundKind::LowHpWarningStopped, pos);
+            }
+            None => (),
+        }
+    }
+
+    fn update_pause_menu(&mut self) {
+        let transition = match self.pause_menu.update(&self.ship_controller.input()) {
+            Some(transition) => transition,
+            None => return,
+        };
+
+        match transition {
+            MenuTransition::Resume => {
+                self.paused = false;
+                self.ship_controller.clear_buffer();
+            }
+            MenuTransition::Restart => self.restart(),
+            MenuTransition::Quit => self.quit(),
+            MenuTransition::OpenLoadout => self.loadout_menu = Some(LoadoutMenu::new(self.loadout, &self.achievements)),
+            MenuTransition::Toggle(SettingsEntry::AutoFire) => self.set_auto_fire(!self.auto_fire, self.auto_fire_interval),
+            MenuTransition::Toggle(SettingsEntry::ScreenShake) => {
+                self.screen_shake.set_enabled(!self.screen_shake.enabled())
+            }
+            MenuTransition::Toggle(SettingsEntry::DamageNumbers) => {
+                self.damage_numbers.set_enabled(!self.damage_numbers.enabled())
+            }
+            MenuTransition::Toggle(SettingsEntry::Palette) => self.palette = self.palette.next(),
+            MenuTransition::Toggle(SettingsEntry::Controls | SettingsEntry::Back) => (),
+            MenuTransition::Rebind(action) => self.ship_controller.start_rebinding(action),
+            MenuTransition::ResetBindings => self.ship_controller.reset_bindings(),
+        }
+    }
+
+    // pause goes back to the pause menu, a confirmed loadout replaces the current one
+    fn update_loadout_menu(&mut self) {
+        let input = self.ship_controller.input();
+        if input.pause.pressed {
+            self.loadout_menu = None;
+            return;
+        }
+        let Some(loadout) = self.loadout_menu.as_mut().and_then(|menu| menu.update(&input)) else {
+            return;
+        };
+        log::info!("Loadout {:?} is used from the next restart", loadout);
+        self.loadout = loadout;
+        self.loadout_menu = None;
+    }
+
+    // the banner is shown while the boss descends, the fight (and its timer) starts afterwards
+    // the reticle shows while the boss is in the Targeted stage
+    fn update_target_lock(&mut self) {
+        let mut stage_changes = self.events.iter().filter_map(|event| match event {
+            GameEvent::StageChanged { stage } => Some(*stage),
+            _ => None,
+        });
+        if let Some(stage) = stage_changes.next_back() {
+            if stage == TARGETED_STAGE_NAME {
+                self.target_lock.activate(self.ship.shape().width());
+            } else {
+                self.target_lock.release();
+            }
+        }
+        if self.boss.is_none() {
+            self.target_lock.release();
+        }
+        self.target_lock.update();
+    }
+
+    // the ship caught by a closing wall is pushed inward
+    fn update_arena_walls(&mut self) {
+        let walls = match &mut self.arena_walls {
+            Some(walls) => walls,
+            None => return,
+        };
+
+        walls.update();
+        let screen_width = screen_size().x;
+        if walls.gone() {
+            self.arena_walls = None;
+            set_playfield_inset(0);
+            return;
+        }
+        set_playfield_inset(walls.inset(screen_width));
+        self.ship.drift(Vec2i { x: 0, y: 0 });
+    }
+
+    fn update_boss_intro(&mut self) {
+        let (boss, intro_started) = match (&mut self.boss, self.boss_intro_started) {
+            (Some(boss), Some(intro_started)) if boss.in_intro() => (boss, intro_started),
+            _ => return,
+        };
+
+        if self.ship_controller.fire_pressed() {
+            boss.skip_intro();
+        } else if intro_started.elapsed() >= BOSS_INTRO_DURATION {
+            boss.end_intro();
+        } else {
+            return;
+        }
+
+        self.arena_walls = boss.arena_walls().map(ArenaWalls::new);
+        self.boss_intro_started = None;
+        self.boss_spawn_time = GameInstant::now();
+        self.barriers = boss.raise_barriers();
+        boss.set_shielded(!self.barriers.is_empty());
+        self.ship_hp_at_boss_spawn = self.ship.hp();
+        self.events.push(GameEvent::BossIntroEnded);
+    }
+
+    // shots fired while the slow motion is active are slowed down as well, the kill cam beat slows them further
+    fn update_slow_mo(&mut self) {
+        self.slow_mo_effects.retain(|effect| effect.active());
+
+        let mut multiplier = if self.slow_mo_effects.is_empty() { 1.0 } else { SLOW_MO_SHOT_SPEED_MULTIPLIER };
+        let mut ship_time_scale = 1.0;
+        if let Some(kill_cam) = &self.kill_cam {
+            multiplier = multiplier.min(kill_cam.time_scale());
+            ship_time_scale = kill_cam.time_scale();
+            if kill_cam.finished() {
+                self.kill_cam = None;
+            }
+        }
+        // the bomb slow motion is on the player's side, only the kill cam slows the ship
+        self.ship.set_time_scale(ship_time_scale);
+        self.starfield.set_speed_multiplier(multiplier);
+        self.projectiles.boss_shots_mut().iter_mut().for_each(|shot| shot.speed_multiplier(multiplier));
+    }
+
+    pub fn activate_piercing_shots(&mut self) {
+        self.piercing_shots = Some(PiercingShotPowerUp::new(PIERCING_SHOT_DURATION));
+    }
+
+    pub fn activate_reflector(&mut self) {
+        self.reflector = Some(ReflectorPowerUp::new(REFLECTOR_DURATION, REFLECTOR_MAX_REFLECTIONS));
+    }
+
+    pub fn activate_shield_bubble(&mut self) {
+        self.shield_bubble = Some(ShieldBubble);
+    }
+
+    pub fn activate_power_up(&mut self, kind: PowerUpKind) {
+        match kind {
+            PowerUpKind::PiercingShots => self.activate_piercing_shots(),
+            PowerUpKind::Reflector => self.activate_reflector(),
+            PowerUpKind::ShieldBubble => self.activate_shield_bubble(),
+        }
+        self.events.push(GameEvent::PowerUpGranted { kind });
+    }
+
+    // the main shot goes first, so it isn't the one dropped at the shots limit
+    fn add_player_shot(&mut self, shot: Shot) {
+        let angle = shot.angle();
+        self.add_single_player_shot(shot);
+        if self.loadout.primary == PrimaryWeapon::Spread {
+            for offset in [-SPREAD_WEAPON_ANGLE, SPREAD_WEAPON_ANGLE] {
+                let side_shot = self.ship.shoot_at(angle + offset);
+                self.add_single_player_shot(side_shot);
+            }
+        }
+    }
+
+    fn add_single_player_shot(&mut self, shot: Shot) {
+        let piercing = self.piercing_shots.is_some_and(|power_up| power_up.active());
+        let shot = if piercing { shot.with_piercing(PIERCING_SHOT_MAX_HITS) } else { shot };
+        if self.projectiles.player_shots().len() < MAX_SHOTS as usize {
+            let fired = SoundKind::ShotFired { owner: ShotOwner::Player, count: 1 };
+            self.sound_events.push(fired, shot.shape().center());
+            self.projectiles.add_player_shot(shot);
+            self.stats.shots_fired += 1;
+        }
+    }
+
+    fn handle_intersections(&mut self) {
+        let mut emitters = vec![];
+        let ship_hp = self.ship.hp();
+
+        let (rammed_comets, comets) = self.comets.drain(..).partition(|comet| self.ship.intersects(comet));
+        self.comets = comets;
+        for comet in rammed_comets {
+            self.ship.take_contact_damage(&comet);
+            self.screen_shake.add_trauma(CONTACT_DAMAGE_TRAUMA);
+            self.sound_events.push(SoundKind::ContactDamage, self.ship.shape().center());
+            emitters.push(Emitter::explosion(comet.shape().center()));
+            self.kill_comet(comet);
+        }
+        self.handle_grazes();
+        if let Some(beam) = self.boss.as_ref().and_then(|boss| boss.beam()) {
+            if beam.intersects(self.ship.shape()) {
+                self.ship.hit(self.nightmare.boss_shot_damage(beam.damage));
+                self.screen_shake.add_trauma(SMALL_TRAUMA);
+                self.sound_events.push(SoundKind::Hit { target_kind: TargetKind::Ship }, self.ship.shape().center());
+            }
+        }
+        let hit_indices = self.projectiles.boss_shots_colliding(&self.ship);
+        let mut hit_shots = self.projectiles.take_boss_shots(&hit_indices);
+        if let Some(bubble) = self.shield_bubble.filter(|_| !hit_shots.is_empty()) {
+            self.shield_bubble = None;
+            let shot = bubble.reflect(hit_shots.remove(0));
+            self.events.push(GameEvent::ShotReflected { pos: shot.shape().center() });
+            self.projectiles.add_player_shot(shot);
+        }
+        for boss_shot in hit_shots {
+            self.ship.hit(self.nightmare.boss_shot_damage(boss_shot.damage()));
+            self.screen_shake.add_trauma(SMALL_TRAUMA);
+            self.sound_events.push(SoundKind::Hit { target_kind: TargetKind::Ship }, boss_shot.shape().center());
+            emitters.push(Emitter::hit_spark(boss_shot.shape().center()));
+        }
+
+        let comets = &mut self.comets;
+        let damage_numbers = &mut self.damage_numbers;
+        let sound_events = &mut self.sound_events;
+        let ship_shape = self.ship.shape();
+        let mut destroyed_comets = vec![];
+        // piercing shots go on after destroying a comet, the boss stops any shot since it would be hit again
+        self.projectiles.player_shots_mut().retain_mut(|shot| {
+            let destroyed = GameLevel::destroy_comets_by_shot(comets, shot, ship_shape);
+            let hit = destroyed.is_some();
+            if hit {
+                damage_numbers.add(DamageTarget::Comet, shot.shape().center(), shot.damage(), false);
+                sound_events.push(SoundKind::Hit { target_kind: TargetKind::Comet }, shot.shape().center());
+            }
+            destroyed_comets.extend(destroyed);
+            !hit || !shot.register_hit()
+        });
+        self.ship.add_energy(destroyed_comets.len() as i32 * ENERGY_KILL_BONUS);
+        self.stats.shots_hit += destroyed_comets.len() as u32;
+        self.stats.comets_destroyed += destroyed_comets.len() as u32;
+        for comet in destroyed_comets {
+            emitters.push(Emitter::explosion(comet.shape().center()));
+            self.kill_comet(comet);
+            self.events.push(GameEvent::CometDestroyed);
+        }
+
+        self.handle_weapon_arm_hits(&mut emitters);
+        self.handle_barrier_hits(&mut emitters);
+
+        if let Some(boss) = &mut self.boss {  // TODO: play with borriwng here
+            let invulnerable = boss.invulnerable();
+            let nightmare = self.nightmare;
+            self.projectiles.player_shots_mut().retain(|shot| {
+                let intersects = boss.intersects(shot);
+                if intersects && !invulnerable {
+                    let damage = nightmare.player_shot_damage(shot.damage(), boss.hp_max());
+                    let was_stunned = boss.stunned();
+                    let damage = boss.take_hit(damage);
+                    boss.add_damage_mark(shot.shape().center());
+                    // the hit which stuns the boss counts as a critical one
+                    let stunning = !was_stunned && boss.stunned();
+                    if stunning {
+                        self.events.push(GameEvent::BossStunned);
+                        self.sound_events.push(SoundKind::BossStunned, boss.shape().center());
+                    }
+                    self.stats.shots_hit += 1;
+                    self.adaptive_difficulty.record(PerformanceEvent::BossDamageDealt(damage));
+                    self.damage_numbers.add(DamageTarget::Boss, shot.shape().center(), damage, stunning);
+                    self.sound_events.push(SoundKind::Hit { target_kind: TargetKind::Boss }, shot.shape().center());
+                    emitters.push(Emitter::hit_spark(shot.shape().center()));
+                }
+                !intersects
+            });
+
+            if !boss.alive() {
+                // the arm and the barriers go down with the boss
+                if let Some(arm) = self.weapon_arm.take() {
+                    emitters.push(Emitter::explosion(arm.shape().center()));
+                }
+                for barrier in self.barriers.drain(..) {
+                    emitters.push(Emitter::explosion(barrier.shape().center()));
+                }
+                if boss.kill_cam() && self.kill_cam.is_none() {
+                    self.kill_cam = Some(KillCam::new());
+                    self.ship.make_invulnerable(KillCam::total_duration());
+                    self.events.push(GameEvent::KillCam { boss_center: boss.shape().center() });
+                }
+                emitters.append(&mut GameLevel::boss_death_burst(boss));
+                self.screen_shake.add_trauma(LARGE_TRAUMA);
+                self.sound_events.push(SoundKind::Explosion { size: ExplosionSize::Large }, boss.shape().center());
+                log::debug!("{} defeated, movement predictability {:.2}", boss.name(), boss.average_predictability());
+                let drop = boss.drop_powerup();
+                if let Some(mut dead_boss) = self.boss.take() {
+                    dead_boss.start_dying();
+                    self.dying_bosses.push(dead_boss);
+                }
+                // fades out over the death clip
+                let fade = MusicFade::new(1.0, 0.0, Boss::death_ticks());
+                self.music_fade = Some(fade);
+                self.sound_events.push(SoundKind::FadeMusic { fade }, self.ship.shape().center());
+                if let Some(walls) = &mut self.arena_walls {
+                    walls.open();
+                }
+                self.boss_defeated = true;
+                let frames = self.spawner.cutscene_after_boss();
+                self.start_cutscene(frames);
+                self.rumble.push(RumbleSource::BossDeath);
+                // later levels have tougher bosses
+                let boss_difficulty = self.level_number.clamp(1, u8::MAX as i32) as u8;
+                self.stats.record_boss_kill(boss_difficulty, self.boss_spawn_time.elapsed());
+                self.progressive_difficulty.record_boss_kill();
+                // there is no pickup entity for power-ups, the drop is granted right away
+                if let Some(kind) = drop {
+                    self.activate_power_up(kind);
+                }
+                self.events.push(GameEvent::WaveComplete);
+                self.ship.add_energy(ENERGY_KILL_BONUS);
+
+                if self.ship.hp() >= self.ship_hp_at_boss_spawn {
+                    self.events.push(GameEvent::NoDamageBossKill);
+                }
+                if self.boss_spawn_time.elapsed() < SPEED_KILL_DURATION {
+                    self.events.push(GameEvent::BossKillUnder60Seconds);
+                }
+            }
+        }
+
+        emitters.iter().for_each(|emitter| self.particles.emit(emitter));
+        let damage_taken = ship_hp.max(0) - self.ship.hp().max(0);
+        if damage_taken > 0 {
+            self.rumble.push(RumbleSource::Damage);
+            self.adaptive_difficulty.record(PerformanceEvent::DamageTaken(damage_taken));
+            self.events.push(GameEvent::PlayerHit);
+        }
+        self.stats.damage_taken += damage_taken;
+    }
+
+    fn boss_death_burst(boss: &Boss) -> Vec<Emitter> {
+        let shape = boss.shape();
+        let center = shape.center();
+        let quarter = shape.width() / 4;
+
+        let mut burst = vec![Emitter::explosion(center), Emitter::smoke(center)];
+        let offset = Vec2i { x: quarter, y: quarter };
+        for offset in [-offset, offset] {
+            burst.push(Emitter::explosion(center + offset));
+        }
+        burst
+    }
+    fn extend_combo(&mut self, hits: u32) {
+        let previous = self.combo;
+        self.combo += hits;
+        if previous < COMBO_ACHIEVEMENT_HITS && self.combo >= COMBO_ACHIEVEMENT_HITS {
+            self.events.push(GameEvent::ComboOf50);
+        }
+    }
+
+    fn handle_objects_off_screen(&mut self) {
+        let screen_rect = screen_rect();
+
+        let (comets, left_comets): (Vec<Comet>, Vec<Comet>) = self.comets.drain(..).partition(|comet| {
+            comet.shape().appear_in_rect(&screen_rect)
+        });
+        self.comets = comets;
+        for comet in left_comets {
+            self.release_comet(&comet);
+        }
+    }
+
+    fn handle_grazes(&mut self) {
+        let ship = &self.ship;
+        let ship_center = ship.shape().center();
+        let graze_distance = match self.loadout.passive {
+            Passive::MagnetRadius => GRAZE_DISTANCE * 3 / 2,
+            _ => GRAZE_DISTANCE,
+        };
+        let graze_radius = ship.shape().width() / 2 + graze_distance;
+
+        let reflector = &mut self.reflector;
+        let mut grazed = vec![];
+        let mut reflected = vec![];
+        for (idx, shot) in self.projectiles.boss_shots_mut().iter_mut().enumerate() {
+            let diff = shot.shape().center() - ship_center;
+            let near = diff.magnitude_sq() <= (graze_radius as i64).pow(2);
+            if near && !shot.intersects(ship) && shot.graze() {
+                if reflector.as_mut().is_some_and(|reflector| reflector.try_reflect()) {
+                    reflected.push(idx);
+                } else {
+                    grazed.push(shot.shape().center());
+                }
+            }
+        }
+
+        // taken out of the boss shots before the hit check, so a reflected shot can't hurt the ship
+        let boss_center = self.boss.as_ref().map(|boss| boss.shape().center());
+        for mut shot in self.projectiles.take_boss_shots(&reflected) {
+            let pos = shot.shape().center();
+            let angle = boss_center.map(|center| angle_to_target(pos, center))
+                .filter(|angle| angle.is_finite())
+                .map(|angle| angle.round() as i32)
+                .unwrap_or((shot.angle() + 180).rem_euclid(360));
+            shot.reflect(angle);
+            self.projectiles.add_player_shot(shot);
+            self.events.push(GameEvent::ShotReflected { pos });
+        }
+
+        for pos in grazed {
+            self.energy_absorb.spawn(pos, GRAZE_ENERGY);
+            self.rumble.push(RumbleSource::Graze);
+            self.adaptive_difficulty.record(PerformanceEvent::Graze);
+            self.events.push(GameEvent::Graze { pos });
+        }
+    }
+
+    // converts the boss shots it reaches into pickups, past the cap the shots are just cleared
+    fn update_shockwave(&mut self) {
+        let shockwave = match &mut self.shockwave {
+            Some(shockwave) => shockwave,
+            None => return,
+        };
+        shockwave.update();
+
+        let mut converted = vec![];
+        self.projectiles.boss_shots_mut().retain(|shot| {
+            let touched = shockwave.touches(shot.shape().center());
+            if touched {
+                converted.push(shot.shape().center());
+            }
+            !touched
+        });
+        if shockwave.finished() {
+            self.shockwave = None;
+        }
+
+        for pos in converted {
+            if self.pickups.len() < MAX_PICKUPS {
+                self.pickups.push(ScorePickup::new(pos));
+            }
+            self.sound_events.push(SoundKind::ShotConverted, pos);
+            self.events.push(GameEvent::ShotConverted { pos });
+        }
+    }
+
+    fn update_pickups(&mut self) {
+        let magnet_radius = match self.loadout.passive {
+            Passive::MagnetRadius => PICKUP_MAGNET_RADIUS * 3 / 2,
+            _ => PICKUP_MAGNET_RADIUS,
+        };
+        let ship_center = self.ship.shape().center();
+        self.pickups.iter_mut().for_each(|pickup| pickup.update(ship_center, magnet_radius));
+
+        let ship = &self.ship;
+        let collected_before = self.pickups.len();
+        self.pickups.retain(|pickup| !ship.alive() || !pickup.intersects(ship));
+        for _ in self.pickups.len()..collected_before {
+            self.stats.pickups_collected += 1;
+            self.sound_events.push(SoundKind::PickupCollected, ship_center);
+            self.events.push(GameEvent::PickupCollected);
+        }
+
+        let screen_rect = screen_rect();
+        self.pickups.retain(|pickup| pickup.shape().appear_in_rect(&screen_rect));
+    }
+
+    fn update_energy_absorb(&mut self) {
+        let absorbed = self.energy_absorb.update(self.ship.shape().center());
+        if absorbed > 0 {
+            self.ship.add_energy(absorbed);
+            self.events.push(GameEvent::EnergyAbsorbed { amount: absorbed });
+        }
+    }
+
+    // shards appearing next to the ship fly away from it
+    fn destroy_comets_by_shot(comets: &mut Vec<Comet>, shot: &Shot, ship: &Shape) -> Option<Comet> {
+        let idx = comets.iter().position(|x| x.intersects(shot));
+        if let Some(idx) = idx {
+            let destroyed = comets.remove(idx);
+            if let Some(mut shards_from_destroyed) = destroyed.spawn_shards() {
+                for shard in shards_from_destroyed.iter_mut().filter(|shard| !safe_from_ship(shard.shape(), ship)) {
+                    let angle = steer_away(shard.shape().center(), shard.angle(), ship.center());
+                    shard.set_angle(angle);
+                }
+                comets.append(&mut shards_from_destroyed);
+            }
+            return Some(destroyed);
+        }
+
+        None
+    }
+
+    // killed entities stay around only to finish their death animation
+    fn kill_comet(&mut self, mut comet: Comet) {
+        self.release_comet(&comet);
+        let explosion = SoundKind::Explosion { size: ExplosionSize::Small };
+        self.sound_events.push(explosion, comet.shape().center());
+        comet.start_dying();
+        self.dying_comets.push(comet);
+    }
+
+    // lets the background spawner which created the comet spawn a new one
+    fn release_comet(&mut self, comet: &Comet) {
+        let spawner = comet.owner().and_then(|idx| self.background_spawners.get_mut(idx));
+        if let Some(spawner) = spawner {
+            spawner.on_child_death();
+        }
+    }
+
+    fn remove_finished_dying(&mut self) {
+        self.dying_comets.iter_mut().for_each(|comet| comet.update_animation());
+        self.dying_comets.retain(|comet| !comet.animator().finished());
+
+        self.dying_bosses.iter_mut().for_each(|boss| boss.update_effects());
+        self.dying_bosses.retain(|boss| !boss.animator().finished());
+
+        self.update_music_fade();
+    }
+
+    // music fades out while the boss is dying, the fanfare starts once it's gone
+    fn update_music_fade(&mut self) {
+        if self.music_fade.is_none() || !self.dying_bosses.is_empty() {
+            return;
+        }
+
+        let pos = self.ship.shape().center();
+        self.music_fade = None;
+        self.sound_events.push(SoundKind::ChangeTrack { track: MusicTrack::VictoryFanfare }, pos);
+        self.sound_events.push(SoundKind::SetMusicVolume { volume: 1.0 }, pos);
+    }
+
+    fn spawn_entities(&mut self) {
+        let player_points = 10;  // TODO implement player_points
+
+        let boss_due = self.spawner.has_boss()
+            && (self.boss_only || self.waves_cleared > 0 || player_points >= self.spawner.boss_spawn_points());
+        if boss_due && self.waves_cleared == 0 {
+            self.waves_cleared = 1;
+            if !self.boss_only {
+                self.events.push(GameEvent::WaveComplete);
+            }
+            self.checkpoint_pending = true;
+        }
+        if boss_due && !self.boss_cutscene_played {
+            self.boss_cutscene_played = true;
+            let frames = self.spawner.cutscene_before_boss();
+            if self.start_cutscene(frames) {
+                return;
+            }
+        }
+        if boss_due && self.boss.is_none() && !self.boss_defeated {
+            let Some(mut boss) = self.spawner.spawn_boss() else {
+                return;
+            };
+            #[cfg(feature = "hot-reload")]
+            {
+                boss = self.tuning.restructure(boss);
+                boss.apply_tuning(&self.tuning);
+            }
+            boss.set_difficulty_multiplier(self.difficulty_multiplier());
+            #[cfg(feature = "debug-commands")]
+            if let Some(hp) = self.boss_hp {
+                boss.set_hp(hp);
+            }
+            boss.start_intro();
+            self.boss = Some(boss);
+            self.boss_intro_started = Some(GameInstant::now());
+            self.boss_stuck_reported = false;
+            self.starfield.boost(BOSS_INTRO_STARFIELD_BOOST_TICKS);
+            self.events.push(GameEvent::BossIntroStarted);
+        }
+
+        if self.boss.is_none() && !self.boss_only {
+            let comets_limit = self.spawner.calc_comets_limit(player_points);
+            if self.comets.len() < comets_limit as usize {
+                let spawner = &self.spawner;
+                match find_safe(self.ship.shape(), || spawner.spawn_comet(3)) {
+                    Some(comet) => self.comets.push(comet),
+                    None if !self.comet_spawn_blocked_reported => {
+                        log::warn!("No safe position for a comet around the ship at {}", self.ship.shape().pos());
+                        self.comet_spawn_blocked_reported = true;
+                    }
+                    None => (),
+                }
+            }
+        }
+
+        for (idx, spawner) in self.background_spawners.iter_mut().enumerate() {
+            if let Some(mut comet) = spawner.try_spawn() {
+                comet.set_owner(idx);
+                self.delayed_comets.push((comet, 0));
+            }
+        }
+        self.admit_delayed_comets();
+    }
+
+    // comets from fixed positions wait while the ship is in the way
+    fn admit_delayed_comets(&mut self) {
+        let ship = self.ship.shape();
+        for (comet, waited) in std::mem::take(&mut self.delayed_comets) {
+            if safe_from_ship(comet.shape(), ship) {
+                self.comets.push(comet);
+                continue;
+            }
+            if waited + 1 == MAX_SPAWN_DELAY_TICKS {
+                log::warn!("Comet spawn at {} is blocked by the ship for {} ticks", comet.shape().pos(), MAX_SPAWN_DELAY_TICKS);
+            }
+            self.delayed_comets.push((comet, waited + 1));
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// the boss has its bar in the hud, the parts around it get one of their own
+fn push_health_bar(list: &mut DrawList, target: &impl Destroyable, shape: &Shape) {
+    let Some(bar) = target.health_bar(shape.width()) else {
+        return;
+    };
+    let pos = HealthBar::pos_above(shape);
+    list.push(DrawItem::new(DrawLayer::Overlay, DrawKind::HealthBar, pos, bar.size()));
+    list.push(DrawItem::new(DrawLayer::Overlay, DrawKind::HealthBarFill, pos, bar.filled_size()));
+}
+
+// the ghost trails the fill after a hit, the segments are split at the stage thresholds
+fn push_boss_health_bar(list: &mut DrawList, view: &BossHealthView) {
+    let screen = screen_rect();
+    let pos = Vec2i { x: screen.top_left.x + BOSS_BAR_MARGIN, y: screen.top_left.y + BOSS_BAR_MARGIN };
+    let width = screen.width() - BOSS_BAR_MARGIN * 2;
+    let bar_width = |percent: f32| (width as f32 * percent).round() as i32;
+
+    list.push(DrawItem::new(DrawLayer::Overlay, DrawKind::BossHealthBar, pos, Vec2i { x: width, y: BOSS_BAR_HEIGHT })
+        .with_frame(view.segments_remaining() as u32));
+    list.push(DrawItem::new(DrawLayer::Overlay, DrawKind::BossHealthGhost, pos,
+        Vec2i { x: bar_width(view.ghost_percent()), y: BOSS_BAR_HEIGHT }));
+    let tint = if view.invulnerable() { Tint::Invulnerable } else { Tint::None };
+    list.push(DrawItem::new(DrawLayer::Overlay, DrawKind::BossHealthFill, pos,
+        Vec2i { x: bar_width(view.percent()), y: BOSS_BAR_HEIGHT }).with_tint(tint, 1.0));
+    for edge in view.segment_edges() {
+        let edge_pos = Vec2i { x: pos.x + bar_width(*edge), y: pos.y };
+        list.push(DrawItem::new(DrawLayer::Overlay, DrawKind::BossHealthSegment, edge_pos, Vec2i { x: 1, y: BOSS_BAR_HEIGHT }));
+    }
+    if let Some(percent) = view.secondary_percent() {
+        let part_pos = Vec2i { x: pos.x, y: pos.y + BOSS_BAR_HEIGHT + 1 };
+        list.push(DrawItem::new(DrawLayer::Overlay, DrawKind::BossPartHealth, part_pos,
+            Vec2i { x: bar_width(percent), y: BOSS_BAR_HEIGHT / 2 }));
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    use crate::achievements::AchievementId;
+    use crate::effects::kill_cam::KILL_CAM_TIME_SCALE;
+    use crate::entities::comet::CometKind;
+    use crate::globals::{seed_rng, set_screen_size};
+    use crate::input::{Action, Axis, Bindings, SourceInput};
+    use crate::input::test_support::ScriptedSource;
+    use crate::replay::{Replay, ReplayHeader};
+    use crate::spawners::spawners_impl::create_level_spawner;
+    use crate::timing::duration_to_ticks;
+    use crate::loadout::LoadoutSlot;
+    use crate::ui::loadout_menu::LoadoutRow;
+    use crate::ui::pause_menu::MenuPage;
+
+    const MAX_FIGHT_TICKS: u32 = 10_000;
+
+    // boss-only easy level, the ship can't die and fires on its own
+    fn scripted_fight() -> GameLevel {
+        scripted_fight_at(1)
+    }
+
+    fn scripted_fight_at(level: i32) -> GameLevel {
+        set_screen_size(Vec2i { x: 300, y: 300 });
+        seed_rng(163);
+        let mut game = GameLevel::new(Rc::new(InputMgr::new(Bindings::new())), create_level_spawner(level));
+        // at the bottom, right under the middle of the boss path
+        game.ship.shape_mut().set_pos(Vec2i { x: 100, y: 190 });
+        game.set_run_info(level, None);
+        game.set_persistence(false);
+        game.set_boss_only(true);
+        game.set_god_mode(true);
+        game.set_auto_fire(true, Duration::from_millis(100));
+        game
+    }
+
+    // the fight milestones, shots and hits are left out as they repeat all the time
+    fn milestone(kind: SoundKind) -> bool {
+        matches!(kind,
+            SoundKind::BossStageChanged
+            | SoundKind::BossStunned
+            | SoundKind::Explosion { .. }
+            | SoundKind::ChangeTrack { .. })
+    }
+
+    #[test]
+    fn scripted_fight_sound_sequence() {
+        let mut game = scripted_fight();
+        let mut milestones = vec![];
+        let mut boss_shots = 0;
+        let mut boss_hits = 0;
+        let mut critical_numbers = 0;
+        for _ in 0..MAX_FIGHT_TICKS {
+            game.update();
+            for event in game.sound_events() {
+                match event.kind {
+                    SoundKind::ShotFired { owner: ShotOwner::Boss, count } => boss_shots += count,
+                    SoundKind::Hit { target_kind: TargetKind::Boss } => boss_hits += 1,
+                    // the stunning hit is shown as a critical one
+                    SoundKind::BossStunned => {
+                        critical_numbers = game.draw_list().iter_kind(DrawKind::CriticalDamageNumber).count();
+                        milestones.push(event.kind);
+                    }
+                    kind if milestone(kind) => milestones.push(kind),
+                    _ => (),
+                }
+            }
+            if milestones.last() == Some(&SoundKind::ChangeTrack { track: MusicTrack::VictoryFanfare }) {
+                break;
+            }
+        }
+
+        assert!(boss_shots > 0);
+        assert!(boss_hits > 0);
+        assert_eq!(critical_numbers, 1);
+        assert_eq!(milestones, vec![
+            SoundKind::BossStageChanged,
+            SoundKind::BossStageChanged,
+            SoundKind::BossStunned,
+            SoundKind::Explosion { size: ExplosionSize::Large },
+            SoundKind::ChangeTrack { track: MusicTrack::VictoryFanfare },
+        ]);
+    }
+
+    #[test]
+    fn level_summary_available_without_a_window() {
+        let mut game = scripted_fight();
+        for _ in 0..MAX_FIGHT_TICKS {
+            game.update();
+            if game.level_summary().is_some() {
+                break;
+            }
+        }
+        let summary = game.level_summary().expect("level never cleared");
+        assert!(summary.score > 0);
+        assert_eq!(summary.damage_taken, 0);
+    }
+
+    // the straight shots of the scripted ship can't reach the barriers of the final boss
+    fn final_fight_until(game: &mut GameLevel, done: impl Fn(&GameLevel) -> bool) {
+        for _ in 0..MAX_FIGHT_TICKS {
+            game.barriers.clear();
+            game.update();
+            if done(game) {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn victory_lap_pickups_count_in_summary() {
+        let mut game = scripted_fight_at(FINAL_BOSS_LEVEL);
+        final_fight_until(&mut game, |game| game.victory_lap.is_some());
+        assert!(game.victory_lap.is_some(), "victory lap never started");
+        assert!(game.level_summary().is_none());
+
+        let score_at_start = ScoreBreakdown::from_stats(&game.stats).total();
+        let collected_at_start = game.stats.pickups_collected;
+        let center = game.ship.shape().center();
+        game.pickups.extend((0..3).map(|_| ScorePickup::new(center)));
+
+        let lap_ticks = duration_to_ticks(VICTORY_LAP_DURATION);
+        for _ in 0..lap_ticks + 1 {
+            game.update();
+        }
+        let summary = game.level_summary().expect("no summary after the lap");
+        assert_eq!(game.stats.pickups_collected, collected_at_start + 3);
+        assert_eq!(summary.score, ScoreBreakdown::from_stats(&game.stats).total());
+        assert!(summary.score > score_at_start);
+    }
+
+    #[test]
+    fn dead_ship_skips_victory_lap() {
+        let mut game = scripted_fight_at(FINAL_BOSS_LEVEL);
+        final_fight_until(&mut game, |game| game.boss_defeated);
+        assert!(game.boss_defeated);
+        *game.ship.hp_mut() = 0;
+
+        for _ in 0..MAX_FIGHT_TICKS {
+            game.update();
+            assert!(game.victory_lap.is_none());
+            if game.level_summary().is_some() {
+                break;
+            }
+        }
+        assert!(game.level_summary().is_some());
+    }
+
+    fn final_fight_with_arm() -> GameLevel {
+        let mut game = scripted_fight_at(FINAL_BOSS_LEVEL);
+        final_fight_until(&mut game, |game| game.weapon_arm.is_some());
+        assert!(game.weapon_arm.is_some(), "arm never detached");
+        game.update();
+        let health = game.hud_state().boss_health.as_ref().unwrap();
+        assert!(health.secondary_percent().is_some());
+        assert_eq!(game.draw_list().iter_kind(DrawKind::BossPartHealth).count(), 1);
+        game
+    }
+
+    #[test]
+    fn arm_killed_before_the_boss() {
+        let mut game = final_fight_with_arm();
+        *game.weapon_arm.as_mut().unwrap().hp_mut() = 0;
+        assert!(game.shield_bubble.is_none());
+        game.update();
+        assert!(game.weapon_arm.is_none());
+        assert!(game.shield_bubble.is_some());
+        assert!(game.boss.as_ref().is_some_and(|boss| boss.alive()));
+        assert!(game.hud_state().boss_health.as_ref().unwrap().secondary_percent().is_none());
+        assert_eq!(game.draw_list().iter_kind(DrawKind::BossPartHealth).count(), 0);
+
+        final_fight_until(&mut game, |game| game.boss_defeated);
+        assert!(game.boss_defeated);
+        assert!(game.weapon_arm.is_none());
+    }
+
+    #[test]
+    fn boss_killed_before_the_arm() {
+        let mut game = final_fight_with_arm();
+        *game.boss.as_mut().unwrap().hp_mut() = 0;
+        game.update();
+        assert!(game.boss_defeated);
+        // the arm goes down with the boss and never fires again
+        assert!(game.weapon_arm.is_none());
+        for _ in 0..120 {
+            game.update();
+            assert!(game.weapon_arm.is_none());
+            assert!(game.sound_events().iter().all(|event| !matches!(event.kind, SoundKind::ShotFired { owner: ShotOwner::Boss, .. })));
+        }
+    }
+
+    #[test]
+    fn last_stage_kill_grants_piercing_shots() {
+        let mut game = scripted_fight();
+        // slower fire, so the boss isn't stunned to death before its last stage
+        game.set_auto_fire(true, Duration::from_millis(300));
+        for _ in 0..MAX_FIGHT_TICKS {
+            game.update();
+            if game.boss.as_ref().is_some_and(|boss| boss.in_last_stage()) {
+                break;
+            }
+        }
+        assert!(game.boss.as_ref().is_some_and(|boss| boss.in_last_stage()));
+        assert!(game.piercing_shots.is_none());
+
+        *game.boss.as_mut().unwrap().hp_mut() = 0;
+        game.update();
+        assert!(game.boss_defeated);
+        assert!(game.piercing_shots.is_some_and(|piercing_shots| piercing_shots.active()));
+    }
+
+    #[test]
+    fn piercing_shots_are_drawn_apart() {
+        let mut game = scripted_fight();
+        for _ in 0..30 {
+            game.update();
+        }
+        assert!(game.draw_list().iter_kind(DrawKind::PlayerShot).all(|item| item.frame == 0));
+        game.activate_piercing_shots();
+        for _ in 0..30 {
+            game.update();
+        }
+        assert!(game.draw_list().iter_kind(DrawKind::PlayerShot).any(|item| item.frame == 1));
+    }
+
+    #[test]
+    fn missiles_turn_toward_the_boss() {
+        let mut game = scripted_fight();
+        final_fight_until(&mut game, |game| game.homing_target(Vec2i::zero()).is_some());
+        let boss = game.homing_target(Vec2i::zero()).unwrap().pos;
+        let shot = || Shot::new(Shape::new(boss + Vec2i::new(-100, 100), 4), 10, 0, 1);
+
+        game.projectiles.player_shots_mut().push(shot());
+        game.steer_missiles();
+        assert_eq!(game.projectiles.player_shots().last().unwrap().angle(), 0);
+
+        game.set_replay_loadout(Loadout { secondary: SecondaryWeapon::Missiles, ..Loadout::default() });
+        game.projectiles.player_shots_mut().push(shot());
+        game.steer_missiles();
+        let angle = game.projectiles.player_shots().last().unwrap().angle();
+        assert!(angle > 0 && angle < 45, "{}", angle);
+    }
+
+    #[test]
+    fn free_flight_keeps_a_few_background_comets_alive() {
+        let mut game = scripted_fight_at(3);
+        let mut most_alive = 0;
+        for _ in 0..duration_to_ticks(Duration::from_secs(30)) {
+            game.update();
+            most_alive = most_alive.max(game.comets.iter().filter(|comet| comet.owner().is_some()).count());
+        }
+        assert!(most_alive > 0);
+        assert!(most_alive <= 4, "{}", most_alive);
+    }
+
+    #[test]
+    fn free_flight_has_a_crosswind() {
+        let mut game = scripted_fight_at(3);
+        game.update();
+        assert_eq!(game.draw_list().iter_kind(DrawKind::ForceZone).count(), 1);
+        let middle = Vec2i::new(screen_size().x / 2, screen_size().y * 5 / 12);
+        assert!(game.force_zones.drift_at(middle).x > 0);
+    }
+
+    #[test]
+    fn dynamic_difficulty_shows_in_the_hud() {
+        let mut game = scripted_fight();
+        game.update();
+        assert_eq!(game.hud_state().adaptive_difficulty, None);
+        game.set_difficulty(Difficulty::Dynamic);
+        game.update();
+        assert_eq!(game.hud_state().adaptive_difficulty, game.adaptive_difficulty_multiplier());
+        assert!(game.hud_state().adaptive_difficulty.is_some());
+    }
+
+    #[test]
+    fn earlier_stage_kill_grants_nothing() {
+        let mut game = scripted_fight();
+        final_fight_until(&mut game, |game| game.boss_defeated);
+        assert!(game.boss_defeated);
+        assert!(game.piercing_shots.is_none());
+    }
+
+    #[test]
+    fn new_level_starts_with_everything_locked() {
+        let game = scripted_fight();
+        assert!(!game.persistence);
+        assert!(!game.achievements.unlocked(AchievementId::Sharpshooter));
+    }
+
+    #[test]
+    fn injected_achievements_unlock_loadout_options() {
+        let mut game = scripted_fight();
+        let config = LoadoutConfig { primary: "spread".to_string(), ..LoadoutConfig::default() };
+        game.set_loadout(&config);
+        assert_eq!(game.loadout.primary, PrimaryWeapon::Straight);
+
+        let mut achievements = AchievementSystem::default();
+        achievements.check(&GameEvent::ComboOf50);
+        game.achievements = achievements;
+        game.set_loadout(&config);
+        assert_eq!(game.loadout.primary, PrimaryWeapon::Spread);
+    }
+
+    #[test]
+    fn boss_rush_has_no_objectives() {
+        let mut game = scripted_fight();
+        assert!(game.objectives.views().is_empty());
+        game.set_boss_only(false);
+        assert!(!game.objectives.views().is_empty());
+    }
+
+    #[test]
+    fn music_intensity_follows_the_fight_smoothly() {
+        let mut game = scripted_fight();
+        // hold fire so the boss stays in its stage long enough to reach the plateau
+        game.set_auto_fire(false, Duration::from_millis(100));
+        for _ in 0..20 * 60 {
+            let before = game.music_intensity.value();
+            game.update();
+            let after = game.music_intensity.value();
+            assert!((after - before).abs() <= 0.1 / 60.0 + 1e-6, "{before} -> {after}");
+        }
+        let boss = game.boss.as_ref().unwrap();
+        assert!(!boss.in_intro());
+        let plateau = boss.intensity_hint();
+        assert!((game.music_intensity.value() - plateau).abs() < 1e-3, "{} != {plateau}", game.music_intensity.value());
+    }
+
+    #[test]
+    fn one_kill_cam_beat_per_boss() {
+        let mut game = scripted_fight();
+        let mut beats = 0;
+        let mut was_running = false;
+        for _ in 0..MAX_FIGHT_TICKS {
+            game.update();
+            let running = game.kill_cam.is_some();
+            if running && !was_running {
+                beats += 1;
+                assert!(game.ship.invulnerable());
+            }
+            was_running = running;
+            if game.level_summary().is_some() && !running {
+                break;
+            }
+        }
+        assert_eq!(beats, 1);
+    }
+
+    #[test]
+    fn pause_freezes_kill_cam() {
+        let mut game = scripted_fight();
+        game.kill_cam = Some(KillCam::new());
+        game.paused = true;
+        for _ in 0..duration_to_ticks(KillCam::total_duration()) * 2 {
+            game.update();
+        }
+        assert!(game.kill_cam.is_some());
+        assert_eq!(game.kill_cam.as_ref().unwrap().time_scale(), 1.0);
+
+        game.paused = false;
+        for _ in 0..duration_to_ticks(Duration::from_millis(300)) {
+            game.update();
+        }
+        assert_eq!(game.kill_cam.as_ref().unwrap().time_scale(), KILL_CAM_TIME_SCALE);
+    }
+
+    #[test]
+    fn fixed_spawn_waits_for_the_ship_to_move() {
+        let mut game = scripted_fight();
+        let ship_pos = game.ship.shape().pos();
+        game.delayed_comets.push((Comet::new(CometKind::Simple, ship_pos, 180, 1), 0));
+
+        for _ in 0..MAX_SPAWN_DELAY_TICKS * 2 {
+            game.admit_delayed_comets();
+        }
+        assert!(game.comets.is_empty());
+        assert_eq!(game.delayed_comets[0].1, MAX_SPAWN_DELAY_TICKS * 2);
+
+        game.ship.shape_mut().set_pos(ship_pos + Vec2i::new(0, 200));
+        game.admit_delayed_comets();
+        assert_eq!(game.comets.len(), 1);
+        assert!(game.delayed_comets.is_empty());
+    }
+
+    #[test]
+    fn shot_comet_sounds_a_hit() {
+        let mut game = scripted_fight();
+        let comet = Comet::new(CometKind::Simple, Vec2i::new(40, 70), 180, 1);
+        let shot = Shot::new(Shape::new(comet.shape().center(), 4), 1, 0, 1);
+        game.comets.push(comet);
+        game.add_single_player_shot(shot);
+        game.handle_intersections();
+        assert!(game.sound_events().iter().any(|event| event.kind == SoundKind::Hit { target_kind: TargetKind::Comet }));
+    }
+
+    #[test]
+    fn shards_next_to_ship_fly_away_from_it() {
+        seed_rng(197);
+        let ship = Shape::new(Vec2i::new(0, 100), 40);
+        for _ in 0..20 {
+            let mut comets = vec![Comet::new(CometKind::Double, Vec2i::new(40, 70), 180, 1)];
+            let shot = Shot::new(Shape::new(comets[0].shape().center(), 4), 1, 0, 1);
+            GameLevel::destroy_comets_by_shot(&mut comets, &shot, &ship).unwrap();
+
+            assert_eq!(comets.len(), 2);
+            for shard in &comets {
+                // the ship is to the left, so no shard heads left
+                assert!((shard.angle() as f32).to_radians().sin() > -1e-4, "shard heads to the ship at {}", shard.angle());
+            }
+        }
+    }
+
+    #[test]
+    fn bomb_slow_mo_halves_boss_shots_for_its_duration() {
+        let mut game = scripted_fight();
+        game.slow_mo_effects.push(SlowMoEffect::new(SLOW_MO_DURATION));
+        let shots = (0..6).map(|x| Shot::new(Shape::new(Vec2i::new(x * 20, 0), 4), 4, 180, 10)).collect();
+        game.projectiles.add_boss_shots(shots);
+
+        for _ in 0..duration_to_ticks(SLOW_MO_DURATION) {
+            game.update_slow_mo();
+            assert!(game.projectiles.boss_shots().iter().all(|shot| shot.velocity().y == 2.0));
+            advance_game_clock();
+        }
+
+        game.update_slow_mo();
+        assert!(game.projectiles.boss_shots().iter().all(|shot| shot.velocity().y == 4.0));
+    }
+
+    #[test]
+    fn low_hp_warning_resets_on_restart() {
+        let mut game = scripted_fight();
+        *game.ship.hp_mut() = 1;
+        game.update_low_hp_warning();
+        assert!(game.low_hp_warning.active());
+
+        game.restart();
+        assert_eq!(game.ship.hp_percent(), 1.0);
+        game.update();
+        assert!(!game.low_hp_warning.active());
+    }
+
+    // boss only levels spawn the boss on the first tick, the events of that tick must reach drain_events
+    #[test]
+    fn collision_events_kept_on_boss_spawn_tick() {
+        let mut game = scripted_fight();
+        // grazing the ship right above it, each one leaves an event
+        let shots = (0..50).map(|x| Shot::new(Shape::new(Vec2i::new(123 + x, 176), 4), 1, 180, 1)).collect();
+        game.projectiles.add_boss_shots(shots);
+        game.update();
+        assert!(game.boss.is_some());
+    }
+
+    // boss shots next to the ship, inside the graze margin but clear of the hurtbox
+    fn add_grazing_shots(game: &mut GameLevel, count: usize, damage: i32) {
+        let center = game.ship.shape().center();
+        let pos = Vec2i::new(center.x - game.ship.shape().width() / 2 - 10, center.y);
+        let shots = (0..count).map(|_| Shot::new(Shape::new(pos, 4), 1, 180, damage)).collect();
+        game.projectiles.add_boss_shots(shots);
+    }
+
+    fn reflector_fight() -> GameLevel {
+        let mut game = scripted_fight();
+        game.set_god_mode(false);
+        game.set_auto_fire(false, Duration::from_millis(100));
+        game.update();
+        game.projectiles.boss_shots_mut().clear();
+        game.activate_reflector();
+        game
+    }
+
+    #[test]
+    fn reflector_caps_reflections() {
+        let mut game = reflector_fight();
+        add_grazing_shots(&mut game, REFLECTOR_MAX_REFLECTIONS as usize + 2, 10);
+        game.update();
+        assert_eq!(game.projectiles.player_shots().len(), REFLECTOR_MAX_REFLECTIONS as usize);
+        assert!(game.projectiles.player_shots().iter().all(|shot| shot.damage() == 5));
+        // the rest were grazed as usual
+        assert_eq!(game.projectiles.boss_shots().len(), 2);
+        assert!(game.hud_state().reflector_remaining.is_none());
+    }
+
+    #[test]
+    fn reflected_shot_no_longer_hurts_the_ship() {
+        let mut game = reflector_fight();
+        let hp = game.ship.hp();
+        add_grazing_shots(&mut game, 1, 10);
+        game.update();
+        assert_eq!(game.projectiles.player_shots().len(), 1);
+
+        // parked right on the ship, it's a player shot now
+        let center = game.ship.shape().center();
+        game.projectiles.player_shots_mut()[0].shape_mut().set_pos(center);
+        game.update();
+        assert_eq!(game.ship.hp(), hp);
+    }
+
+    #[test]
+    fn shots_grazing_after_expiry_are_not_reflected() {
+        let mut game = reflector_fight();
+        game.reflector = Some(ReflectorPowerUp::new(Duration::from_millis(100), REFLECTOR_MAX_REFLECTIONS));
+        add_grazing_shots(&mut game, 1, 10);
+        game.update();
+        assert_eq!(game.projectiles.player_shots().len(), 1);
+        assert!(game.hud_state().reflector_remaining.is_some());
+
+        for _ in 0..duration_to_ticks(Duration::from_millis(100)) {
+            game.update();
+        }
+        assert!(game.hud_state().reflector_remaining.is_none());
+        game.projectiles.player_shots_mut().clear();
+        add_grazing_shots(&mut game, 1, 10);
+        game.update();
+        assert!(game.projectiles.player_shots().is_empty());
+        assert_eq!(game.projectiles.boss_shots().len(), 1);
+    }
+
+    #[test]
+    fn barriers_get_a_health_bar_above_them() {
+        let mut game = scripted_fight();
+        game.barriers.push(KineticBarrier::new(Vec2i { x: 150, y: 60 }, 30, 0.0, 0.05, crate::entities::boss::kinetic_barrier::KINETIC_BARRIER_MAX_HP));
+        game.update();
+        let shape = game.barriers[0].shape().clone();
+        let bars: Vec<_> = game.draw_list().iter_kind(DrawKind::HealthBar).collect();
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].pos, HealthBar::pos_above(&shape));
+        assert_eq!(bars[0].size.x, shape.width());
+        assert_eq!(game.draw_list().iter_kind(DrawKind::HealthBarFill).count(), 1);
+    }
+
+    #[test]
+    fn boss_bar_shows_the_hit_behind_the_ghost() {
+        let mut game = scripted_fight();
+        while game.boss.is_none() {
+            game.update();
+        }
+        let boss = game.boss.as_mut().unwrap();
+        *boss.hp_mut() = boss.max_hp() / 4;
+        game.update();
+
+        let list = game.draw_list();
+        let width = |kind| list.iter_kind(kind).next().unwrap().size.x;
+        assert!(width(DrawKind::BossHealthFill) < width(DrawKind::BossHealthGhost));
+        assert_eq!(width(DrawKind::BossHealthGhost), width(DrawKind::BossHealthBar));
+        let view = game.hud_state().boss_health.as_ref().unwrap();
+        assert_eq!(list.iter_kind(DrawKind::BossHealthSegment).count(), view.segment_edges().len());
+        assert_eq!(list.iter_kind(DrawKind::BossHealthBar).next().unwrap().frame, view.segments_remaining() as u32);
+    }
+
+    #[cfg(feature = "debug-skip-wave")]
+    #[test]
+    fn skipped_run_matches_a_played_one_at_the_wave() {
+        // only boss rush clears a wave so far, the first one as the boss comes in
+        let seeded = || {
+            set_screen_size(Vec2i { x: 200, y: 200 });
+            seed_rng(189);
+            let mut game = GameLevel::new(Rc::new(InputMgr::new(Bindings::new())), create_level_spawner(1));
+            game.set_persistence(false);
+            game.set_run_info(1, Some(189));
+            game.set_boss_only(true);
+            game
+        };
+        // the rolls after the wave have to match too, the games share the clock so they run one after another
+        let after_wave = |game: &mut GameLevel| -> Vec<_> {
+            (0..300).map(|_| {
+                game.update();
+                tick_snapshot(game)
+            }).collect()
+        };
+
+        let mut played = seeded();
+        while played.waves_cleared < 1 {
+            played.update();
+        }
+        let played_ticks = after_wave(&mut played);
+
+        let script = Rc::new(Cell::new(SourceInput::default()));
+        let input_mgr = Rc::new(InputMgr::from_sources(Bindings::new(), vec![Box::new(ScriptedSource(Rc::clone(&script)))]));
+        let mut skipped = seeded();
+        skipped.ship_controller = PlayerShipController::new(Rc::clone(&input_mgr));
+        for tick in 0..300 {
+            script.set(scripted_input(tick));
+            input_mgr.update();
+            skipped.update();
+        }
+        script.set(SourceInput::default());
+        input_mgr.update();
+        assert!(skipped.skip_to_wave(1));
+        input_mgr.update();
+        let skipped_ticks = after_wave(&mut skipped);
+
+        assert!(played_ticks.iter().flatten().any(|(kind, _, _)| *kind == DrawKind::BossShot));
+        assert_eq!(skipped_ticks, played_ticks);
+        assert_eq!(skipped.stats.shots_fired, played.stats.shots_fired);
+        assert_eq!(skipped.ship.hp(), played.ship.hp());
+    }
+
+    #[test]
+    fn tagged_items_follow_the_palette() {
+        let mut game = scripted_fight();
+        game.set_palette(PalettePreset::Deuteranopia);
+        while game.draw_list().iter_kind(DrawKind::BossShot).next().is_none() {
+            game.update();
+        }
+        let styles: Vec<_> = game.draw_list().iter_kind(DrawKind::BossShot).map(|item| item.style).collect();
+        assert!(!styles.is_empty());
+        assert!(styles.iter().all(|style| *style == Some(PalettePreset::Deuteranopia.style(VisualTag::BossShot))));
+        assert!(game.draw_list().iter_kind(DrawKind::Ship).all(|item| item.style.is_none()));
+    }
+
+    // what a front-end sees of a tick, the cosmetic rng isn't seeded so stars and particles are left out
+    fn tick_snapshot(game: &GameLevel) -> Vec<(DrawKind, Vec2i, Vec2i)> {
+        game.draw_list().items().iter()
+            .filter(|item| !matches!(item.kind, DrawKind::Star | DrawKind::Particle | DrawKind::AbsorbParticle))
+            .map(|item| (item.kind, item.pos, item.size))
+            .collect()
+    }
+
+    // the shake is rolled by the cosmetic rng too
+    fn steady_fight() -> GameLevel {
+        let mut game = scripted_fight();
+        game.screen_shake.set_enabled(false);
+        game
+    }
+
+    fn phased_fight(inject: impl Fn(&mut GameLevel)) -> Vec<Vec<(DrawKind, Vec2i, Vec2i)>> {
+        let mut game = steady_fight();
+        (0..600).map(|_| {
+            if game.apply_input() {
+                game.resolve_collisions();
+                inject(&mut game);
+                game.step_entities();
+                game.step_spawners();
+                game.drain_events();
+                game.build_draw_list();
+            }
+            tick_snapshot(&game)
+        }).collect()
+    }
+
+    #[test]
+    fn phases_match_the_monolithic_update() {
+        let mut game = steady_fight();
+        let monolithic: Vec<_> = (0..600).map(|_| {
+            game.update();
+            tick_snapshot(&game)
+        }).collect();
+
+        assert!(monolithic.iter().flatten().any(|(kind, _, _)| *kind == DrawKind::BossShot));
+        assert_eq!(phased_fight(|_| ()), monolithic);
+        // an embedder step reading the world in the middle doesn't change anything
+        let injected = phased_fight(|game| {
+            assert!(game.ship.alive());
+            let _ = game.projectiles.boss_shots().len();
+        });
+        assert_eq!(injected, monolithic);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "called after")]
+    fn phases_out_of_order_assert() {
+        let mut game = scripted_fight();
+        assert!(game.apply_input());
+        game.step_entities();
+        game.resolve_collisions();
+    }
+
+    // timings are recorded only with the metrics feature
+    #[cfg(feature = "debug-commands")]
+    #[test]
+    fn boss_spawns_with_the_debug_hp() {
+        let mut game = scripted_fight();
+        game.set_boss_hp(7);
+        while game.boss.is_none() {
+            game.update();
+        }
+        assert_eq!(game.boss.as_ref().unwrap().hp(), 7);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn collision_phase_within_budget_at_2000_shots() {
+        const FRAMES: u32 = 10;
+        const BUDGET_PER_FRAME: Duration = Duration::from_millis(20);
+
+        let mut game = scripted_fight();
+        let shot = |x: i32, y: i32, angle: i32| Shot::new(Shape::new(Vec2i::new(x, y), 4), 1, angle, 1);
+        for idx in 0..1000 {
+            game.projectiles.add_player_shot(shot(idx % 200, 100 + idx / 200, 0));
+        }
+        game.projectiles.add_boss_shots((0..1000).map(|idx| shot(idx % 200, 20 + idx / 200, 180)).collect());
+
+        for _ in 0..FRAMES {
+            game.update();
+        }
+        assert!(game.phase_totals().get(Phase::Collision) < BUDGET_PER_FRAME * FRAMES);
+    }
+
+    #[test]
+    fn fire_skips_dialogue_on_first_frame() {
+        set_screen_size(Vec2i { x: 200, y: 200 });
+        let script = Rc::new(Cell::new(SourceInput { fire: true, ..SourceInput::default() }));
+        let input_mgr = Rc::new(InputMgr::from_sources(Bindings::new(), vec![Box::new(ScriptedSource(Rc::clone(&script)))]));
+        let mut game = GameLevel::new(Rc::clone(&input_mgr), create_level_spawner(3));
+        game.set_persistence(false);
+        assert!(game.dialogue.is_some());
+
+        input_mgr.update();
+        game.update();
+        assert!(game.dialogue.is_none());
+        assert!(game.projectiles.player_shots().is_empty());
+    }
+
+    #[test]
+    fn autosave_round_trip_resumes_the_run() {
+        set_screen_size(Vec2i { x: 200, y: 200 });
+        let mut game = GameLevel::new(Rc::new(InputMgr::new(Bindings::new())), create_level_spawner(1));
+        game.set_persistence(false);
+        game.set_difficulty(Difficulty::Nightmare);
+        *game.ship.hp_mut() = 40;
+        game.ship.set_energy(25);
+        game.stats.shots_fired = 30;
+        game.stats.shots_hit = 12;
+        game.stats.bosses_defeated = 1;
+
+        let toml = toml::to_string(&game.make_autosave()).unwrap();
+        let autosave: Autosave = toml::from_str(&toml).unwrap();
+        assert_eq!(autosave.next_level(), 2);
+        assert_eq!(autosave.difficulty, Difficulty::Nightmare);
+
+        let mut resumed = GameLevel::new(Rc::new(InputMgr::new(Bindings::new())), create_level_spawner(autosave.next_level()));
+        resumed.set_persistence(false);
+        resumed.resume_from_autosave(&autosave);
+        assert_eq!(resumed.ship.hp(), 40);
+        assert_eq!(resumed.ship.energy(), 25);
+        assert_eq!(resumed.stats.shots_fired, 30);
+        assert_eq!(resumed.stats.shots_hit, 12);
+        assert_eq!(resumed.stats.bosses_defeated, 1);
+    }
+
+    // weaves, fires in bursts and dashes now and then
+    fn scripted_input(tick: u32) -> SourceInput {
+        SourceInput {
+            axis: crate::input::Axis::new(((tick / 40) % 3) as f32 - 1.0, 0.0),
+            fire: tick % 30 < 20,
+            dash: tick.is_multiple_of(200),
+            ..SourceInput::default()
+        }
+    }
+
+    // ship and boss positions at the end of the run, with what the player managed meanwhile
+    fn replay_run(input_mgr: InputMgr, script: impl Fn(u32), mut record: impl FnMut(SourceInput)) -> (Vec<(DrawKind, Vec2i, Vec2i)>, [u32; 4]) {
+        set_screen_size(Vec2i { x: 200, y: 200 });
+        seed_rng(155);
+        let input_mgr = Rc::new(input_mgr);
+        let mut game = GameLevel::new(Rc::clone(&input_mgr), create_level_spawner(1));
+        game.set_persistence(false);
+        game.set_ship_kind(ShipKind::Interceptor);
+        for tick in 0..900 {
+            script(tick);
+            input_mgr.update();
+            record(input_mgr.last_input());
+            game.update();
+        }
+        let stats = &game.stats;
+        (tick_snapshot(&game), [stats.shots_fired, stats.shots_hit, stats.damage_taken as u32, stats.comets_destroyed])
+    }
+
+    #[test]
+    fn replayed_run_matches_the_recorded_one() {
+        let script = Rc::new(Cell::new(SourceInput::default()));
+        let input_mgr = InputMgr::from_sources(Bindings::new(), vec![Box::new(ScriptedSource(Rc::clone(&script)))]);
+        let mut replay = Replay::new(ReplayHeader::new(155, 1, Difficulty::Normal));
+        let recorded = replay_run(input_mgr, |tick| script.set(scripted_input(tick)), |input| replay.record(input));
+        assert_eq!(replay.ticks(), 900);
+        assert!(recorded.1[0] > 0);
+
+        let input_mgr = InputMgr::from_sources(Bindings::new(), vec![Box::new(replay.source())]);
+        let played = replay_run(input_mgr, |_| (), |_| ());
+        assert_eq!(played, recorded);
+    }
+
+    #[test]
+    fn controls_page_starts_rebinding() {
+        let script = Rc::new(Cell::new(SourceInput::default()));
+        let input_mgr = Rc::new(InputMgr::from_sources(Bindings::new(), vec![Box::new(ScriptedSource(Rc::clone(&script)))]));
+        let mut game = GameLevel::new(Rc::clone(&input_mgr), create_level_spawner(1));
+        game.set_persistence(false);
+        game.paused = true;
+
+        let down = SourceInput { axis: crate::input::Axis::new(0.0, 1.0), ..SourceInput::default() };
+        let up = SourceInput { axis: crate::input::Axis::new(0.0, -1.0), ..SourceInput::default() };
+        let fire = SourceInput { fire: true, ..SourceInput::default() };
+        // settings, then controls at the end of its entries, then the first action
+        for input in [down, down, fire, up, up, fire, down, fire] {
+            for input in [input, SourceInput::default()] {
+                script.set(input);
+                input_mgr.update();
+                game.update();
+            }
+        }
+        assert_eq!(input_mgr.rebinding(), Some(Action::MoveDown));
+    }
+
+    #[test]
+    fn cutscene_backgrounds_hide_the_level() {
+        let mut game = scripted_fight();
+        for _ in 0..50 {
+            game.update();
+        }
+        let frame = |text, background| CutsceneFrame { text, duration: Duration::from_secs(1), background };
+        game.start_cutscene(vec![
+            frame("level", BackgroundConfig::Level),
+            frame("stars", BackgroundConfig::Starfield),
+            frame("black", BackgroundConfig::Black),
+        ]);
+        // items besides the stars, and the stars
+        let mut shown = || {
+            game.update();
+            let list = game.draw_list();
+            let stars = list.iter_kind(DrawKind::Star).count();
+            let items = (list.items().len() - stars, stars);
+            game.cutscene.as_mut().unwrap().next_frame();
+            items
+        };
+        assert!(shown().0 > 0);
+        assert!(matches!(shown(), (0, stars) if stars > 0));
+        assert_eq!(shown(), (0, 0));
+    }
+
+    #[test]
+    fn hit_ship_flashes() {
+        let mut game = scripted_fight();
+        game.set_god_mode(false);
+        game.update();
+        game.ship.hit(1);
+        game.fill_draw_list();
+        assert_eq!(game.draw_list().iter_kind(DrawKind::Ship).next().unwrap().flash, 1.0);
+    }
+
+    #[test]
+    fn dash_leaves_a_ghost_until_it_is_ready_again() {
+        let mut game = scripted_fight();
+        game.update();
+        assert_eq!(game.draw_list().iter_kind(DrawKind::DashGhost).count(), 0);
+        assert!(game.ship.dash(Vec2i::new(1, 0)));
+        game.fill_draw_list();
+        assert_eq!(game.draw_list().iter_kind(DrawKind::DashGhost).count(), 1);
+        while !game.ship.can_dash() {
+            game.update();
+        }
+        assert_eq!(game.draw_list().iter_kind(DrawKind::DashGhost).count(), 0);
+    }
+
+    #[test]
+    fn shake_moves_the_playfield_but_not_the_overlay() {
+        let mut game = scripted_fight();
+        for _ in 0..50 {
+            game.update();
+        }
+        game.screen_shake.reset();
+        game.fill_draw_list();
+        let overlay = |game: &GameLevel| -> Vec<Vec2i> {
+            game.draw_list().items().iter().filter(|item| item.layer == DrawLayer::Overlay).map(|item| item.pos).collect()
+        };
+        let steady = overlay(&game);
+        assert!(!steady.is_empty());
+
+        while game.screen_shake.offset() == Vec2i::zero() {
+            game.screen_shake.add_trauma(1.0);
+            game.screen_shake.update();
+        }
+        game.fill_draw_list();
+        let ship = game.draw_list().iter_kind(DrawKind::Ship).next().unwrap();
+        assert_eq!(ship.pos, game.ship.shape().pos() + game.screen_shake.offset());
+        assert_eq!(overlay(&game), steady);
+    }
+
+    #[test]
+    fn pause_skips_the_cutscene() {
+        let script = Rc::new(Cell::new(SourceInput::default()));
+        let input_mgr = Rc::new(InputMgr::from_sources(Bindings::new(), vec![Box::new(ScriptedSource(Rc::clone(&script)))]));
+        let mut game = GameLevel::new(Rc::clone(&input_mgr), create_level_spawner(FINAL_BOSS_LEVEL));
+        game.set_persistence(false);
+        assert!(game.start_cutscene(game.spawner.cutscene_after_boss()));
+
+        script.set(SourceInput { pause: true, ..SourceInput::default() });
+        input_mgr.update();
+        game.update();
+        assert!(game.cutscene().is_none());
+        assert!(!game.paused);
+    }
+
+    #[test]
+    fn open_pause_menu_is_shown_in_the_hud() {
+        set_screen_size(Vec2i::new(200, 200));
+        let script = Rc::new(Cell::new(SourceInput::default()));
+        let input_mgr = Rc::new(InputMgr::from_sources(Bindings::new(), vec![Box::new(ScriptedSource(Rc::clone(&script)))]));
+        let mut game = GameLevel::new(Rc::clone(&input_mgr), create_level_spawner(1));
+        game.set_persistence(false);
+        let mut step = |input: SourceInput| {
+            script.set(input);
+            input_mgr.update();
+            game.update();
+            game.hud_state().pause_menu
+        };
+
+        assert_eq!(step(SourceInput::default()), None);
+        assert_eq!(step(SourceInput { pause: true, ..SourceInput::default() }), Some((MenuPage::Main, 0)));
+        assert_eq!(step(SourceInput { axis: Axis::new(0.0, 1.0), ..SourceInput::default() }), Some((MenuPage::Main, 1)));
+        step(SourceInput::default());
+        assert_eq!(step(SourceInput { pause: true, ..SourceInput::default() }), None);
+    }
+
+    #[test]
+    fn loadout_is_picked_in_the_pause_menu() {
+        set_screen_size(Vec2i::new(200, 200));
+        let script = Rc::new(Cell::new(SourceInput::default()));
+        let input_mgr = Rc::new(InputMgr::from_sources(Bindings::new(), vec![Box::new(ScriptedSource(Rc::clone(&script)))]));
+        let mut game = GameLevel::new(Rc::clone(&input_mgr), create_level_spawner(1));
+        game.set_persistence(false);
+        let up = SourceInput { axis: Axis::new(0.0, -1.0), ..SourceInput::default() };
+        let down = SourceInput { axis: Axis::new(0.0, 1.0), ..SourceInput::default() };
+        let right = SourceInput { axis: Axis::new(1.0, 0.0), ..SourceInput::default() };
+        let fire = SourceInput { fire: true, ..SourceInput::default() };
+        let pause = SourceInput { pause: true, ..SourceInput::default() };
+        // every input is followed by an idle tick, so the next one is a new press
+        let mut feed = |inputs: &[SourceInput]| {
+            for input in inputs {
+                for input in [*input, SourceInput::default()] {
+                    script.set(input);
+                    input_mgr.update();
+                    game.update();
+                }
+            }
+            (game.hud_state().loadout_menu, game.loadout())
+        };
+
+        let primary = LoadoutRow::Slot(LoadoutSlot::Primary);
+        assert_eq!(feed(&[pause, up, up, fire]), (Some((primary, Loadout::default())), Loadout::default()));
+        let picked = Loadout { passive: Passive::MagnetRadius, ..Loadout::default() };
+        assert_eq!(feed(&[up, up, right, down]), (Some((LoadoutRow::Start, picked)), Loadout::default()));
+        assert_eq!(feed(&[fire]), (None, picked));
+
+        // the pause menu is still on the loadout entry, pause goes back to it without picking anything
+        assert_eq!(feed(&[fire, right]), (Some((primary, picked)), picked));
+        assert_eq!(feed(&[pause]), (None, picked));
+        assert!(game.paused);
+    }
+
+    #[test]
+    fn no_sounds_while_paused() {
+        let mut game = scripted_fight();
+        for _ in 0..200 {
+            game.update();
+        }
+        game.paused = true;
+        for _ in 0..200 {
+            game.update();
+            assert!(game.sound_events().is_empty());
+        }
+    }
 }
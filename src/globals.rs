@@ -1,17 +1,31 @@
+use std::cell::{Cell, RefCell};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
 use crate::math::{Rect, Vec2i};
+use crate::timing::{game_tick, set_game_tick};
+
+// keeps the cosmetic rolls from repeating the gameplay ones of the same seed
+const COSMETIC_SEED_SALT: u64 = 0x5eed_c05e_71c5;
 
-static mut G_SCREEN_SIZE: Vec2i = Vec2i { x: 0, y: 0};
+// the game runs on a single thread, thread locals keep parallel tests from seeing each other's state
+thread_local! {
+    static G_SCREEN_SIZE: Cell<Vec2i> = const { Cell::new(Vec2i { x: 0, y: 0 }) };
+    static G_MIRROR_MODE: Cell<bool> = const { Cell::new(false) };
+    static G_PLAYFIELD_INSET: Cell<i32> = const { Cell::new(0) };
+    static G_DETERMINISTIC: Cell<bool> = const { Cell::new(false) };
+    static G_RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+    // a stream of its own, cosmetic effects are off in headless runs and must not shift the gameplay rolls
+    static G_COSMETIC_RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
 
 pub fn set_screen_size(size: Vec2i) {
-    unsafe {
-        G_SCREEN_SIZE = size;
-    }
+    G_SCREEN_SIZE.with(|screen_size| screen_size.set(size));
 }
 
 pub fn screen_size() -> Vec2i {
-    unsafe {
-        return G_SCREEN_SIZE.clone();
-    }
+    G_SCREEN_SIZE.with(|screen_size| screen_size.get())
 }
 
 pub fn screen_rect() -> Rect {  // TODO change this to a common coords
@@ -20,3 +34,102 @@ pub fn screen_rect() -> Rect {  // TODO change this to a common coords
         bottom_right: screen_size()
     }
 }
+
+// arena walls narrow the playfield by the inset from both sides
+pub fn set_playfield_inset(inset: i32) {
+    G_PLAYFIELD_INSET.with(|playfield_inset| playfield_inset.set(inset.max(0)));
+}
+
+// the area the ship, the boss and the shots are kept in
+pub fn playfield_rect() -> Rect {
+    let inset = G_PLAYFIELD_INSET.with(|playfield_inset| playfield_inset.get());
+    let mut rect = screen_rect();
+    rect.top_left.x += inset;
+    rect.bottom_right.x -= inset;
+    rect
+}
+
+// upper part of the playfield the boss and its parts stay in
+pub fn boss_arena_rect() -> Rect {
+    let playfield = playfield_rect();
+    playfield.split_horizontal(playfield.top_left.y + playfield.height() * 2 / 5).0
+}
+
+pub fn set_mirror_mode(enabled: bool) {
+    G_MIRROR_MODE.with(|mirror_mode| mirror_mode.set(enabled));
+}
+
+pub fn mirror_mode() -> bool {
+    G_MIRROR_MODE.with(|mirror_mode| mirror_mode.get())
+}
+
+// patterns are authored for the normal mode, these convert them to the world space so mirror mode
+// reflects them across the vertical center line of the screen
+pub fn mirror_x(x: i32, width: i32) -> i32 {
+    if !mirror_mode() {
+        return x;
+    }
+    screen_size().x - x - width
+}
+
+pub fn mirror_angle(angle: i32) -> i32 {
+    if !mirror_mode() {
+        return angle;
+    }
+    (360 - angle).rem_euclid(360)
+}
+
+// simulation movement uses fixed-point math, so a run plays out the same on every machine
+pub fn set_deterministic(enabled: bool) {
+    G_DETERMINISTIC.with(|deterministic| deterministic.set(enabled));
+}
+
+pub fn deterministic() -> bool {
+    G_DETERMINISTIC.with(|deterministic| deterministic.get())
+}
+
+pub fn seed_rng(seed: u64) {
+    G_RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+pub fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    G_RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+// derived from the run seed, so a seeded run looks the same every time too
+pub fn seed_cosmetic_rng(seed: u64) {
+    G_COSMETIC_RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed ^ COSMETIC_SEED_SALT));
+}
+
+// for particles, screen shake, the starfield and anything else which doesn't affect the gameplay
+pub fn with_cosmetic_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    G_COSMETIC_RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+// what a level changes in the globals besides the screen, a demo played over a paused run puts it back
+// afterwards, so the run continues with the same timers and rolls
+pub struct GlobalsSnapshot {
+    game_tick: i64,
+    rng: StdRng,
+    mirror_mode: bool,
+    playfield_inset: i32,
+    deterministic: bool,
+}
+
+pub fn snapshot_globals() -> GlobalsSnapshot {
+    GlobalsSnapshot {
+        game_tick: game_tick(),
+        rng: with_rng(|rng| rng.clone()),
+        mirror_mode: mirror_mode(),
+        playfield_inset: G_PLAYFIELD_INSET.with(|playfield_inset| playfield_inset.get()),
+        deterministic: deterministic(),
+    }
+}
+
+pub fn restore_globals(snapshot: GlobalsSnapshot) {
+    set_game_tick(snapshot.game_tick);
+    G_RNG.with(|rng| *rng.borrow_mut() = snapshot.rng);
+    set_mirror_mode(snapshot.mirror_mode);
+    set_playfield_inset(snapshot.playfield_inset);
+    set_deterministic(snapshot.deterministic);
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{self, StorageError};
+
+//-----------------------------------------------------------------------------
+
+const HIGH_SCORES_FILE: &str = "high_scores.toml";
+const MAX_ENTRIES: usize = 10;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub score: u32,
+    pub level: i32,
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub mirror: bool,
+    #[serde(default)]
+    pub adaptive: bool,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct HighScores {
+    entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        match storage::load::<HighScores>(HIGH_SCORES_FILE) {
+            Ok(scores) => scores,
+            Err(StorageError::NotFound(_)) => HighScores::default(),
+            Err(err) => {
+                log::warn!("Failed to load high scores: {}", err);
+                HighScores::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), StorageError> {
+        storage::save(HIGH_SCORES_FILE, self)
+    }
+
+    // adaptive difficulty runs have their own table
+    pub fn table(&self, adaptive: bool) -> impl Iterator<Item = &HighScoreEntry> {
+        self.entries.iter().filter(move |entry| entry.adaptive == adaptive)
+    }
+
+    // 1-based place the score would take, ties go below the existing entries
+    pub fn placement(&self, score: u32, adaptive: bool) -> Option<usize> {
+        let place = self.table(adaptive).take_while(|entry| entry.score >= score).count();
+        if place < MAX_ENTRIES && score > 0 {
+            Some(place + 1)
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        let adaptive = entry.adaptive;
+        let place = self.placement(entry.score, adaptive)?;
+        let idx = self.entries.iter().take_while(|existing| existing.score >= entry.score).count();
+        self.entries.insert(idx, entry);
+
+        let mut kept = 0;
+        self.entries.retain(|entry| {
+            if entry.adaptive != adaptive {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_ENTRIES
+        });
+        Some(place)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32, adaptive: bool) -> HighScoreEntry {
+        HighScoreEntry { score, level: 1, seed: None, mirror: false, adaptive }
+    }
+
+    fn full_table() -> HighScores {
+        let mut scores = HighScores::default();
+        for score in 1..=MAX_ENTRIES as u32 {
+            scores.insert(entry(score * 100, false));
+        }
+        scores
+    }
+
+    #[test]
+    fn first_score_is_first_place() {
+        assert_eq!(HighScores::default().placement(10, false), Some(1));
+        assert_eq!(HighScores::default().placement(0, false), None);
+    }
+
+    #[test]
+    fn ties_go_below_existing_entries() {
+        let scores = full_table();
+        assert_eq!(scores.placement(1000, false), Some(2));
+        assert_eq!(scores.placement(1001, false), Some(1));
+    }
+
+    #[test]
+    fn too_low_score_doesnt_place() {
+        let scores = full_table();
+        assert_eq!(scores.placement(100, false), None);
+        assert_eq!(scores.placement(150, false), Some(MAX_ENTRIES));
+    }
+
+    #[test]
+    fn insert_keeps_table_size() {
+        let mut scores = full_table();
+        assert_eq!(scores.insert(entry(550, false)), Some(6));
+        assert_eq!(scores.table(false).count(), MAX_ENTRIES);
+        assert_eq!(scores.table(false).last().unwrap().score, 200);
+    }
+
+    #[test]
+    fn adaptive_runs_have_their_own_table() {
+        let mut scores = full_table();
+        assert_eq!(scores.insert(entry(50, true)), Some(1));
+        assert_eq!(scores.table(true).count(), 1);
+        assert_eq!(scores.table(false).count(), MAX_ENTRIES);
+    }
+}
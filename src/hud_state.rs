@@ -0,0 +1,79 @@
+use crate::entities::boss::health_view::BossHealthView;
+use crate::loadout::Loadout;
+use crate::lock_on::LockTargetKind;
+use crate::objectives::ObjectiveView;
+use crate::ui::loadout_menu::LoadoutRow;
+use crate::ui::pause_menu::MenuPage;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+pub struct HudState {
+    pub ship_hp_percent: f32,
+    pub ship_energy_percent: f32,
+    pub god_mode: bool,
+    // multiplier of the adaptive difficulty while it's on, for the debug overlay
+    pub adaptive_difficulty: Option<f32>,
+    // the attract mode plays the run, not the player
+    pub demo: bool,
+    pub low_hp_warning: bool,
+    pub in_force_zone: bool,
+
+    pub boss_health: Option<BossHealthView>,
+    pub boss_defeated: bool,
+    pub boss_banner: Option<&'static str>,
+    pub safe_zone_x: Option<i32>,
+    // kind and hp percent of the locked target
+    pub lock_on: Option<(LockTargetKind, f32)>,
+    pub cutscene_text: Option<&'static str>,
+    pub dialogue_speaker: Option<&'static str>,
+    pub dialogue_text: Option<&'static str>,
+    // score so far, shown while the victory lap lasts
+    pub victory_lap_score: Option<u32>,
+    // seconds left of the reflector power-up
+    pub reflector_remaining: Option<f32>,
+    pub shield_bubble: bool,
+    pub objectives: Vec<ObjectiveView>,
+    // for the adaptive music, 0..1
+    pub music_intensity: f32,
+    // page and selected entry of the open pause menu
+    pub pause_menu: Option<(MenuPage, usize)>,
+    // selected row and picked loadout of the loadout menu opened from the pause menu
+    pub loadout_menu: Option<(LoadoutRow, Loadout)>,
+
+    pub level_name: &'static str,
+}
+
+//-----------------------------------------------------------------------------
+
+impl Default for HudState {
+    fn default() -> Self {
+        Self {
+            ship_hp_percent: 1.0,
+            ship_energy_percent: 1.0,
+            god_mode: false,
+            adaptive_difficulty: None,
+            demo: false,
+            low_hp_warning: false,
+            in_force_zone: false,
+            boss_health: None,
+            boss_defeated: false,
+            boss_banner: None,
+            safe_zone_x: None,
+            lock_on: None,
+            cutscene_text: None,
+            dialogue_speaker: None,
+            dialogue_text: None,
+            victory_lap_score: None,
+            reflector_remaining: None,
+            shield_bubble: false,
+            objectives: vec![],
+            music_intensity: 0.0,
+            pause_menu: None,
+            loadout_menu: None,
+            level_name: "",
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use winit::event::VirtualKeyCode;
+
+use super::gamepad::GamepadButton;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Bomb,
+    Dash,
+    CycleLock,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::Bomb,
+        Action::Dash,
+        Action::CycleLock,
+        Action::Pause,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Fire => "fire",
+            Action::Bomb => "bomb",
+            Action::Dash => "dash",
+            Action::CycleLock => "cycle_lock",
+            Action::Pause => "pause",
+        }
+    }
+
+    fn default_inputs(&self) -> Vec<PhysicalInput> {
+        use GamepadButton::*;
+        use PhysicalInput::{Button, Key};
+        use VirtualKeyCode as K;
+
+        match self {
+            Action::MoveUp => vec![Key(K::W), Key(K::Up), Button(DPadUp)],
+            Action::MoveDown => vec![Key(K::S), Key(K::Down), Button(DPadDown)],
+            Action::MoveLeft => vec![Key(K::A), Key(K::Left), Button(DPadLeft)],
+            Action::MoveRight => vec![Key(K::D), Key(K::Right), Button(DPadRight)],
+            Action::Fire => vec![Key(K::Space), Button(South)],
+            Action::Bomb => vec![Key(K::B), Button(East)],
+            Action::Dash => vec![Key(K::LShift), Key(K::RShift), Button(RightShoulder)],
+            Action::CycleLock => vec![Key(K::Tab), Button(North)],
+            Action::Pause => vec![Key(K::Escape), Key(K::P), Button(Start)],
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PhysicalInput {
+    Key(VirtualKeyCode),
+    Button(GamepadButton),
+}
+
+//-----------------------------------------------------------------------------
+
+pub struct Bindings {
+    inputs: Vec<(Action, Vec<PhysicalInput>)>,
+}
+
+impl Bindings {
+    pub fn new() -> Self {
+        Self {
+            inputs: Action::ALL.iter().map(|action| (*action, action.default_inputs())).collect(),
+        }
+    }
+
+    // actions which are missing or can't be parsed (e.g. a key removed from the list) get their defaults
+    pub fn from_config(config: &BTreeMap<String, toml::Value>) -> Self {
+        let mut bindings = Bindings::new();
+        for (action, inputs) in bindings.inputs.iter_mut() {
+            let parsed = config.get(action.name()).map(|value| value.clone().try_into::<Vec<PhysicalInput>>());
+            match parsed {
+                Some(Ok(parsed)) => *inputs = parsed,
+                Some(Err(err)) => log::warn!("Invalid bindings for '{}', using defaults: {}", action.name(), err),
+                None => (),
+            }
+        }
+        bindings
+    }
+
+    pub fn to_config(&self) -> BTreeMap<String, toml::Value> {
+        self.inputs.iter()
+            .filter_map(|(action, inputs)| {
+                let value = toml::Value::try_from(inputs).ok()?;
+                Some((action.name().to_string(), value))
+            })
+            .collect()
+    }
+
+    pub fn reset_to_defaults(&mut self) {
+        *self = Bindings::new();
+    }
+
+    pub fn inputs(&self, action: Action) -> &[PhysicalInput] {
+        self.inputs.iter()
+            .find(|(x, _)| *x == action)
+            .map(|(_, inputs)| inputs.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn is_bound(&self, action: Action, input: PhysicalInput) -> bool {
+        self.inputs(action).contains(&input)
+    }
+
+    // replaces the bindings of the same device kind, an input already taken by another action
+    // is swapped, so that action gets the replaced inputs; returns the action which lost the input
+    pub fn bind(&mut self, action: Action, input: PhysicalInput) -> Option<Action> {
+        let same_device = |x: &PhysicalInput| std::mem::discriminant(x) == std::mem::discriminant(&input);
+
+        let replaced: Vec<PhysicalInput> = self.inputs(action).iter().copied().filter(|x| same_device(x)).collect();
+        let conflict = Action::ALL.iter().copied().find(|other| *other != action && self.is_bound(*other, input));
+
+        if let Some(other) = conflict {
+            log::warn!("{:?} is already bound to '{}', swapping with '{}'", input, other.name(), action.name());
+            let other_inputs = self.inputs_mut(other);
+            other_inputs.retain(|x| *x != input);
+            other_inputs.extend(replaced.iter().copied());
+        }
+
+        let inputs = self.inputs_mut(action);
+        inputs.retain(|x| !same_device(x));
+        inputs.push(input);
+
+        conflict
+    }
+
+    fn inputs_mut(&mut self, action: Action) -> &mut Vec<PhysicalInput> {
+        let idx = self.inputs.iter().position(|(x, _)| *x == action).unwrap();
+        &mut self.inputs[idx].1
+    }
+}
+
+//-----------------------------------------------------------------------------
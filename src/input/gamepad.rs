@@ -0,0 +1,161 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rumble::RumbleEvent;
+
+use super::bindings::{Bindings, PhysicalInput};
+use super::input_state::{Aim, Axis, InputSource, SourceInput};
+
+//-----------------------------------------------------------------------------
+
+const DEFAULT_DEADZONE: f32 = 0.2;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    RightShoulder,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+// raw device state is pushed in by the platform backend, the source only interprets it
+// winit 0.27 has no gamepad events, so until a backend exists only the tests push any state
+pub struct GamepadSource {
+    bindings: Rc<RefCell<Bindings>>,
+    stick: Axis,
+    right_stick: Axis,
+    deadzone: f32,
+    buttons: Vec<GamepadButton>,
+}
+
+impl GamepadSource {
+    pub fn new(bindings: Rc<RefCell<Bindings>>) -> Self {
+        Self {
+            bindings,
+            stick: Axis::default(),
+            right_stick: Axis::default(),
+            deadzone: DEFAULT_DEADZONE,
+            buttons: vec![],
+        }
+    }
+
+    #[cfg(test)]
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        self.deadzone = deadzone.clamp(0.0, 1.0);
+    }
+
+    #[cfg(test)]
+    pub fn set_stick(&mut self, stick: Axis) {
+        self.stick = stick;
+    }
+
+    #[cfg(test)]
+    pub fn set_button(&mut self, button: GamepadButton, held: bool) {
+        self.buttons.retain(|x| *x != button);
+        if held {
+            self.buttons.push(button);
+        }
+    }
+
+    #[cfg(test)]
+    pub fn set_right_stick(&mut self, stick: Axis) {
+        self.right_stick = stick;
+    }
+
+    #[cfg(test)]
+    pub fn disconnect(&mut self) {
+        self.stick = Axis::default();
+        self.right_stick = Axis::default();
+        self.buttons.clear();
+    }
+}
+
+impl InputSource for GamepadSource {
+    fn poll(&mut self) -> SourceInput {
+        let bindings = self.bindings.borrow();
+        let mut input = SourceInput::from_actions(|action| {
+            bindings.inputs(action).iter().any(|input| match input {
+                PhysicalInput::Button(button) => self.buttons.contains(button),
+                _ => false,
+            })
+        });
+
+        // buttons bound to movement (the d-pad by default) win over the stick when both are used
+        if input.axis.length() == 0.0 {
+            input.axis = self.stick.apply_deadzone(self.deadzone);
+        }
+
+        let aim = self.right_stick.apply_deadzone(self.deadzone);
+        if aim.length() > 0.0 {
+            input.aim = Some(Aim::Direction(aim));
+        }
+        input
+    }
+
+    // played by the platform backend once there is one
+    fn rumble(&mut self, event: RumbleEvent) {
+        log::trace!("Gamepad rumble {:.2} for {:?}", event.strength, event.duration);
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> GamepadSource {
+        GamepadSource::new(Rc::new(RefCell::new(Bindings::new())))
+    }
+
+    #[test]
+    fn stick_inside_the_deadzone_is_ignored() {
+        let mut gamepad = source();
+        gamepad.set_stick(Axis::new(0.1, 0.0));
+        assert_eq!(gamepad.poll().axis, Axis::default());
+
+        gamepad.set_deadzone(0.05);
+        assert!(gamepad.poll().axis.x > 0.0);
+    }
+
+    #[test]
+    fn dpad_wins_over_the_stick() {
+        let mut gamepad = source();
+        gamepad.set_stick(Axis::new(1.0, 0.0));
+        gamepad.set_button(GamepadButton::DPadLeft, true);
+        assert!(gamepad.poll().axis.x < 0.0);
+
+        gamepad.set_button(GamepadButton::DPadLeft, false);
+        assert!(gamepad.poll().axis.x > 0.0);
+    }
+
+    #[test]
+    fn right_stick_aims() {
+        let mut gamepad = source();
+        gamepad.set_right_stick(Axis::new(0.0, -1.0));
+        assert_eq!(gamepad.poll().aim, Some(Aim::Direction(Axis::new(0.0, -1.0))));
+    }
+
+    #[test]
+    fn disconnect_releases_everything() {
+        let mut gamepad = source();
+        gamepad.set_stick(Axis::new(1.0, 0.0));
+        gamepad.set_right_stick(Axis::new(1.0, 0.0));
+        gamepad.set_button(GamepadButton::South, true);
+        assert!(gamepad.poll().fire);
+
+        gamepad.disconnect();
+        assert_eq!(gamepad.poll(), SourceInput::default());
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,115 @@
+use super::bindings::Action;
+use super::input_state::InputState;
+
+//-----------------------------------------------------------------------------
+
+// ~120ms at 60 updates per second
+pub const DEFAULT_BUFFER_TICKS: i32 = 7;
+
+const BUFFERED_ACTIONS: [Action; 3] = [Action::Fire, Action::Bomb, Action::Dash];
+
+//-----------------------------------------------------------------------------
+
+// remembers presses for a few simulation ticks, so an action pressed slightly too early isn't lost
+pub struct InputBuffer {
+    window_ticks: i32,
+    presses: Vec<(Action, i32)>,
+}
+
+impl InputBuffer {
+    pub fn new(window_ticks: i32) -> Self {
+        Self { window_ticks, presses: vec![] }
+    }
+
+    // must be called once per simulation tick
+    pub fn record(&mut self, state: &InputState) {
+        self.presses.iter_mut().for_each(|(_, age)| *age += 1);
+        self.presses.retain(|(_, age)| *age < self.window_ticks);
+
+        for action in BUFFERED_ACTIONS {
+            if state.button(action).pressed {
+                self.presses.retain(|(x, _)| *x != action);
+                self.presses.push((action, 0));
+            }
+        }
+    }
+
+    // the first system able to act on the press takes it
+    pub fn consume(&mut self, action: Action) -> bool {
+        let idx = self.presses.iter().position(|(x, _)| *x == action);
+        if let Some(idx) = idx {
+            self.presses.remove(idx);
+        }
+        idx.is_some()
+    }
+
+    pub fn buffered(&self, action: Action) -> bool {
+        self.presses.iter().any(|(x, _)| *x == action)
+    }
+
+    // on pause, death, menus etc., so presses never leak into another state
+    pub fn clear(&mut self) {
+        self.presses.clear();
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::ButtonState;
+
+    fn pressed(action: Action) -> InputState {
+        let mut state = InputState::default();
+        let press = ButtonState { pressed: true, held: true, released: false };
+        match action {
+            Action::Fire => state.fire = press,
+            Action::Bomb => state.bomb = press,
+            Action::Dash => state.dash = press,
+            _ => unreachable!(),
+        }
+        state
+    }
+
+    #[test]
+    fn press_is_kept_for_the_window() {
+        let mut buffer = InputBuffer::new(DEFAULT_BUFFER_TICKS);
+        buffer.record(&pressed(Action::Dash));
+        for _ in 1..DEFAULT_BUFFER_TICKS {
+            buffer.record(&InputState::default());
+        }
+        assert!(buffer.buffered(Action::Dash));
+
+        buffer.record(&InputState::default());
+        assert!(!buffer.buffered(Action::Dash));
+    }
+
+    #[test]
+    fn press_is_consumed_once() {
+        let mut buffer = InputBuffer::new(DEFAULT_BUFFER_TICKS);
+        buffer.record(&pressed(Action::Bomb));
+        assert!(buffer.consume(Action::Bomb));
+        assert!(!buffer.consume(Action::Bomb));
+    }
+
+    #[test]
+    fn new_press_restarts_the_window() {
+        let mut buffer = InputBuffer::new(DEFAULT_BUFFER_TICKS);
+        buffer.record(&pressed(Action::Dash));
+        for _ in 1..DEFAULT_BUFFER_TICKS {
+            buffer.record(&InputState::default());
+        }
+        buffer.record(&pressed(Action::Dash));
+        buffer.record(&InputState::default());
+        assert!(buffer.buffered(Action::Dash));
+    }
+
+    #[test]
+    fn clear_drops_pending_presses() {
+        let mut buffer = InputBuffer::new(DEFAULT_BUFFER_TICKS);
+        buffer.record(&pressed(Action::Bomb));
+        buffer.clear();
+        assert!(!buffer.consume(Action::Bomb));
+    }
+}
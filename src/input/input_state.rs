@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use winit::event::WindowEvent;
+
+use crate::math::Vec2i;
+use crate::rumble::RumbleEvent;
+
+use super::bindings::Action;
+
+//-----------------------------------------------------------------------------
+
+// in screen coordinates, so positive y points down
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Axis {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Axis {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn clamp_length(self) -> Axis {
+        let length = self.length();
+        if length > 1.0 {
+            Axis::new(self.x / length, self.y / length)
+        } else {
+            self
+        }
+    }
+
+    // radial deadzone, the rest of the range is rescaled so movement still starts from zero
+    pub fn apply_deadzone(self, deadzone: f32) -> Axis {
+        let length = self.length();
+        if length <= deadzone || deadzone >= 1.0 {
+            return Axis::default();
+        }
+
+        let scale = ((length - deadzone) / (1.0 - deadzone)).min(1.0) / length;
+        Axis::new(self.x * scale, self.y * scale)
+    }
+
+    // snaps the direction to one of 8 sectors of 45 degrees each
+    pub fn to_8_way(self) -> Vec2i {
+        if self.length() == 0.0 {
+            return Vec2i { x: 0, y: 0 };
+        }
+
+        let sector = (self.y.atan2(self.x).to_degrees() / 45.0).round() as i32;
+        match sector.rem_euclid(8) {
+            0 => Vec2i { x: 1, y: 0 },
+            1 => Vec2i { x: 1, y: 1 },
+            2 => Vec2i { x: 0, y: 1 },
+            3 => Vec2i { x: -1, y: 1 },
+            4 => Vec2i { x: -1, y: 0 },
+            5 => Vec2i { x: -1, y: -1 },
+            6 => Vec2i { x: 0, y: -1 },
+            _ => Vec2i { x: 1, y: -1 },
+        }
+    }
+}
+
+// where the aiming mode fires to: a cursor in playfield coordinates or a stick direction
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Aim {
+    Point(Vec2i),
+    Direction(Axis),
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ButtonState {
+    pub pressed: bool,
+    pub held: bool,
+    pub released: bool,
+}
+
+impl ButtonState {
+    pub fn from_frames(was_held: bool, held: bool) -> Self {
+        Self {
+            pressed: held && !was_held,
+            held,
+            released: !held && was_held,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// what a single device reports for the current frame
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SourceInput {
+    pub axis: Axis,
+    pub aim: Option<Aim>,
+    pub fire: bool,
+    pub bomb: bool,
+    pub dash: bool,
+    pub cycle_lock: bool,
+    pub pause: bool,
+}
+
+impl SourceInput {
+    pub fn from_actions(held: impl Fn(Action) -> bool) -> SourceInput {
+        let direction = |negative, positive| held(positive) as i32 as f32 - held(negative) as i32 as f32;
+        let axis = Axis::new(direction(Action::MoveLeft, Action::MoveRight), direction(Action::MoveUp, Action::MoveDown));
+
+        SourceInput {
+            axis: axis.clamp_length(),
+            aim: None,
+            fire: held(Action::Fire),
+            bomb: held(Action::Bomb),
+            dash: held(Action::Dash),
+            cycle_lock: held(Action::CycleLock),
+            pause: held(Action::Pause),
+        }
+    }
+
+    pub fn merge(self, rhs: SourceInput) -> SourceInput {
+        SourceInput {
+            axis: Axis::new(self.axis.x + rhs.axis.x, self.axis.y + rhs.axis.y).clamp_length(),
+            aim: self.aim.or(rhs.aim),
+            fire: self.fire || rhs.fire,
+            bomb: self.bomb || rhs.bomb,
+            dash: self.dash || rhs.dash,
+            cycle_lock: self.cycle_lock || rhs.cycle_lock,
+            pause: self.pause || rhs.pause,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// the only thing gameplay code is allowed to look at
+#[derive(Copy, Clone, Debug, Default)]
+pub struct InputState {
+    pub axis: Axis,
+    pub aim: Option<Aim>,
+    pub fire: ButtonState,
+    pub bomb: ButtonState,
+    pub dash: ButtonState,
+    pub cycle_lock: ButtonState,
+    pub pause: ButtonState,
+}
+
+impl InputState {
+    // movement actions are reported through the axis and have no button state
+    pub fn button(&self, action: Action) -> ButtonState {
+        match action {
+            Action::Fire => self.fire,
+            Action::Bomb => self.bomb,
+            Action::Dash => self.dash,
+            Action::CycleLock => self.cycle_lock,
+            Action::Pause => self.pause,
+            _ => ButtonState::default(),
+        }
+    }
+
+    pub fn from_frames(prev: &SourceInput, current: &SourceInput) -> Self {
+        Self {
+            axis: current.axis,
+            aim: current.aim,
+            fire: ButtonState::from_frames(prev.fire, current.fire),
+            bomb: ButtonState::from_frames(prev.bomb, current.bomb),
+            dash: ButtonState::from_frames(prev.dash, current.dash),
+            cycle_lock: ButtonState::from_frames(prev.cycle_lock, current.cycle_lock),
+            pause: ButtonState::from_frames(prev.pause, current.pause),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+pub trait InputSource {
+    fn handle_event(&mut self, _event: &WindowEvent) {}
+
+    fn poll(&mut self) -> SourceInput;
+
+    // only the devices which can rumble do anything with it
+    fn rumble(&mut self, _event: RumbleEvent) {}
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(lhs: Axis, rhs: Axis) {
+        assert!((lhs.x - rhs.x).abs() < 1e-5 && (lhs.y - rhs.y).abs() < 1e-5, "{:?} != {:?}", lhs, rhs);
+    }
+
+    #[test]
+    fn deadzone_swallows_small_tilts() {
+        assert_eq!(Axis::new(0.1, 0.1).apply_deadzone(0.2), Axis::default());
+        assert_eq!(Axis::new(0.2, 0.0).apply_deadzone(0.2), Axis::default());
+    }
+
+    #[test]
+    fn deadzone_rescales_the_rest_of_the_range() {
+        assert_close(Axis::new(0.6, 0.0).apply_deadzone(0.2), Axis::new(0.5, 0.0));
+        assert_close(Axis::new(0.0, -1.0).apply_deadzone(0.2), Axis::new(0.0, -1.0));
+    }
+
+    #[test]
+    fn full_deadzone_disables_the_stick() {
+        assert_eq!(Axis::new(1.0, 0.0).apply_deadzone(1.0), Axis::default());
+    }
+
+    #[test]
+    fn zero_axis_has_no_direction() {
+        assert_eq!(Axis::default().to_8_way(), Vec2i::new(0, 0));
+    }
+
+    #[test]
+    fn axis_snaps_to_8_ways() {
+        assert_eq!(Axis::new(1.0, 0.0).to_8_way(), Vec2i::new(1, 0));
+        assert_eq!(Axis::new(0.7, 0.7).to_8_way(), Vec2i::new(1, 1));
+        assert_eq!(Axis::new(0.0, -1.0).to_8_way(), Vec2i::new(0, -1));
+        assert_eq!(Axis::new(-1.0, 0.0).to_8_way(), Vec2i::new(-1, 0));
+        assert_eq!(Axis::new(-0.5, -0.5).to_8_way(), Vec2i::new(-1, -1));
+    }
+
+    #[test]
+    fn axis_snaps_to_nearest_sector() {
+        // 20 degrees below the x axis is closer to right than to down-right
+        assert_eq!(Axis::new(0.94, 0.34).to_8_way(), Vec2i::new(1, 0));
+        assert_eq!(Axis::new(0.5, 0.87).to_8_way(), Vec2i::new(1, 1));
+    }
+
+    #[test]
+    fn merged_sources_clamp_the_axis() {
+        let keyboard = SourceInput { axis: Axis::new(1.0, 0.0), fire: true, ..Default::default() };
+        let gamepad = SourceInput { axis: Axis::new(0.0, 1.0), ..Default::default() };
+        let merged = keyboard.merge(gamepad);
+        assert!(merged.fire);
+        assert!((merged.axis.length() - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,44 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use winit::event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
+
+use super::bindings::{Bindings, PhysicalInput};
+use super::input_state::{InputSource, SourceInput};
+
+//-----------------------------------------------------------------------------
+
+pub struct KeyboardSource {
+    bindings: Rc<RefCell<Bindings>>,
+    held_keys: HashSet<VirtualKeyCode>,
+}
+
+impl KeyboardSource {
+    pub fn new(bindings: Rc<RefCell<Bindings>>) -> Self {
+        Self { bindings, held_keys: HashSet::new() }
+    }
+}
+
+impl InputSource for KeyboardSource {
+    fn handle_event(&mut self, event: &WindowEvent) {
+        if let WindowEvent::KeyboardInput { input: KeyboardInput { virtual_keycode: Some(key), state, .. }, .. } = event {
+            match state {
+                ElementState::Pressed => self.held_keys.insert(*key),
+                ElementState::Released => self.held_keys.remove(key),
+            };
+        }
+    }
+
+    fn poll(&mut self) -> SourceInput {
+        let bindings = self.bindings.borrow();
+        SourceInput::from_actions(|action| {
+            bindings.inputs(action).iter().any(|input| match input {
+                PhysicalInput::Key(key) => self.held_keys.contains(key),
+                _ => false,
+            })
+        })
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,26 @@
+mod bindings;
+mod input_state;
+mod input_buffer;
+mod keyboard;
+mod gamepad;
+mod mouse;
+#[cfg(test)]
+pub mod test_support;
+
+#[cfg(test)]
+pub type Axis = input_state::Axis;
+pub type Aim = input_state::Aim;
+#[cfg(test)]
+pub type ButtonState = input_state::ButtonState;
+pub type Action = bindings::Action;
+pub type Bindings = bindings::Bindings;
+pub type PhysicalInput = bindings::PhysicalInput;
+pub type InputState = input_state::InputState;
+pub type InputBuffer = input_buffer::InputBuffer;
+pub use input_buffer::DEFAULT_BUFFER_TICKS;
+pub type SourceInput = input_state::SourceInput;
+pub use input_state::InputSource;
+
+pub type KeyboardSource = keyboard::KeyboardSource;
+pub type GamepadSource = gamepad::GamepadSource;
+pub type MouseSource = mouse::MouseSource;
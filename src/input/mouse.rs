@@ -0,0 +1,70 @@
+use winit::event::WindowEvent;
+
+use crate::globals::screen_size;
+use crate::math::Vec2i;
+
+use super::input_state::{Aim, InputSource, SourceInput};
+
+//-----------------------------------------------------------------------------
+
+pub struct MouseSource {
+    cursor: Option<Vec2i>,
+    window_size: Vec2i,
+}
+
+impl MouseSource {
+    pub fn new() -> Self {
+        Self {
+            cursor: None,
+            window_size: Vec2i { x: 0, y: 0 },
+        }
+    }
+}
+
+impl InputSource for MouseSource {
+    fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = Some(Vec2i { x: position.x as i32, y: position.y as i32 })
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+            WindowEvent::Resized(size) => {
+                self.window_size = Vec2i { x: size.width as i32, y: size.height as i32 }
+            }
+            _ => (),
+        }
+    }
+
+    fn poll(&mut self) -> SourceInput {
+        SourceInput {
+            aim: self.cursor
+                .and_then(|cursor| window_to_playfield(cursor, self.window_size, screen_size()))
+                .map(Aim::Point),
+            ..SourceInput::default()
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// the playfield is scaled uniformly and centered, so the window may have margins on two sides
+pub fn window_to_playfield(pos: Vec2i, window_size: Vec2i, playfield_size: Vec2i) -> Option<Vec2i> {
+    if window_size.x <= 0 || window_size.y <= 0 || playfield_size.x <= 0 || playfield_size.y <= 0 {
+        return None;
+    }
+
+    let scale_x = window_size.x as f32 / playfield_size.x as f32;
+    let scale_y = window_size.y as f32 / playfield_size.y as f32;
+    let scale = scale_x.min(scale_y);
+
+    let margin_x = (window_size.x as f32 - playfield_size.x as f32 * scale) / 2.0;
+    let margin_y = (window_size.y as f32 - playfield_size.y as f32 * scale) / 2.0;
+
+    let playfield_pos = Vec2i {
+        x: ((pos.x as f32 - margin_x) / scale) as i32,
+        y: ((pos.y as f32 - margin_y) / scale) as i32,
+    };
+    Some(playfield_pos.clamp(Vec2i { x: 0, y: 0 }, playfield_size))
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,17 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use super::{InputSource, SourceInput};
+
+//-----------------------------------------------------------------------------
+
+// reports whatever the test put in the cell, added to an InputMgr next to the real devices
+pub struct ScriptedSource(pub Rc<Cell<SourceInput>>);
+
+impl InputSource for ScriptedSource {
+    fn poll(&mut self) -> SourceInput {
+        self.0.get()
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -1,7 +1,114 @@
-pub struct InputMgr;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use winit::event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
+
+use crate::input::{Action, Bindings, GamepadSource, InputSource, InputState, KeyboardSource, MouseSource, PhysicalInput, SourceInput};
+use crate::rumble::RumbleEvent;
+use crate::settings::Settings;
+
+// merges all the devices, so any of them can be used without switching modes
+pub struct InputMgr {
+    sources: RefCell<Vec<Box<dyn InputSource>>>,
+    bindings: Rc<RefCell<Bindings>>,
+    rebinding: Cell<Option<Action>>,
+    last_input: Cell<SourceInput>,
+    state: Cell<InputState>,
+}
 
 impl InputMgr {
-    pub fn is_pressed(&self, keycode: char) -> bool {
-        false
+    pub fn new(bindings: Bindings) -> Self {
+        let bindings = Rc::new(RefCell::new(bindings));
+        let sources: Vec<Box<dyn InputSource>> = vec![
+            Box::new(KeyboardSource::new(Rc::clone(&bindings))),
+            Box::new(GamepadSource::new(Rc::clone(&bindings))),
+            Box::new(MouseSource::new()),
+        ];
+        InputMgr::with_sources(bindings, sources)
+    }
+
+    // no devices, e.g. a replay feeding recorded input
+    pub fn from_sources(bindings: Bindings, sources: Vec<Box<dyn InputSource>>) -> Self {
+        InputMgr::with_sources(Rc::new(RefCell::new(bindings)), sources)
+    }
+
+    fn with_sources(bindings: Rc<RefCell<Bindings>>, sources: Vec<Box<dyn InputSource>>) -> Self {
+        Self {
+            sources: RefCell::new(sources),
+            bindings,
+            rebinding: Cell::new(None),
+            last_input: Cell::new(SourceInput::default()),
+            state: Cell::new(InputState::default()),
+        }
+    }
+
+    pub fn handle_event(&self, event: &WindowEvent) {
+        if let WindowEvent::KeyboardInput { input: KeyboardInput { virtual_keycode: Some(key), state: ElementState::Pressed, .. }, .. } = event {
+            if self.rebinding().is_some() {
+                match key {
+                    VirtualKeyCode::Escape => self.cancel_rebinding(),
+                    _ => self.capture_input(PhysicalInput::Key(*key)),
+                }
+                return;
+            }
+        }
+        self.sources.borrow_mut().iter_mut().for_each(|source| source.handle_event(event));
+    }
+
+    // called once per simulation tick before the game update, so edges never span several ticks
+    pub fn update(&self) {
+        let input = self.sources.borrow_mut().iter_mut()
+            .map(|source| source.poll())
+            .fold(SourceInput::default(), SourceInput::merge);
+
+        self.state.set(InputState::from_frames(&self.last_input.get(), &input));
+        self.last_input.set(input);
+    }
+
+    pub fn rumble(&self, event: RumbleEvent) {
+        self.sources.borrow_mut().iter_mut().for_each(|source| source.rumble(event));
+    }
+
+    pub fn state(&self) -> InputState {
+        self.state.get()
+    }
+
+    // every source merged, as the last update saw it
+    pub fn last_input(&self) -> SourceInput {
+        self.last_input.get()
+    }
+
+    // the next pressed key or button gets bound to the action
+    pub fn start_rebinding(&self, action: Action) {
+        self.rebinding.set(Some(action));
+    }
+
+    pub fn cancel_rebinding(&self) {
+        self.rebinding.set(None);
+    }
+
+    pub fn rebinding(&self) -> Option<Action> {
+        self.rebinding.get()
+    }
+
+    // gamepad backends report presses here while rebinding is in progress
+    pub fn capture_input(&self, input: PhysicalInput) {
+        if let Some(action) = self.rebinding.take() {
+            self.bindings.borrow_mut().bind(action, input);
+            self.save_bindings();
+        }
+    }
+
+    pub fn reset_bindings(&self) {
+        self.bindings.borrow_mut().reset_to_defaults();
+        self.save_bindings();
+    }
+
+    fn save_bindings(&self) {
+        let mut settings = Settings::load();
+        settings.bindings = self.bindings.borrow().to_config();
+        if let Err(err) = settings.save() {
+            log::warn!("Failed to save settings: {}", err);
+        }
     }
 }
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+use crate::achievements::{AchievementId, AchievementSystem};
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PrimaryWeapon {
+    Straight,
+    Spread,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SecondaryWeapon {
+    Missiles,
+    ExtraBomb,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Passive {
+    MagnetRadius,
+    EnergyRegen,
+    Speed,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LoadoutSlot {
+    Primary,
+    Secondary,
+    Passive,
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LoadoutOption {
+    Primary(PrimaryWeapon),
+    Secondary(SecondaryWeapon),
+    Passive(Passive),
+}
+
+impl LoadoutOption {
+    pub fn slot(&self) -> LoadoutSlot {
+        match self {
+            LoadoutOption::Primary(_) => LoadoutSlot::Primary,
+            LoadoutOption::Secondary(_) => LoadoutSlot::Secondary,
+            LoadoutOption::Passive(_) => LoadoutSlot::Passive,
+        }
+    }
+}
+
+pub struct LoadoutEntry {
+    pub option: LoadoutOption,
+    // the name used in the settings file
+    pub name: &'static str,
+    // available from the start when there is no achievement to unlock it
+    pub unlocked_by: Option<AchievementId>,
+}
+
+// in the order the menu shows them
+pub const LOADOUT_OPTIONS: &[LoadoutEntry] = &[
+    LoadoutEntry { option: LoadoutOption::Primary(PrimaryWeapon::Straight), name: "straight", unlocked_by: None },
+    LoadoutEntry { option: LoadoutOption::Primary(PrimaryWeapon::Spread), name: "spread", unlocked_by: Some(AchievementId::Sharpshooter) },
+    LoadoutEntry { option: LoadoutOption::Secondary(SecondaryWeapon::ExtraBomb), name: "extra_bomb", unlocked_by: None },
+    LoadoutEntry { option: LoadoutOption::Secondary(SecondaryWeapon::Missiles), name: "missiles", unlocked_by: Some(AchievementId::SpeedDemon) },
+    LoadoutEntry { option: LoadoutOption::Passive(Passive::EnergyRegen), name: "energy_regen", unlocked_by: None },
+    LoadoutEntry { option: LoadoutOption::Passive(Passive::MagnetRadius), name: "magnet_radius", unlocked_by: None },
+    LoadoutEntry { option: LoadoutOption::Passive(Passive::Speed), name: "speed", unlocked_by: Some(AchievementId::Untouchable) },
+];
+
+pub fn option_unlocked(entry: &LoadoutEntry, achievements: &AchievementSystem) -> bool {
+    entry.unlocked_by.is_none_or(|id| achievements.unlocked(id))
+}
+
+// unlocked options of a slot, in the menu order
+pub fn available_options(slot: LoadoutSlot, achievements: &AchievementSystem) -> Vec<LoadoutOption> {
+    LOADOUT_OPTIONS.iter()
+        .filter(|entry| entry.option.slot() == slot && option_unlocked(entry, achievements))
+        .map(|entry| entry.option)
+        .collect()
+}
+
+//-----------------------------------------------------------------------------
+
+// kept as names in the settings, so an unknown option resets only its own slot
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoadoutConfig {
+    pub primary: String,
+    pub secondary: String,
+    pub passive: String,
+}
+
+impl Default for LoadoutConfig {
+    fn default() -> Self {
+        Loadout::default().to_config()
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Loadout {
+    pub primary: PrimaryWeapon,
+    pub secondary: SecondaryWeapon,
+    pub passive: Passive,
+}
+
+impl Default for Loadout {
+    fn default() -> Self {
+        Self {
+            primary: PrimaryWeapon::Straight,
+            secondary: SecondaryWeapon::ExtraBomb,
+            passive: Passive::EnergyRegen,
+        }
+    }
+}
+
+impl Loadout {
+    // a locked or unknown option falls back to the default of its slot
+    pub fn from_config(config: &LoadoutConfig, achievements: &AchievementSystem) -> Self {
+        let mut loadout = Loadout::default();
+        for (slot, name) in [
+            (LoadoutSlot::Primary, &config.primary),
+            (LoadoutSlot::Secondary, &config.secondary),
+            (LoadoutSlot::Passive, &config.passive),
+        ] {
+            let entry = LOADOUT_OPTIONS.iter().find(|entry| entry.option.slot() == slot && entry.name == name.as_str());
+            match entry {
+                Some(entry) if option_unlocked(entry, achievements) => loadout.set(entry.option),
+                Some(_) => log::warn!("Loadout option '{}' is locked, using the default", name),
+                None => log::warn!("Unknown {:?} loadout option '{}', using the default", slot, name),
+            }
+        }
+        loadout
+    }
+
+    pub fn to_config(self) -> LoadoutConfig {
+        LoadoutConfig {
+            primary: option_name(LoadoutOption::Primary(self.primary)).to_string(),
+            secondary: option_name(LoadoutOption::Secondary(self.secondary)).to_string(),
+            passive: option_name(LoadoutOption::Passive(self.passive)).to_string(),
+        }
+    }
+
+    pub fn get(&self, slot: LoadoutSlot) -> LoadoutOption {
+        match slot {
+            LoadoutSlot::Primary => LoadoutOption::Primary(self.primary),
+            LoadoutSlot::Secondary => LoadoutOption::Secondary(self.secondary),
+            LoadoutSlot::Passive => LoadoutOption::Passive(self.passive),
+        }
+    }
+
+    pub fn set(&mut self, option: LoadoutOption) {
+        match option {
+            LoadoutOption::Primary(primary) => self.primary = primary,
+            LoadoutOption::Secondary(secondary) => self.secondary = secondary,
+            LoadoutOption::Passive(passive) => self.passive = passive,
+        }
+    }
+}
+
+fn option_name(option: LoadoutOption) -> &'static str {
+    LOADOUT_OPTIONS.iter()
+        .find(|entry| entry.option == option)
+        .map(|entry| entry.name)
+        .expect("Every loadout option is registered")
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::GameEvent;
+
+    fn config(primary: &str, secondary: &str, passive: &str) -> LoadoutConfig {
+        LoadoutConfig { primary: primary.to_string(), secondary: secondary.to_string(), passive: passive.to_string() }
+    }
+
+    #[test]
+    fn config_round_trips() {
+        let mut achievements = AchievementSystem::default();
+        achievements.check(&GameEvent::ComboOf50);
+        let loadout = Loadout {
+            primary: PrimaryWeapon::Spread,
+            secondary: SecondaryWeapon::ExtraBomb,
+            passive: Passive::MagnetRadius,
+        };
+        assert_eq!(Loadout::from_config(&loadout.to_config(), &achievements), loadout);
+    }
+
+    #[test]
+    fn locked_option_falls_back_to_default() {
+        let achievements = AchievementSystem::default();
+        let loadout = Loadout::from_config(&config("spread", "missiles", "magnet_radius"), &achievements);
+        assert_eq!(loadout.primary, PrimaryWeapon::Straight);
+        assert_eq!(loadout.secondary, SecondaryWeapon::ExtraBomb);
+        assert_eq!(loadout.passive, Passive::MagnetRadius);
+    }
+
+    #[test]
+    fn unknown_option_resets_only_its_slot() {
+        let achievements = AchievementSystem::default();
+        let loadout = Loadout::from_config(&config("laser", "extra_bomb", "magnet_radius"), &achievements);
+        assert_eq!(loadout.primary, PrimaryWeapon::Straight);
+        assert_eq!(loadout.passive, Passive::MagnetRadius);
+
+        // names belong to their slot
+        let loadout = Loadout::from_config(&config("straight", "speed", "extra_bomb"), &achievements);
+        assert_eq!(loadout, Loadout::default());
+    }
+
+    #[test]
+    fn locked_options_are_not_available() {
+        let mut achievements = AchievementSystem::default();
+        assert_eq!(available_options(LoadoutSlot::Primary, &achievements), vec![LoadoutOption::Primary(PrimaryWeapon::Straight)]);
+        achievements.check(&GameEvent::ComboOf50);
+        assert_eq!(available_options(LoadoutSlot::Primary, &achievements).len(), 2);
+    }
+}
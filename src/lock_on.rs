@@ -0,0 +1,136 @@
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+// only tough targets can be locked, comets and bullets are never listed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LockTargetKind {
+    Boss,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct LockTarget {
+    pub kind: LockTargetKind,
+    // tells apart targets of the same kind
+    pub id: usize,
+    pub pos: Vec2i,
+    pub hp_percent: f32,
+}
+
+impl LockTarget {
+    fn same_as(&self, kind: LockTargetKind, id: usize) -> bool {
+        self.kind == kind && self.id == id
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+pub struct LockOn {
+    locked: Option<(LockTargetKind, usize)>,
+}
+
+impl LockOn {
+    pub fn new() -> Self {
+        Self { locked: None }
+    }
+
+    pub fn clear(&mut self) {
+        self.locked = None;
+    }
+
+    // targets are cycled left to right, top to bottom on ties
+    pub fn cycle(&mut self, targets: &[LockTarget]) {
+        let mut sorted: Vec<&LockTarget> = targets.iter().collect();
+        sorted.sort_by_key(|target| (target.pos.x, target.pos.y));
+
+        let current_idx = self.locked
+            .and_then(|(kind, id)| sorted.iter().position(|target| target.same_as(kind, id)));
+        let next = match current_idx {
+            Some(idx) => sorted.get((idx + 1) % sorted.len()),
+            None => sorted.first(),
+        };
+        if let Some(next) = next {
+            self.locked = Some((next.kind, next.id));
+        }
+    }
+
+    // the lock breaks once its target is gone, e.g. killed or despawned by a stage switch
+    pub fn update(&mut self, targets: &[LockTarget]) {
+        if self.current(targets).is_none() {
+            self.locked = None;
+        }
+    }
+
+    pub fn current<'a>(&self, targets: &'a [LockTarget]) -> Option<&'a LockTarget> {
+        let (kind, id) = self.locked?;
+        targets.iter().find(|target| target.same_as(kind, id))
+    }
+
+    // for homing weapons, the nearest target is used without a lock
+    pub fn target_or_nearest<'a>(&self, targets: &'a [LockTarget], from: Vec2i) -> Option<&'a LockTarget> {
+        self.current(targets).or_else(|| {
+            targets.iter().min_by_key(|target| {
+                let diff = target.pos - from;
+                diff.x as i64 * diff.x as i64 + diff.y as i64 * diff.y as i64
+            })
+        })
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(id: usize, x: i32, y: i32) -> LockTarget {
+        LockTarget { kind: LockTargetKind::Boss, id, pos: Vec2i { x, y }, hp_percent: 1.0 }
+    }
+
+    fn locked_id(lock_on: &LockOn, targets: &[LockTarget]) -> Option<usize> {
+        lock_on.current(targets).map(|target| target.id)
+    }
+
+    #[test]
+    fn cycles_by_x_then_y() {
+        let targets = [target(0, 50, 10), target(1, 10, 30), target(2, 50, 5), target(3, 10, 20)];
+        let mut lock_on = LockOn::new();
+
+        let mut order = vec![];
+        for _ in 0..5 {
+            lock_on.cycle(&targets);
+            order.push(locked_id(&lock_on, &targets).unwrap());
+        }
+        assert_eq!(order, vec![3, 1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn cycling_without_targets_is_noop() {
+        let mut lock_on = LockOn::new();
+        lock_on.cycle(&[]);
+        assert!(lock_on.current(&[]).is_none());
+    }
+
+    #[test]
+    fn lock_breaks_when_target_is_gone() {
+        let targets = [target(0, 10, 10), target(1, 100, 10)];
+        let mut lock_on = LockOn::new();
+        lock_on.cycle(&targets);
+        lock_on.cycle(&targets);
+        assert_eq!(locked_id(&lock_on, &targets), Some(1));
+
+        // the same id showing up later must not pick the lock back up
+        lock_on.update(&targets[..1]);
+        assert!(lock_on.current(&targets).is_none());
+        assert_eq!(lock_on.target_or_nearest(&targets, Vec2i { x: 90, y: 10 }).unwrap().id, 1);
+        assert_eq!(lock_on.target_or_nearest(&targets[..1], Vec2i { x: 90, y: 10 }).unwrap().id, 0);
+    }
+
+    #[test]
+    fn lock_wins_over_nearest() {
+        let targets = [target(0, 10, 10), target(1, 100, 10)];
+        let mut lock_on = LockOn::new();
+        lock_on.cycle(&targets);
+        assert_eq!(lock_on.target_or_nearest(&targets, Vec2i { x: 100, y: 10 }).unwrap().id, 0);
+    }
+}
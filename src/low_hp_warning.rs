@@ -0,0 +1,86 @@
+//-----------------------------------------------------------------------------
+
+const START_HP_PERCENT: f32 = 0.25;
+const STOP_HP_PERCENT: f32 = 0.35;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LowHpChange {
+    Started,
+    Stopped,
+}
+
+// the gap between the thresholds keeps the warning from strobing when hp hovers around one of them
+pub struct LowHpWarning {
+    active: bool,
+}
+
+impl LowHpWarning {
+    pub fn new() -> Self {
+        Self { active: false }
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    pub fn reset(&mut self) {
+        self.active = false;
+    }
+
+    pub fn update(&mut self, hp_percent: f32) -> Option<LowHpChange> {
+        if !self.active && hp_percent < START_HP_PERCENT {
+            self.active = true;
+            return Some(LowHpChange::Started);
+        }
+        if self.active && hp_percent > STOP_HP_PERCENT {
+            self.active = false;
+            return Some(LowHpChange::Stopped);
+        }
+        None
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_below_the_lower_threshold() {
+        let mut warning = LowHpWarning::new();
+        assert_eq!(warning.update(0.25), None);
+        assert_eq!(warning.update(0.24), Some(LowHpChange::Started));
+        assert!(warning.active());
+    }
+
+    #[test]
+    fn stays_on_within_the_band() {
+        let mut warning = LowHpWarning::new();
+        warning.update(0.1);
+        for hp_percent in [0.26, 0.3, 0.2, 0.35, 0.24] {
+            assert_eq!(warning.update(hp_percent), None);
+            assert!(warning.active());
+        }
+    }
+
+    #[test]
+    fn stops_above_the_upper_threshold() {
+        let mut warning = LowHpWarning::new();
+        warning.update(0.1);
+        assert_eq!(warning.update(0.36), Some(LowHpChange::Stopped));
+        assert_eq!(warning.update(0.3), None);
+        assert!(!warning.active());
+    }
+
+    #[test]
+    fn reset_turns_it_off_silently() {
+        let mut warning = LowHpWarning::new();
+        warning.update(0.1);
+        warning.reset();
+        assert!(!warning.active());
+        assert_eq!(warning.update(1.0), None);
+    }
+}
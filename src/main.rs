@@ -1,14 +1,120 @@
+mod achievements;
+mod attract;
+mod audio;
+mod autosave;
+mod checkpoint;
 mod constants;
+mod cutscene;
+mod difficulty;
+mod effects;
 mod entities;
+mod events;
 mod game_level;
 mod math;
 mod spawners;
+mod starfield;
 mod globals;
+mod high_scores;
+mod hud_state;
+mod paths;
 mod player_ship_controller;
+mod power_ups;
+mod profiling;
+mod rumble;
+mod run_summary;
+mod score;
+mod screen_shake;
+mod settings;
+mod slow_mo;
+mod sound_events;
+mod storage;
+mod timing;
+mod ui;
+#[cfg(feature = "hot-reload")]
+mod tuning;
+mod victory_lap;
 
 mod app;
+mod cli;
+mod input;
 mod input_mgr;
+mod loadout;
+mod logger;
+mod lock_on;
+mod music_intensity;
+mod objectives;
+mod replay;
+mod low_hp_warning;
+mod validation;
 
-fn main() {
-    app::App::new().run()
+use std::process::ExitCode;
+
+use cli::CliError;
+use replay::Replay;
+
+fn main() -> ExitCode {
+    logger::init();
+    let mut options = match cli::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(CliError::HelpRequested) => {
+            println!("{}", cli::USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(CliError::Invalid(message)) => {
+            eprintln!("error: {}\n\n{}", message, cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    if options.validate_assets {
+        return validate_assets();
+    }
+
+    paths::set_portable(options.portable);
+    let replay = match options.replay.clone().map(|path| Replay::load(&path)) {
+        Some(Ok(replay)) => {
+            options.apply_replay(&replay.header);
+            Some(replay)
+        }
+        Some(Err(err)) => {
+            eprintln!("error: can't play the replay: {}", err);
+            return ExitCode::FAILURE;
+        }
+        None => None,
+    };
+    let autosave = autosave::resolve_autosave(&mut options, autosave::AutosaveManager::load());
+    // a recording is only worth something if the run can be played out the same again
+    if options.record.is_some() && options.seed.is_none() {
+        options.seed = Some(rand::random());
+    }
+    if let Err(CliError::Invalid(message)) = cli::validate(&options) {
+        eprintln!("error: {}\n\n{}", message, cli::USAGE);
+        return ExitCode::from(2);
+    }
+    globals::set_mirror_mode(options.mirror);
+    // a seeded run is expected to play out the same anywhere
+    globals::set_deterministic(options.deterministic || options.seed.is_some());
+    if let Some(seed) = options.seed {
+        globals::seed_rng(seed);
+        globals::seed_cosmetic_rng(seed);
+    }
+
+    app::App::new(options, autosave, replay).run();
+    ExitCode::SUCCESS
+}
+
+// the content is checked on the default screen, the playfield of a real run
+fn validate_assets() -> ExitCode {
+    globals::set_screen_size(math::Vec2i { x: 200, y: 200 });
+    let errors = validation::validate_assets();
+    for error in &errors {
+        eprintln!("{}", error);
+    }
+    if errors.is_empty() {
+        println!("All assets are valid");
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("{} problems found", errors.len());
+        ExitCode::FAILURE
+    }
 }
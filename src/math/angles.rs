@@ -0,0 +1,125 @@
+use super::{Vec2f, Vec2i};
+
+//-----------------------------------------------------------------------------
+
+// degrees clockwise from "up" in [0, 360), NaN when there is no direction (from == to)
+pub fn angle_to_target(from: Vec2i, to: Vec2i) -> f32 {
+    let diff = to - from;
+    if diff.x == 0 && diff.y == 0 {
+        return f32::NAN;
+    }
+
+    Vec2f::new(diff.x as f32, diff.y as f32).to_polar().angle_deg
+}
+
+// inverse of the shot movement, (0, 1) is 180 (down) and (1, 0) is 90 (right); 0 for a standing shot
+pub fn angle_from_velocity(vx: f32, vy: f32) -> i32 {
+    let angle = Vec2f::new(vx, vy).to_polar().angle_deg;
+    (angle.round() as i32).rem_euclid(360)
+}
+
+// interpolates through the shortest arc, so 350 -> 10 passes 0 instead of 180
+pub fn angle_lerp(a: i32, b: i32, t: f32) -> i32 {
+    let diff = (b - a + 180).rem_euclid(360) - 180;
+    let angle = a as f32 + diff as f32 * t;
+    (angle.round() as i32).rem_euclid(360)
+}
+
+//-----------------------------------------------------------------------------
+
+// evenly stepped angles across the range around the center, the last one is included even off the step
+pub struct ShotAngleIterator {
+    current: i32,
+    end: i32,
+    step: i32,
+    done: bool,
+}
+
+impl ShotAngleIterator {
+    pub fn spread(center: i32, range: i32, step: usize) -> Self {
+        let start = center - range / 2;
+        let end = center + range / 2;
+        let step = i32::try_from(step).unwrap_or(i32::MAX).max(1);
+        Self { current: start, end, step, done: start > end }
+    }
+}
+
+impl Iterator for ShotAngleIterator {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<i32> {
+        if self.done {
+            return None;
+        }
+
+        // an overshooting step lands on the end
+        let angle = self.current.min(self.end);
+        if angle == self.end {
+            self.done = true;
+        } else {
+            self.current = self.current.saturating_add(self.step);
+        }
+        Some(angle)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn angle_from_velocity_matches_shot_directions() {
+        assert_eq!(angle_from_velocity(0.0, 1.0), 180);
+        assert_eq!(angle_from_velocity(1.0, 0.0), 90);
+        assert_eq!(angle_from_velocity(0.0, -1.0), 0);
+        assert_eq!(angle_from_velocity(-1.0, 0.0), 270);
+        assert_eq!(angle_from_velocity(1.0, -1.0), 45);
+    }
+
+    #[test]
+    fn standing_shot_has_angle_zero() {
+        assert_eq!(angle_from_velocity(0.0, 0.0), 0);
+    }
+
+    #[test]
+    fn lerp_wraps_through_zero() {
+        assert_eq!(angle_lerp(350, 10, 0.5), 0);
+        assert_eq!(angle_lerp(10, 350, 0.5), 0);
+    }
+
+    #[test]
+    fn lerp_takes_shortest_arc() {
+        assert_eq!(angle_lerp(0, 90, 0.5), 45);
+        assert_eq!(angle_lerp(90, 0, 0.5), 45);
+        assert_eq!(angle_lerp(300, 60, 0.25), 330);
+    }
+
+    #[test]
+    fn lerp_ends() {
+        assert_eq!(angle_lerp(350, 10, 0.0), 350);
+        assert_eq!(angle_lerp(350, 10, 1.0), 10);
+    }
+
+    fn spread(center: i32, range: i32, step: usize) -> Vec<i32> {
+        ShotAngleIterator::spread(center, range, step).collect()
+    }
+
+    #[test]
+    fn spread_divisible_by_step() {
+        assert_eq!(spread(180, 60, 15), vec![150, 165, 180, 195, 210]);
+        assert_eq!(spread(0, 0, 10), vec![0]);
+    }
+
+    #[test]
+    fn spread_includes_end_off_the_step() {
+        assert_eq!(spread(180, 60, 25), vec![150, 175, 200, 210]);
+        assert_eq!(spread(90, 40, 100), vec![70, 110]);
+    }
+
+    #[test]
+    fn spread_zero_step_moves_by_one() {
+        assert_eq!(spread(0, 4, 0), vec![-2, -1, 0, 1, 2]);
+    }
+}
@@ -0,0 +1,101 @@
+use std::ops::Mul;
+
+use super::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+const FRACTION_BITS: u32 = 10;
+const ONE: i32 = 1 << FRACTION_BITS;
+
+// sin of 0..=90 degrees scaled by 1 << TABLE_BITS, precomputed so every platform gets the same values
+// finer than Fixed, so a whole pixel step like 4 * cos(1) = 3.9994 isn't rounded up to 4
+const TABLE_BITS: u32 = 16;
+const SIN_TABLE: [i32; 91] = [
+    0, 1144, 2287, 3430, 4572, 5712, 6850, 7987, 9121, 10252,
+    11380, 12505, 13626, 14742, 15855, 16962, 18064, 19161, 20252, 21336,
+    22415, 23486, 24550, 25607, 26656, 27697, 28729, 29753, 30767, 31772,
+    32768, 33754, 34729, 35693, 36647, 37590, 38521, 39441, 40348, 41243,
+    42126, 42995, 43852, 44695, 45525, 46341, 47143, 47930, 48703, 49461,
+    50203, 50931, 51643, 52339, 53020, 53684, 54332, 54963, 55578, 56175,
+    56756, 57319, 57865, 58393, 58903, 59396, 59870, 60326, 60764, 61183,
+    61584, 61966, 62328, 62672, 62997, 63303, 63589, 63856, 64104, 64332,
+    64540, 64729, 64898, 65048, 65177, 65287, 65376, 65446, 65496, 65526,
+    65536,
+];
+
+//-----------------------------------------------------------------------------
+
+// fixed-point number with 10 fractional bits, for movement which has to be the same on every machine
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    pub fn from_int(value: i32) -> Fixed {
+        Fixed(value << FRACTION_BITS)
+    }
+
+    // exact for multipliers like 0.5 or 1.25, the rest is rounded to the closest step
+    pub fn from_f32(value: f32) -> Fixed {
+        Fixed((value * ONE as f32).round() as i32)
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, rhs: Fixed) -> Fixed {
+        Fixed(((self.0 as i64 * rhs.0 as i64) >> FRACTION_BITS) as i32)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// in the table scale
+fn table_sin(angle: i32) -> i32 {
+    let angle = angle.rem_euclid(360);
+    match angle {
+        0..=90 => SIN_TABLE[angle as usize],
+        91..=180 => SIN_TABLE[(180 - angle) as usize],
+        181..=270 => -SIN_TABLE[(angle - 180) as usize],
+        _ => -SIN_TABLE[(360 - angle) as usize],
+    }
+}
+
+// whole pixels moved in a tick, truncated the same way as the float path
+pub fn movement_step(angle: i32, speed: Fixed) -> Vec2i {
+    let truncate = |sin: i32| {
+        let step = sin as i64 * speed.0 as i64;
+        (step.signum() * (step.abs() >> (TABLE_BITS + FRACTION_BITS))) as i32
+    };
+    Vec2i { x: truncate(table_sin(angle)), y: truncate(-table_sin(angle + 90)) }
+}
+
+// the float path counterpart of the truncation in movement_step, float noise like 0.49999997 for
+// cos(60) would otherwise take a whole pixel off every step
+pub fn truncate_step(step: f32) -> i32 {
+    let scale = (1 << TABLE_BITS) as f32;
+    ((step * scale).round() / scale) as i32
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_matches_exact_angles() {
+        assert_eq!(table_sin(30), 1 << (TABLE_BITS - 1));
+        assert_eq!(table_sin(180 + 90), -(1 << TABLE_BITS));
+        assert_eq!(table_sin(-90), -(1 << TABLE_BITS));
+        assert_eq!(movement_step(180, Fixed::from_int(4)), Vec2i::new(0, 4));
+    }
+
+    #[test]
+    fn step_truncates_towards_zero() {
+        assert_eq!(movement_step(60, Fixed::from_int(4)), Vec2i::new(3, -2));
+        assert_eq!(movement_step(1, Fixed::from_int(4)), Vec2i::new(0, -3));
+        assert_eq!(truncate_step(-1.9999999), -2);
+        assert_eq!(truncate_step(-1.9), -1);
+    }
+}
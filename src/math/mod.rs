@@ -1,5 +1,9 @@
+pub mod angles;
+pub mod fixed;
+pub mod polar;
 mod vec2;
 mod rect;
 
 pub type Vec2i = vec2::Vec2i;
+pub type Vec2f = vec2::Vec2f;
 pub type Rect = rect::Rect;
@@ -0,0 +1,53 @@
+use super::Vec2f;
+
+//-----------------------------------------------------------------------------
+
+// the angle is in degrees clockwise from "up", like the shot angles
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PolarVec {
+    pub magnitude: f32,
+    pub angle_deg: f32,
+}
+
+impl PolarVec {
+    pub fn new(magnitude: f32, angle_deg: f32) -> Self {
+        Self { magnitude, angle_deg }
+    }
+
+    // screen y grows downwards, so "up" is negative y
+    pub fn to_cartesian(self) -> Vec2f {
+        let (sin, cos) = self.angle_deg.to_radians().sin_cos();
+        Vec2f { x: self.magnitude * sin, y: -self.magnitude * cos }
+    }
+}
+
+impl Vec2f {
+    // the angle is 0 for the zero vector
+    pub fn to_polar(self) -> PolarVec {
+        // atan2 of a negative zero y would give 180
+        if self.x == 0.0 && self.y == 0.0 {
+            return PolarVec::new(0.0, 0.0);
+        }
+        let angle_deg = self.x.atan2(-self.y).to_degrees().rem_euclid(360.0);
+        PolarVec { magnitude: self.length(), angle_deg }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polar_round_trip() {
+        let vec = Vec2f::new(3.0, -4.0);
+        let polar = vec.to_polar();
+        assert_eq!(polar.magnitude, 5.0);
+        let back = polar.to_cartesian();
+        assert!((back.x - vec.x).abs() < 1e-4 && (back.y - vec.y).abs() < 1e-4);
+        assert_eq!(Vec2f::new(0.0, 0.0).to_polar(), PolarVec::new(0.0, 0.0));
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -1,7 +1,126 @@
 use super::Vec2i;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Rect {
     pub top_left: Vec2i,
     pub bottom_right: Vec2i,
 }
+
+//-----------------------------------------------------------------------------
+
+impl Rect {
+    pub fn center(&self) -> Vec2i {
+        Vec2i {
+            x: (self.top_left.x + self.bottom_right.x) / 2,
+            y: (self.top_left.y + self.bottom_right.y) / 2,
+        }
+    }
+
+    pub fn top_center(&self) -> Vec2i {
+        Vec2i { x: self.center().x, y: self.top_left.y }
+    }
+
+    pub fn bottom_center(&self) -> Vec2i {
+        Vec2i { x: self.center().x, y: self.bottom_right.y }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.bottom_right.x - self.top_left.x
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom_right.y - self.top_left.y
+    }
+
+    // i64, a large rect overflows i32
+    pub fn area(&self) -> i64 {
+        self.width() as i64 * self.height() as i64
+    }
+
+    pub fn perimeter(&self) -> i32 {
+        2 * (self.width() + self.height())
+    }
+
+    // top and bottom parts sharing the edge at y, clamped into the rect
+    pub fn split_horizontal(&self, y: i32) -> (Rect, Rect) {
+        let y = y.clamp(self.top_left.y, self.bottom_right.y);
+        let top = Rect { top_left: self.top_left, bottom_right: Vec2i { x: self.bottom_right.x, y } };
+        let bottom = Rect { top_left: Vec2i { x: self.top_left.x, y }, bottom_right: self.bottom_right };
+        (top, bottom)
+    }
+
+    // left and right parts sharing the edge at x, clamped into the rect
+    pub fn split_vertical(&self, x: i32) -> (Rect, Rect) {
+        let x = x.clamp(self.top_left.x, self.bottom_right.x);
+        let left = Rect { top_left: self.top_left, bottom_right: Vec2i { x, y: self.bottom_right.y } };
+        let right = Rect { top_left: Vec2i { x, y: self.top_left.y }, bottom_right: self.bottom_right };
+        (left, right)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::globals::{boss_arena_rect, set_playfield_inset, set_screen_size};
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+        Rect { top_left: Vec2i::new(left, top), bottom_right: Vec2i::new(right, bottom) }
+    }
+
+    fn corners(rect: &Rect) -> (Vec2i, Vec2i) {
+        (rect.top_left, rect.bottom_right)
+    }
+
+    #[test]
+    fn area_and_perimeter() {
+        let rect = rect(0, 0, 10, 20);
+        assert_eq!(rect.area(), 200);
+        assert_eq!(rect.perimeter(), 60);
+    }
+
+    #[test]
+    fn large_area_doesnt_overflow() {
+        let rect = rect(0, 0, 100_000, 100_000);
+        assert_eq!(rect.area(), 10_000_000_000);
+    }
+
+    #[test]
+    fn horizontal_halves_are_adjacent_and_cover_the_rect() {
+        let whole = rect(10, 20, 110, 220);
+        let (top, bottom) = whole.split_horizontal(100);
+        assert_eq!(top.bottom_right.y, bottom.top_left.y);
+        assert_eq!(corners(&top), (whole.top_left, Vec2i::new(110, 100)));
+        assert_eq!(corners(&bottom), (Vec2i::new(10, 100), whole.bottom_right));
+        assert_eq!(top.height() + bottom.height(), whole.height());
+    }
+
+    #[test]
+    fn vertical_halves_are_adjacent_and_cover_the_rect() {
+        let whole = rect(10, 20, 110, 220);
+        let (left, right) = whole.split_vertical(40);
+        assert_eq!(left.bottom_right.x, right.top_left.x);
+        assert_eq!(corners(&left), (whole.top_left, Vec2i::new(40, 220)));
+        assert_eq!(corners(&right), (Vec2i::new(40, 20), whole.bottom_right));
+        assert_eq!(left.width() + right.width(), whole.width());
+    }
+
+    #[test]
+    fn split_outside_is_clamped() {
+        let whole = rect(0, 0, 100, 100);
+        let (top, bottom) = whole.split_horizontal(-50);
+        assert_eq!(top.height(), 0);
+        assert_eq!(corners(&bottom), corners(&whole));
+        let (left, right) = whole.split_vertical(500);
+        assert_eq!(corners(&left), corners(&whole));
+        assert_eq!(right.width(), 0);
+    }
+
+    #[test]
+    fn boss_arena_is_the_upper_two_fifths() {
+        set_screen_size(Vec2i::new(300, 500));
+        set_playfield_inset(0);
+        assert_eq!(corners(&boss_arena_rect()), (Vec2i::new(0, 0), Vec2i::new(300, 200)));
+    }
+}
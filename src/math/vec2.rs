@@ -1,4 +1,8 @@
-#[derive(Copy, Clone)]
+use serde::{Deserialize, Serialize};
+
+use super::Rect;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Vec2i {
     pub x: i32,
     pub y: i32,
@@ -6,6 +10,63 @@ pub struct Vec2i {
 
 //-----------------------------------------------------------------------------
 
+impl Vec2i {
+    pub fn new(x: i32, y: i32) -> Self {
+        Vec2i { x, y }
+    }
+
+    pub fn zero() -> Self {
+        Vec2i { x: 0, y: 0 }
+    }
+
+    // widened, so it doesn't overflow for any coordinates, only (i32::MIN, i32::MIN) saturates
+    pub fn magnitude_sq(&self) -> i64 {
+        let (x, y) = (self.x as i64, self.y as i64);
+        (x * x).saturating_add(y * y)
+    }
+
+    pub fn distance_to(&self, other: Vec2i) -> f32 {
+        let dx = other.x as f64 - self.x as f64;
+        let dy = other.y as f64 - self.y as f64;
+        dx.hypot(dy) as f32
+    }
+
+    // an axis with min past max collapses to the middle between the two instead of panicking
+    pub fn clamp(self, min: Vec2i, max: Vec2i) -> Vec2i {
+        Vec2i {
+            x: clamp_axis(self.x, min.x, max.x),
+            y: clamp_axis(self.y, min.y, max.y),
+        }
+    }
+
+    // top left position of a square of the given size kept fully inside the rect,
+    // a square bigger than the rect ends up centered on it
+    pub fn clamp_to_rect(self, rect: &Rect, size: i32) -> Vec2i {
+        self.clamp(rect.top_left, rect.bottom_right - size)
+    }
+
+    pub fn rotate_90_cw(&self) -> Vec2i {
+        Vec2i { x: self.y, y: -self.x }
+    }
+
+    pub fn rotate_90_ccw(&self) -> Vec2i {
+        Vec2i { x: -self.y, y: self.x }
+    }
+
+    // positive angles rotate the same way as rotate_90_ccw
+    pub fn rotate_by_angle(&self, angle_deg: i32) -> Vec2i {
+        let (sin, cos) = (angle_deg as f32).to_radians().sin_cos();
+        let x = self.x as f32;
+        let y = self.y as f32;
+        Vec2i {
+            x: (x * cos - y * sin).round() as i32,
+            y: (x * sin + y * cos).round() as i32,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
 impl std::ops::Add<Vec2i> for Vec2i {
     type Output = Vec2i;
 
@@ -47,3 +108,155 @@ impl std::ops::Sub<i32> for Vec2i {
 }
 
 //-----------------------------------------------------------------------------
+
+impl std::ops::Neg for Vec2i {
+    type Output = Vec2i;
+
+    fn neg(self) -> Self::Output {
+        Vec2i { x: -self.x, y: -self.y }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+impl From<(i32, i32)> for Vec2i {
+    fn from((x, y): (i32, i32)) -> Self {
+        Vec2i { x, y }
+    }
+}
+
+impl From<Vec2i> for (i32, i32) {
+    fn from(vec: Vec2i) -> Self {
+        (vec.x, vec.y)
+    }
+}
+
+fn clamp_axis(value: i32, min: i32, max: i32) -> i32 {
+    if min > max {
+        min + (max - min) / 2
+    } else {
+        value.clamp(min, max)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+impl std::fmt::Display for Vec2i {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Vec2f {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2f {
+    pub fn new(x: f32, y: f32) -> Self {
+        Vec2f { x, y }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.x.hypot(self.y)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const PROPERTY_ITERATIONS: usize = 1000;
+    // sums of two coordinates stay in i32
+    const MAX_COORD: i32 = i32::MAX / 2;
+
+    // runs the property on random vectors, seeded so a failure reproduces
+    fn for_random_vecs(property: impl Fn(Vec2i, Vec2i)) {
+        let mut rng = StdRng::seed_from_u64(214);
+        let mut random_vec = || Vec2i::new(rng.gen_range(-MAX_COORD..=MAX_COORD), rng.gen_range(-MAX_COORD..=MAX_COORD));
+        for _ in 0..PROPERTY_ITERATIONS {
+            let (a, b) = (random_vec(), random_vec());
+            property(a, b);
+        }
+    }
+
+    #[test]
+    fn add_is_commutative() {
+        for_random_vecs(|a, b| assert_eq!(a + b, b + a));
+    }
+
+    #[test]
+    fn neg_flips_both_coordinates() {
+        assert_eq!(-Vec2i::new(3, -5), Vec2i::new(-3, 5));
+        assert_eq!(-Vec2i::zero(), Vec2i::zero());
+        for_random_vecs(|a, _| assert_eq!(-(-a), a));
+    }
+
+    #[test]
+    fn add_negated_is_zero() {
+        for_random_vecs(|a, _| assert_eq!(a + -a, Vec2i::zero()));
+    }
+
+    #[test]
+    fn magnitude_sq_is_never_negative() {
+        for_random_vecs(|a, _| assert!(a.magnitude_sq() >= 0));
+        assert!(Vec2i::new(i32::MIN, i32::MIN).magnitude_sq() >= 0);
+    }
+
+    #[test]
+    fn clamp_stays_within_bounds() {
+        for_random_vecs(|a, b| {
+            let min = Vec2i::new(a.x.min(b.x), a.y.min(b.y));
+            let max = Vec2i::new(a.x.max(b.x), a.y.max(b.y));
+            let clamped = (a + b).clamp(min, max);
+            assert!(clamped.x >= min.x && clamped.x <= max.x);
+            assert!(clamped.y >= min.y && clamped.y <= max.y);
+        });
+    }
+
+    #[test]
+    fn distance_to_self_is_zero() {
+        for_random_vecs(|a, _| assert_eq!(a.distance_to(a), 0.0));
+        assert_eq!(Vec2i::new(i32::MIN, i32::MAX).distance_to(Vec2i::new(i32::MIN, i32::MAX)), 0.0);
+    }
+
+    fn rect() -> Rect {
+        Rect { top_left: Vec2i::new(0, 0), bottom_right: Vec2i::new(100, 50) }
+    }
+
+    #[test]
+    fn point_inside_stays_unchanged() {
+        let point = Vec2i::new(30, 20);
+        assert_eq!(point.clamp(Vec2i::new(0, 0), Vec2i::new(100, 50)), point);
+    }
+
+    #[test]
+    fn point_outside_snaps_to_nearest_edge() {
+        let (min, max) = (Vec2i::new(0, 0), Vec2i::new(100, 50));
+        assert_eq!(Vec2i::new(-10, 20).clamp(min, max), Vec2i::new(0, 20));
+        assert_eq!(Vec2i::new(130, 80).clamp(min, max), Vec2i::new(100, 50));
+    }
+
+    #[test]
+    fn inverted_bounds_collapse_to_middle() {
+        assert_eq!(Vec2i::new(7, 7).clamp(Vec2i::new(10, 0), Vec2i::new(0, 10)), Vec2i::new(5, 7));
+    }
+
+    #[test]
+    fn clamp_to_rect_keeps_whole_square_inside() {
+        assert_eq!(Vec2i::new(95, 45).clamp_to_rect(&rect(), 10), Vec2i::new(90, 40));
+        assert_eq!(Vec2i::new(-5, 10).clamp_to_rect(&rect(), 10), Vec2i::new(0, 10));
+    }
+
+    #[test]
+    fn square_bigger_than_rect_is_centered() {
+        assert_eq!(Vec2i::new(0, 0).clamp_to_rect(&rect(), 60).y, -5);
+    }
+}
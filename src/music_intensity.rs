@@ -0,0 +1,137 @@
+use crate::events::GameEvent;
+
+//-----------------------------------------------------------------------------
+
+// without a boss the level is calm
+const NO_BOSS_INTENSITY: f32 = 0.2;
+const DAMAGE_BUMP: f32 = 0.2;
+// the damage bump fades out in about 3 seconds
+const DAMAGE_DECAY_PER_TICK: f32 = 1.0 / (3.0 * 60.0);
+const LOW_HP_BUMP: f32 = 0.15;
+// intros, dialogues and pause
+const CALM_FACTOR: f32 = 0.5;
+// 0.1 per second at 60 ticks per second
+const MAX_CHANGE_PER_TICK: f32 = 0.1 / 60.0;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct IntensityInputs {
+    // hint of the current boss stage, None without a boss
+    pub stage_hint: Option<f32>,
+    pub low_hp: bool,
+    // intro, dialogue, cutscene or pause
+    pub calm: bool,
+}
+
+// target = (stage hint or 0.2) + 0.2 * recent damage + 0.15 on low hp, halved while calm, clamped to 0..1
+// the value follows the target by at most 0.1 per second
+pub struct MusicIntensity {
+    value: f32,
+    // 1.0 right after a hit, decays to 0.0
+    recent_damage: f32,
+}
+
+impl MusicIntensity {
+    pub fn new() -> Self {
+        Self { value: NO_BOSS_INTENSITY * CALM_FACTOR, recent_damage: 0.0 }
+    }
+
+    pub fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::PlayerHit = event {
+            self.recent_damage = 1.0;
+        }
+    }
+
+    pub fn target(&self, inputs: &IntensityInputs) -> f32 {
+        let mut target = inputs.stage_hint.unwrap_or(NO_BOSS_INTENSITY) + DAMAGE_BUMP * self.recent_damage;
+        if inputs.low_hp {
+            target += LOW_HP_BUMP;
+        }
+        if inputs.calm {
+            target *= CALM_FACTOR;
+        }
+        target.clamp(0.0, 1.0)
+    }
+
+    pub fn update(&mut self, inputs: &IntensityInputs) {
+        let target = self.target(inputs);
+        self.value += (target - self.value).clamp(-MAX_CHANGE_PER_TICK, MAX_CHANGE_PER_TICK);
+        self.recent_damage = (self.recent_damage - DAMAGE_DECAY_PER_TICK).max(0.0);
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settle(intensity: &mut MusicIntensity, inputs: &IntensityInputs) {
+        // 10 seconds is enough to cross the whole 0..1 range
+        for _ in 0..600 {
+            intensity.update(inputs);
+        }
+    }
+
+    #[test]
+    fn never_moves_faster_than_the_bound() {
+        let mut intensity = MusicIntensity::new();
+        let inputs = IntensityInputs { stage_hint: Some(0.9), low_hp: true, calm: false };
+        for _ in 0..600 {
+            let before = intensity.value();
+            intensity.update(&inputs);
+            assert!((intensity.value() - before).abs() <= MAX_CHANGE_PER_TICK + 1e-6);
+        }
+        // 0.1 per second, so a second after a drop to calm it's at most 0.1 lower
+        let top = intensity.value();
+        let calm = IntensityInputs { stage_hint: None, low_hp: false, calm: true };
+        for _ in 0..60 {
+            intensity.update(&calm);
+        }
+        assert!(top - intensity.value() <= 0.1 + 1e-4);
+    }
+
+    #[test]
+    fn plateaus_at_the_stage_hint() {
+        let mut intensity = MusicIntensity::new();
+        for hint in [0.2, 0.3, 0.4, 0.6, 0.8, 0.9] {
+            settle(&mut intensity, &IntensityInputs { stage_hint: Some(hint), low_hp: false, calm: false });
+            assert!((intensity.value() - hint).abs() < 1e-4, "{} != {hint}", intensity.value());
+        }
+        settle(&mut intensity, &IntensityInputs::default());
+        assert!((intensity.value() - NO_BOSS_INTENSITY).abs() < 1e-4);
+    }
+
+    #[test]
+    fn low_hp_adds_and_calm_halves() {
+        let intensity = MusicIntensity::new();
+        let fight = IntensityInputs { stage_hint: Some(0.6), low_hp: true, calm: false };
+        assert!((intensity.target(&fight) - 0.75).abs() < 1e-6);
+        let calm = IntensityInputs { calm: true, ..fight };
+        assert!((intensity.target(&calm) - 0.375).abs() < 1e-6);
+        // clamped at the top
+        let peak = IntensityInputs { stage_hint: Some(0.9), ..fight };
+        assert_eq!(intensity.target(&peak), 1.0);
+    }
+
+    #[test]
+    fn hit_bump_decays_in_three_seconds() {
+        let mut intensity = MusicIntensity::new();
+        let inputs = IntensityInputs { stage_hint: Some(0.4), low_hp: false, calm: false };
+        intensity.on_event(&GameEvent::PlayerHit);
+        assert!((intensity.target(&inputs) - 0.6).abs() < 1e-6);
+        for _ in 0..90 {
+            intensity.update(&inputs);
+        }
+        assert!((intensity.target(&inputs) - 0.5).abs() < 1e-3);
+        for _ in 0..90 {
+            intensity.update(&inputs);
+        }
+        assert!((intensity.target(&inputs) - 0.4).abs() < 1e-3);
+    }
+}
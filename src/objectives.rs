@@ -0,0 +1,365 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+use crate::run_summary::RunStats;
+use crate::storage::{self, StorageError};
+
+//-----------------------------------------------------------------------------
+
+const OBJECTIVES_FILE: &str = "objectives.toml";
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ObjectiveEvent {
+    CometDestroyed,
+    BombUsed,
+    PlayerHit,
+    Graze,
+    PickupCollected,
+}
+
+impl ObjectiveEvent {
+    fn matches(&self, event: &GameEvent) -> bool {
+        matches!(
+            (self, event),
+            (ObjectiveEvent::CometDestroyed, GameEvent::CometDestroyed)
+                | (ObjectiveEvent::BombUsed, GameEvent::BombUsed)
+                | (ObjectiveEvent::PlayerHit, GameEvent::PlayerHit)
+                | (ObjectiveEvent::Graze, GameEvent::Graze { .. })
+                | (ObjectiveEvent::PickupCollected, GameEvent::PickupCollected)
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ObjectiveStat {
+    // in percents
+    Accuracy,
+    DamageTaken,
+    PickupsCollected,
+}
+
+impl ObjectiveStat {
+    fn value(&self, stats: &RunStats) -> f32 {
+        match self {
+            ObjectiveStat::Accuracy => stats.accuracy() * 100.0,
+            ObjectiveStat::DamageTaken => stats.damage_taken as f32,
+            ObjectiveStat::PickupsCollected => stats.pickups_collected as f32,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Predicate {
+    // the event happens at least target times
+    Count { event: ObjectiveEvent, target: u32 },
+    // checked once the level ends
+    AtLeast { stat: ObjectiveStat, value: f32 },
+    AtMost { stat: ObjectiveStat, value: f32 },
+    // the event never happens
+    Never { event: ObjectiveEvent },
+}
+
+// optional goal of a level, completing it gives the bonus score
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Objective {
+    pub id: &'static str,
+    pub text: &'static str,
+    pub predicate: Predicate,
+    pub bonus: u32,
+}
+
+impl Objective {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() {
+            return Err(format!("objective \"{}\" has no id", self.text));
+        }
+        match self.predicate {
+            Predicate::Count { target: 0, .. } => Err(format!("objective {} has a zero target", self.id)),
+            Predicate::AtLeast { value, .. } | Predicate::AtMost { value, .. } if !value.is_finite() || value < 0.0 => {
+                Err(format!("objective {} has an invalid threshold {}", self.id, value))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ObjectiveStatus {
+    InProgress,
+    Completed,
+    Failed,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ObjectiveView {
+    pub text: &'static str,
+    pub progress: u32,
+    pub target: u32,
+    pub status: ObjectiveStatus,
+}
+
+impl fmt::Display for ObjectiveView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}/{} {:?}", self.text, self.progress, self.target, self.status)
+    }
+}
+
+struct ObjectiveTracker {
+    objective: Objective,
+    count: u32,
+    status: ObjectiveStatus,
+}
+
+impl ObjectiveTracker {
+    fn on_event(&mut self, event: &GameEvent) {
+        if self.status != ObjectiveStatus::InProgress {
+            return;
+        }
+        match self.objective.predicate {
+            Predicate::Count { event: kind, target } if kind.matches(event) => {
+                self.count += 1;
+                if self.count >= target {
+                    self.status = ObjectiveStatus::Completed;
+                }
+            }
+            Predicate::Never { event: kind } if kind.matches(event) => self.status = ObjectiveStatus::Failed,
+            _ => (),
+        }
+    }
+
+    fn finalize(&mut self, stats: &RunStats) {
+        if self.status != ObjectiveStatus::InProgress {
+            return;
+        }
+        let completed = match self.objective.predicate {
+            Predicate::Count { .. } => false,
+            Predicate::AtLeast { stat, value } => stat.value(stats) >= value,
+            Predicate::AtMost { stat, value } => stat.value(stats) <= value,
+            Predicate::Never { .. } => true,
+        };
+        self.status = if completed { ObjectiveStatus::Completed } else { ObjectiveStatus::Failed };
+    }
+
+    fn view(&self) -> ObjectiveView {
+        let target = match self.objective.predicate {
+            Predicate::Count { target, .. } => target,
+            _ => 1,
+        };
+        let progress = match self.objective.predicate {
+            Predicate::Count { .. } => self.count.min(target),
+            _ => (self.status == ObjectiveStatus::Completed) as u32,
+        };
+        ObjectiveView { text: self.objective.text, progress, target, status: self.status }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// evaluates the level objectives from the game events, the result is settled once at the level end
+pub struct Objectives {
+    trackers: Vec<ObjectiveTracker>,
+    finalized: bool,
+}
+
+impl Objectives {
+    // invalid objectives are dropped
+    pub fn new(objectives: Vec<Objective>) -> Self {
+        let trackers = objectives.into_iter()
+            .filter(|objective| match objective.validate() {
+                Ok(()) => true,
+                Err(err) => {
+                    log::warn!("Skipping level objective: {}", err);
+                    false
+                }
+            })
+            .map(|objective| ObjectiveTracker { objective, count: 0, status: ObjectiveStatus::InProgress })
+            .collect();
+        Self { trackers, finalized: false }
+    }
+
+    pub fn none() -> Self {
+        Self::new(vec![])
+    }
+
+    pub fn on_event(&mut self, event: &GameEvent) {
+        if !self.finalized {
+            self.trackers.iter_mut().for_each(|tracker| tracker.on_event(event));
+        }
+    }
+
+    // the completed objectives, empty when already finalized
+    pub fn finalize(&mut self, stats: &RunStats) -> Vec<Objective> {
+        if self.finalized {
+            return vec![];
+        }
+        self.finalized = true;
+
+        self.trackers.iter_mut().for_each(|tracker| tracker.finalize(stats));
+        self.trackers.iter()
+            .filter(|tracker| tracker.status == ObjectiveStatus::Completed)
+            .map(|tracker| tracker.objective)
+            .collect()
+    }
+
+    pub fn views(&self) -> Vec<ObjectiveView> {
+        self.trackers.iter().map(|tracker| tracker.view()).collect()
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct LevelObjectives {
+    level: i32,
+    completed: Vec<String>,
+}
+
+// objectives ever completed, per level
+#[derive(Default, Serialize, Deserialize)]
+pub struct ObjectiveProgress {
+    levels: Vec<LevelObjectives>,
+}
+
+impl ObjectiveProgress {
+    pub fn load() -> Self {
+        match storage::load::<ObjectiveProgress>(OBJECTIVES_FILE) {
+            Ok(progress) => progress,
+            Err(StorageError::NotFound(_)) => ObjectiveProgress::default(),
+            Err(err) => {
+                log::warn!("Failed to load objectives progress: {}", err);
+                ObjectiveProgress::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), StorageError> {
+        storage::save(OBJECTIVES_FILE, self)
+    }
+
+    pub fn completed(&self, level: i32, id: &str) -> bool {
+        self.levels.iter().any(|entry| entry.level == level && entry.completed.iter().any(|done| done == id))
+    }
+
+    // returns false if it was already completed before
+    pub fn mark_completed(&mut self, level: i32, id: &str) -> bool {
+        if self.completed(level, id) {
+            return false;
+        }
+        match self.levels.iter_mut().find(|entry| entry.level == level) {
+            Some(entry) => entry.completed.push(id.to_string()),
+            None => self.levels.push(LevelObjectives { level, completed: vec![id.to_string()] }),
+        }
+        true
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn objective(id: &'static str, predicate: Predicate) -> Objective {
+        Objective { id, text: "test objective", predicate, bonus: 100 }
+    }
+
+    fn ids(objectives: &[Objective]) -> Vec<&'static str> {
+        objectives.iter().map(|objective| objective.id).collect()
+    }
+
+    fn stats(shots_fired: u32, shots_hit: u32) -> RunStats {
+        RunStats { shots_fired, shots_hit, ..RunStats::default() }
+    }
+
+    #[test]
+    fn counter_completes_at_target() {
+        let mut objectives = Objectives::new(vec![
+            objective("comets", Predicate::Count { event: ObjectiveEvent::CometDestroyed, target: 3 }),
+        ]);
+        for _ in 0..2 {
+            objectives.on_event(&GameEvent::CometDestroyed);
+            objectives.on_event(&GameEvent::BombUsed);
+        }
+        assert_eq!(objectives.views()[0].progress, 2);
+        assert_eq!(objectives.views()[0].status, ObjectiveStatus::InProgress);
+        assert_eq!(objectives.views()[0].to_string(), "test objective 2/3 InProgress");
+
+        objectives.on_event(&GameEvent::CometDestroyed);
+        assert_eq!(objectives.views()[0].status, ObjectiveStatus::Completed);
+        assert_eq!(ids(&objectives.finalize(&RunStats::default())), vec!["comets"]);
+    }
+
+    #[test]
+    fn unfinished_counter_fails_at_level_end() {
+        let mut objectives = Objectives::new(vec![
+            objective("comets", Predicate::Count { event: ObjectiveEvent::CometDestroyed, target: 3 }),
+        ]);
+        objectives.on_event(&GameEvent::CometDestroyed);
+        assert!(objectives.finalize(&RunStats::default()).is_empty());
+        assert_eq!(objectives.views()[0].status, ObjectiveStatus::Failed);
+    }
+
+    #[test]
+    fn never_fails_on_first_event() {
+        let no_bombs = objective("no_bombs", Predicate::Never { event: ObjectiveEvent::BombUsed });
+
+        let mut clean = Objectives::new(vec![no_bombs]);
+        clean.on_event(&GameEvent::CometDestroyed);
+        assert_eq!(ids(&clean.finalize(&RunStats::default())), vec!["no_bombs"]);
+
+        let mut bombed = Objectives::new(vec![no_bombs]);
+        bombed.on_event(&GameEvent::BombUsed);
+        assert_eq!(bombed.views()[0].status, ObjectiveStatus::Failed);
+        assert!(bombed.finalize(&RunStats::default()).is_empty());
+    }
+
+    #[test]
+    fn thresholds_are_checked_at_level_end() {
+        let accuracy = objective("accuracy", Predicate::AtLeast { stat: ObjectiveStat::Accuracy, value: 80.0 });
+        let unhurt = objective("unhurt", Predicate::AtMost { stat: ObjectiveStat::DamageTaken, value: 0.0 });
+
+        let mut objectives = Objectives::new(vec![accuracy, unhurt]);
+        assert_eq!(ids(&objectives.finalize(&stats(10, 8))), vec!["accuracy", "unhurt"]);
+
+        let mut objectives = Objectives::new(vec![accuracy, unhurt]);
+        let hurt = RunStats { damage_taken: 10, ..stats(10, 7) };
+        assert!(objectives.finalize(&hurt).is_empty());
+    }
+
+    #[test]
+    fn finalized_only_once() {
+        let mut objectives = Objectives::new(vec![
+            objective("no_bombs", Predicate::Never { event: ObjectiveEvent::BombUsed }),
+        ]);
+        assert_eq!(objectives.finalize(&RunStats::default()).len(), 1);
+        objectives.on_event(&GameEvent::BombUsed);
+        assert!(objectives.finalize(&RunStats::default()).is_empty());
+        assert_eq!(objectives.views()[0].status, ObjectiveStatus::Completed);
+    }
+
+    #[test]
+    fn invalid_objectives_are_dropped() {
+        let objectives = Objectives::new(vec![
+            objective("", Predicate::Never { event: ObjectiveEvent::BombUsed }),
+            objective("zero", Predicate::Count { event: ObjectiveEvent::Graze, target: 0 }),
+            objective("nan", Predicate::AtLeast { stat: ObjectiveStat::Accuracy, value: f32::NAN }),
+            objective("ok", Predicate::Never { event: ObjectiveEvent::PlayerHit }),
+        ]);
+        assert_eq!(objectives.views().len(), 1);
+    }
+
+    #[test]
+    fn completion_is_stored_per_level() {
+        let mut progress = ObjectiveProgress::default();
+        assert!(progress.mark_completed(1, "no_bombs"));
+        assert!(!progress.mark_completed(1, "no_bombs"));
+        assert!(progress.completed(1, "no_bombs"));
+        assert!(!progress.completed(2, "no_bombs"));
+    }
+}
@@ -0,0 +1,132 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+//-----------------------------------------------------------------------------
+
+const APP_DIR_NAME: &str = "space";
+const ASSETS_DIR_NAME: &str = "assets";
+
+// points all the directories to a single root, used by portable installs and tests
+const ROOT_OVERRIDE_VAR: &str = "SPACE_HOME";
+
+static mut G_PORTABLE: bool = false;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug)]
+pub enum DirKind {
+    Config,
+    Data,
+}
+
+impl DirKind {
+    fn subdir_name(&self) -> &'static str {
+        match self {
+            DirKind::Config => "config",
+            DirKind::Data => "data",
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub struct PathError {
+    pub kind: DirKind,
+    pub path: Option<PathBuf>,
+    pub source: Option<io::Error>,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.path, &self.source) {
+            (Some(path), Some(err)) if err.kind() == io::ErrorKind::PermissionDenied => {
+                write!(f, "no permission to create {:?} directory {}", self.kind, path.display())
+            }
+            (Some(path), Some(err)) => {
+                write!(f, "failed to create {:?} directory {}: {}", self.kind, path.display(), err)
+            }
+            _ => write!(f, "unable to locate {:?} directory, set {} to override", self.kind, ROOT_OVERRIDE_VAR),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+pub fn set_portable(portable: bool) {
+    unsafe {
+        G_PORTABLE = portable;
+    }
+}
+
+pub fn portable() -> bool {
+    unsafe {
+        G_PORTABLE
+    }
+}
+
+pub fn config_dir() -> Result<PathBuf, PathError> {
+    ensure_dir(DirKind::Config)
+}
+
+pub fn data_dir() -> Result<PathBuf, PathError> {
+    ensure_dir(DirKind::Data)
+}
+
+// read-only files shipped with the game, next to the executable, or in the source tree for cargo runs
+pub fn assets_dir() -> PathBuf {
+    let exe_dir = env::current_exe().ok().and_then(|exe| exe.parent().map(|dir| dir.join(ASSETS_DIR_NAME)));
+    match exe_dir {
+        Some(dir) if dir.is_dir() => dir,
+        _ => PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(ASSETS_DIR_NAME),
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+fn ensure_dir(kind: DirKind) -> Result<PathBuf, PathError> {
+    let path = resolve(kind).ok_or(PathError { kind, path: None, source: None })?;
+    fs::create_dir_all(&path).map_err(|err| PathError { kind, path: Some(path.clone()), source: Some(err) })?;
+    Ok(path)
+}
+
+fn resolve(kind: DirKind) -> Option<PathBuf> {
+    if portable() {
+        let exe_dir = env::current_exe().ok()?.parent()?.to_path_buf();
+        return Some(exe_dir.join(kind.subdir_name()));
+    }
+
+    if let Some(root) = non_empty_var(ROOT_OVERRIDE_VAR) {
+        return Some(root.join(kind.subdir_name()));
+    }
+
+    platform_dir(kind).map(|dir| dir.join(APP_DIR_NAME))
+}
+
+#[cfg(target_os = "windows")]
+fn platform_dir(_kind: DirKind) -> Option<PathBuf> {
+    non_empty_var("APPDATA")
+}
+
+#[cfg(target_os = "macos")]
+fn platform_dir(_kind: DirKind) -> Option<PathBuf> {
+    Some(non_empty_var("HOME")?.join("Library").join("Application Support"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn platform_dir(kind: DirKind) -> Option<PathBuf> {
+    let (xdg_var, home_fallback) = match kind {
+        DirKind::Config => ("XDG_CONFIG_HOME", ".config"),
+        DirKind::Data => ("XDG_DATA_HOME", ".local/share"),
+    };
+    non_empty_var(xdg_var).or_else(|| non_empty_var("HOME").map(|home| home.join(home_fallback)))
+}
+
+fn non_empty_var(name: &str) -> Option<PathBuf> {
+    env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
+
+//-----------------------------------------------------------------------------
@@ -1,26 +1,191 @@
 use std::rc::Rc;
 
-use crate::constants::SHIP_MOVE_STEP;
+use crate::constants::BOMB_ENERGY_COST;
 use crate::entities::shape::Shaped;
 use crate::entities::ship::Ship;
+use crate::entities::shot::Shot;
+use crate::input::{Action, Aim, InputBuffer, InputState, DEFAULT_BUFFER_TICKS};
 use crate::input_mgr::InputMgr;
+use crate::math::angles::angle_to_target;
+
+// aiming is limited to the forward facing arc, the ship can't shoot backwards
+const MAX_AIM_ANGLE: f32 = 75.0;
 
 pub struct PlayerShipController {
-    input_mgr: Rc<InputMgr>
+    input_mgr: Rc<InputMgr>,
+    buffer: InputBuffer,
+    aim_mode: bool,
 }
 
 impl PlayerShipController {
     pub fn new(input_mgr: Rc<InputMgr>) -> Self {
-        Self { input_mgr }
+        Self {
+            input_mgr,
+            buffer: InputBuffer::new(DEFAULT_BUFFER_TICKS),
+            aim_mode: false,
+        }
+    }
+
+    pub fn set_aim_mode(&mut self, aim_mode: bool) {
+        self.aim_mode = aim_mode;
+    }
+
+    pub fn input(&self) -> InputState {
+        self.input_mgr.state()
+    }
+
+    pub fn fire_pressed(&self) -> bool {
+        self.input_mgr.state().fire.pressed
+    }
+
+    pub fn cycle_lock_pressed(&self) -> bool {
+        self.input_mgr.state().cycle_lock.pressed
     }
 
-    pub fn update(&self, ship: &mut Ship) {
+    pub fn pause_toggled(&self) -> bool {
+        self.input_mgr.state().pause.pressed
+    }
+
+    // the bindings live in the input manager, the menu reaches them through here
+    pub fn start_rebinding(&self, action: Action) {
+        self.input_mgr.start_rebinding(action);
+    }
+
+    pub fn reset_bindings(&self) {
+        self.input_mgr.reset_bindings();
+    }
+
+    pub fn clear_buffer(&mut self) {
+        self.buffer.clear();
+    }
+
+    pub fn update(&mut self, ship: &mut Ship) -> Option<Shot> {
+        let input = self.input_mgr.state();
+        self.buffer.record(&input);
+
+        ship.steer(input.axis.to_8_way());
+        let offset = ship.move_offset();
         let shape = ship.shape_mut();
-        let mut new_pos = shape.pos();
-        new_pos.x -= SHIP_MOVE_STEP * self.input_mgr.is_pressed('A') as i32;
-        new_pos.x += SHIP_MOVE_STEP * self.input_mgr.is_pressed('D') as i32;
-        new_pos.y += SHIP_MOVE_STEP * self.input_mgr.is_pressed('W') as i32;
-        new_pos.y -= SHIP_MOVE_STEP * self.input_mgr.is_pressed('S') as i32;
-        shape.set_pos(new_pos);
+        shape.set_pos(shape.pos() + offset);
+
+        if input.fire.held {
+            return match input.aim.filter(|_| self.aim_mode) {
+                Some(aim) => Some(ship.shoot_at(PlayerShipController::aim_angle(ship, aim))),
+                None => Some(ship.shoot()),
+            };
+        }
+        None
+    }
+
+    fn aim_angle(ship: &Ship, aim: Aim) -> i32 {
+        let angle = match aim {
+            Aim::Point(point) => angle_to_target(ship.muzzle(), point),
+            Aim::Direction(direction) => direction.x.atan2(-direction.y).to_degrees(),
+        };
+        if !angle.is_finite() {
+            return 0;
+        }
+
+        // to (-180, 180], so the clamp is symmetric around "up"
+        let signed_angle = (angle + 180.0).rem_euclid(360.0) - 180.0;
+        signed_angle.clamp(-MAX_AIM_ANGLE, MAX_AIM_ANGLE).round() as i32
+    }
+
+    // a press buffered slightly before the cooldown ends isn't lost, without a direction it dashes forward
+    pub fn take_dash(&mut self, ship: &mut Ship) -> bool {
+        if !self.buffer.buffered(Action::Dash) || !ship.can_dash() {
+            return false;
+        }
+
+        let mut direction = self.input_mgr.state().axis.to_8_way();
+        if direction.x == 0 && direction.y == 0 {
+            direction.y = -1;
+        }
+        ship.dash(direction) && self.buffer.consume(Action::Dash)
+    }
+
+    // a buffered press waits until the ship has enough energy
+    pub fn take_bomb(&mut self, ship: &mut Ship) -> bool {
+        if self.buffer.buffered(Action::Bomb) && ship.use_bomb(BOMB_ENERGY_COST) {
+            return self.buffer.consume(Action::Bomb);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    use crate::constants::DASH_COOLDOWN;
+    use crate::entities::shape::Shape;
+    use crate::input::{Bindings, SourceInput};
+    use crate::input::test_support::ScriptedSource;
+    use crate::math::Vec2i;
+    use crate::timing::{advance_game_clock, duration_to_ticks};
+
+    fn controller() -> (PlayerShipController, Rc<InputMgr>, Rc<Cell<SourceInput>>) {
+        let script = Rc::new(Cell::new(SourceInput::default()));
+        let input_mgr = Rc::new(InputMgr::from_sources(Bindings::new(), vec![Box::new(ScriptedSource(Rc::clone(&script)))]));
+        (PlayerShipController::new(Rc::clone(&input_mgr)), input_mgr, script)
+    }
+
+    fn tick(controller: &mut PlayerShipController, input_mgr: &InputMgr, ship: &mut Ship) {
+        advance_game_clock();
+        input_mgr.update();
+        controller.update(ship);
+    }
+
+    fn ship() -> Ship {
+        Ship::new(Shape::new(Vec2i::new(100, 100), 10), 100, 100)
+    }
+
+    #[test]
+    fn dash_pressed_before_cooldown_ends_is_buffered() {
+        let (mut controller, input_mgr, script) = controller();
+        let mut ship = ship();
+        assert!(ship.dash(Vec2i::new(0, -1)));
+
+        for _ in 0..duration_to_ticks(DASH_COOLDOWN) - 3 {
+            advance_game_clock();
+        }
+        script.set(SourceInput { dash: true, ..Default::default() });
+        tick(&mut controller, &input_mgr, &mut ship);
+        assert!(!controller.take_dash(&mut ship));
+
+        script.set(SourceInput::default());
+        let dashed = (0..DEFAULT_BUFFER_TICKS).any(|_| {
+            tick(&mut controller, &input_mgr, &mut ship);
+            controller.take_dash(&mut ship)
+        });
+        assert!(dashed);
+    }
+
+    #[test]
+    fn held_bomb_is_a_single_press() {
+        let (mut controller, input_mgr, script) = controller();
+        let mut ship = ship();
+        script.set(SourceInput { bomb: true, ..Default::default() });
+
+        let mut bombs = 0;
+        for _ in 0..DEFAULT_BUFFER_TICKS * 2 {
+            tick(&mut controller, &input_mgr, &mut ship);
+            bombs += controller.take_bomb(&mut ship) as i32;
+        }
+        assert_eq!(bombs, 1);
+    }
+
+    #[test]
+    fn cleared_buffer_doesnt_fire_after_pause() {
+        let (mut controller, input_mgr, script) = controller();
+        let mut ship = ship();
+        script.set(SourceInput { bomb: true, ..Default::default() });
+        tick(&mut controller, &input_mgr, &mut ship);
+
+        // pausing clears the buffer, the bomb still held on resume isn't a new press
+        controller.clear_buffer();
+        tick(&mut controller, &input_mgr, &mut ship);
+        assert!(!controller.take_bomb(&mut ship));
     }
 }
@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use crate::entities::shot::Shot;
+use crate::timing::GameInstant;
+
+//-----------------------------------------------------------------------------
+
+pub const PIERCING_SHOT_DURATION: Duration = Duration::from_secs(8);
+pub const PIERCING_SHOT_MAX_HITS: u8 = 3;
+
+pub const REFLECTOR_DURATION: Duration = Duration::from_secs(8);
+pub const REFLECTOR_MAX_REFLECTIONS: u8 = 10;
+
+const SHIELD_BUBBLE_DAMAGE_MULTIPLIER: i32 = 2;
+const ANGLE_UP: i32 = 0;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PowerUpKind {
+    PiercingShots,
+    Reflector,
+    ShieldBubble,
+}
+
+//-----------------------------------------------------------------------------
+
+// player shots fired while active pass through several targets
+#[derive(Copy, Clone, Debug)]
+pub struct PiercingShotPowerUp {
+    end: GameInstant,
+}
+
+impl PiercingShotPowerUp {
+    pub fn new(duration: Duration) -> Self {
+        Self { end: GameInstant::now() + duration }
+    }
+
+    pub fn active(&self) -> bool {
+        self.active_at(GameInstant::now())
+    }
+
+    pub fn active_at(&self, now: GameInstant) -> bool {
+        now < self.end
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// boss shots grazing the ship while active are sent back, up to a number of reflections
+#[derive(Copy, Clone, Debug)]
+pub struct ReflectorPowerUp {
+    end: GameInstant,
+    reflections_left: u8,
+}
+
+impl ReflectorPowerUp {
+    pub fn new(duration: Duration, max_reflections: u8) -> Self {
+        Self { end: GameInstant::now() + duration, reflections_left: max_reflections }
+    }
+
+    pub fn active(&self) -> bool {
+        GameInstant::now() < self.end && self.reflections_left > 0
+    }
+
+    pub fn time_remaining(&self) -> Duration {
+        self.end.saturating_duration_since(GameInstant::now())
+    }
+
+    // uses up one reflection, false once expired or used up
+    pub fn try_reflect(&mut self) -> bool {
+        if !self.active() {
+            return false;
+        }
+        self.reflections_left -= 1;
+        true
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// single use, the first boss shot reaching the ship is sent straight up instead of hurting it
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ShieldBubble;
+
+impl ShieldBubble {
+    pub fn reflect(self, mut shot: Shot) -> Shot {
+        let damage = shot.damage() * SHIELD_BUBBLE_DAMAGE_MULTIPLIER;
+        shot.reflect(ANGLE_UP);
+        shot.with_damage(damage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::{advance_game_clock, duration_to_ticks};
+
+    #[test]
+    fn reflector_stops_at_the_cap() {
+        let mut reflector = ReflectorPowerUp::new(REFLECTOR_DURATION, REFLECTOR_MAX_REFLECTIONS);
+        for _ in 0..REFLECTOR_MAX_REFLECTIONS {
+            assert!(reflector.try_reflect());
+        }
+        assert!(!reflector.try_reflect());
+        assert!(!reflector.active());
+    }
+
+    #[test]
+    fn reflector_expires() {
+        let mut reflector = ReflectorPowerUp::new(REFLECTOR_DURATION, REFLECTOR_MAX_REFLECTIONS);
+        for _ in 0..duration_to_ticks(REFLECTOR_DURATION) - 1 {
+            advance_game_clock();
+        }
+        assert!(reflector.active());
+        advance_game_clock();
+        assert!(!reflector.active());
+        assert_eq!(reflector.time_remaining(), Duration::ZERO);
+        assert!(!reflector.try_reflect());
+    }
+}
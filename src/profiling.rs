@@ -0,0 +1,269 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+//-----------------------------------------------------------------------------
+
+pub const DEFAULT_SLOW_FRAME_THRESHOLD: Duration = Duration::from_millis(20);
+
+const PHASES_COUNT: usize = 7;
+
+// about two seconds of frames
+pub const DEFAULT_FRAME_TIMING_WINDOW: usize = 120;
+const FRAME_SPIKE_FACTOR: u32 = 2;
+
+//-----------------------------------------------------------------------------
+
+// times the given expression into the profiler phase, with the metrics feature off it's just the expression
+#[macro_export]
+macro_rules! profile_phase {
+    ($profiler:expr, $phase:expr, $body:expr) => {{
+        let _phase: $crate::profiling::Phase = $phase;
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+        let result = $body;
+        #[cfg(feature = "metrics")]
+        $profiler.record(_phase, start.elapsed());
+        result
+    }};
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Phase {
+    Ship,
+    Boss,
+    Shots,
+    Collision,
+    Particles,
+    Spawner,
+    Events,
+}
+
+impl Phase {
+    pub const ALL: [Phase; PHASES_COUNT] = [
+        Phase::Ship,
+        Phase::Boss,
+        Phase::Shots,
+        Phase::Collision,
+        Phase::Particles,
+        Phase::Spawner,
+        Phase::Events,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Ship => "ship",
+            Phase::Boss => "boss",
+            Phase::Shots => "shots",
+            Phase::Collision => "collision",
+            Phase::Particles => "particles",
+            Phase::Spawner => "spawner",
+            Phase::Events => "events",
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Default)]
+pub struct PhaseTimings {
+    phases: [Duration; PHASES_COUNT],
+}
+
+impl PhaseTimings {
+    pub fn get(&self, phase: Phase) -> Duration {
+        self.phases[phase as usize]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().sum()
+    }
+
+    fn add(&mut self, phase: Phase, time: Duration) {
+        self.phases[phase as usize] += time;
+    }
+}
+
+impl std::fmt::Display for PhaseTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, phase) in Phase::ALL.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}={:?}", phase.name(), self.get(*phase))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EntityCounts {
+    pub comets: usize,
+    pub player_shots: usize,
+    pub boss_shots: usize,
+    pub particles: usize,
+}
+
+impl std::fmt::Display for EntityCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "comets={} player_shots={} boss_shots={} particles={}",
+            self.comets, self.player_shots, self.boss_shots, self.particles)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+pub struct FrameProfiler {
+    frame: PhaseTimings,
+    totals: PhaseTimings,
+    slow_frame_threshold: Duration,
+}
+
+impl FrameProfiler {
+    pub fn new() -> Self {
+        Self {
+            frame: PhaseTimings::default(),
+            totals: PhaseTimings::default(),
+            slow_frame_threshold: DEFAULT_SLOW_FRAME_THRESHOLD,
+        }
+    }
+
+    pub fn set_slow_frame_threshold(&mut self, threshold: Duration) {
+        self.slow_frame_threshold = threshold;
+    }
+
+    // filled by profile_phase!, which times only with the metrics feature on
+    #[cfg(any(test, feature = "metrics"))]
+    pub fn record(&mut self, phase: Phase, time: Duration) {
+        self.frame.add(phase, time);
+    }
+
+    // sums of every finished frame, e.g. for perf checks of headless runs
+    pub fn totals(&self) -> &PhaseTimings {
+        &self.totals
+    }
+
+    pub fn end_frame(&mut self, counts: EntityCounts) {
+        if self.frame.total() > self.slow_frame_threshold {
+            log::warn!("Slow frame {:?}: {} {}", self.frame.total(), self.frame, counts);
+        }
+        for phase in Phase::ALL {
+            self.totals.add(phase, self.frame.get(phase));
+        }
+        self.frame = PhaseTimings::default();
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// whole frame times over the last frames, for spotting hitches
+pub struct FrameTimingStats {
+    window: VecDeque<Duration>,
+    max_window: usize,
+}
+
+impl FrameTimingStats {
+    pub fn new(max_window: usize) -> Self {
+        let max_window = max_window.max(1);
+        Self { window: VecDeque::with_capacity(max_window), max_window }
+    }
+
+    pub fn record(&mut self, dt: Duration) {
+        if self.window.len() == self.max_window {
+            self.window.pop_front();
+        }
+        self.window.push_back(dt);
+    }
+
+    pub fn min(&self) -> Duration {
+        self.window.iter().min().copied().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.window.iter().max().copied().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.window.is_empty() {
+            return Duration::ZERO;
+        }
+        self.window.iter().sum::<Duration>() / self.window.len() as u32
+    }
+
+    // nearest rank, the max of a window shorter than 100 frames
+    pub fn p99(&self) -> Duration {
+        if self.window.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut sorted: Vec<Duration> = self.window.iter().copied().collect();
+        sorted.sort();
+        let rank = (sorted.len() * 99).div_ceil(100);
+        sorted[rank.max(1) - 1]
+    }
+
+    // the last frame took more than twice the mean
+    pub fn is_frame_spike(&self) -> bool {
+        match self.window.back() {
+            Some(last) => *last > self.mean() * FRAME_SPIKE_FACTOR,
+            None => false,
+        }
+    }
+}
+
+impl std::fmt::Display for FrameTimingStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "min={:?} max={:?} mean={:?} p99={:?}", self.min(), self.max(), self.mean(), self.p99())
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn frames_add_up_into_totals() {
+        let mut profiler = FrameProfiler::new();
+        profiler.record(Phase::Collision, MS);
+        profiler.record(Phase::Collision, MS);
+        profiler.end_frame(EntityCounts::default());
+        profiler.record(Phase::Collision, MS);
+        profiler.record(Phase::Ship, MS);
+        profiler.end_frame(EntityCounts::default());
+
+        assert_eq!(profiler.totals().get(Phase::Collision), MS * 3);
+        assert_eq!(profiler.totals().get(Phase::Ship), MS);
+        assert_eq!(profiler.totals().total(), MS * 4);
+    }
+
+    #[test]
+    fn report_lists_every_phase() {
+        let mut timings = PhaseTimings::default();
+        timings.add(Phase::Boss, MS);
+        assert_eq!(
+            timings.to_string(),
+            "ship=0ns boss=1ms shots=0ns collision=0ns particles=0ns spawner=0ns events=0ns"
+        );
+    }
+
+    #[test]
+    fn counts_are_reported_by_name() {
+        let counts = EntityCounts { comets: 3, player_shots: 2, boss_shots: 5, particles: 40 };
+        assert_eq!(counts.to_string(), "comets=3 player_shots=2 boss_shots=5 particles=40");
+    }
+
+    #[test]
+    fn frame_timing_window_is_capped() {
+        let mut stats = FrameTimingStats::new(3);
+        for ms in [10, 1, 2, 3] {
+            stats.record(MS * ms);
+        }
+        assert_eq!(stats.max(), MS * 3);
+        assert_eq!(stats.min(), MS);
+        assert_eq!(stats.mean(), MS * 2);
+    }
+}
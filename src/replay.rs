@@ -0,0 +1,208 @@
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::entities::ship::ShipKind;
+use crate::game_level::GameLevel;
+use crate::globals::{seed_cosmetic_rng, seed_rng, set_deterministic, set_mirror_mode};
+use crate::input::{InputSource, SourceInput};
+use crate::loadout::Loadout;
+use crate::storage::{self, StorageError};
+
+//-----------------------------------------------------------------------------
+
+// bumped whenever a change to the simulation makes older recordings play out differently
+const REPLAY_VERSION: u32 = 1;
+
+//-----------------------------------------------------------------------------
+
+// everything a run needs to play out the same again, besides the input
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub version: u32,
+    pub seed: u64,
+    pub level: i32,
+    pub difficulty: Difficulty,
+    pub ship: ShipKind,
+    pub loadout: Loadout,
+    pub mirror: bool,
+    pub boss_only: bool,
+    pub auto_fire: bool,
+    pub auto_fire_interval_ms: u64,
+    pub aim_mode: bool,
+}
+
+impl ReplayHeader {
+    pub fn new(seed: u64, level: i32, difficulty: Difficulty) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            seed,
+            level,
+            difficulty,
+            ship: ShipKind::Standard,
+            loadout: Loadout::default(),
+            mirror: false,
+            boss_only: false,
+            auto_fire: false,
+            auto_fire_interval_ms: 0,
+            aim_mode: false,
+        }
+    }
+}
+
+// the same input for several ticks in a row, most of a run is long stretches of it
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct InputRun {
+    ticks: u32,
+    input: SourceInput,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    inputs: Vec<InputRun>,
+}
+
+impl Replay {
+    pub fn new(header: ReplayHeader) -> Self {
+        Self { header, inputs: vec![] }
+    }
+
+    // the merged input of a tick, called once per update
+    pub fn record(&mut self, input: SourceInput) {
+        match self.inputs.last_mut() {
+            Some(run) if run.input == input => run.ticks += 1,
+            _ => self.inputs.push(InputRun { ticks: 1, input }),
+        }
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.inputs.iter().map(|run| run.ticks).sum()
+    }
+
+    // the globals the recorded run started with
+    pub fn apply_globals(&self) {
+        seed_rng(self.header.seed);
+        seed_cosmetic_rng(self.header.seed);
+        set_mirror_mode(self.header.mirror);
+        set_deterministic(true);
+    }
+
+    // the run setup of the header, everything else of the game doesn't change how it plays out
+    pub fn set_up(&self, game: &mut GameLevel) {
+        let header = &self.header;
+        game.set_difficulty(header.difficulty);
+        game.set_boss_only(header.boss_only);
+        game.set_run_info(header.level, Some(header.seed));
+        game.set_ship_kind(header.ship);
+        game.set_auto_fire(header.auto_fire, Duration::from_millis(header.auto_fire_interval_ms));
+        game.set_aim_mode(header.aim_mode);
+        game.set_replay_loadout(header.loadout);
+    }
+
+    pub fn source(&self) -> ReplaySource {
+        ReplaySource { inputs: self.inputs.clone(), run: 0, tick_in_run: 0 }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), StorageError> {
+        let payload = toml::to_string(self).map_err(|err| StorageError::Serialize(err.to_string()))?;
+        storage::write_atomic(path, payload.as_bytes())
+    }
+
+    // a recording of another version would desync, so it's refused instead of played wrong
+    pub fn load(path: &Path) -> Result<Replay, StorageError> {
+        let payload = storage::read_with_fallback(path)?;
+        let text = String::from_utf8(payload).map_err(|err| StorageError::Deserialize(err.to_string()))?;
+        let replay: Replay = toml::from_str(&text).map_err(|err| StorageError::Deserialize(err.to_string()))?;
+        if replay.header.version != REPLAY_VERSION {
+            return Err(StorageError::Deserialize(format!(
+                "replay version {} can't be played, expected {}", replay.header.version, REPLAY_VERSION
+            )));
+        }
+        Ok(replay)
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// plays the recorded input back tick by tick, nothing is pressed once it runs out
+pub struct ReplaySource {
+    inputs: Vec<InputRun>,
+    run: usize,
+    tick_in_run: u32,
+}
+
+impl InputSource for ReplaySource {
+    fn poll(&mut self) -> SourceInput {
+        let Some(run) = self.inputs.get(self.run) else {
+            return SourceInput::default();
+        };
+
+        let input = run.input;
+        self.tick_in_run += 1;
+        if self.tick_in_run >= run.ticks {
+            self.run += 1;
+            self.tick_in_run = 0;
+        }
+        input
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Aim, Axis};
+    use crate::math::Vec2i;
+
+    fn input(fire: bool, x: f32) -> SourceInput {
+        SourceInput { fire, axis: Axis::new(x, 0.0), ..SourceInput::default() }
+    }
+
+    fn recorded(inputs: &[SourceInput]) -> Replay {
+        let mut replay = Replay::new(ReplayHeader::new(7, 1, Difficulty::Normal));
+        inputs.iter().for_each(|input| replay.record(*input));
+        replay
+    }
+
+    #[test]
+    fn repeated_input_is_stored_once() {
+        let replay = recorded(&[input(false, 0.0), input(true, 1.0), input(true, 1.0), input(true, 1.0), input(false, 0.0)]);
+        assert_eq!(replay.inputs.len(), 3);
+        assert_eq!(replay.ticks(), 5);
+    }
+
+    #[test]
+    fn source_plays_the_ticks_back_in_order() {
+        let inputs = [input(false, 0.0), input(true, 1.0), input(true, 1.0), input(false, -1.0)];
+        let mut source = recorded(&inputs).source();
+        let played: Vec<SourceInput> = (0..inputs.len()).map(|_| source.poll()).collect();
+        assert_eq!(played, inputs);
+        assert_eq!(source.poll(), SourceInput::default());
+    }
+
+    #[test]
+    fn replay_survives_a_round_trip() {
+        let mut replay = recorded(&[input(true, 0.5), input(false, 0.0)]);
+        replay.record(SourceInput { aim: Some(Aim::Point(Vec2i::new(20, 40))), ..SourceInput::default() });
+        replay.header.ship = ShipKind::Tank;
+        let text = toml::to_string(&replay).unwrap();
+        assert_eq!(toml::from_str::<Replay>(&text).unwrap(), replay);
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let dir = std::env::temp_dir().join(format!("space-replay-{}", std::process::id()));
+        let path = dir.join("old.replay");
+        let mut replay = recorded(&[input(true, 0.0)]);
+        replay.header.version = REPLAY_VERSION + 1;
+        replay.save(&path).unwrap();
+        assert!(matches!(Replay::load(&path), Err(StorageError::Deserialize(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+//-----------------------------------------------------------------------------
+
+const SHORT_RUMBLE: Duration = Duration::from_millis(120);
+const MEDIUM_RUMBLE: Duration = Duration::from_millis(200);
+const LONG_RUMBLE: Duration = Duration::from_millis(600);
+
+const BOSS_DEATH_PULSES: i32 = 3;
+const BOSS_DEATH_PULSE_GAP_TICKS: i32 = 20;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RumbleSource {
+    Damage,
+    Bomb,
+    BossDeath,
+    Graze,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RumbleEvent {
+    pub strength: f32,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RumbleSettings {
+    pub enabled: bool,
+    pub scale: f32,
+
+    pub damage: f32,
+    pub bomb: f32,
+    pub boss_death: f32,
+    pub graze: f32,
+}
+
+impl Default for RumbleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            scale: 1.0,
+            damage: 1.0,
+            bomb: 0.8,
+            boss_death: 1.0,
+            graze: 0.1,
+        }
+    }
+}
+
+impl RumbleSettings {
+    fn event(&self, source: RumbleSource) -> RumbleEvent {
+        let (strength, duration) = match source {
+            RumbleSource::Damage => (self.damage, SHORT_RUMBLE),
+            RumbleSource::Bomb => (self.bomb, LONG_RUMBLE),
+            RumbleSource::BossDeath => (self.boss_death, MEDIUM_RUMBLE),
+            RumbleSource::Graze => (self.graze, SHORT_RUMBLE),
+        };
+        RumbleEvent {
+            strength: (strength * self.scale).clamp(0.0, 1.0),
+            duration,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// holds at most one event per tick, overlapping requests are merged instead of queued
+pub struct RumbleQueue {
+    settings: RumbleSettings,
+    current: Option<RumbleEvent>,
+    scheduled: Vec<(i32, RumbleEvent)>,
+}
+
+impl RumbleQueue {
+    pub fn new() -> Self {
+        Self {
+            settings: RumbleSettings::default(),
+            current: None,
+            scheduled: vec![],
+        }
+    }
+
+    pub fn set_settings(&mut self, settings: RumbleSettings) {
+        self.settings = settings;
+        if !self.settings.enabled {
+            self.clear();
+        }
+    }
+
+    // produced by the last update
+    pub fn event(&self) -> Option<RumbleEvent> {
+        self.current
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.scheduled.clear();
+    }
+
+    // scheduled pulses are kept and continue after the pause
+    pub fn suspend(&mut self) {
+        self.current = None;
+    }
+
+    pub fn push(&mut self, source: RumbleSource) {
+        if !self.settings.enabled {
+            return;
+        }
+
+        let event = self.settings.event(source);
+        match source {
+            RumbleSource::BossDeath => {
+                self.merge(event);
+                for pulse in 1..BOSS_DEATH_PULSES {
+                    self.scheduled.push((pulse * BOSS_DEATH_PULSE_GAP_TICKS, event));
+                }
+            }
+            _ => self.merge(event),
+        }
+    }
+
+    // called once per simulation tick, not while paused
+    pub fn update(&mut self) {
+        self.current = None;
+
+        self.scheduled.iter_mut().for_each(|(ticks_left, _)| *ticks_left -= 1);
+        let due: Vec<RumbleEvent> = self.scheduled.iter()
+            .filter(|(ticks_left, _)| *ticks_left <= 0)
+            .map(|(_, event)| *event)
+            .collect();
+        self.scheduled.retain(|(ticks_left, _)| *ticks_left > 0);
+        due.into_iter().for_each(|event| self.merge(event));
+    }
+
+    fn merge(&mut self, event: RumbleEvent) {
+        self.current = Some(match self.current {
+            Some(current) => RumbleEvent {
+                strength: current.strength.max(event.strength),
+                duration: current.duration.max(event.duration),
+            },
+            None => event,
+        });
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_requests_take_the_max() {
+        let mut queue = RumbleQueue::new();
+        queue.push(RumbleSource::Graze);
+        queue.push(RumbleSource::Damage);
+        queue.push(RumbleSource::Bomb);
+
+        let settings = RumbleSettings::default();
+        assert_eq!(queue.event(), Some(RumbleEvent { strength: settings.damage, duration: LONG_RUMBLE }));
+    }
+
+    #[test]
+    fn events_last_one_tick() {
+        let mut queue = RumbleQueue::new();
+        queue.push(RumbleSource::Damage);
+        queue.update();
+        assert_eq!(queue.event(), None);
+    }
+
+    #[test]
+    fn disabled_rumble_emits_nothing() {
+        let mut queue = RumbleQueue::new();
+        queue.set_settings(RumbleSettings { enabled: false, ..Default::default() });
+        queue.push(RumbleSource::Bomb);
+        assert_eq!(queue.event(), None);
+    }
+
+    #[test]
+    fn scale_applies_to_every_source() {
+        let mut queue = RumbleQueue::new();
+        queue.set_settings(RumbleSettings { scale: 0.5, ..Default::default() });
+        queue.push(RumbleSource::Damage);
+        assert_eq!(queue.event().unwrap().strength, 0.5);
+    }
+
+    #[test]
+    fn boss_death_pulses() {
+        let mut queue = RumbleQueue::new();
+        queue.push(RumbleSource::BossDeath);
+
+        let mut pulses = queue.event().is_some() as i32;
+        for _ in 0..BOSS_DEATH_PULSES * BOSS_DEATH_PULSE_GAP_TICKS {
+            queue.update();
+            pulses += queue.event().is_some() as i32;
+        }
+        assert_eq!(pulses, BOSS_DEATH_PULSES);
+    }
+
+    #[test]
+    fn scheduled_pulses_survive_the_pause() {
+        let mut queue = RumbleQueue::new();
+        queue.push(RumbleSource::BossDeath);
+        queue.suspend();
+        assert_eq!(queue.event(), None);
+
+        for _ in 0..BOSS_DEATH_PULSE_GAP_TICKS {
+            queue.update();
+        }
+        assert!(queue.event().is_some());
+    }
+}
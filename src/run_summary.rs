@@ -0,0 +1,261 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::achievements::AchievementId;
+use crate::loadout::Loadout;
+use crate::score::challenge::WAVE_CLEAN_BONUS;
+
+//-----------------------------------------------------------------------------
+
+const COMET_POINTS: u32 = 10;
+const BOSS_BONUS_POINTS: u32 = 500;
+// bosses killed faster get the whole time bonus, it drops for every second over
+const FAST_KILL_THRESHOLD_SECS: u64 = 60;
+const MAX_BOSS_TIME_BONUS: u32 = 1000;
+const BOSS_TIME_BONUS_STEP: u32 = 10;
+const PICKUP_POINTS: u32 = 5;
+
+// a level cleared faster than this gets the whole time part of the rank
+const PAR_TIME: Duration = Duration::from_secs(120);
+
+//-----------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Default)]
+pub struct RunStats {
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub damage_taken: i32,
+    pub comets_destroyed: u32,
+    pub bosses_defeated: u32,
+    pub boss_points: u32,
+    pub boss_time_bonus: u32,
+    pub clean_waves: u32,
+    pub pickups_collected: u32,
+    pub objective_bonus: u32,
+    pub unlocks: Vec<AchievementId>,
+}
+
+impl RunStats {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.0;
+        }
+        (self.shots_hit as f32 / self.shots_fired as f32).min(1.0)
+    }
+
+    // the points awarded for the kill, time bonus included
+    pub fn record_boss_kill(&mut self, boss_difficulty: u8, time_to_kill: Duration) -> u32 {
+        let seconds_over = time_to_kill.as_secs().saturating_sub(FAST_KILL_THRESHOLD_SECS);
+        let penalty = u32::try_from(seconds_over).unwrap_or(u32::MAX).saturating_mul(BOSS_TIME_BONUS_STEP);
+        let time_bonus = MAX_BOSS_TIME_BONUS.saturating_sub(penalty);
+        let points = BOSS_BONUS_POINTS * boss_difficulty as u32;
+
+        self.bosses_defeated += 1;
+        self.boss_points += points;
+        self.boss_time_bonus += time_bonus;
+        points + time_bonus
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ScoreBreakdown {
+    pub kills: u32,
+    pub boss_bonus: u32,
+    pub boss_time_bonus: u32,
+    pub clean_wave_bonus: u32,
+    pub pickups: u32,
+    pub objectives: u32,
+}
+
+impl ScoreBreakdown {
+    pub fn from_stats(stats: &RunStats) -> Self {
+        Self {
+            kills: stats.comets_destroyed * COMET_POINTS,
+            boss_bonus: stats.boss_points,
+            boss_time_bonus: stats.boss_time_bonus,
+            clean_wave_bonus: stats.clean_waves * WAVE_CLEAN_BONUS,
+            pickups: stats.pickups_collected * PICKUP_POINTS,
+            objectives: stats.objective_bonus,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.kills + self.boss_bonus + self.boss_time_bonus + self.clean_wave_bonus + self.pickups + self.objectives
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+pub struct RunSummary {
+    pub score: ScoreBreakdown,
+    pub stats: RunStats,
+    // 1-based place in the high score table
+    pub high_score_place: Option<usize>,
+    pub level: i32,
+    pub seed: Option<u64>,
+    pub adaptive: bool,
+    pub loadout: Loadout,
+    // fixed-point movement was used
+    pub deterministic: bool,
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let score = &self.score;
+        write!(f, "Run of level {}: score {} (kills {}, bosses {} + {} time, clean waves {}, pickups {}, objectives {})",
+            self.level, score.total(), score.kills, score.boss_bonus, score.boss_time_bonus, score.clean_wave_bonus,
+            score.pickups, score.objectives)?;
+        write!(f, ", accuracy {:.0}%, damage taken {}", self.stats.accuracy() * 100.0, self.stats.damage_taken)?;
+        if let Some(place) = self.high_score_place {
+            write!(f, ", high score #{}", place)?;
+        }
+        if let Some(seed) = self.seed {
+            write!(f, ", seed {}", seed)?;
+        }
+        write!(f, ", loadout {:?}/{:?}/{:?}", self.loadout.primary, self.loadout.secondary, self.loadout.passive)?;
+        if self.adaptive {
+            write!(f, ", adaptive")?;
+        }
+        if self.deterministic {
+            write!(f, ", deterministic")?;
+        }
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Rank {
+    S,
+    A,
+    B,
+    C,
+    D,
+}
+
+impl Rank {
+    // 50 points for accuracy, 30 for the hp kept and 20 for the time, linear within each part:
+    // S from 90, A from 75, B from 60, C from 40, D below
+    pub fn compute(accuracy: f32, damage_taken: i32, max_hp: i32, time: Duration) -> Rank {
+        let accuracy_points = accuracy.clamp(0.0, 1.0) * 50.0;
+
+        let hp_kept = 1.0 - damage_taken.max(0) as f32 / max_hp.max(1) as f32;
+        let hp_points = hp_kept.clamp(0.0, 1.0) * 30.0;
+
+        let time_ratio = PAR_TIME.as_secs_f32() / time.as_secs_f32().max(f32::EPSILON);
+        let time_points = time_ratio.min(1.0) * 20.0;
+
+        match accuracy_points + hp_points + time_points {
+            points if points >= 90.0 => Rank::S,
+            points if points >= 75.0 => Rank::A,
+            points if points >= 60.0 => Rank::B,
+            points if points >= 40.0 => Rank::C,
+            _ => Rank::D,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LevelSummary {
+    pub time: Duration,
+    pub score: u32,
+    pub accuracy: f32,
+    pub damage_taken: i32,
+    pub rank: Rank,
+    pub unlocks: Vec<AchievementId>,
+}
+
+impl LevelSummary {
+    pub fn new(stats: &RunStats, max_hp: i32, time: Duration) -> Self {
+        Self {
+            time,
+            score: ScoreBreakdown::from_stats(stats).total(),
+            accuracy: stats.accuracy(),
+            damage_taken: stats.damage_taken,
+            rank: Rank::compute(stats.accuracy(), stats.damage_taken, max_hp, time),
+            unlocks: stats.unlocks.clone(),
+        }
+    }
+}
+
+impl fmt::Display for LevelSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Level cleared in {:.1?}: score {}, accuracy {:.0}%, damage taken {}, rank {:?}",
+            self.time, self.score, self.accuracy * 100.0, self.damage_taken, self.rank)?;
+        if !self.unlocks.is_empty() {
+            write!(f, ", unlocked {:?}", self.unlocks)?;
+        }
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flawless_fast_level_is_s() {
+        assert_eq!(Rank::compute(1.0, 0, 100, PAR_TIME), Rank::S);
+    }
+
+    #[test]
+    fn rank_parts_add_up() {
+        // 50 + 30 + 10
+        assert_eq!(Rank::compute(1.0, 0, 100, PAR_TIME * 2), Rank::S);
+        // 25 + 30 + 20
+        assert_eq!(Rank::compute(0.5, 0, 100, PAR_TIME), Rank::A);
+        // 25 + 15 + 20
+        assert_eq!(Rank::compute(0.5, 50, 100, PAR_TIME), Rank::B);
+        // 0 + 30 + 20
+        assert_eq!(Rank::compute(0.0, 0, 100, PAR_TIME), Rank::C);
+    }
+
+    #[test]
+    fn worst_level_is_d() {
+        assert_eq!(Rank::compute(0.0, 100, 100, PAR_TIME * 10), Rank::D);
+    }
+
+    #[test]
+    fn rank_parts_are_capped() {
+        assert_eq!(Rank::compute(2.0, -50, 100, Duration::ZERO), Rank::S);
+        assert_eq!(Rank::compute(0.0, 500, 100, PAR_TIME * 10), Rank::D);
+    }
+
+    #[test]
+    fn slow_boss_kill_loses_time_bonus() {
+        let mut stats = RunStats::default();
+        let fast = stats.record_boss_kill(1, Duration::from_secs(FAST_KILL_THRESHOLD_SECS));
+        let slow = stats.record_boss_kill(1, Duration::from_secs(FAST_KILL_THRESHOLD_SECS + 10));
+        assert_eq!(fast, BOSS_BONUS_POINTS + MAX_BOSS_TIME_BONUS);
+        assert_eq!(slow, BOSS_BONUS_POINTS + MAX_BOSS_TIME_BONUS - 10 * BOSS_TIME_BONUS_STEP);
+        assert_eq!(ScoreBreakdown::from_stats(&stats).total(), fast + slow);
+    }
+
+    #[test]
+    fn fast_hard_boss_kill_gets_full_time_bonus() {
+        let mut stats = RunStats::default();
+        let points = stats.record_boss_kill(8, Duration::from_secs(FAST_KILL_THRESHOLD_SECS - 1));
+        assert_eq!(points, BOSS_BONUS_POINTS * 8 + MAX_BOSS_TIME_BONUS);
+    }
+
+    #[test]
+    fn time_bonus_bottoms_out_at_zero() {
+        let mut stats = RunStats::default();
+        let points = stats.record_boss_kill(2, Duration::from_secs(60 * 60));
+        assert_eq!(points, BOSS_BONUS_POINTS * 2);
+    }
+
+    #[test]
+    fn level_summary_lists_the_unlocks() {
+        let stats = RunStats { shots_fired: 4, shots_hit: 3, unlocks: vec![AchievementId::Sharpshooter], ..RunStats::default() };
+        let summary = LevelSummary::new(&stats, 100, PAR_TIME);
+        assert_eq!(summary.to_string(),
+            "Level cleared in 120.0s: score 0, accuracy 75%, damage taken 0, rank A, unlocked [Sharpshooter]");
+    }
+}
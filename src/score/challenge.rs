@@ -0,0 +1,33 @@
+use crate::events::GameEvent;
+
+//-----------------------------------------------------------------------------
+
+pub const WAVE_CLEAN_BONUS: u32 = 250;
+
+//-----------------------------------------------------------------------------
+
+// spots the waves finished without taking damage, the run stats count their bonus
+#[derive(Clone, Debug)]
+pub struct ChallengeModeScore {
+    current_wave_clean: bool,
+}
+
+impl ChallengeModeScore {
+    pub fn new() -> Self {
+        Self { current_wave_clean: true }
+    }
+
+    // true when the event finished a clean wave
+    pub fn on_event(&mut self, event: &GameEvent) -> bool {
+        match event {
+            GameEvent::PlayerHit => {
+                self.current_wave_clean = false;
+                false
+            }
+            GameEvent::WaveComplete => std::mem::replace(&mut self.current_wave_clean, true),
+            _ => false,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
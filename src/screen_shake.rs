@@ -0,0 +1,74 @@
+use rand::Rng;
+
+use crate::globals::with_cosmetic_rng;
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+const MAX_SHAKE_OFFSET: f32 = 8.0;
+const TRAUMA_DECAY_PER_TICK: f32 = 0.02;
+
+pub const SMALL_TRAUMA: f32 = 0.15;
+pub const LARGE_TRAUMA: f32 = 0.6;
+pub const CONTACT_DAMAGE_TRAUMA: f32 = 0.3;
+
+//-----------------------------------------------------------------------------
+
+// offsets only the rendered playfield, gameplay coordinates are never affected
+pub struct ScreenShake {
+    trauma: f32,
+    offset: Vec2i,
+    enabled: bool,
+}
+
+//-----------------------------------------------------------------------------
+
+impl ScreenShake {
+    pub fn new() -> Self {
+        Self {
+            trauma: 0.0,
+            offset: Vec2i { x: 0, y: 0 },
+            enabled: true,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.reset();
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn reset(&mut self) {
+        self.trauma = 0.0;
+        self.offset = Vec2i { x: 0, y: 0 };
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        if self.enabled {
+            self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+        }
+    }
+
+    pub fn offset(&self) -> Vec2i {
+        self.offset
+    }
+
+    // squared trauma keeps small hits subtle while big ones still feel violent
+    pub fn update(&mut self) {
+        let magnitude = MAX_SHAKE_OFFSET * self.trauma.powi(2);
+        let (x, y) = with_cosmetic_rng(|rng| (rng.gen_range(-1.0..=1.0f32), rng.gen_range(-1.0..=1.0f32)));
+        self.offset = Vec2i {
+            x: (x * magnitude).round() as i32,
+            y: (y * magnitude).round() as i32,
+        };
+
+        self.trauma = (self.trauma - TRAUMA_DECAY_PER_TICK).max(0.0);
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::AUTO_FIRE_INTERVAL;
+use crate::loadout::LoadoutConfig;
+use crate::paths;
+use crate::profiling::DEFAULT_SLOW_FRAME_THRESHOLD;
+use crate::rumble::RumbleSettings;
+use crate::storage::{self, StorageError};
+use crate::ui::palette::PalettePreset;
+
+//-----------------------------------------------------------------------------
+
+const SETTINGS_FILE: &str = "settings.toml";
+
+//-----------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub enum SettingsError {
+    ZeroAutoFireInterval,
+    ZeroSlowFrameThreshold,
+    InvalidRumbleStrength { field: &'static str, value: f32 },
+}
+
+// bindings are kept raw, so a broken entry only resets its own action
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub bindings: BTreeMap<String, toml::Value>,
+    pub auto_fire: bool,
+    pub auto_fire_interval_ms: u64,
+    pub aim_mode: bool,
+    pub palette: PalettePreset,
+    pub rumble: RumbleSettings,
+    // frames updating longer get logged with the metrics feature on
+    pub slow_frame_threshold_ms: u64,
+    // the last used one
+    pub loadout: LoadoutConfig,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bindings: BTreeMap::new(),
+            auto_fire: false,
+            auto_fire_interval_ms: AUTO_FIRE_INTERVAL.as_millis() as u64,
+            aim_mode: false,
+            palette: PalettePreset::Default,
+            rumble: RumbleSettings::default(),
+            slow_frame_threshold_ms: DEFAULT_SLOW_FRAME_THRESHOLD.as_millis() as u64,
+            loadout: LoadoutConfig::default(),
+        }
+    }
+}
+
+impl Settings {
+    // layers e.g. user preferences over a preset, a field left at its default in overrides keeps the base value,
+    // nested settings are merged field by field and bindings action by action
+    pub fn merge(base: &Settings, overrides: &Settings) -> Settings {
+        let defaults = Settings::default();
+        let mut bindings = base.bindings.clone();
+        bindings.extend(overrides.bindings.iter().map(|(action, value)| (action.clone(), value.clone())));
+
+        let (rumble, overrides_rumble, default_rumble) = (&base.rumble, &overrides.rumble, &defaults.rumble);
+        let (loadout, overrides_loadout, default_loadout) = (&base.loadout, &overrides.loadout, &defaults.loadout);
+        Settings {
+            bindings,
+            auto_fire: pick(&base.auto_fire, &overrides.auto_fire, &defaults.auto_fire),
+            auto_fire_interval_ms: pick(&base.auto_fire_interval_ms, &overrides.auto_fire_interval_ms, &defaults.auto_fire_interval_ms),
+            aim_mode: pick(&base.aim_mode, &overrides.aim_mode, &defaults.aim_mode),
+            palette: pick(&base.palette, &overrides.palette, &defaults.palette),
+            rumble: RumbleSettings {
+                enabled: pick(&rumble.enabled, &overrides_rumble.enabled, &default_rumble.enabled),
+                scale: pick(&rumble.scale, &overrides_rumble.scale, &default_rumble.scale),
+                damage: pick(&rumble.damage, &overrides_rumble.damage, &default_rumble.damage),
+                bomb: pick(&rumble.bomb, &overrides_rumble.bomb, &default_rumble.bomb),
+                boss_death: pick(&rumble.boss_death, &overrides_rumble.boss_death, &default_rumble.boss_death),
+                graze: pick(&rumble.graze, &overrides_rumble.graze, &default_rumble.graze),
+            },
+            slow_frame_threshold_ms: pick(&base.slow_frame_threshold_ms, &overrides.slow_frame_threshold_ms, &defaults.slow_frame_threshold_ms),
+            loadout: LoadoutConfig {
+                primary: pick(&loadout.primary, &overrides_loadout.primary, &default_loadout.primary),
+                secondary: pick(&loadout.secondary, &overrides_loadout.secondary, &default_loadout.secondary),
+                passive: pick(&loadout.passive, &overrides_loadout.passive, &default_loadout.passive),
+            },
+        }
+    }
+
+    pub fn auto_fire_interval(&self) -> Duration {
+        Duration::from_millis(self.auto_fire_interval_ms)
+    }
+
+    pub fn slow_frame_threshold(&self) -> Duration {
+        Duration::from_millis(self.slow_frame_threshold_ms)
+    }
+
+    // zero intervals would fire every frame or log every frame
+    pub fn validate(&self) -> Result<(), Vec<SettingsError>> {
+        let mut errors = vec![];
+        if self.auto_fire_interval_ms == 0 {
+            errors.push(SettingsError::ZeroAutoFireInterval);
+        }
+        if self.slow_frame_threshold_ms == 0 {
+            errors.push(SettingsError::ZeroSlowFrameThreshold);
+        }
+
+        let rumble = &self.rumble;
+        let strengths = [
+            ("scale", rumble.scale),
+            ("damage", rumble.damage),
+            ("bomb", rumble.bomb),
+            ("boss_death", rumble.boss_death),
+            ("graze", rumble.graze),
+        ];
+        for (field, value) in strengths {
+            if !value.is_finite() || value < 0.0 {
+                errors.push(SettingsError::InvalidRumbleStrength { field, value });
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    // only the invalid values are reset
+    fn reset_invalid(&mut self, errors: &[SettingsError]) {
+        let defaults = Settings::default();
+        for error in errors {
+            log::warn!("Invalid setting, using the default: {:?}", error);
+            match error {
+                SettingsError::ZeroAutoFireInterval => self.auto_fire_interval_ms = defaults.auto_fire_interval_ms,
+                SettingsError::ZeroSlowFrameThreshold => self.slow_frame_threshold_ms = defaults.slow_frame_threshold_ms,
+                SettingsError::InvalidRumbleStrength { .. } => self.rumble = defaults.rumble.clone(),
+            }
+        }
+    }
+
+    // the user settings layered over the preset shipped in the assets dir, if there is one
+    pub fn load() -> Self {
+        let user = match storage::load_config::<Settings>(SETTINGS_FILE) {
+            Ok(settings) => settings,
+            Err(StorageError::NotFound(_)) => Settings::default(),
+            Err(err) => {
+                log::warn!("Failed to load settings, using defaults: {}", err);
+                Settings::default()
+            }
+        };
+        let mut settings = Settings::merge(&load_preset(&paths::assets_dir().join(SETTINGS_FILE)), &user);
+        if let Err(errors) = settings.validate() {
+            settings.reset_invalid(&errors);
+        }
+        settings
+    }
+
+    pub fn save(&self) -> Result<(), StorageError> {
+        storage::save_config(SETTINGS_FILE, self)
+    }
+}
+
+// plain toml edited by hand, unlike the saved settings
+fn load_preset(path: &Path) -> Settings {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Settings::default(),
+        Err(err) => {
+            log::warn!("Failed to read the settings preset {}: {}", path.display(), err);
+            return Settings::default();
+        }
+    };
+    toml::from_str(&text).unwrap_or_else(|err| {
+        log::warn!("Settings preset {} ignored: {}", path.display(), err);
+        Settings::default()
+    })
+}
+
+fn pick<T: Clone + PartialEq>(base: &T, overrides: &T, default: &T) -> T {
+    if overrides != default { overrides.clone() } else { base.clone() }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn override_wins_over_base() {
+        let base = Settings { auto_fire_interval_ms: 50, ..Settings::default() };
+        let overrides = Settings { auto_fire_interval_ms: 80, ..Settings::default() };
+        assert_eq!(Settings::merge(&base, &overrides).auto_fire_interval_ms, 80);
+    }
+
+    #[test]
+    fn unset_override_keeps_base() {
+        let base = Settings { auto_fire_interval_ms: 50, aim_mode: true, ..Settings::default() };
+        let overrides = Settings { auto_fire: true, ..Settings::default() };
+        let merged = Settings::merge(&base, &overrides);
+        assert_eq!(merged.auto_fire_interval_ms, 50);
+        assert!(merged.aim_mode);
+        assert!(merged.auto_fire);
+    }
+
+    #[test]
+    fn nested_settings_merge_field_by_field() {
+        let mut base = Settings::default();
+        base.rumble.scale = 0.5;
+        base.loadout.passive = "Magnet".to_string();
+        let mut overrides = Settings::default();
+        overrides.rumble.enabled = false;
+        overrides.loadout.primary = "Spread".to_string();
+
+        let merged = Settings::merge(&base, &overrides);
+        assert_eq!(merged.rumble.scale, 0.5);
+        assert!(!merged.rumble.enabled);
+        assert_eq!(merged.loadout.passive, "Magnet");
+        assert_eq!(merged.loadout.primary, "Spread");
+    }
+
+    #[test]
+    fn bindings_merge_per_action() {
+        let mut base = Settings::default();
+        base.bindings.insert("fire".to_string(), toml::Value::from("Space"));
+        base.bindings.insert("bomb".to_string(), toml::Value::from("B"));
+        let mut overrides = Settings::default();
+        overrides.bindings.insert("bomb".to_string(), toml::Value::from("X"));
+
+        let merged = Settings::merge(&base, &overrides);
+        assert_eq!(merged.bindings["fire"], toml::Value::from("Space"));
+        assert_eq!(merged.bindings["bomb"], toml::Value::from("X"));
+    }
+
+    #[test]
+    fn layers_chain_from_defaults_to_user() {
+        let preset = Settings { auto_fire_interval_ms: 50, aim_mode: true, ..Settings::default() };
+        let user = Settings { auto_fire_interval_ms: 80, ..Settings::default() };
+        let merged = Settings::merge(&Settings::merge(&Settings::default(), &preset), &user);
+        assert_eq!(merged.auto_fire_interval_ms, 80);
+        assert!(merged.aim_mode);
+        assert_eq!(merged, Settings { auto_fire_interval_ms: 80, aim_mode: true, ..Settings::default() });
+    }
+
+    #[test]
+    fn preset_is_read_as_plain_toml() {
+        let dir = std::env::temp_dir().join(format!("space-settings-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(SETTINGS_FILE);
+        assert_eq!(load_preset(&path), Settings::default());
+
+        fs::write(&path, "aim_mode = true\nauto_fire_interval_ms = 80\n").unwrap();
+        let preset = load_preset(&path);
+        assert_eq!((preset.aim_mode, preset.auto_fire_interval_ms), (true, 80));
+
+        fs::write(&path, "aim_mode = 3\n").unwrap();
+        assert_eq!(load_preset(&path), Settings::default());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert_eq!(Settings::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn zero_intervals_are_invalid() {
+        let settings = Settings { auto_fire_interval_ms: 0, slow_frame_threshold_ms: 0, ..Settings::default() };
+        assert_eq!(settings.validate(), Err(vec![
+            SettingsError::ZeroAutoFireInterval,
+            SettingsError::ZeroSlowFrameThreshold,
+        ]));
+        let settings = Settings { auto_fire_interval_ms: 1, slow_frame_threshold_ms: 1, ..Settings::default() };
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
+    #[test]
+    fn negative_or_non_finite_rumble_is_invalid() {
+        let mut settings = Settings::default();
+        settings.rumble.scale = -0.5;
+        settings.rumble.bomb = f32::INFINITY;
+        settings.rumble.graze = 0.0;
+        let errors = settings.validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[0], SettingsError::InvalidRumbleStrength { field: "scale", .. }));
+        assert!(matches!(errors[1], SettingsError::InvalidRumbleStrength { field: "bomb", .. }));
+
+        let mut settings = Settings::default();
+        settings.rumble.damage = f32::NAN;
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn only_invalid_values_are_reset() {
+        let mut settings = Settings { auto_fire_interval_ms: 0, slow_frame_threshold_ms: 40, ..Settings::default() };
+        settings.rumble.scale = -1.0;
+        let errors = settings.validate().unwrap_err();
+        settings.reset_invalid(&errors);
+
+        let defaults = Settings::default();
+        assert_eq!(settings.auto_fire_interval_ms, defaults.auto_fire_interval_ms);
+        assert_eq!(settings.slow_frame_threshold_ms, 40);
+        assert_eq!(settings.rumble, defaults.rumble);
+        assert_eq!(settings.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use crate::timing::GameInstant;
+
+//-----------------------------------------------------------------------------
+
+pub const SLOW_MO_DURATION: Duration = Duration::from_secs(3);
+pub const SLOW_MO_SHOT_SPEED_MULTIPLIER: f32 = 0.5;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug)]
+pub struct SlowMoEffect {
+    end: GameInstant,
+}
+
+impl SlowMoEffect {
+    pub fn new(duration: Duration) -> Self {
+        Self { end: GameInstant::now() + duration }
+    }
+
+    pub fn active(&self) -> bool {
+        self.active_at(GameInstant::now())
+    }
+
+    pub fn active_at(&self, now: GameInstant) -> bool {
+        now < self.end
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::{advance_game_clock, duration_to_ticks};
+
+    #[test]
+    fn active_for_its_duration() {
+        let effect = SlowMoEffect::new(SLOW_MO_DURATION);
+        for _ in 0..duration_to_ticks(SLOW_MO_DURATION) {
+            assert!(effect.active());
+            advance_game_clock();
+        }
+        assert!(!effect.active());
+    }
+}
@@ -0,0 +1,141 @@
+use crate::audio::music_fade::MusicFade;
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ShotOwner {
+    Player,
+    Boss,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TargetKind {
+    Ship,
+    Boss,
+    Comet,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExplosionSize {
+    Small,
+    Large,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MusicTrack {
+    Level,
+    VictoryFanfare,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SoundKind {
+    ShotFired { owner: ShotOwner, count: u32 },
+    Hit { target_kind: TargetKind },
+    ContactDamage,
+    Explosion { size: ExplosionSize },
+    BombUsed,
+    BossStageChanged,
+    BossStunned,
+    BossEnraged,
+    ShotConverted,
+    PickupCollected,
+    LowHpWarningStarted,
+    LowHpWarningStopped,
+    DialogueLine,
+    AchievementUnlocked,
+    SetMusicVolume { volume: f32 },
+    FadeMusic { fade: MusicFade },
+    ChangeTrack { track: MusicTrack },
+}
+
+// position is in world coordinates, for panning
+#[derive(Copy, Clone, Debug)]
+pub struct SoundEvent {
+    pub kind: SoundKind,
+    pub pos: Vec2i,
+}
+
+//-----------------------------------------------------------------------------
+
+// collects the sounds of a single tick, the audio side reads them after the update
+pub struct SoundEventQueue {
+    events: Vec<SoundEvent>,
+}
+
+impl SoundEventQueue {
+    pub fn new() -> Self {
+        Self { events: vec![] }
+    }
+
+    pub fn events(&self) -> &[SoundEvent] {
+        &self.events
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    // shots of one owner fired within a tick are a single volley sound, other sounds are
+    // deduplicated by kind and position
+    pub fn push(&mut self, kind: SoundKind, pos: Vec2i) {
+        if let SoundKind::ShotFired { owner, count } = kind {
+            let queued_volley = self.events.iter_mut().find_map(|event| match &mut event.kind {
+                SoundKind::ShotFired { owner: queued_owner, count } if *queued_owner == owner => Some(count),
+                _ => None,
+            });
+            if let Some(queued_count) = queued_volley {
+                *queued_count += count;
+                return;
+            }
+        }
+
+        let duplicate = self.events.iter().any(|event| event.kind == kind && event.pos == pos);
+        if !duplicate {
+            self.events.push(SoundEvent { kind, pos });
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(queue: &SoundEventQueue) -> Vec<SoundKind> {
+        queue.events().iter().map(|event| event.kind).collect()
+    }
+
+    #[test]
+    fn volley_is_one_sound_with_count() {
+        let mut queue = SoundEventQueue::new();
+        for x in 0..15 {
+            queue.push(SoundKind::ShotFired { owner: ShotOwner::Boss, count: 1 }, Vec2i::new(x, 0));
+        }
+        queue.push(SoundKind::ShotFired { owner: ShotOwner::Player, count: 1 }, Vec2i::zero());
+        assert_eq!(kinds(&queue), vec![
+            SoundKind::ShotFired { owner: ShotOwner::Boss, count: 15 },
+            SoundKind::ShotFired { owner: ShotOwner::Player, count: 1 },
+        ]);
+    }
+
+    #[test]
+    fn same_sound_at_same_position_is_deduplicated() {
+        let mut queue = SoundEventQueue::new();
+        let explosion = SoundKind::Explosion { size: ExplosionSize::Small };
+        queue.push(explosion, Vec2i::new(10, 10));
+        queue.push(explosion, Vec2i::new(10, 10));
+        queue.push(explosion, Vec2i::new(50, 10));
+        assert_eq!(queue.events().len(), 2);
+        assert_eq!(queue.events()[1].pos, Vec2i::new(50, 10));
+    }
+
+    #[test]
+    fn cleared_after_tick() {
+        let mut queue = SoundEventQueue::new();
+        queue.push(SoundKind::LowHpWarningStarted, Vec2i::zero());
+        queue.clear();
+        assert!(queue.events().is_empty());
+    }
+}
@@ -3,12 +3,51 @@ pub mod spawners_impl;
 use crate::constants::*;
 use crate::entities::boss::Boss;
 use crate::entities::comet::Comet;
+use crate::cutscene::CutsceneFrame;
+use crate::cutscene::dialogue::DialogueLine;
+use crate::entities::force_zone::ForceZone;
+use crate::entities::spawner::Spawner as BackgroundSpawner;
+use crate::objectives::Objective;
 
 pub trait Spawner {
+    fn level_name(&self) -> &'static str;
     fn boss_spawn_points(&self) -> i32;
 
     fn spawn_comet(&self, min_speed: i32) -> Comet;
-    fn spawn_boss(&self) -> Boss;
+    // None for levels without a boss
+    fn spawn_boss(&self) -> Option<Boss>;
+
+    fn has_boss(&self) -> bool {
+        true
+    }
+
+    // wind zones of the level, created anew on every (re)start
+    fn force_zones(&self) -> Vec<ForceZone> {
+        vec![]
+    }
+
+    // shown when the level starts, spawning waits until it's over
+    fn dialogue(&self) -> Vec<DialogueLine> {
+        vec![]
+    }
+
+    // played before the boss spawns and after it's defeated
+    fn cutscene_before_boss(&self) -> Vec<CutsceneFrame> {
+        vec![]
+    }
+    fn cutscene_after_boss(&self) -> Vec<CutsceneFrame> {
+        vec![]
+    }
+
+    // run alongside the regular comets, also created anew on every (re)start
+    fn background_spawners(&self) -> Vec<BackgroundSpawner> {
+        vec![]
+    }
+
+    // optional goals of the level, rewarded with bonus score
+    fn objectives(&self) -> Vec<Objective> {
+        vec![]
+    }
 
     fn calc_comets_limit(&self, player_points: i32) -> i32 {
         player_points / POINTS_TO_ADD_COMET + MIN_COMETS
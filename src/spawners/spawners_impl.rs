@@ -1,10 +1,17 @@
+use std::time::Duration;
+
+use crate::cutscene::{BackgroundConfig, CutsceneFrame};
+use crate::cutscene::dialogue::DialogueLine;
 use crate::entities::comet::{Comet, CometKind};
+use crate::entities::force_zone::ForceZone;
 use crate::entities::shape::Shape;
+use crate::entities::spawner::Spawner as BackgroundSpawner;
 use crate::entities::boss::Boss;
 use crate::entities::boss::stage_factories::*;
 
-use crate::globals::screen_size;
-use crate::math::Vec2i;
+use crate::globals::{mirror_angle, mirror_mode, mirror_x, screen_rect, screen_size, with_rng};
+use crate::math::{Rect, Vec2i};
+use crate::objectives::{Objective, ObjectiveEvent, ObjectiveStat, Predicate};
 
 use super::Spawner;
 
@@ -16,28 +23,57 @@ const EASY_LEVEL_BOSS_SPAWN_POINTS: i32 = 50;
 const MEDIUM_LEVEL_BOSS_SPAWN_POINTS: i32 = 50;
 const FREE_LEVEL_BOSS_SPAWN_POINTS: i32 = i32::MAX;
 
+pub const LEVELS_COUNT: i32 = 3;
+// the free level has no boss
+pub const FINAL_BOSS_LEVEL: i32 = 2;
+
 const EASY_LEVEL_BOSS_WIDTH: i32 = 100;
 const EASY_LEVEL_BOSS_MAX_HP: i32 = 100;
 
+const MEDIUM_LEVEL_BOSS_WIDTH: i32 = 120;
+const MEDIUM_LEVEL_BOSS_MAX_HP: i32 = 150;
+
+// a big comet from each upper corner now and then, on top of the regular ones
+const FREE_LEVEL_BACKGROUND_INTERVAL: Duration = Duration::from_secs(4);
+const FREE_LEVEL_BACKGROUND_MAX_ALIVE: u8 = 2;
+// a band across the middle of the screen blowing to the right, it never expires
+const FREE_LEVEL_CROSSWIND_DRIFT: i32 = 2;
+
 //-----------------------------------------------------------------------------
 
 fn spawn_comet_common(kind: CometKind, min_speed: i32) -> Comet {
     let screen_size = screen_size();
+    let center_x = screen_rect().center().x;
 
-    let mut rng = rand::thread_rng();
-    let spawn_x = rng.gen_range(0..screen_size.x);
+    let (spawn_x, angle) = with_rng(|rng| {
+        let spawn_x = rng.gen_range(0..screen_size.x);
 
-    let angle_range = if spawn_x > screen_size.x / 2 { 180..270 } else { 270..360 };
-    let angle = rng.gen_range(angle_range);
+        let angle_range = if spawn_x > center_x { 180..270 } else { 270..360 };
+        (spawn_x, rng.gen_range(angle_range))
+    });
 
-    let spawn_pos = Vec2i{ x: spawn_x, y: -Comet::get_width(kind) };
-    Comet::new(kind, spawn_pos, angle, min_speed)
+    let width = Comet::get_width(kind);
+    let spawn_pos = Vec2i{ x: mirror_x(spawn_x, width), y: -width };
+    Comet::new(kind, spawn_pos, mirror_angle(angle), min_speed)
+}
+
+pub fn create_level_spawner(level: i32) -> Box<dyn Spawner> {
+    match level {
+        1 => Box::new(EasyLevelSpawner{}),
+        2 => Box::new(MediumLevelSpawner{}),
+        3 => Box::new(FreeLevelSpawner{}),
+        _ => panic!("Level out of range")
+    }
 }
 
 //-----------------------------------------------------------------------------
 
 pub struct EasyLevelSpawner;
 impl Spawner for EasyLevelSpawner {
+    fn level_name(&self) -> &'static str {
+        "Easy"
+    }
+
     fn boss_spawn_points(&self) -> i32 {
         EASY_LEVEL_BOSS_SPAWN_POINTS
     }
@@ -46,43 +82,156 @@ impl Spawner for EasyLevelSpawner {
         spawn_comet_common(CometKind::Simple, min_speed)
     }
 
-    fn spawn_boss(&self) -> Boss {
-        let spawn_x = screen_size().x / 2 + EASY_LEVEL_BOSS_WIDTH / 2;
-        let pos =Vec2i { x: spawn_x, y: -EASY_LEVEL_BOSS_WIDTH };
-        let boss_shape = Shape::new(pos, EASY_LEVEL_BOSS_WIDTH);
-        Boss::new(boss_shape, EASY_LEVEL_BOSS_MAX_HP, Box::new(EasyBossStageFactory{}))
+    fn objectives(&self) -> Vec<Objective> {
+        vec![
+            Objective {
+                id: "easy_comets",
+                text: "Destroy 15 asteroids",
+                predicate: Predicate::Count { event: ObjectiveEvent::CometDestroyed, target: 15 },
+                bonus: 100,
+            },
+            Objective {
+                id: "easy_no_bombs",
+                text: "Don't use bombs",
+                predicate: Predicate::Never { event: ObjectiveEvent::BombUsed },
+                bonus: 100,
+            },
+            Objective {
+                id: "easy_untouched",
+                text: "Don't get hit",
+                predicate: Predicate::Never { event: ObjectiveEvent::PlayerHit },
+                bonus: 150,
+            },
+        ]
+    }
+
+    fn spawn_boss(&self) -> Option<Boss> {
+        let spawn_pos = Boss::spawn_position(&screen_rect(), EASY_LEVEL_BOSS_WIDTH);
+        let boss_shape = Shape::new(spawn_pos, EASY_LEVEL_BOSS_WIDTH);
+        Some(Boss::new(boss_shape, EASY_LEVEL_BOSS_MAX_HP, Box::new(EasyBossStageFactory{})))
     }
 }
 
 pub struct MediumLevelSpawner;
 impl Spawner for MediumLevelSpawner {
+    fn level_name(&self) -> &'static str {
+        "Medium"
+    }
+
     fn boss_spawn_points(&self) -> i32 {
         MEDIUM_LEVEL_BOSS_SPAWN_POINTS
     }
 
     fn spawn_comet(&self, min_speed: i32) -> Comet {
-        let kind = rand::random::<CometKind>();
+        let kind = with_rng(|rng| rng.gen::<CometKind>());
         spawn_comet_common(kind, min_speed)
     }
 
-    fn spawn_boss(&self) -> Boss {
-        todo!()
+    fn objectives(&self) -> Vec<Objective> {
+        vec![
+            Objective {
+                id: "medium_accuracy",
+                text: "Finish with at least 80% accuracy",
+                predicate: Predicate::AtLeast { stat: ObjectiveStat::Accuracy, value: 80.0 },
+                bonus: 200,
+            },
+            Objective {
+                id: "medium_untouched",
+                text: "Take no more than 30 damage",
+                predicate: Predicate::AtMost { stat: ObjectiveStat::DamageTaken, value: 30.0 },
+                bonus: 200,
+            },
+            Objective {
+                id: "medium_grazes",
+                text: "Graze 20 shots",
+                predicate: Predicate::Count { event: ObjectiveEvent::Graze, target: 20 },
+                bonus: 150,
+            },
+            Objective {
+                id: "medium_pickups",
+                text: "Finish with at least 10 pickups collected",
+                predicate: Predicate::AtLeast { stat: ObjectiveStat::PickupsCollected, value: 10.0 },
+                bonus: 100,
+            },
+        ]
+    }
+
+    fn cutscene_before_boss(&self) -> Vec<CutsceneFrame> {
+        vec![
+            CutsceneFrame { text: "Something big is closing in.", duration: Duration::from_secs(2), background: BackgroundConfig::Level },
+        ]
+    }
+
+    fn cutscene_after_boss(&self) -> Vec<CutsceneFrame> {
+        vec![
+            CutsceneFrame { text: "The sector is quiet again.", duration: Duration::from_secs(3), background: BackgroundConfig::Starfield },
+            CutsceneFrame { text: "Mission complete.", duration: Duration::from_secs(2), background: BackgroundConfig::Black },
+        ]
+    }
+
+    fn spawn_boss(&self) -> Option<Boss> {
+        let spawn_pos = Boss::spawn_position(&screen_rect(), MEDIUM_LEVEL_BOSS_WIDTH);
+        let boss_shape = Shape::new(spawn_pos, MEDIUM_LEVEL_BOSS_WIDTH);
+        Some(Boss::new(boss_shape, MEDIUM_LEVEL_BOSS_MAX_HP, Box::new(MediumBossStageFactory{})))
     }
 }
 
 pub struct FreeLevelSpawner;
 impl Spawner for FreeLevelSpawner {
+    fn level_name(&self) -> &'static str {
+        "Free flight"
+    }
+
+    fn dialogue(&self) -> Vec<DialogueLine> {
+        vec![
+            DialogueLine { speaker: "Control", text: "No boss out here, just rocks.", duration: Duration::from_secs(2) },
+            DialogueLine { speaker: "Pilot", text: "Then I'll take my time.", duration: Duration::from_secs(2) },
+        ]
+    }
+
     fn boss_spawn_points(&self) -> i32 {
         FREE_LEVEL_BOSS_SPAWN_POINTS
     }
 
+    fn objectives(&self) -> Vec<Objective> {
+        vec![
+            Objective {
+                id: "free_pickups",
+                text: "Collect 25 pickups",
+                predicate: Predicate::Count { event: ObjectiveEvent::PickupCollected, target: 25 },
+                bonus: 100,
+            },
+        ]
+    }
+
     fn spawn_comet(&self, min_speed: i32) -> Comet {
-        let kind = rand::random::<CometKind>();
+        let kind = with_rng(|rng| rng.gen::<CometKind>());
         spawn_comet_common(kind, min_speed)
     }
 
-    fn spawn_boss(&self) -> Boss {
-        panic!("This should never happen")
+    fn force_zones(&self) -> Vec<ForceZone> {
+        let screen = screen_size();
+        let rect = Rect { top_left: Vec2i::new(0, screen.y / 3), bottom_right: Vec2i::new(screen.x, screen.y / 2) };
+        let drift = if mirror_mode() { -FREE_LEVEL_CROSSWIND_DRIFT } else { FREE_LEVEL_CROSSWIND_DRIFT };
+        vec![ForceZone::new(rect, Vec2i::new(drift, 0), None)]
+    }
+
+    fn background_spawners(&self) -> Vec<BackgroundSpawner> {
+        let width = Comet::get_width(CometKind::Double);
+        [screen_size().x / 4, screen_size().x * 3 / 4].into_iter()
+            .map(|x| {
+                let position = Vec2i { x: x - width / 2, y: -width };
+                BackgroundSpawner::new(position, CometKind::Double, FREE_LEVEL_BACKGROUND_INTERVAL, FREE_LEVEL_BACKGROUND_MAX_ALIVE)
+            })
+            .collect()
+    }
+
+    fn spawn_boss(&self) -> Option<Boss> {
+        None
+    }
+
+    fn has_boss(&self) -> bool {
+        false
     }
 }
 
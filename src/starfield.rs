@@ -0,0 +1,149 @@
+use rand::Rng;
+
+use crate::globals::with_cosmetic_rng;
+use crate::math::Vec2i;
+
+//-----------------------------------------------------------------------------
+
+struct LayerConfig {
+    stars_count: usize,
+    speed: f32,
+    brightness: f32,
+}
+
+// far layers are dim and slow, near ones bright and fast
+const LAYERS: [LayerConfig; 3] = [
+    LayerConfig { stars_count: 40, speed: 0.5, brightness: 0.3 },
+    LayerConfig { stars_count: 25, speed: 1.0, brightness: 0.6 },
+    LayerConfig { stars_count: 12, speed: 2.0, brightness: 1.0 },
+];
+
+const BOOST_MULTIPLIER: f32 = 3.0;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug)]
+pub struct Star {
+    pub layer: usize,
+    pub pos: Vec2i,
+    pub brightness: f32,
+}
+
+pub struct Starfield {
+    layers: Vec<Vec<(f32, f32)>>,
+    size: Vec2i,
+
+    speed_multiplier: f32,
+    boost_ticks_left: i32,
+    enabled: bool,
+}
+
+//-----------------------------------------------------------------------------
+
+impl Starfield {
+    pub fn new(size: Vec2i) -> Self {
+        let mut starfield = Self {
+            layers: vec![],
+            size,
+            speed_multiplier: 1.0,
+            boost_ticks_left: 0,
+            enabled: true,
+        };
+        starfield.generate();
+        starfield
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    // follows the game time scale, 1.0 is the normal speed
+    pub fn set_speed_multiplier(&mut self, multiplier: f32) {
+        self.speed_multiplier = multiplier;
+    }
+
+    pub fn boost(&mut self, ticks: i32) {
+        self.boost_ticks_left = ticks;
+    }
+
+    pub fn resize(&mut self, size: Vec2i) {
+        if size.x != self.size.x || size.y != self.size.y {
+            self.size = size;
+            self.generate();
+        }
+    }
+
+    pub fn update(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        let boost = if self.boost_ticks_left > 0 { BOOST_MULTIPLIER } else { 1.0 };
+        self.boost_ticks_left = (self.boost_ticks_left - 1).max(0);
+
+        let height = self.size.y.max(1) as f32;
+        for (layer, config) in self.layers.iter_mut().zip(LAYERS.iter()) {
+            let step = config.speed * self.speed_multiplier * boost;
+            layer.iter_mut().for_each(|(_, y)| *y = (*y + step).rem_euclid(height));
+        }
+    }
+
+    pub fn stars(&self) -> impl Iterator<Item = Star> + '_ {
+        self.layers.iter().enumerate().flat_map(|(layer_idx, layer)| {
+            layer.iter().map(move |(x, y)| Star {
+                layer: layer_idx,
+                pos: Vec2i { x: *x as i32, y: *y as i32 },
+                brightness: LAYERS[layer_idx].brightness,
+            })
+        })
+    }
+
+    fn generate(&mut self) {
+        let width = self.size.x.max(1) as f32;
+        let height = self.size.y.max(1) as f32;
+
+        self.layers = with_cosmetic_rng(|rng| {
+            LAYERS.iter()
+                .map(|config| {
+                    (0..config.stars_count)
+                        .map(|_| (rng.gen_range(0.0..width), rng.gen_range(0.0..height)))
+                        .collect()
+                })
+                .collect()
+        });
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_star_y(starfield: &Starfield) -> f32 {
+        starfield.layers[0][0].1
+    }
+
+    #[test]
+    fn slowed_stars_move_at_half_speed() {
+        let mut normal = Starfield::new(Vec2i::new(100, 1000));
+        let mut slowed = Starfield::new(Vec2i::new(100, 1000));
+        normal.layers[0][0].1 = 0.0;
+        slowed.layers[0][0].1 = 0.0;
+        slowed.set_speed_multiplier(0.5);
+
+        for _ in 0..10 {
+            normal.update();
+            slowed.update();
+        }
+        assert_eq!(first_star_y(&slowed) * 2.0, first_star_y(&normal));
+    }
+
+    #[test]
+    fn stars_wrap_at_bottom() {
+        let mut starfield = Starfield::new(Vec2i::new(100, 100));
+        starfield.layers[2][0].1 = 99.0;
+        starfield.update();
+        assert!(starfield.stars().all(|star| star.pos.y >= 0 && star.pos.y < 100));
+    }
+}
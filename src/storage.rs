@@ -0,0 +1,326 @@
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::paths::{self, PathError};
+
+//-----------------------------------------------------------------------------
+
+const HEADER_MAGIC: &str = "space-save";
+const TEMP_EXTENSION: &str = "tmp";
+const BACKUP_EXTENSION: &str = "bak";
+
+//-----------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    Directory(PathError),
+    NotFound(PathBuf),
+    Corrupted(PathBuf),
+    Serialize(String),
+    Deserialize(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Io(err) => write!(f, "{}", err),
+            StorageError::Directory(err) => write!(f, "{}", err),
+            StorageError::NotFound(path) => write!(f, "{} not found", path.display()),
+            StorageError::Corrupted(path) => write!(f, "{} is corrupted", path.display()),
+            StorageError::Serialize(message) => write!(f, "failed to serialize: {}", message),
+            StorageError::Deserialize(message) => write!(f, "failed to deserialize: {}", message),
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(err: io::Error) -> Self {
+        StorageError::Io(err)
+    }
+}
+
+impl From<PathError> for StorageError {
+    fn from(err: PathError) -> Self {
+        StorageError::Directory(err)
+    }
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) -> Result<(), StorageError> {
+    save_to(&paths::data_dir()?.join(name), value)
+}
+
+pub fn load<T: DeserializeOwned>(name: &str) -> Result<T, StorageError> {
+    load_from(&paths::data_dir()?.join(name))
+}
+
+pub fn save_config<T: Serialize>(name: &str, value: &T) -> Result<(), StorageError> {
+    save_to(&paths::config_dir()?.join(name), value)
+}
+
+pub fn load_config<T: DeserializeOwned>(name: &str) -> Result<T, StorageError> {
+    load_from(&paths::config_dir()?.join(name))
+}
+
+// the backup goes too, so the removed save can't be restored from it
+pub fn remove(name: &str) -> Result<(), StorageError> {
+    let path = paths::data_dir()?.join(name);
+    let backup_path = sibling_path(&path, BACKUP_EXTENSION);
+    if !path.exists() && !backup_path.exists() {
+        return Err(StorageError::NotFound(path));
+    }
+
+    for path in [path, backup_path] {
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn save_to<T: Serialize>(path: &Path, value: &T) -> Result<(), StorageError> {
+    let payload = toml::to_string(value).map_err(|err| StorageError::Serialize(err.to_string()))?;
+    write_atomic(path, payload.as_bytes())
+}
+
+fn load_from<T: DeserializeOwned>(path: &Path) -> Result<T, StorageError> {
+    let payload = read_with_fallback(path)?;
+    let text = String::from_utf8(payload).map_err(|err| StorageError::Deserialize(err.to_string()))?;
+    toml::from_str(&text).map_err(|err| StorageError::Deserialize(err.to_string()))
+}
+
+//-----------------------------------------------------------------------------
+
+pub fn write_atomic(path: &Path, payload: &[u8]) -> Result<(), StorageError> {
+    write_atomic_with(path, payload, |from, to| fs::rename(from, to))
+}
+
+// the rename is passed in so the tests can make it fail
+fn write_atomic_with(path: &Path, payload: &[u8], rename: impl Fn(&Path, &Path) -> io::Result<()>) -> Result<(), StorageError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temp_path = sibling_path(path, TEMP_EXTENSION);
+    {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(make_header(payload).as_bytes())?;
+        file.write_all(payload)?;
+        file.sync_all()?;
+    }
+
+    // the previous version stays readable as a backup until the new one is in place
+    let backup_path = sibling_path(path, BACKUP_EXTENSION);
+    let had_previous = path.exists();
+    if had_previous {
+        rename(path, &backup_path)?;
+    }
+    if let Err(err) = rename(&temp_path, path) {
+        // the previous version goes back in place, read_with_fallback would find it in the backup otherwise
+        if had_previous {
+            let _ = rename(&backup_path, path);
+        }
+        let _ = fs::remove_file(&temp_path);
+        return Err(err.into());
+    }
+    sync_parent_dir(path);
+
+    Ok(())
+}
+
+pub fn read_with_fallback(path: &Path) -> Result<Vec<u8>, StorageError> {
+    let err = match read_verified(path) {
+        Ok(payload) => return Ok(payload),
+        Err(err) => err,
+    };
+
+    let backup_path = sibling_path(path, BACKUP_EXTENSION);
+    match read_verified(&backup_path) {
+        Ok(payload) => {
+            log::warn!("Failed to read {} ({}), restored from backup", path.display(), err);
+            Ok(payload)
+        }
+        Err(StorageError::NotFound(_)) => Err(err),
+        Err(backup_err) => {
+            log::warn!("Backup {} is unusable too ({})", backup_path.display(), backup_err);
+            Err(err)
+        }
+    }
+}
+
+fn read_verified(path: &Path) -> Result<Vec<u8>, StorageError> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Err(StorageError::NotFound(path.to_path_buf())),
+        Err(err) => return Err(err.into()),
+    };
+
+    let corrupted = || StorageError::Corrupted(path.to_path_buf());
+
+    let header_end = data.iter().position(|&b| b == b'\n').ok_or_else(corrupted)?;
+    let header = std::str::from_utf8(&data[..header_end]).map_err(|_| corrupted())?;
+    let payload = &data[header_end + 1..];
+
+    let mut fields = header.split(' ');
+    if fields.next() != Some(HEADER_MAGIC) {
+        return Err(corrupted());
+    }
+    let length: usize = fields.next().and_then(|x| x.parse().ok()).ok_or_else(corrupted)?;
+    let hash = fields.next().and_then(|x| u64::from_str_radix(x, 16).ok()).ok_or_else(corrupted)?;
+
+    if length != payload.len() || hash != checksum(payload) {
+        return Err(corrupted());
+    }
+
+    Ok(payload.to_vec())
+}
+
+//-----------------------------------------------------------------------------
+
+// the extension is appended, so settings.toml and settings.json don't share settings.bak
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+fn make_header(payload: &[u8]) -> String {
+    format!("{} {} {:016x}\n", HEADER_MAGIC, payload.len(), checksum(payload))
+}
+
+// FNV-1a, good enough to notice truncated or flipped bytes
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325u64, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) {
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) {}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Record {
+        score: u32,
+    }
+
+    fn test_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("space-storage-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("record.toml")
+    }
+
+    // first save becomes the backup, second one the current file
+    fn save_twice(path: &Path) {
+        save_to(path, &Record { score: 1 }).unwrap();
+        save_to(path, &Record { score: 2 }).unwrap();
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let path = test_path("round-trip");
+        save_to(&path, &Record { score: 7 }).unwrap();
+        assert_eq!(load_from::<Record>(&path).unwrap(), Record { score: 7 });
+        assert!(!sibling_path(&path, TEMP_EXTENSION).exists());
+        assert_eq!(sibling_path(&path, TEMP_EXTENSION), path.with_file_name("record.toml.tmp"));
+    }
+
+    #[test]
+    fn truncated_file_falls_back_to_backup() {
+        let path = test_path("truncated");
+        save_twice(&path);
+
+        let data = fs::read(&path).unwrap();
+        fs::write(&path, &data[..data.len() - 3]).unwrap();
+
+        assert_eq!(load_from::<Record>(&path).unwrap(), Record { score: 1 });
+    }
+
+    #[test]
+    fn corrupted_byte_falls_back_to_backup() {
+        let path = test_path("corrupted");
+        save_twice(&path);
+
+        let mut data = fs::read(&path).unwrap();
+        let last = data.len() - 2;
+        data[last] ^= 0x01;
+        fs::write(&path, data).unwrap();
+
+        assert!(matches!(read_verified(&path), Err(StorageError::Corrupted(_))));
+        assert_eq!(load_from::<Record>(&path).unwrap(), Record { score: 1 });
+    }
+
+    #[test]
+    fn failed_rename_keeps_previous_save() {
+        let path = test_path("rename");
+        save_to(&path, &Record { score: 1 }).unwrap();
+
+        // a non-empty directory in place of the backup makes the rename fail
+        let backup_path = sibling_path(&path, BACKUP_EXTENSION);
+        fs::create_dir_all(backup_path.join("blocker")).unwrap();
+
+        assert!(save_to(&path, &Record { score: 2 }).is_err());
+        assert_eq!(load_from::<Record>(&path).unwrap(), Record { score: 1 });
+    }
+
+    #[test]
+    fn failed_temp_rename_restores_previous_save() {
+        let path = test_path("temp-rename");
+        save_to(&path, &Record { score: 1 }).unwrap();
+
+        let payload = toml::to_string(&Record { score: 2 }).unwrap();
+        let result = write_atomic_with(&path, payload.as_bytes(), |from, to| {
+            if from == sibling_path(to, TEMP_EXTENSION) {
+                return Err(io::Error::other("rename refused"));
+            }
+            fs::rename(from, to)
+        });
+        assert!(matches!(result, Err(StorageError::Io(_))));
+        assert!(read_verified(&path).is_ok());
+        assert_eq!(load_from::<Record>(&path).unwrap(), Record { score: 1 });
+        assert!(!sibling_path(&path, TEMP_EXTENSION).exists());
+    }
+
+    #[test]
+    fn backups_of_files_with_the_same_stem_are_kept_apart() {
+        let path = test_path("stems");
+        let other = path.with_extension("replay");
+        save_twice(&path);
+        save_to(&other, &Record { score: 3 }).unwrap();
+        save_to(&other, &Record { score: 4 }).unwrap();
+
+        fs::write(&path, "broken").unwrap();
+        assert_eq!(load_from::<Record>(&path).unwrap(), Record { score: 1 });
+        assert!(sibling_path(&other, BACKUP_EXTENSION).exists());
+    }
+
+    #[test]
+    fn missing_file_is_not_found() {
+        let path = test_path("missing");
+        assert!(matches!(load_from::<Record>(&path), Err(StorageError::NotFound(_))));
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use super::GameInstant;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone)]
+pub struct Cooldown {
+    interval: Duration,
+    last: GameInstant,
+}
+
+//-----------------------------------------------------------------------------
+
+impl Cooldown {
+    pub fn new(interval: Duration) -> Self {
+        Self::started_at(interval, GameInstant::now())
+    }
+
+    pub fn started_at(interval: Duration, last: GameInstant) -> Self {
+        Self { interval, last }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    // keeps the time of the last reset
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    pub fn ready(&self) -> bool {
+        self.ready_at(GameInstant::now())
+    }
+    pub fn ready_at(&self, now: GameInstant) -> bool {
+        self.last + self.interval <= now
+    }
+
+    pub fn reset(&mut self) {
+        self.reset_at(GameInstant::now())
+    }
+    pub fn reset_at(&mut self, now: GameInstant) {
+        self.last = now;
+    }
+
+    // pushes the next ready time back, e.g. by the time its owner was interrupted
+    pub fn delay(&mut self, by: Duration) {
+        self.last = self.last + by;
+    }
+
+    pub fn time_remaining(&self) -> Duration {
+        self.time_remaining_at(GameInstant::now())
+    }
+    pub fn time_remaining_at(&self, now: GameInstant) -> Duration {
+        (self.last + self.interval).saturating_duration_since(now)
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,81 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+//-----------------------------------------------------------------------------
+
+pub const TICKS_PER_SECOND: u64 = 60;
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+thread_local! {
+    static G_TICK: Cell<i64> = const { Cell::new(0) };
+}
+
+// moves the game time one tick forward, called once per update the world isn't paused in
+pub fn advance_game_clock() {
+    G_TICK.with(|tick| tick.set(tick.get() + 1));
+}
+
+pub fn game_tick() -> i64 {
+    G_TICK.with(|tick| tick.get())
+}
+
+// puts the clock back to a tick taken by game_tick, timers of the run it belongs to continue as they were
+pub fn set_game_tick(value: i64) {
+    G_TICK.with(|tick| tick.set(value));
+}
+
+// rounded to the nearest tick
+pub fn duration_to_ticks(duration: Duration) -> i64 {
+    ((duration.as_nanos() * TICKS_PER_SECOND as u128 + NANOS_PER_SECOND / 2) / NANOS_PER_SECOND) as i64
+}
+
+pub fn ticks_to_duration(ticks: i64) -> Duration {
+    let nanos = ticks.max(0) as u128 * NANOS_PER_SECOND / TICKS_PER_SECOND as u128;
+    Duration::from_nanos(nanos as u64)
+}
+
+//-----------------------------------------------------------------------------
+
+// point in game time, unlike Instant it stands still while the game is paused and doesn't depend
+// on how fast the updates run, so headless runs and slow frames see the same timers
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameInstant {
+    tick: i64,
+}
+
+impl GameInstant {
+    pub fn now() -> Self {
+        Self { tick: game_tick() }
+    }
+
+    pub fn ticks_since(&self, earlier: GameInstant) -> i64 {
+        self.tick - earlier.tick
+    }
+
+    pub fn saturating_duration_since(&self, earlier: GameInstant) -> Duration {
+        ticks_to_duration(self.ticks_since(earlier))
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        GameInstant::now().saturating_duration_since(*self)
+    }
+}
+
+impl std::ops::Add<Duration> for GameInstant {
+    type Output = GameInstant;
+
+    fn add(self, duration: Duration) -> Self::Output {
+        GameInstant { tick: self.tick + duration_to_ticks(duration) }
+    }
+}
+
+// game time may go below zero, so unlike Instant this never fails
+impl std::ops::Sub<Duration> for GameInstant {
+    type Output = GameInstant;
+
+    fn sub(self, duration: Duration) -> Self::Output {
+        GameInstant { tick: self.tick - duration_to_ticks(duration) }
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,7 @@
+mod cooldown;
+mod game_clock;
+
+pub type Cooldown = cooldown::Cooldown;
+pub type GameInstant = game_clock::GameInstant;
+
+pub use game_clock::{advance_game_clock, duration_to_ticks, game_tick, set_game_tick, ticks_to_duration, TICKS_PER_SECOND};
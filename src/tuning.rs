@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Deserialize;
+
+use crate::entities::boss::Boss;
+use crate::entities::boss::boss_stages::{BossStage, BossStagesFactory, StageName};
+use crate::paths;
+use crate::power_ups::PowerUpKind;
+use crate::spawners::spawners_impl::{create_level_spawner, LEVELS_COUNT};
+use crate::validation::{AssetError, AssetValidator};
+
+//-----------------------------------------------------------------------------
+
+const TUNING_FILE: &str = "tuning.toml";
+// an mtime check per second is plenty for a file edited by hand
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+//-----------------------------------------------------------------------------
+
+// values of a stage, found by its name, which can change in the middle of a fight
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StageTuning {
+    pub interval_ms: Option<u64>,
+    pub move_speed: Option<i32>,
+}
+
+impl StageTuning {
+    pub fn interval(&self) -> Option<Duration> {
+        self.interval_ms.map(Duration::from_millis)
+    }
+}
+
+// the stage list of a boss, found by the boss name, only a newly spawned boss picks it up
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BossStructure {
+    pub stages: Option<Vec<String>>,
+}
+
+// balancing overrides edited while the game runs, see TuningWatcher
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tuning {
+    pub stages: HashMap<String, StageTuning>,
+    pub bosses: HashMap<String, BossStructure>,
+}
+
+impl Tuning {
+    pub fn parse(text: &str) -> Result<Tuning, Vec<AssetError>> {
+        let tuning: Tuning = toml::from_str(text).map_err(|err| {
+            let mut validator = AssetValidator::new(TUNING_FILE);
+            validator.error("", err.to_string());
+            validator.into_errors()
+        })?;
+        let errors = tuning.validate();
+        if errors.is_empty() { Ok(tuning) } else { Err(errors) }
+    }
+
+    // names are checked against the bosses of the shipped levels
+    pub fn validate(&self) -> Vec<AssetError> {
+        let mut validator = AssetValidator::new(TUNING_FILE);
+        let bosses = shipped_bosses();
+        let known_stage = |name: &str| bosses.iter().any(|(_, stages)| stages.contains(&name));
+
+        for (name, stage) in &self.stages {
+            let path = format!("stages.{}", name);
+            validator.check(known_stage(name), &path, || format!("no boss has a stage called {}", name));
+            if let Some(interval) = stage.interval() {
+                validator.positive_duration(format!("{}.interval_ms", path), interval);
+            }
+            if let Some(speed) = stage.move_speed {
+                validator.check(speed > 0, format!("{}.move_speed", path), || format!("{} is not positive", speed));
+            }
+        }
+
+        for (name, boss) in &self.bosses {
+            let path = format!("bosses.{}", name);
+            let Some((_, boss_stages)) = bosses.iter().find(|(boss_name, _)| boss_name == name) else {
+                validator.error(path, format!("no level has a boss called {}", name));
+                continue;
+            };
+            let Some(stages) = &boss.stages else {
+                continue;
+            };
+            // every stage but the intro and the last one ends at a hp threshold of the boss
+            validator.check(stages.len() == boss_stages.len(), format!("{}.stages", path), || {
+                format!("{} stages listed, the hp thresholds of {} need {}", stages.len(), name, boss_stages.len())
+            });
+            for (idx, stage) in stages.iter().enumerate() {
+                validator.check(boss_stages.contains(&stage.as_str()), format!("{}.stages[{}]", path, idx), || {
+                    format!("{} has no stage called {}", name, stage)
+                });
+            }
+        }
+
+        validator.into_errors()
+    }
+
+    pub fn stage(&self, name: StageName) -> Option<&StageTuning> {
+        self.stages.get(name)
+    }
+
+    pub fn structure(&self, boss_name: &str) -> Option<&BossStructure> {
+        self.bosses.get(boss_name)
+    }
+
+    // a boss without a stage list of its own comes out as it went in
+    pub fn restructure(&self, boss: Boss) -> Boss {
+        let Some(stages) = self.structure(boss.name()).and_then(|structure| structure.stages.clone()) else {
+            return boss;
+        };
+        boss.with_stage_factory(|inner| {
+            let names = stage_names(inner.as_ref());
+            let order = stages.iter()
+                .filter_map(|stage| names.iter().position(|name| name == stage))
+                .map(|idx| idx as i32)
+                .collect();
+            Box::new(ReorderedStages { inner, order })
+        })
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// names of the stages of every boss in the shipped levels
+fn shipped_bosses() -> Vec<(&'static str, Vec<StageName>)> {
+    (1..=LEVELS_COUNT)
+        .filter_map(|level| create_level_spawner(level).spawn_boss())
+        .map(|boss| (boss.name(), stage_names(boss.stage_factory())))
+        .collect()
+}
+
+fn stage_names(factory: &dyn BossStagesFactory) -> Vec<StageName> {
+    (0..factory.stages_count()).map(|idx| factory.create(idx).name()).collect()
+}
+
+// the stages of a boss in the order given by the tuning, everything else is left to the boss itself
+struct ReorderedStages {
+    inner: Box<dyn BossStagesFactory>,
+    order: Vec<i32>,
+}
+
+impl BossStagesFactory for ReorderedStages {
+    fn create(&self, idx: i32) -> Box<dyn BossStage> {
+        self.inner.create(self.order[idx as usize])
+    }
+
+    fn stages_count(&self) -> i32 {
+        self.order.len() as i32
+    }
+
+    fn hp_thresholds(&self) -> Vec<f32> {
+        self.inner.hp_thresholds()
+    }
+
+    fn boss_name(&self) -> &'static str {
+        self.inner.boss_name()
+    }
+
+    fn arena_walls(&self) -> Option<f32> {
+        self.inner.arena_walls()
+    }
+
+    fn weapon_arm_threshold(&self) -> Option<f32> {
+        self.inner.weapon_arm_threshold()
+    }
+
+    fn kinetic_barriers(&self) -> u32 {
+        self.inner.kinetic_barriers()
+    }
+
+    fn final_stage_drop(&self) -> Option<PowerUpKind> {
+        self.inner.final_stage_drop()
+    }
+
+    fn kill_cam(&self) -> bool {
+        self.inner.kill_cam()
+    }
+
+    fn enrage_in_last_stage(&self) -> bool {
+        self.inner.enrage_in_last_stage()
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// polls the modification time of tuning.toml in the config dir, an invalid edit keeps the previous values
+pub struct TuningWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_poll: Option<Instant>,
+}
+
+impl TuningWatcher {
+    pub fn new() -> Option<Self> {
+        match paths::config_dir() {
+            Ok(dir) => Some(TuningWatcher::with_path(dir.join(TUNING_FILE))),
+            Err(err) => {
+                log::warn!("Tuning is not watched, no config dir: {:?}", err);
+                None
+            }
+        }
+    }
+
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path, modified: None, last_poll: None }
+    }
+
+    // the new tuning once the file changed and is valid
+    pub fn poll(&mut self) -> Option<Tuning> {
+        let now = Instant::now();
+        if self.last_poll.is_some_and(|last_poll| now - last_poll < POLL_INTERVAL) {
+            return None;
+        }
+        self.last_poll = Some(now);
+        self.reload()
+    }
+
+    fn reload(&mut self) -> Option<Tuning> {
+        let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified()).ok()?;
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(err) => {
+                log::warn!("Failed to read {}: {}", self.path.display(), err);
+                return None;
+            }
+        };
+        match Tuning::parse(&text) {
+            Ok(tuning) => {
+                log::info!("Reloaded {}", self.path.display());
+                Some(tuning)
+            }
+            Err(errors) => {
+                for error in errors {
+                    log::error!("{}", error);
+                }
+                log::error!("{} rejected, the previous values are kept", self.path.display());
+                None
+            }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::globals::set_screen_size;
+    use crate::math::Vec2i;
+
+    #[test]
+    fn stage_values_are_parsed() {
+        let tuning = Tuning::parse("[stages.Targeted]\ninterval_ms = 250\nmove_speed = 5\n").unwrap();
+        let stage = tuning.stage("Targeted").unwrap();
+        assert_eq!(stage.interval(), Some(Duration::from_millis(250)));
+        assert_eq!(stage.move_speed, Some(5));
+        assert!(tuning.stage("SpreadShooting").is_none());
+    }
+
+    #[test]
+    fn stage_tuning_is_scaled_like_the_built_in_values() {
+        use crate::entities::boss::boss_stages::BossTuning;
+        use crate::entities::boss::easy_stages::SimpleShootingDown;
+
+        let mut stage = SimpleShootingDown::new();
+        stage.apply_stage_tuning(&StageTuning { interval_ms: Some(200), move_speed: None });
+        stage.set_tuning(BossTuning { shot_interval_scale: 0.5, ..BossTuning::default() });
+        assert_eq!(stage.shot_interval(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn invalid_values_are_all_reported() {
+        let text = "[stages.Targeted]\ninterval_ms = 0\n[stages.Missing]\n[bosses.Sentinel]\nstages = [\"Appear\", \"Nope\"]\n";
+        let mut paths: Vec<String> = Tuning::parse(text).unwrap_err().into_iter().map(|err| err.path).collect();
+        paths.sort();
+        assert_eq!(paths, [
+            "bosses.Sentinel.stages",
+            "bosses.Sentinel.stages[1]",
+            "stages.Missing",
+            "stages.Targeted.interval_ms",
+        ]);
+    }
+
+    #[test]
+    fn syntax_errors_are_reported_too() {
+        let errors = Tuning::parse("[stages.Targeted\n").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].source, TUNING_FILE);
+    }
+
+    #[test]
+    fn restructured_boss_follows_the_new_stage_list() {
+        set_screen_size(Vec2i::new(200, 200));
+        let text = "[bosses.Sentinel]\nstages = [\"Appear\", \"Targeted\", \"SpreadShooting\", \"SimpleShootingDown\"]\n";
+        let tuning = Tuning::parse(text).unwrap();
+        let boss = create_level_spawner(1).spawn_boss().unwrap();
+        let boss = tuning.restructure(boss);
+        assert_eq!(stage_names(boss.stage_factory()), ["Appear", "Targeted", "SpreadShooting", "SimpleShootingDown"]);
+        assert_eq!(boss.name(), "Sentinel");
+    }
+
+    #[test]
+    fn watcher_keeps_the_old_values_after_an_invalid_edit() {
+        let dir = std::env::temp_dir().join(format!("space-tuning-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(TUNING_FILE);
+        let mut watcher = TuningWatcher::with_path(path.clone());
+        assert_eq!(watcher.reload(), None);
+
+        fs::write(&path, "[stages.Targeted]\ninterval_ms = 250\n").unwrap();
+        assert!(watcher.reload().is_some());
+        assert_eq!(watcher.reload(), None);
+
+        // the mtime resolution of some file systems is a second
+        watcher.modified = None;
+        fs::write(&path, "[stages.Targeted]\ninterval_ms = 0\n").unwrap();
+        assert_eq!(watcher.reload(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+//-----------------------------------------------------------------------------
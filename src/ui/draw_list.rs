@@ -0,0 +1,165 @@
+use crate::entities::flash::Tint;
+use crate::entities::visual_tag::VisualTag;
+use crate::math::Vec2i;
+use crate::ui::palette::{PalettePreset, TagStyle};
+
+//-----------------------------------------------------------------------------
+
+// back to front
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DrawLayer {
+    Background,
+    Zones,
+    Hazards,
+    Boss,
+    Ship,
+    Shots,
+    Effects,
+    Overlay,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DrawKind {
+    Star,
+    ForceZone,
+    ArenaWall,
+    Comet,
+    Pickup,
+    Boss,
+    WeaponArm,
+    KineticBarrier,
+    DamageMark,
+    HealthBar,
+    HealthBarFill,
+    // the boss bar of the hud, its frame is the number of segments left
+    BossHealthBar,
+    BossHealthGhost,
+    BossHealthFill,
+    BossHealthSegment,
+    // the detached part of the boss, a thinner bar under the main one
+    BossPartHealth,
+    // where the ship dashed from, until the dash is ready again
+    DashGhost,
+    Ship,
+    TargetLock,
+    PlayerShot,
+    BossShot,
+    LaserBeam,
+    Shockwave,
+    Particle,
+    AbsorbParticle,
+    DamageNumber,
+    // a hit on a weak point, styled apart from the regular numbers
+    CriticalDamageNumber,
+    SafeZoneMarker,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct DrawItem {
+    pub layer: DrawLayer,
+    pub kind: DrawKind,
+    // animation frame of the kind's sprite sheet, the amount for damage numbers and absorb particles
+    pub frame: u32,
+    pub pos: Vec2i,
+    pub size: Vec2i,
+    // degrees, 0 is up
+    pub rotation: i32,
+    pub tag: Option<VisualTag>,
+    // color and outline of the tag in the palette the player picked
+    pub style: Option<TagStyle>,
+    pub alpha: f32,
+    // color shift and how strong it is, 0..1
+    pub tint: Option<(Tint, f32)>,
+    // white flash of a fresh hit, 0..1
+    pub flash: f32,
+    // keeps the insertion order within a layer
+    order: u32,
+}
+
+impl DrawItem {
+    pub fn new(layer: DrawLayer, kind: DrawKind, pos: Vec2i, size: Vec2i) -> Self {
+        Self { layer, kind, frame: 0, pos, size, rotation: 0, tag: None, style: None, alpha: 1.0, tint: None, flash: 0.0, order: 0 }
+    }
+
+    // most of the entities are square
+    pub fn square(layer: DrawLayer, kind: DrawKind, pos: Vec2i, width: i32) -> Self {
+        DrawItem::new(layer, kind, pos, Vec2i { x: width, y: width })
+    }
+
+    pub fn with_frame(mut self, frame: u32) -> Self {
+        self.frame = frame;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: i32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_tag(mut self, tag: VisualTag, palette: PalettePreset) -> Self {
+        self.tag = Some(tag);
+        self.style = Some(palette.style(tag));
+        self
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_tint(mut self, tint: Tint, amount: f32) -> Self {
+        self.tint = Some((tint, amount.clamp(0.0, 1.0))).filter(|_| tint != Tint::None);
+        self
+    }
+
+    pub fn with_flash(mut self, flash: f32) -> Self {
+        self.flash = flash.clamp(0.0, 1.0);
+        self
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// everything visible in a frame, the renderer only has to walk it front to back
+pub struct DrawList {
+    items: Vec<DrawItem>,
+}
+
+impl DrawList {
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    // keeps the buffer, so rebuilding it every frame doesn't allocate once it is large enough
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn push(&mut self, mut item: DrawItem) {
+        item.order = self.items.len() as u32;
+        self.items.push(item);
+    }
+
+    // everything but the overlay, e.g. the screen shake, which must not move the hud
+    pub fn shift_playfield(&mut self, offset: Vec2i) {
+        for item in self.items.iter_mut().filter(|item| item.layer != DrawLayer::Overlay) {
+            item.pos = item.pos + offset;
+        }
+    }
+
+    // unstable sort doesn't allocate, the insertion order keeps it deterministic
+    pub fn sort(&mut self) {
+        self.items.sort_unstable_by_key(|item| (item.layer, item.order));
+    }
+
+    pub fn items(&self) -> &[DrawItem] {
+        &self.items
+    }
+
+    #[cfg(test)]
+    pub fn iter_kind(&self, kind: DrawKind) -> impl Iterator<Item = &DrawItem> + '_ {
+        self.items.iter().filter(move |item| item.kind == kind)
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,146 @@
+use crate::input::InputState;
+use crate::achievements::AchievementSystem;
+use crate::loadout::{available_options, Loadout, LoadoutOption, LoadoutSlot};
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LoadoutRow {
+    Slot(LoadoutSlot),
+    Start,
+}
+
+const ROWS: [LoadoutRow; 4] = [
+    LoadoutRow::Slot(LoadoutSlot::Primary),
+    LoadoutRow::Slot(LoadoutSlot::Secondary),
+    LoadoutRow::Slot(LoadoutSlot::Passive),
+    LoadoutRow::Start,
+];
+
+//-----------------------------------------------------------------------------
+
+// up and down pick a row, left and right cycle the options of the slot, fire on start confirms
+pub struct LoadoutMenu {
+    loadout: Loadout,
+    // unlocked options per slot, in the ROWS order
+    options: [Vec<LoadoutOption>; 3],
+    selected: usize,
+    last_direction: (i32, i32),
+}
+
+impl LoadoutMenu {
+    // only the unlocked options can be picked
+    pub fn new(loadout: Loadout, achievements: &AchievementSystem) -> Self {
+        let options = [
+            available_options(LoadoutSlot::Primary, achievements),
+            available_options(LoadoutSlot::Secondary, achievements),
+            available_options(LoadoutSlot::Passive, achievements),
+        ];
+        Self { loadout, options, selected: 0, last_direction: (0, 0) }
+    }
+
+    pub fn loadout(&self) -> Loadout {
+        self.loadout
+    }
+
+    pub fn selected_row(&self) -> LoadoutRow {
+        ROWS[self.selected]
+    }
+
+    // returns the chosen loadout once confirmed
+    pub fn update(&mut self, input: &InputState) -> Option<Loadout> {
+        let direction = input.axis.to_8_way();
+        let (last_x, last_y) = self.last_direction;
+        // moves only on a new tilt, holding the direction doesn't scroll
+        if direction.y != 0 && direction.y != last_y {
+            self.selected = (self.selected as i32 + direction.y).rem_euclid(ROWS.len() as i32) as usize;
+        }
+        if direction.x != 0 && direction.x != last_x {
+            self.cycle(direction.x);
+        }
+        self.last_direction = (direction.x, direction.y);
+
+        if input.fire.pressed && self.selected_row() == LoadoutRow::Start {
+            return Some(self.loadout);
+        }
+        None
+    }
+
+    fn cycle(&mut self, step: i32) {
+        let slot = match self.selected_row() {
+            LoadoutRow::Slot(slot) => slot,
+            LoadoutRow::Start => return,
+        };
+        let options = &self.options[self.selected];
+        if options.is_empty() {
+            return;
+        }
+
+        let current = options.iter().position(|option| *option == self.loadout.get(slot)).unwrap_or(0);
+        let next = (current as i32 + step).rem_euclid(options.len() as i32) as usize;
+        self.loadout.set(options[next]);
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::GameEvent;
+    use crate::input::{Axis, ButtonState};
+    use crate::loadout::{Passive, PrimaryWeapon, SecondaryWeapon};
+
+    const PRESS: ButtonState = ButtonState { pressed: true, held: true, released: false };
+
+    fn tilt(x: f32, y: f32) -> InputState {
+        InputState { axis: Axis::new(x, y), ..Default::default() }
+    }
+
+    fn fire() -> InputState {
+        InputState { fire: PRESS, ..Default::default() }
+    }
+
+    // every input is followed by an idle frame, so the next tilt is a new one
+    fn feed(menu: &mut LoadoutMenu, inputs: &[InputState]) -> Option<Loadout> {
+        inputs.iter()
+            .flat_map(|input| [menu.update(input), menu.update(&InputState::default())])
+            .flatten()
+            .last()
+    }
+
+    #[test]
+    fn picks_options_and_confirms() {
+        let mut achievements = AchievementSystem::default();
+        achievements.check(&GameEvent::ComboOf50);
+        let mut menu = LoadoutMenu::new(Loadout::default(), &achievements);
+
+        let down = tilt(0.0, 1.0);
+        let right = tilt(1.0, 0.0);
+        let chosen = feed(&mut menu, &[right, down, down, right, down, fire()]);
+        assert_eq!(chosen, Some(Loadout {
+            primary: PrimaryWeapon::Spread,
+            secondary: SecondaryWeapon::ExtraBomb,
+            passive: Passive::MagnetRadius,
+        }));
+    }
+
+    #[test]
+    fn locked_options_are_skipped() {
+        let achievements = AchievementSystem::default();
+        let mut menu = LoadoutMenu::new(Loadout::default(), &achievements);
+
+        // only straight is unlocked, passives wrap around past the locked speed
+        feed(&mut menu, &[tilt(1.0, 0.0), tilt(0.0, -1.0), tilt(0.0, -1.0), tilt(-1.0, 0.0)]);
+        assert_eq!(menu.loadout().primary, PrimaryWeapon::Straight);
+        assert_eq!(menu.loadout().passive, Passive::MagnetRadius);
+    }
+
+    #[test]
+    fn fire_confirms_only_on_start() {
+        let mut menu = LoadoutMenu::new(Loadout::default(), &AchievementSystem::default());
+        assert_eq!(feed(&mut menu, &[fire()]), None);
+        assert_eq!(menu.selected_row(), LoadoutRow::Slot(LoadoutSlot::Primary));
+        assert_eq!(feed(&mut menu, &[tilt(0.0, -1.0), fire()]), Some(Loadout::default()));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod draw_list;
+pub mod loadout_menu;
+pub mod palette;
+pub mod pause_menu;
+pub mod safe_zone_indicator;
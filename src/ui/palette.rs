@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::visual_tag::VisualTag;
+
+//-----------------------------------------------------------------------------
+
+// indices into the renderer color table
+pub type ColorId = u32;
+
+const COLOR_CYAN: ColorId = 0;
+const COLOR_RED: ColorId = 1;
+const COLOR_ORANGE: ColorId = 2;
+const COLOR_GREY: ColorId = 3;
+const COLOR_BLUE: ColorId = 4;
+const COLOR_YELLOW: ColorId = 5;
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TagStyle {
+    pub color: ColorId,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PalettePreset {
+    Default,
+    Deuteranopia,
+    Protanopia,
+}
+
+impl PalettePreset {
+    pub fn next(&self) -> PalettePreset {
+        match self {
+            PalettePreset::Default => PalettePreset::Deuteranopia,
+            PalettePreset::Deuteranopia => PalettePreset::Protanopia,
+            PalettePreset::Protanopia => PalettePreset::Default,
+        }
+    }
+
+    // no wildcard arms, so a new tag or preset can't be left without a style
+    pub fn style(&self, tag: VisualTag) -> TagStyle {
+        use PalettePreset::*;
+        use VisualTag::*;
+
+        let color = match (self, tag) {
+            (Default, PlayerShot) => COLOR_CYAN,
+            (Default, BossShot) => COLOR_RED,
+            (Default, Hazard) => COLOR_GREY,
+
+            (Deuteranopia, PlayerShot) => COLOR_BLUE,
+            (Deuteranopia, BossShot) => COLOR_ORANGE,
+            (Deuteranopia, Hazard) => COLOR_GREY,
+
+            (Protanopia, PlayerShot) => COLOR_CYAN,
+            (Protanopia, BossShot) => COLOR_YELLOW,
+            (Protanopia, Hazard) => COLOR_GREY,
+        };
+        TagStyle { color }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAGS: [VisualTag; 3] = [VisualTag::PlayerShot, VisualTag::BossShot, VisualTag::Hazard];
+
+    #[test]
+    fn every_tag_has_its_own_color_in_every_preset() {
+        let mut preset = PalettePreset::Default;
+        for _ in 0..3 {
+            let colors: Vec<ColorId> = TAGS.iter().map(|tag| preset.style(*tag).color).collect();
+            for (idx, color) in colors.iter().enumerate() {
+                assert!(!colors[idx + 1..].contains(color), "{:?} reuses a color for {:?}", preset, TAGS[idx]);
+            }
+            preset = preset.next();
+        }
+        assert_eq!(preset, PalettePreset::Default);
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,339 @@
+use crate::input::{Action, InputState};
+
+//-----------------------------------------------------------------------------
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PauseEntry {
+    Resume,
+    Restart,
+    Settings,
+    Loadout,
+    Quit,
+}
+
+// only the settings which are safe to change in the middle of a run
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SettingsEntry {
+    AutoFire,
+    ScreenShake,
+    DamageNumbers,
+    Palette,
+    Controls,
+    Back,
+}
+
+// an action per entry, selecting it binds the next pressed key or button to it
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ControlsEntry {
+    Rebind(Action),
+    ResetToDefaults,
+    Back,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConfirmEntry {
+    Yes,
+    No,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MenuPage {
+    Main,
+    Settings,
+    Controls,
+    // restart and quit drop the current run, so they are asked once more
+    Confirm(PauseEntry),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MenuTransition {
+    Resume,
+    Restart,
+    Quit,
+    OpenLoadout,
+    Toggle(SettingsEntry),
+    Rebind(Action),
+    ResetBindings,
+}
+
+//-----------------------------------------------------------------------------
+
+const MAIN_ENTRIES: [PauseEntry; 5] = [
+    PauseEntry::Resume,
+    PauseEntry::Restart,
+    PauseEntry::Settings,
+    PauseEntry::Loadout,
+    PauseEntry::Quit,
+];
+const SETTINGS_ENTRIES: [SettingsEntry; 6] = [
+    SettingsEntry::AutoFire,
+    SettingsEntry::ScreenShake,
+    SettingsEntry::DamageNumbers,
+    SettingsEntry::Palette,
+    SettingsEntry::Controls,
+    SettingsEntry::Back,
+];
+// the actions first, then the reset and back entries
+const CONTROLS_ENTRIES_COUNT: usize = Action::ALL.len() + 2;
+const CONFIRM_ENTRIES: [ConfirmEntry; 2] = [ConfirmEntry::No, ConfirmEntry::Yes];
+
+//-----------------------------------------------------------------------------
+
+pub struct PauseMenu {
+    page: MenuPage,
+    selected: usize,
+    last_direction: i32,
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        Self {
+            page: MenuPage::Main,
+            selected: 0,
+            last_direction: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = PauseMenu::new();
+    }
+
+    pub fn page(&self) -> MenuPage {
+        self.page
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_entry(&self) -> PauseEntry {
+        MAIN_ENTRIES[self.selected.min(MAIN_ENTRIES.len() - 1)]
+    }
+
+    pub fn selected_settings_entry(&self) -> SettingsEntry {
+        SETTINGS_ENTRIES[self.selected.min(SETTINGS_ENTRIES.len() - 1)]
+    }
+
+    pub fn selected_controls_entry(&self) -> ControlsEntry {
+        match Action::ALL.get(self.selected) {
+            Some(action) => ControlsEntry::Rebind(*action),
+            None if self.selected == Action::ALL.len() => ControlsEntry::ResetToDefaults,
+            None => ControlsEntry::Back,
+        }
+    }
+
+    pub fn selected_confirm_entry(&self) -> ConfirmEntry {
+        CONFIRM_ENTRIES[self.selected.min(CONFIRM_ENTRIES.len() - 1)]
+    }
+
+    // fire selects, pause goes one page back (or resumes from the main page)
+    pub fn update(&mut self, input: &InputState) -> Option<MenuTransition> {
+        self.navigate(input.axis.to_8_way().y);
+
+        if input.pause.pressed {
+            return self.back();
+        }
+        if input.fire.pressed {
+            return self.select();
+        }
+        None
+    }
+
+    // moves only on a new tilt, holding the direction doesn't scroll
+    fn navigate(&mut self, direction: i32) {
+        if direction != 0 && direction != self.last_direction {
+            let count = self.entries_count() as i32;
+            self.selected = (self.selected as i32 + direction).rem_euclid(count) as usize;
+        }
+        self.last_direction = direction;
+    }
+
+    fn entries_count(&self) -> usize {
+        match self.page {
+            MenuPage::Main => MAIN_ENTRIES.len(),
+            MenuPage::Settings => SETTINGS_ENTRIES.len(),
+            MenuPage::Controls => CONTROLS_ENTRIES_COUNT,
+            MenuPage::Confirm(_) => CONFIRM_ENTRIES.len(),
+        }
+    }
+
+    fn open(&mut self, page: MenuPage) {
+        self.page = page;
+        self.selected = 0;
+    }
+
+    fn back(&mut self) -> Option<MenuTransition> {
+        match self.page {
+            MenuPage::Main => Some(MenuTransition::Resume),
+            MenuPage::Controls => {
+                self.open(MenuPage::Settings);
+                None
+            }
+            _ => {
+                self.open(MenuPage::Main);
+                None
+            }
+        }
+    }
+
+    fn select(&mut self) -> Option<MenuTransition> {
+        match self.page {
+            MenuPage::Main => match self.selected_entry() {
+                PauseEntry::Resume => Some(MenuTransition::Resume),
+                PauseEntry::Loadout => Some(MenuTransition::OpenLoadout),
+                PauseEntry::Settings => {
+                    self.open(MenuPage::Settings);
+                    None
+                }
+                entry => {
+                    self.open(MenuPage::Confirm(entry));
+                    None
+                }
+            },
+            MenuPage::Settings => match self.selected_settings_entry() {
+                SettingsEntry::Controls => {
+                    self.open(MenuPage::Controls);
+                    None
+                }
+                SettingsEntry::Back => {
+                    self.open(MenuPage::Main);
+                    None
+                }
+                entry => Some(MenuTransition::Toggle(entry)),
+            },
+            MenuPage::Controls => match self.selected_controls_entry() {
+                ControlsEntry::Rebind(action) => Some(MenuTransition::Rebind(action)),
+                ControlsEntry::ResetToDefaults => Some(MenuTransition::ResetBindings),
+                ControlsEntry::Back => {
+                    self.open(MenuPage::Settings);
+                    None
+                }
+            },
+            MenuPage::Confirm(entry) => {
+                let confirmed = self.selected_confirm_entry() == ConfirmEntry::Yes;
+                self.open(MenuPage::Main);
+                match entry {
+                    PauseEntry::Restart if confirmed => Some(MenuTransition::Restart),
+                    PauseEntry::Quit if confirmed => Some(MenuTransition::Quit),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Axis, ButtonState};
+
+    const PRESS: ButtonState = ButtonState { pressed: true, held: true, released: false };
+
+    fn idle() -> InputState {
+        InputState::default()
+    }
+
+    fn down() -> InputState {
+        InputState { axis: Axis::new(0.0, 1.0), ..Default::default() }
+    }
+
+    fn up() -> InputState {
+        InputState { axis: Axis::new(0.0, -1.0), ..Default::default() }
+    }
+
+    fn fire() -> InputState {
+        InputState { fire: PRESS, ..Default::default() }
+    }
+
+    fn pause() -> InputState {
+        InputState { pause: PRESS, ..Default::default() }
+    }
+
+    // every input is followed by an idle frame, so the next tilt is a new one
+    fn feed(menu: &mut PauseMenu, inputs: &[InputState]) -> Vec<MenuTransition> {
+        inputs.iter()
+            .flat_map(|input| [menu.update(input), menu.update(&idle())])
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn navigation_wraps_around() {
+        let mut menu = PauseMenu::new();
+        feed(&mut menu, &[up()]);
+        assert_eq!(menu.selected_entry(), PauseEntry::Quit);
+        feed(&mut menu, &[down(), down()]);
+        assert_eq!(menu.selected_entry(), PauseEntry::Restart);
+    }
+
+    #[test]
+    fn held_direction_moves_once() {
+        let mut menu = PauseMenu::new();
+        for _ in 0..10 {
+            menu.update(&down());
+        }
+        assert_eq!(menu.selected_entry(), PauseEntry::Restart);
+    }
+
+    #[test]
+    fn resume_from_main_page() {
+        let mut menu = PauseMenu::new();
+        assert_eq!(feed(&mut menu, &[fire()]), vec![MenuTransition::Resume]);
+        assert_eq!(feed(&mut menu, &[pause()]), vec![MenuTransition::Resume]);
+    }
+
+    #[test]
+    fn restart_needs_confirmation() {
+        let mut menu = PauseMenu::new();
+        assert!(feed(&mut menu, &[down(), fire()]).is_empty());
+        assert_eq!(menu.page(), MenuPage::Confirm(PauseEntry::Restart));
+        assert_eq!(menu.selected_confirm_entry(), ConfirmEntry::No);
+
+        assert_eq!(feed(&mut menu, &[down(), fire()]), vec![MenuTransition::Restart]);
+        assert_eq!(menu.page(), MenuPage::Main);
+    }
+
+    #[test]
+    fn declined_quit_returns_to_main_page() {
+        let mut menu = PauseMenu::new();
+        assert!(feed(&mut menu, &[up(), fire(), fire()]).is_empty());
+        assert_eq!(menu.page(), MenuPage::Main);
+    }
+
+    #[test]
+    fn loadout_is_opened_without_confirmation() {
+        let mut menu = PauseMenu::new();
+        assert_eq!(feed(&mut menu, &[up(), up(), fire()]), vec![MenuTransition::OpenLoadout]);
+        assert_eq!(menu.page(), MenuPage::Main);
+    }
+
+    #[test]
+    fn settings_toggle_and_back() {
+        let mut menu = PauseMenu::new();
+        feed(&mut menu, &[down(), down(), fire()]);
+        assert_eq!(menu.page(), MenuPage::Settings);
+
+        assert_eq!(feed(&mut menu, &[down(), fire()]), vec![MenuTransition::Toggle(SettingsEntry::ScreenShake)]);
+        assert!(feed(&mut menu, &[pause()]).is_empty());
+        assert_eq!(menu.page(), MenuPage::Main);
+    }
+
+    #[test]
+    fn controls_rebind_reset_and_back() {
+        let mut menu = PauseMenu::new();
+        feed(&mut menu, &[down(), down(), fire(), up(), up(), fire()]);
+        assert_eq!(menu.page(), MenuPage::Controls);
+
+        assert_eq!(feed(&mut menu, &[fire()]), vec![MenuTransition::Rebind(Action::MoveUp)]);
+        assert_eq!(feed(&mut menu, &[up(), up(), fire()]), vec![MenuTransition::ResetBindings]);
+        assert_eq!(menu.selected_controls_entry(), ControlsEntry::ResetToDefaults);
+        assert!(feed(&mut menu, &[down(), fire()]).is_empty());
+        assert_eq!(menu.page(), MenuPage::Settings);
+
+        feed(&mut menu, &[up(), up(), fire()]);
+        assert!(feed(&mut menu, &[pause()]).is_empty());
+        assert_eq!(menu.page(), MenuPage::Settings);
+    }
+}
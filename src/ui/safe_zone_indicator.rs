@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use crate::timing::GameInstant;
+
+//-----------------------------------------------------------------------------
+
+const SHOW_DURATION: Duration = Duration::from_millis(500);
+
+//-----------------------------------------------------------------------------
+
+// downward arrow pointing at a column which a dense volley doesn't cover
+#[derive(Copy, Clone, Debug)]
+pub struct SafeZoneIndicator {
+    x: i32,
+    shown_at: GameInstant,
+}
+
+impl SafeZoneIndicator {
+    pub fn new(x: i32) -> Self {
+        Self { x, shown_at: GameInstant::now() }
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible_at(GameInstant::now())
+    }
+
+    pub fn visible_at(&self, now: GameInstant) -> bool {
+        now.saturating_duration_since(self.shown_at) < SHOW_DURATION
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,270 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+use crate::cutscene::CutsceneFrame;
+use crate::cutscene::dialogue::{Dialogue, DialogueError};
+use crate::entities::boss::boss_stages::BossStagesFactory;
+use crate::globals::screen_rect;
+use crate::math::Rect;
+use crate::spawners::Spawner;
+use crate::spawners::spawners_impl::{create_level_spawner, LEVELS_COUNT};
+
+//-----------------------------------------------------------------------------
+
+// one problem found in the content, the path points at the value, e.g. boss.stages[2].interval_ms
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssetError {
+    pub source: String,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.source, self.path, self.message)
+    }
+}
+
+// collects every problem of a source instead of stopping at the first one
+pub struct AssetValidator {
+    source: String,
+    errors: Vec<AssetError>,
+}
+
+impl AssetValidator {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self { source: source.into(), errors: vec![] }
+    }
+
+    pub fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(AssetError { source: self.source.clone(), path: path.into(), message: message.into() });
+    }
+
+    pub fn check(&mut self, ok: bool, path: impl Into<String>, message: impl FnOnce() -> String) {
+        if !ok {
+            self.error(path, message());
+        }
+    }
+
+    pub fn positive_duration(&mut self, path: impl Into<String>, duration: Duration) {
+        self.check(!duration.is_zero(), path, || "must be longer than zero".to_string());
+    }
+
+    pub fn percent(&mut self, path: impl Into<String>, value: f32) {
+        self.check(value > 0.0 && value < 1.0, path, || format!("{} is not a fraction between 0 and 1", value));
+    }
+
+    // every value is below the one before it
+    pub fn strictly_decreasing(&mut self, path: &str, values: &[f32]) {
+        for (idx, pair) in values.windows(2).enumerate() {
+            self.check(pair[1] < pair[0], format!("{}[{}]", path, idx + 1), || {
+                format!("{} must be lower than the previous {}", pair[1], pair[0])
+            });
+        }
+    }
+
+    pub fn inside_playfield(&mut self, path: impl Into<String>, rect: &Rect) {
+        let screen = screen_rect();
+        let inside = rect.top_left.x >= screen.top_left.x && rect.top_left.y >= screen.top_left.y
+            && rect.bottom_right.x <= screen.bottom_right.x && rect.bottom_right.y <= screen.bottom_right.y
+            && rect.width() > 0 && rect.height() > 0;
+        self.check(inside, path, || format!("{:?} is empty or leaves the {:?} playfield", rect, screen));
+    }
+
+    pub fn into_errors(self) -> Vec<AssetError> {
+        self.errors
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+// the thresholds split the fight between the intro stage and the last one, which both have none
+pub fn validate_boss_stages(validator: &mut AssetValidator, path: &str, factory: &dyn BossStagesFactory) {
+    let stages_count = factory.stages_count();
+    validator.check(stages_count >= 2, format!("{}.stages", path), || {
+        format!("a boss needs an intro and a fighting stage, got {}", stages_count)
+    });
+
+    let thresholds = factory.hp_thresholds();
+    let thresholds_path = format!("{}.hp_thresholds", path);
+    for (idx, threshold) in thresholds.iter().enumerate() {
+        validator.percent(format!("{}[{}]", thresholds_path, idx), *threshold);
+    }
+    validator.strictly_decreasing(&thresholds_path, &thresholds);
+    validator.check(thresholds.len() as i32 == stages_count - 2, thresholds_path, || {
+        format!("{} thresholds for {} stages, expected one per stage between the intro and the last one",
+            thresholds.len(), stages_count)
+    });
+
+    if let Some(walls) = factory.arena_walls() {
+        validator.check((0.0..0.5).contains(&walls), format!("{}.arena_walls", path), || {
+            format!("walls covering {} of the width on each side leave no room", walls)
+        });
+    }
+    if let Some(threshold) = factory.weapon_arm_threshold() {
+        validator.percent(format!("{}.weapon_arm_threshold", path), threshold);
+    }
+
+    for idx in 0..stages_count {
+        let stage = factory.create(idx);
+        let stage_path = format!("{}.stages[{}]", path, idx);
+        let Some(interval) = stage.shot_interval() else {
+            continue;
+        };
+        validator.positive_duration(format!("{}.interval_ms", stage_path), interval);
+        let shots = stage.shot_count_this_interval();
+        validator.check(shots >= 1, format!("{}.shot_count", stage_path), || {
+            format!("{} shoots every {:?} but fires no shots", stage.name(), interval)
+        });
+        let profile = stage.shot_profile();
+        if let Some(speed) = profile.speed {
+            validator.check(speed > 0, format!("{}.shot_speed", stage_path), || format!("{} is not positive", speed));
+        }
+        if let Some(width) = profile.width {
+            validator.check(width > 0, format!("{}.shot_width", stage_path), || format!("{} is not positive", width));
+        }
+    }
+}
+
+fn validate_dialogue(validator: &mut AssetValidator, spawner: &dyn Spawner) {
+    let lines = spawner.dialogue();
+    if lines.is_empty() {
+        return;
+    }
+    if let Err(err) = Dialogue::new(lines) {
+        match err {
+            DialogueError::NoLines => validator.error("dialogue", "has no lines"),
+            DialogueError::EmptyText(idx) => validator.error(format!("dialogue[{}].text", idx), "is empty"),
+            DialogueError::BadDuration(idx) => {
+                validator.error(format!("dialogue[{}].duration", idx), "is too short or too long to be read")
+            }
+        }
+    }
+}
+
+fn validate_cutscene(validator: &mut AssetValidator, path: &str, frames: &[CutsceneFrame]) {
+    for (idx, frame) in frames.iter().enumerate() {
+        validator.check(!frame.text.trim().is_empty(), format!("{}[{}].text", path, idx), || "is empty".to_string());
+        validator.positive_duration(format!("{}[{}].duration", path, idx), frame.duration);
+    }
+}
+
+pub fn validate_level(level: i32) -> Vec<AssetError> {
+    let spawner = create_level_spawner(level);
+    let mut validator = AssetValidator::new(format!("level {} ({})", level, spawner.level_name()));
+
+    validator.check(spawner.calc_comets_limit(0) >= 1, "comets_limit", || "no comets at the start".to_string());
+
+    let mut ids = HashSet::new();
+    for (idx, objective) in spawner.objectives().iter().enumerate() {
+        if let Err(message) = objective.validate() {
+            validator.error(format!("objectives[{}]", idx), message);
+        }
+        validator.check(ids.insert(objective.id), format!("objectives[{}].id", idx), || {
+            format!("{} is used by another objective", objective.id)
+        });
+    }
+
+    validate_dialogue(&mut validator, spawner.as_ref());
+    validate_cutscene(&mut validator, "cutscene_before_boss", &spawner.cutscene_before_boss());
+    validate_cutscene(&mut validator, "cutscene_after_boss", &spawner.cutscene_after_boss());
+
+    for (idx, zone) in spawner.force_zones().iter().enumerate() {
+        validator.inside_playfield(format!("force_zones[{}]", idx), zone.rect());
+    }
+
+    match spawner.spawn_boss() {
+        Some(boss) => {
+            validator.check(spawner.has_boss(), "boss", || "spawned by a level without a boss".to_string());
+            validator.check(spawner.boss_spawn_points() > 0, "boss_spawn_points", || "must be positive".to_string());
+            validator.check(boss.max_hp() > 0, "boss.max_hp", || format!("{} is not positive", boss.max_hp()));
+            validate_boss_stages(&mut validator, "boss", boss.stage_factory());
+        }
+        None => validator.check(!spawner.has_boss(), "boss", || "the level has a boss but spawns none".to_string()),
+    }
+
+    validator.into_errors()
+}
+
+// every level with its boss, the test suite runs it too, so broken content can't be shipped
+pub fn validate_assets() -> Vec<AssetError> {
+    (1..=LEVELS_COUNT).flat_map(validate_level).collect()
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::boss::boss_stages::{BossStage, ShotProfile};
+    use crate::entities::boss::easy_stages::{AppearStage, SimpleShootingDown};
+    use crate::globals::set_screen_size;
+    use crate::math::Vec2i;
+
+    struct BrokenFactory;
+    impl BossStagesFactory for BrokenFactory {
+        fn create(&self, idx: i32) -> Box<dyn BossStage> {
+            match idx {
+                0 => Box::new(AppearStage::new()),
+                _ => Box::new(SimpleShootingDown::with_shot_profile(ShotProfile::new(0, 4))),
+            }
+        }
+
+        fn stages_count(&self) -> i32 {
+            4
+        }
+
+        fn hp_thresholds(&self) -> Vec<f32> {
+            vec![0.4, 0.7, 1.5]
+        }
+
+        fn boss_name(&self) -> &'static str {
+            "Broken"
+        }
+
+        fn arena_walls(&self) -> Option<f32> {
+            Some(0.6)
+        }
+    }
+
+    #[test]
+    fn shipped_content_is_valid() {
+        set_screen_size(Vec2i::new(200, 200));
+        let errors = validate_assets();
+        assert!(errors.is_empty(), "{}", errors.iter().map(|err| err.to_string()).collect::<Vec<_>>().join("\n"));
+    }
+
+    #[test]
+    fn every_problem_is_reported_with_its_path() {
+        let mut validator = AssetValidator::new("broken.toml");
+        validate_boss_stages(&mut validator, "boss", &BrokenFactory);
+        let errors = validator.into_errors();
+        let paths: Vec<&str> = errors.iter().map(|err| err.path.as_str()).collect();
+        assert_eq!(paths, [
+            "boss.hp_thresholds[2]",
+            "boss.hp_thresholds[1]",
+            "boss.hp_thresholds[2]",
+            "boss.hp_thresholds",
+            "boss.arena_walls",
+            "boss.stages[1].shot_speed",
+            "boss.stages[2].shot_speed",
+            "boss.stages[3].shot_speed",
+        ]);
+        assert!(errors.iter().all(|err| err.source == "broken.toml"));
+        assert_eq!(errors[0].to_string(), "broken.toml: boss.hp_thresholds[2]: 1.5 is not a fraction between 0 and 1");
+    }
+
+    #[test]
+    fn zones_outside_the_playfield_are_reported() {
+        set_screen_size(Vec2i::new(200, 200));
+        let mut validator = AssetValidator::new("level");
+        validator.inside_playfield("inside", &Rect { top_left: Vec2i::new(0, 0), bottom_right: Vec2i::new(200, 100) });
+        validator.inside_playfield("outside", &Rect { top_left: Vec2i::new(150, 0), bottom_right: Vec2i::new(250, 100) });
+        validator.inside_playfield("empty", &Rect { top_left: Vec2i::new(50, 50), bottom_right: Vec2i::new(50, 100) });
+        let paths: Vec<String> = validator.into_errors().into_iter().map(|err| err.path).collect();
+        assert_eq!(paths, ["outside", "empty"]);
+    }
+}
+
+//-----------------------------------------------------------------------------
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use crate::timing::GameInstant;
+
+//-----------------------------------------------------------------------------
+
+pub const VICTORY_LAP_DURATION: Duration = Duration::from_secs(5);
+
+//-----------------------------------------------------------------------------
+
+// free flight after the final boss, nothing spawns or deals damage until it ends
+#[derive(Copy, Clone, Debug)]
+pub struct VictoryLap {
+    started: GameInstant,
+    duration: Duration,
+}
+
+impl VictoryLap {
+    pub fn new(duration: Duration) -> Self {
+        Self { started: GameInstant::now(), duration }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished_at(GameInstant::now())
+    }
+
+    pub fn finished_at(&self, now: GameInstant) -> bool {
+        now.saturating_duration_since(self.started) >= self.duration
+    }
+}
+
+//-----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finishes_after_its_duration() {
+        let lap = VictoryLap::new(VICTORY_LAP_DURATION);
+        let now = GameInstant::now();
+        assert!(!lap.finished_at(now + VICTORY_LAP_DURATION - Duration::from_millis(100)));
+        assert!(lap.finished_at(now + VICTORY_LAP_DURATION));
+    }
+}